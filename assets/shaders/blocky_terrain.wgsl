@@ -23,6 +23,7 @@ struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
     @location(2) uv: vec2<f32>,
+    @location(3) light: vec2<f32>, // Sky and block light (0..1), baked by the mesher
     @location(6) color: vec4<f32>, // Using color.r for material index
 }
 
@@ -33,6 +34,12 @@ struct VertexOutput {
     @location(2) uv: vec2<f32>,
     @location(3) material_index: i32,
     @location(4) ao: f32,
+    @location(5) light: vec2<f32>,
+}
+
+// Light levels fall off geometrically, like they do in the propagation
+fn light_curve(level: f32) -> f32 {
+    return pow(0.8, (1.0 - clamp(level, 0.0, 1.0)) * 15.0);
 }
 
 @vertex
@@ -52,6 +59,7 @@ fn vertex(vertex: VertexInput) -> VertexOutput {
     // Index 0 = Grass, 1 = Dirt, 2 = Rock, 3 = Sand, etc.
     out.material_index = i32(vertex.color.a * 255.0 + 0.5); 
     out.ao = vertex.color.r;
+    out.light = vertex.light;
     
     return out;
 }
@@ -78,9 +86,14 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     let L = normalize(vec3<f32>(0.5, 1.0, 0.5)); // Arbitrary sun dir
     let NdotL = max(dot(N, L), 0.0);
     let ao = clamp(in.ao, 0.0, 1.0);
-    let ambient = vec3<f32>(0.3, 0.3, 0.4) * ao;
+    let sky = light_curve(in.light.x);
+    let block = light_curve(in.light.y) * step(0.001, in.light.y);
+    let ambient = vec3<f32>(0.3, 0.3, 0.4) * ao * sky;
     
-    let lighting = ambient + vec3<f32>(1.0, 0.95, 0.8) * NdotL;
+    // Sunlight only reaches what the sky does; torches add a warm glow
+    let lighting = ambient + vec3<f32>(1.0, 0.95, 0.8) * NdotL * sky
+        + vec3<f32>(1.0, 0.8, 0.55) * block * ao
+        + vec3<f32>(0.02);
     
     return diffuse * vec4<f32>(lighting, 1.0);
 }
@@ -141,6 +141,11 @@ fn sample_normal_tp(uv_yz: vec2<f32>, uv_xz: vec2<f32>, uv_xy: vec2<f32>, w: vec
     return normalize(n0 * w.x + n1 * w.y + n2 * w.z);
 }
 
+// Light levels fall off geometrically, like they do in the propagation
+fn light_curve(level: f32) -> f32 {
+    return pow(0.8, (1.0 - clamp(level, 0.0, 1.0)) * 15.0);
+}
+
 fn get_base_material(atlas_idx: i32) -> i32 {
     if (atlas_idx == 0) { return 0; }
     if (atlas_idx == 2 || atlas_idx == 3) { return 1; }
@@ -222,7 +227,16 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     let spec_power = mix(128.0, 8.0, roughness);
     let spec_intensity = mix(0.3, 0.05, roughness);
 
-    let ambient = 0.35 * ao_factor;
-    let lit = albedo.rgb * (ambient + ndotl * 0.65) + vec3(pow(ndoth, spec_power) * spec_intensity);
+    // Baked voxel light: sky in uv_b.x, block light in uv_b.y
+    var sky = 1.0;
+    var block = vec3<f32>(0.0);
+#ifdef VERTEX_UVS_B
+    sky = light_curve(in.uv_b.x);
+    block = vec3<f32>(1.0, 0.8, 0.55) * light_curve(in.uv_b.y) * step(0.001, in.uv_b.y) * ao_factor;
+#endif
+
+    let ambient = 0.35 * ao_factor * sky;
+    let lit = albedo.rgb * (ambient + ndotl * 0.65 * sky + block + vec3(0.02))
+        + vec3(pow(ndoth, spec_power) * spec_intensity * sky);
     return vec4(lit, albedo.a);
 }
@@ -0,0 +1,98 @@
+//! Compares the per-face blocky mesher with the greedy one.
+//!
+//! Run with `cargo bench --bench chunk_meshing`; the vertex counts for each
+//! scene are printed before the timings.
+
+use bevy::math::{IVec3, UVec3};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use voxel_builder::rendering::ao_config::AmbientOcclusionConfig;
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::meshing::{generate_chunk_mesh, generate_chunk_mesh_greedy};
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::VoxelWorld;
+
+/// A 3x1x3 world filled from `voxel_at`; the centre chunk is the one meshed
+fn scene(voxel_at: impl Fn(IVec3) -> VoxelType) -> VoxelWorld {
+    let mut world = VoxelWorld::new(IVec3::new(3, 1, 3));
+    for cx in 0..3 {
+        for cz in 0..3 {
+            let chunk_pos = IVec3::new(cx, 0, cz);
+            let origin = VoxelWorld::chunk_to_world(chunk_pos);
+            let mut chunk = Chunk::new(chunk_pos);
+            for x in 0..16 {
+                for y in 0..16 {
+                    for z in 0..16 {
+                        let local = UVec3::new(x, y, z);
+                        chunk.set(local, voxel_at(origin + local.as_ivec3()));
+                    }
+                }
+            }
+            world.insert_chunk(chunk);
+        }
+    }
+    world
+}
+
+fn flat(pos: IVec3) -> VoxelType {
+    match pos.y {
+        0..=5 => VoxelType::Rock,
+        6..=7 => VoxelType::SubSoil,
+        8 => VoxelType::TopSoil,
+        _ => VoxelType::Air,
+    }
+}
+
+fn hills(pos: IVec3) -> VoxelType {
+    let height = 8.0 + 3.0 * (pos.x as f32 * 0.3).sin() + 2.0 * (pos.z as f32 * 0.2).cos();
+    let height = height as i32;
+    if pos.y > height {
+        VoxelType::Air
+    } else if pos.y == height {
+        VoxelType::TopSoil
+    } else if pos.y + 3 > height {
+        VoxelType::SubSoil
+    } else {
+        VoxelType::Rock
+    }
+}
+
+/// Worst case: nothing can merge
+fn checkerboard(pos: IVec3) -> VoxelType {
+    if (pos.x + pos.y + pos.z) % 2 == 0 {
+        VoxelType::Rock
+    } else {
+        VoxelType::Air
+    }
+}
+
+fn bench_meshing(c: &mut Criterion) {
+    let ao_config = AmbientOcclusionConfig::default().baked;
+    let scenes: [(&str, fn(IVec3) -> VoxelType); 3] =
+        [("flat", flat), ("hills", hills), ("checkerboard", checkerboard)];
+
+    for (name, voxel_at) in scenes {
+        let world = scene(voxel_at);
+        let chunk = world.get_chunk(IVec3::new(1, 0, 1)).expect("centre chunk");
+
+        let per_face = generate_chunk_mesh(chunk, &world, &ao_config).solid;
+        let greedy = generate_chunk_mesh_greedy(chunk, &world, &ao_config).solid;
+        println!(
+            "{name}: per-face {} vertices, greedy {} vertices ({:.1}x fewer)",
+            per_face.positions.len(),
+            greedy.positions.len(),
+            per_face.positions.len() as f32 / greedy.positions.len().max(1) as f32,
+        );
+
+        let mut group = c.benchmark_group(format!("mesh_{name}"));
+        group.bench_function("per_face", |b| {
+            b.iter(|| generate_chunk_mesh(black_box(chunk), &world, &ao_config))
+        });
+        group.bench_function("greedy", |b| {
+            b.iter(|| generate_chunk_mesh_greedy(black_box(chunk), &world, &ao_config))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_meshing);
+criterion_main!(benches);
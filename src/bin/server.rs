@@ -0,0 +1,311 @@
+//! Dedicated multiplayer server: the voxel world, persistence, entity AI and
+//! physics without a window, renderer or player of its own.
+//!
+//! ```text
+//! server [--config assets/config/server.yaml]
+//! ```
+//!
+//! Every line typed on stdin runs as a chat command; the leading `/` is
+//! optional. `say <message>` talks to the players and `stop` saves the world
+//! and shuts down.
+
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::asset::AssetPlugin;
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use std::io::BufRead;
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+use std::time::Duration;
+use voxel_builder::chat::commands::{
+    self, ChatCommand, ChatCommandAppExt, ChatCommands, HelpCommand,
+};
+use voxel_builder::chat::log::{self as chat_log, ChatLogCommand};
+use voxel_builder::chat::{ChatMessage, ChatState};
+use voxel_builder::entity::EntitySimulationPlugin;
+use voxel_builder::network::commands::{KickCommand, PlayersCommand};
+use voxel_builder::network::config::{ServerConfig, SERVER_CONFIG_PATH};
+use voxel_builder::network::host::{self, HostSession};
+use voxel_builder::network::NetworkSession;
+use voxel_builder::physics::PhysicsSimulationPlugin;
+use voxel_builder::voxel::persistence::{ActiveWorld, SaveSlot};
+use voxel_builder::voxel::plugin::VoxelSimulationPlugin;
+use voxel_builder::voxel::world::VoxelWorld;
+
+const USAGE: &str = "\
+Usage:
+  server [--config <yaml>]
+
+Without --config, assets/config/server.yaml is used when it exists.";
+
+/// Simulation updates per second
+const TICK_RATE: f64 = 30.0;
+
+/// Name shown on chat sent from the console
+const CONSOLE_NAME: &str = "Server";
+
+/// Lines typed on stdin, read on their own thread
+#[derive(Resource)]
+struct Console {
+    lines: Mutex<Receiver<String>>,
+}
+
+impl Console {
+    fn spawn() -> Self {
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            lines: Mutex::new(lines),
+        }
+    }
+}
+
+/// Saves the world every interval
+#[derive(Resource)]
+struct Autosave(Timer);
+
+fn main() -> ExitCode {
+    let mut config_path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => match args.next() {
+                Some(path) => config_path = Some(path),
+                None => {
+                    eprintln!("--config needs a file\n\n{}", USAGE);
+                    return ExitCode::from(2);
+                }
+            },
+            "help" | "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            other => {
+                eprintln!("Unknown argument '{}'\n\n{}", other, USAGE);
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    let config = match load_server_config(config_path.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let host = match HostSession::start(([0, 0, 0, 0], config.port).into(), config.settings()) {
+        Ok(host) => host,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+            1.0 / TICK_RATE,
+        ))),
+        LogPlugin::default(),
+        TransformPlugin,
+        AssetPlugin::default(),
+    ))
+    // Physics reads collider shapes from meshes when asked to
+    .init_asset::<Mesh>()
+    .add_plugins((
+        VoxelSimulationPlugin {
+            slot: Some(SaveSlot::new(&config.world)),
+        },
+        EntitySimulationPlugin,
+        PhysicsSimulationPlugin,
+    ))
+    .init_resource::<NetworkSession>()
+    .insert_resource(console_chat())
+    .init_resource::<ChatCommands>()
+    .add_chat_command(HelpCommand)
+    .add_chat_command(ChatLogCommand)
+    .add_chat_command(PlayersCommand)
+    .add_chat_command(KickCommand)
+    .add_chat_command(SayCommand)
+    .add_chat_command(StopCommand)
+    .insert_resource(host)
+    .insert_resource(Console::spawn())
+    .add_systems(
+        Update,
+        (
+            host::host_session_system.run_if(resource_exists::<HostSession>),
+            run_console_commands,
+            print_chat,
+            chat_log::write_chat_log,
+        )
+            .chain(),
+    )
+    .add_systems(Update, autosave_system.run_if(resource_exists::<Autosave>))
+    .add_systems(Last, save_on_exit);
+
+    if config.autosave_interval_secs > 0.0 {
+        app.insert_resource(Autosave(Timer::from_seconds(
+            config.autosave_interval_secs,
+            TimerMode::Repeating,
+        )));
+    }
+
+    info!(
+        "Serving world '{}' on port {} (up to {} players{})",
+        config.world,
+        config.port,
+        config.max_players,
+        if config.password.is_empty() {
+            ""
+        } else {
+            ", password required"
+        }
+    );
+    info!("Type help for console commands");
+
+    match app.run() {
+        AppExit::Success => ExitCode::SUCCESS,
+        AppExit::Error(_) => ExitCode::FAILURE,
+    }
+}
+
+/// An explicit path must load; the default one falls back to built-in
+/// settings when it doesn't exist
+fn load_server_config(path: Option<&str>) -> Result<ServerConfig, String> {
+    let explicit = path.is_some();
+    let path = path.unwrap_or(SERVER_CONFIG_PATH);
+    if !explicit && !Path::new(path).exists() {
+        return Ok(ServerConfig::default());
+    }
+    ServerConfig::load(path).map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Chat as the console sees it: join/leave notices, player messages and
+/// what was said from here
+fn console_chat() -> ChatState {
+    let mut chat = ChatState::default();
+    chat.username = CONSOLE_NAME.to_string();
+    chat
+}
+
+/// Run the lines typed since the last update as commands
+fn run_console_commands(world: &mut World) {
+    let lines: Vec<String> = {
+        let console = world.resource::<Console>();
+        let receiver = console.lines.lock().unwrap_or_else(|e| e.into_inner());
+        receiver.try_iter().collect()
+    };
+
+    for line in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match commands::execute_command(&line, world) {
+            Ok(reply) if reply.is_empty() => {}
+            Ok(reply) => println!("{}", reply),
+            Err(error) => println!("error: {}", error),
+        }
+    }
+}
+
+/// Echo chat and join/leave notices to the console
+fn print_chat(chat: Res<ChatState>) {
+    for message in chat.unlogged() {
+        println!("<{}> {}", message.user, message.content);
+    }
+}
+
+fn autosave_system(
+    time: Res<Time>,
+    mut autosave: ResMut<Autosave>,
+    mut world: ResMut<VoxelWorld>,
+    mut active_world: ResMut<ActiveWorld>,
+) {
+    if !autosave.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    match active_world.save(&mut world) {
+        Ok(()) => info!("Autosaved '{}'", active_world.metadata.name),
+        Err(e) => warn!("Autosave failed: {}", e),
+    }
+}
+
+fn save_on_exit(
+    mut exits: MessageReader<AppExit>,
+    mut world: ResMut<VoxelWorld>,
+    mut active_world: ResMut<ActiveWorld>,
+) {
+    if exits.read().next().is_none() {
+        return;
+    }
+    match active_world.save(&mut world) {
+        Ok(()) => info!("Saved '{}'", active_world.metadata.name),
+        Err(e) => error!("Failed to save on shutdown: {}", e),
+    }
+}
+
+/// `/say <message>`
+struct SayCommand;
+
+impl ChatCommand for SayCommand {
+    fn name(&self) -> &str {
+        "say"
+    }
+
+    fn usage(&self) -> &str {
+        "<message>"
+    }
+
+    fn description(&self) -> &str {
+        "Send a chat message to every player"
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        if args.is_empty() {
+            return Err("Usage: /say <message>".to_string());
+        }
+        let content = args.join(" ");
+        let host = world
+            .get_resource::<HostSession>()
+            .ok_or("The server isn't running")?;
+        host.send_chat(CONSOLE_NAME, &content);
+
+        // Shown by print_chat and kept in the chat log like player messages
+        world.resource_mut::<ChatState>().push_message(ChatMessage {
+            user: CONSOLE_NAME.to_string(),
+            content,
+        });
+        Ok(String::new())
+    }
+}
+
+/// `/stop`
+struct StopCommand;
+
+impl ChatCommand for StopCommand {
+    fn name(&self) -> &str {
+        "stop"
+    }
+
+    fn description(&self) -> &str {
+        "Save the world and shut the server down"
+    }
+
+    fn run(&self, _args: &[&str], world: &mut World) -> Result<String, String> {
+        world.write_message(AppExit::Success);
+        Ok("Stopping the server".to_string())
+    }
+}
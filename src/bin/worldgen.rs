@@ -0,0 +1,274 @@
+//! Headless world generation and inspection.
+//!
+//! Works on save slots directly, without opening a window or starting Bevy:
+//!
+//! ```text
+//! worldgen generate --seed 42 --name Fixture --size 8,4,8 --saves saves
+//! worldgen stats saves/fixture
+//! worldgen heightmap saves/fixture --out fixture.png
+//! worldgen validate saves/fixture
+//! ```
+
+use bevy::math::IVec3;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use voxel_builder::voxel::inspect::{self, WorldStats};
+use voxel_builder::voxel::persistence::{SaveSlot, SAVES_DIR};
+use voxel_builder::voxel::registry::VoxelRegistry;
+use voxel_builder::voxel::worldgen::{
+    GeneratorConfig, TerrainPipeline, WorldGenerator, WORLDGEN_CONFIG_PATH,
+};
+
+const USAGE: &str = "\
+Usage:
+  worldgen generate --seed <n> [--name <name>] [--size <x,y,z>] [--config <yaml>] [--saves <dir>]
+  worldgen stats <slot dir> [--config <yaml>] [--stored-only]
+  worldgen heightmap <slot dir> --out <png> [--config <yaml>] [--stored-only]
+  worldgen validate <slot dir>
+
+Chunks missing from a save are generated from its seed unless --stored-only is given.";
+
+/// Options that never take a value, so a path after them stays positional
+const BOOLEAN_FLAGS: &[&str] = &["stored-only"];
+
+/// Parsed `--flag value` options plus positional arguments
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Self {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut args = args.peekable();
+
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = if BOOLEAN_FLAGS.contains(&name) {
+                        None
+                    } else {
+                        args.next_if(|next| !next.starts_with("--"))
+                    };
+                    options.push((name.to_string(), value));
+                }
+                None => positional.push(arg),
+            }
+        }
+
+        Self { positional, options }
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(option, _)| option == name)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == name)
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse(std::env::args().skip(1));
+    let Some(command) = args.positional.first().cloned() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+
+    // Voxel names and solidity come from the same registry the game uses
+    VoxelRegistry::load_or_builtin().install_global();
+
+    let result = match command.as_str() {
+        "generate" => generate(&args),
+        "stats" => stats(&args),
+        "heightmap" => heightmap(&args),
+        "validate" => validate(&args),
+        "help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(format!("Unknown command '{}'\n\n{}", other, USAGE)),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn load_generator_config(args: &Args) -> Result<GeneratorConfig, String> {
+    let path = args.value("config").unwrap_or(WORLDGEN_CONFIG_PATH);
+    if args.value("config").is_none() && !Path::new(path).exists() {
+        return Ok(GeneratorConfig::default());
+    }
+    GeneratorConfig::load(path).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn parse_size(value: &str) -> Result<IVec3, String> {
+    let parts: Vec<i32> = value
+        .split(',')
+        .map(|part| part.trim().parse::<i32>())
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Invalid size '{}': {}", value, e))?;
+
+    match parts.as_slice() {
+        [x, y, z] if *x > 0 && *y > 0 && *z > 0 => Ok(IVec3::new(*x, *y, *z)),
+        _ => Err(format!("Size '{}' must be three positive numbers like 32,4,32", value)),
+    }
+}
+
+fn slot_from_arg(args: &Args) -> Result<SaveSlot, String> {
+    let path = args
+        .positional
+        .get(1)
+        .map(PathBuf::from)
+        .ok_or_else(|| format!("Missing slot directory\n\n{}", USAGE))?;
+
+    let id = path
+        .file_name()
+        .ok_or_else(|| format!("{:?} is not a slot directory", path))?
+        .to_string_lossy()
+        .to_string();
+    let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    Ok(SaveSlot::in_dir(root, id))
+}
+
+/// Slot, world size and the generator for chunks it doesn't store
+fn open_slot(args: &Args) -> Result<(SaveSlot, IVec3, Option<TerrainPipeline>), String> {
+    let slot = slot_from_arg(args)?;
+    let meta = slot.read_metadata()?;
+
+    let generator = if args.flag("stored-only") {
+        None
+    } else {
+        Some(TerrainPipeline::new(meta.seed, &load_generator_config(args)?))
+    };
+    Ok((slot, meta.world_size_chunks, generator))
+}
+
+fn generate(args: &Args) -> Result<(), String> {
+    let seed = args
+        .value("seed")
+        .ok_or("generate needs --seed")?
+        .parse::<u64>()
+        .map_err(|e| format!("Invalid seed: {}", e))?;
+    let name = args.value("name").unwrap_or("Generated World");
+    let size = parse_size(args.value("size").unwrap_or("32,4,32"))?;
+    let saves = PathBuf::from(args.value("saves").unwrap_or(SAVES_DIR));
+
+    let generator = TerrainPipeline::new(seed, &load_generator_config(args)?);
+    let start = std::time::Instant::now();
+    let (slot, totals) = inspect::generate_world(&saves, name, &generator, size)?;
+
+    println!(
+        "Generated '{}' (seed {}) into {:?} in {:.2}s",
+        name,
+        seed,
+        slot.directory(),
+        start.elapsed().as_secs_f32()
+    );
+    println!(
+        "  {} chunks, {} water, {} sand, {} dungeon wall, {} dungeon floor voxels",
+        size.x * size.y * size.z,
+        totals.water,
+        totals.sand,
+        totals.dungeon_wall,
+        totals.dungeon_floor
+    );
+    Ok(())
+}
+
+fn stats(args: &Args) -> Result<(), String> {
+    let (slot, size, generator) = open_slot(args)?;
+    let stats = inspect::collect_stats(
+        &slot.store(),
+        size,
+        generator.as_ref().map(|g| g as &dyn WorldGenerator),
+    )?;
+    print_stats(&slot, size, &stats);
+    Ok(())
+}
+
+fn print_stats(slot: &SaveSlot, size: IVec3, stats: &WorldStats) {
+    println!("World {:?} ({}x{}x{} chunks)", slot.directory(), size.x, size.y, size.z);
+    println!(
+        "  chunks: {} stored, {} generated, {} uniform",
+        stats.stored_chunks, stats.generated_chunks, stats.uniform_chunks
+    );
+    println!("  voxel storage: {} KiB", stats.voxel_memory_bytes / 1024);
+
+    let total = stats.total_voxels().max(1) as f64;
+    println!("  voxels:");
+    for (voxel, count) in stats.histogram() {
+        println!(
+            "    {:<16} {:>12} {:>7.3}%",
+            voxel.name(),
+            count,
+            count as f64 / total * 100.0
+        );
+    }
+}
+
+fn heightmap(args: &Args) -> Result<(), String> {
+    let out = args.value("out").ok_or("heightmap needs --out <png>")?;
+    let (slot, size, generator) = open_slot(args)?;
+    let heightmap = inspect::build_heightmap(
+        &slot.store(),
+        size,
+        generator.as_ref().map(|g| g as &dyn WorldGenerator),
+    )?;
+
+    let max_y = size.y * voxel_builder::constants::CHUNK_SIZE_I32 - 1;
+    let image = image::GrayImage::from_raw(
+        heightmap.width as u32,
+        heightmap.depth as u32,
+        heightmap.to_grayscale(max_y),
+    )
+    .ok_or("Heightmap size does not match its pixels")?;
+    image
+        .save(out)
+        .map_err(|e| format!("Failed to write {}: {}", out, e))?;
+
+    println!(
+        "Wrote {}x{} heightmap to {} (highest surface y = {})",
+        heightmap.width,
+        heightmap.depth,
+        out,
+        heightmap
+            .max_height()
+            .map_or("none".to_string(), |h| h.to_string())
+    );
+    Ok(())
+}
+
+fn validate(args: &Args) -> Result<(), String> {
+    let slot = slot_from_arg(args)?;
+    let report = inspect::validate_save(&slot);
+
+    println!(
+        "Checked {} regions, {} chunks in {:?}",
+        report.regions_checked,
+        report.chunks_checked,
+        slot.directory()
+    );
+    for warning in &report.warnings {
+        println!("  warning: {}", warning);
+    }
+    for error in &report.errors {
+        println!("  error: {}", error);
+    }
+
+    if report.is_ok() {
+        println!("Save is valid");
+        Ok(())
+    } else {
+        Err(format!("{} problem(s) found", report.errors.len()))
+    }
+}
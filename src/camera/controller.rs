@@ -1,3 +1,4 @@
+use crate::crafting::CraftingState;
 use crate::interaction::palette::PlacementPaletteState;
 use crate::map::MapState;
 use crate::menu::PauseMenuState;
@@ -5,7 +6,7 @@ use crate::rendering::capabilities::GraphicsCapabilities;
 use crate::rendering::ray_tracing::RayTracingSettings;
 use crate::atmosphere::fog_camera_components;
 use crate::atmosphere::FogConfig;
-use crate::player::Player;
+use crate::player::{Player, PlayerDeath};
 use bevy::prelude::*;
 use bevy::anti_alias::contrast_adaptive_sharpening::ContrastAdaptiveSharpening;
 use bevy::anti_alias::smaa::{Smaa, SmaaPreset};
@@ -160,13 +161,15 @@ pub fn player_camera_system(
     pause_menu: Res<PauseMenuState>,
     palette: Res<PlacementPaletteState>,
     map_state: Res<MapState>,
+    crafting: Res<CraftingState>,
+    death: Res<PlayerDeath>,
 ) {
     let Ok((_window, mut cursor_options)) = windows.single_mut() else {
         return;
     };
     let dt = time.delta_secs();
 
-    if pause_menu.open || palette.open || map_state.open {
+    if pause_menu.open || palette.open || map_state.open || crafting.open || death.dead {
         cursor_options.visible = true;
         cursor_options.grab_mode = CursorGrabMode::None;
         return;
@@ -189,8 +192,10 @@ pub fn player_camera_system(
             }
         }
 
-        // Reset position with R
-        if keys.just_pressed(KeyCode::KeyR) {
+        // Reset position with R; Ctrl+R rotates the world-edit clipboard
+        if keys.just_pressed(KeyCode::KeyR)
+            && !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        {
             camera.yaw = -2.35;
             camera.pitch = -0.4;
             *transform = Transform::from_xyz(256.0, 50.0, 256.0)
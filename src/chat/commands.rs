@@ -0,0 +1,153 @@
+//! Slash commands typed into chat, like `/tp 0 40 0`.
+//!
+//! Plugins register commands with [`ChatCommandAppExt::add_chat_command`].
+//! Typed commands run right after chat input with exclusive world access,
+//! and their replies show up in chat.
+
+use super::ChatState;
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+pub trait ChatCommand: Send + Sync + 'static {
+    /// Typed after the slash, e.g. `tp`
+    fn name(&self) -> &str;
+
+    /// Arguments as shown by /help, e.g. `<x> <y> <z>`
+    fn usage(&self) -> &str {
+        ""
+    }
+
+    fn description(&self) -> &str;
+
+    /// Run with the words after the name. Either way the text is shown to
+    /// the player; an empty reply shows nothing.
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String>;
+}
+
+/// Registered commands and lines waiting to run
+#[derive(Resource, Default)]
+pub struct ChatCommands {
+    commands: BTreeMap<String, Arc<dyn ChatCommand>>,
+    queued: Vec<String>,
+}
+
+impl ChatCommands {
+    /// Add a command, replacing any with the same name
+    pub fn register(&mut self, command: impl ChatCommand) {
+        self.commands
+            .insert(command.name().to_ascii_lowercase(), Arc::new(command));
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn ChatCommand>> {
+        self.commands.get(&name.to_ascii_lowercase()).cloned()
+    }
+
+    /// Commands in name order
+    pub fn iter(&self) -> impl Iterator<Item = &dyn ChatCommand> {
+        self.commands.values().map(|command| command.as_ref())
+    }
+
+    /// Run `line` with the next batch of commands
+    pub fn queue(&mut self, line: impl Into<String>) {
+        self.queued.push(line.into());
+    }
+}
+
+pub trait ChatCommandAppExt {
+    fn add_chat_command(&mut self, command: impl ChatCommand) -> &mut Self;
+}
+
+impl ChatCommandAppExt for App {
+    fn add_chat_command(&mut self, command: impl ChatCommand) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<ChatCommands>()
+            .register(command);
+        self
+    }
+}
+
+/// Whether a chat line is a command rather than a message
+pub fn is_command(line: &str) -> bool {
+    line.trim_start().starts_with('/')
+}
+
+/// Run one command line such as `/tp 0 40 0`
+pub fn execute_command(line: &str, world: &mut World) -> Result<String, String> {
+    let line = line.trim();
+    let mut words = line.strip_prefix('/').unwrap_or(line).split_whitespace();
+    let Some(name) = words.next() else {
+        return Err("Type /help for a list of commands".to_string());
+    };
+    let args: Vec<&str> = words.collect();
+
+    let command = world
+        .get_resource::<ChatCommands>()
+        .and_then(|commands| commands.get(name))
+        .ok_or_else(|| format!("Unknown command /{}; type /help for a list", name))?;
+    command.run(&args, world)
+}
+
+/// Run commands typed since the last frame and show their replies
+pub fn run_queued_commands(world: &mut World) {
+    let queued = match world.get_resource_mut::<ChatCommands>() {
+        Some(mut commands) if !commands.queued.is_empty() => std::mem::take(&mut commands.queued),
+        _ => return,
+    };
+
+    for line in queued {
+        let reply = execute_command(&line, world).unwrap_or_else(|error| error);
+        if let Some(mut chat) = world.get_resource_mut::<ChatState>() {
+            for reply_line in reply.lines() {
+                chat.push_system(reply_line);
+            }
+        }
+    }
+}
+
+fn usage_line(command: &dyn ChatCommand) -> String {
+    if command.usage().is_empty() {
+        format!("/{}", command.name())
+    } else {
+        format!("/{} {}", command.name(), command.usage())
+    }
+}
+
+/// `/help [command]`
+pub struct HelpCommand;
+
+impl ChatCommand for HelpCommand {
+    fn name(&self) -> &str {
+        "help"
+    }
+
+    fn usage(&self) -> &str {
+        "[command]"
+    }
+
+    fn description(&self) -> &str {
+        "List commands, or describe one"
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        let commands = world.resource::<ChatCommands>();
+        match args.first() {
+            Some(name) => {
+                let name = name.trim_start_matches('/');
+                let command = commands
+                    .get(name)
+                    .ok_or_else(|| format!("Unknown command /{}", name))?;
+                Ok(format!(
+                    "{} - {}",
+                    usage_line(command.as_ref()),
+                    command.description()
+                ))
+            }
+            None => Ok(commands
+                .iter()
+                .map(|command| format!("{} - {}", usage_line(command), command.description()))
+                .collect::<Vec<_>>()
+                .join("\n")),
+        }
+    }
+}
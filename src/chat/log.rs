@@ -0,0 +1,118 @@
+//! Optional chat transcript kept in the world's save directory.
+//!
+//! Off by default; `/chatlog on` enables it for the loaded world. Chat while
+//! connected to someone else's world isn't logged into the local one.
+
+use super::commands::ChatCommand;
+use super::{ChatMessage, ChatState};
+use crate::network::guest::GuestSession;
+use crate::voxel::persistence::{unix_now, ActiveWorld};
+use bevy::prelude::*;
+use std::fs::{self, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Append messages as `[unix time] user: content` lines
+pub fn append_chat_log(
+    path: &Path,
+    messages: &[ChatMessage],
+    timestamp: u64,
+) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create chat log directory: {}", e))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open chat log: {}", e))?;
+
+    let mut writer = BufWriter::new(file);
+    for message in messages {
+        writeln!(
+            writer,
+            "[{}] {}: {}",
+            timestamp, message.user, message.content
+        )
+        .map_err(|e| format!("Failed to write chat log: {}", e))?;
+    }
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to write chat log: {}", e))
+}
+
+pub fn write_chat_log(
+    mut chat: ResMut<ChatState>,
+    active_world: Option<Res<ActiveWorld>>,
+    guest: Option<Res<GuestSession>>,
+) {
+    if !chat.has_unlogged() {
+        return;
+    }
+    let messages = chat.take_unlogged();
+
+    let Some(active_world) = active_world else {
+        return;
+    };
+    if !active_world.metadata.chat_log || guest.is_some() {
+        return;
+    }
+    if let Err(e) = append_chat_log(&active_world.slot.chat_log_path(), &messages, unix_now()) {
+        warn!("{}", e);
+    }
+}
+
+/// `/chatlog [on|off]`
+pub struct ChatLogCommand;
+
+impl ChatCommand for ChatLogCommand {
+    fn name(&self) -> &str {
+        "chatlog"
+    }
+
+    fn usage(&self) -> &str {
+        "[on|off]"
+    }
+
+    fn description(&self) -> &str {
+        "Keep a chat transcript for this world"
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        let mut active_world = world
+            .get_resource_mut::<ActiveWorld>()
+            .ok_or("No world is loaded")?;
+        let enabled = match args.first().copied() {
+            None => {
+                let state = if active_world.metadata.chat_log {
+                    "on"
+                } else {
+                    "off"
+                };
+                return Ok(format!(
+                    "Chat log is {} ({})",
+                    state,
+                    active_world.slot.chat_log_path().display()
+                ));
+            }
+            Some("on") => true,
+            Some("off") => false,
+            Some(_) => return Err("Usage: /chatlog [on|off]".to_string()),
+        };
+
+        active_world.metadata.chat_log = enabled;
+        active_world
+            .slot
+            .store()
+            .write_meta(&active_world.metadata)?;
+        Ok(if enabled {
+            format!(
+                "Logging chat to {}",
+                active_world.slot.chat_log_path().display()
+            )
+        } else {
+            "Chat log off".to_string()
+        })
+    }
+}
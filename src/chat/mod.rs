@@ -0,0 +1,397 @@
+//! Chat overlay: messages between players, slash commands and the optional
+//! per-world chat log.
+
+pub mod commands;
+pub mod log;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+
+use crate::menu::PauseMenuState;
+use crate::network::guest::GuestSession;
+use crate::network::host::HostSession;
+use crate::network::protocol::{self, MAX_CHAT_LENGTH};
+use commands::{ChatCommandAppExt, ChatCommands, HelpCommand};
+use log::ChatLogCommand;
+
+/// Messages shown at once; older ones are reached by scrolling
+const VISIBLE_CHAT_MESSAGES: usize = 10;
+
+/// Messages kept for scrolling back
+const MAX_CHAT_MESSAGES: usize = 200;
+
+/// Sent lines remembered for recall with the arrow keys
+const MAX_SENT_LINES: usize = 50;
+
+#[derive(Resource, Debug)]
+pub struct ChatState {
+    pub active: bool,
+    pub buffer: String,
+    pub messages: Vec<ChatMessage>,
+    pub username: String,
+    /// How many messages the view is scrolled back from the newest
+    pub scroll: usize,
+    /// Lines this player sent, oldest first
+    sent: Vec<String>,
+    /// Index into `sent` while recalling, with the line typed before
+    recall: Option<(usize, String)>,
+    /// Messages not yet written to the chat log
+    unlogged: Vec<ChatMessage>,
+}
+
+impl Default for ChatState {
+    fn default() -> Self {
+        Self {
+            active: false,
+            buffer: String::new(),
+            messages: Vec::new(),
+            username: "Player".to_string(),
+            scroll: 0,
+            sent: Vec::new(),
+            recall: None,
+            unlogged: Vec::new(),
+        }
+    }
+}
+
+impl ChatState {
+    pub fn push_message(&mut self, message: ChatMessage) {
+        self.unlogged.push(message.clone());
+        if self.unlogged.len() > MAX_CHAT_MESSAGES {
+            let overflow = self.unlogged.len() - MAX_CHAT_MESSAGES;
+            self.unlogged.drain(0..overflow);
+        }
+
+        self.messages.push(message);
+        if self.messages.len() > MAX_CHAT_MESSAGES {
+            let overflow = self.messages.len() - MAX_CHAT_MESSAGES;
+            self.messages.drain(0..overflow);
+        }
+        // Someone reading older messages keeps their place
+        if self.scroll > 0 {
+            self.scroll = (self.scroll + 1).min(self.max_scroll());
+        }
+    }
+
+    pub fn push_system(&mut self, content: impl Into<String>) {
+        self.push_message(ChatMessage::system(content));
+    }
+
+    pub fn max_scroll(&self) -> usize {
+        self.messages.len().saturating_sub(VISIBLE_CHAT_MESSAGES)
+    }
+
+    /// Scroll towards older (positive) or newer (negative) messages
+    pub fn scroll_by(&mut self, delta: isize) {
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(delta)
+            .min(self.max_scroll());
+    }
+
+    /// The messages currently in view, oldest first
+    pub fn visible_messages(&self) -> &[ChatMessage] {
+        let end = self.messages.len() - self.scroll.min(self.messages.len());
+        let start = end.saturating_sub(VISIBLE_CHAT_MESSAGES);
+        &self.messages[start..end]
+    }
+
+    /// Remember a sent line for recall
+    pub fn remember_sent(&mut self, line: &str) {
+        self.recall = None;
+        if self.sent.last().map(String::as_str) == Some(line) {
+            return;
+        }
+        self.sent.push(line.to_string());
+        if self.sent.len() > MAX_SENT_LINES {
+            self.sent.remove(0);
+        }
+    }
+
+    /// Put the previously sent line into the input (up arrow)
+    pub fn recall_previous(&mut self) {
+        let index = match self.recall.take() {
+            Some((index, draft)) => {
+                let index = index.saturating_sub(1);
+                self.recall = Some((index, draft));
+                index
+            }
+            None if !self.sent.is_empty() => {
+                let index = self.sent.len() - 1;
+                self.recall = Some((index, std::mem::take(&mut self.buffer)));
+                index
+            }
+            None => return,
+        };
+        self.buffer = self.sent[index].clone();
+    }
+
+    /// Step back towards the line being typed before recalling (down arrow)
+    pub fn recall_next(&mut self) {
+        match self.recall.take() {
+            Some((index, draft)) if index + 1 < self.sent.len() => {
+                self.buffer = self.sent[index + 1].clone();
+                self.recall = Some((index + 1, draft));
+            }
+            Some((_, draft)) => self.buffer = draft,
+            None => {}
+        }
+    }
+
+    /// Messages pushed since the last call, for the chat log
+    pub fn take_unlogged(&mut self) -> Vec<ChatMessage> {
+        std::mem::take(&mut self.unlogged)
+    }
+
+    /// Messages pushed since the chat log was last written
+    pub fn unlogged(&self) -> &[ChatMessage] {
+        &self.unlogged
+    }
+
+    pub fn has_unlogged(&self) -> bool {
+        !self.unlogged.is_empty()
+    }
+
+    fn close_input(&mut self) {
+        self.active = false;
+        self.buffer.clear();
+        self.recall = None;
+        self.scroll = 0;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatMessage {
+    pub user: String,
+    pub content: String,
+}
+
+#[derive(Component)]
+struct ChatOverlayRoot;
+
+#[derive(Component)]
+struct ChatLogText;
+
+#[derive(Component)]
+struct ChatInputText;
+
+pub struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChatState>()
+            .init_resource::<ChatCommands>()
+            .add_chat_command(HelpCommand)
+            .add_chat_command(ChatLogCommand)
+            .add_systems(Startup, spawn_chat_overlay)
+            .add_systems(
+                Update,
+                (
+                    toggle_chat_input,
+                    process_chat_characters,
+                    navigate_chat,
+                    submit_chat_message,
+                    commands::run_queued_commands,
+                    log::write_chat_log,
+                    update_chat_log,
+                    update_chat_prompt,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn spawn_chat_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(12.0),
+                right: Val::Px(12.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(6.0)),
+                min_width: Val::Px(280.0),
+                max_width: Val::Px(420.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.45)),
+            ChatOverlayRoot,
+        ))
+        .with_children(|overlay| {
+            overlay.spawn((
+                Text::new(""),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                ChatLogText,
+            ));
+
+            overlay.spawn((
+                Text::new("Press Ctrl+A to chat"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 13.0,
+                    ..default()
+                },
+                TextColor(Color::srgba(0.9, 0.9, 0.9, 0.9)),
+                ChatInputText,
+            ));
+        });
+}
+
+fn toggle_chat_input(keys: Res<ButtonInput<KeyCode>>, mut chat_state: ResMut<ChatState>) {
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        && keys.just_pressed(KeyCode::KeyA)
+    {
+        chat_state.active = true;
+        chat_state.buffer.clear();
+    }
+
+    if chat_state.active && keys.just_pressed(KeyCode::Escape) {
+        chat_state.close_input();
+    }
+}
+
+fn process_chat_characters(
+    mut chat_state: ResMut<ChatState>,
+    mut char_evr: MessageReader<KeyboardInput>,
+    keys: Res<ButtonInput<KeyCode>>,
+    pause_state: Option<Res<PauseMenuState>>,
+) {
+    if !chat_state.active || pause_state.as_ref().map(|p| p.open).unwrap_or(false) {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Backspace) {
+        chat_state.buffer.pop();
+    }
+
+    for ev in char_evr.read() {
+        if !ev.state.is_pressed() {
+            continue;
+        }
+        if let Key::Character(ch) = &ev.logical_key {
+            if chat_state.buffer.chars().count() < MAX_CHAT_LENGTH {
+                chat_state.buffer.push_str(ch);
+            }
+        }
+    }
+}
+
+/// Up/down recall sent lines; Page Up/Down and the mouse wheel scroll back
+fn navigate_chat(
+    mut chat_state: ResMut<ChatState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut mouse_wheel: MessageReader<MouseWheel>,
+) {
+    let wheel: f32 = mouse_wheel.read().map(|wheel| wheel.y).sum();
+    if !chat_state.active {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        chat_state.recall_previous();
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        chat_state.recall_next();
+    }
+
+    let page = VISIBLE_CHAT_MESSAGES as isize - 1;
+    if keys.just_pressed(KeyCode::PageUp) {
+        chat_state.scroll_by(page);
+    }
+    if keys.just_pressed(KeyCode::PageDown) {
+        chat_state.scroll_by(-page);
+    }
+    if wheel != 0.0 {
+        chat_state.scroll_by(wheel.signum() as isize);
+    }
+}
+
+fn submit_chat_message(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut chat_state: ResMut<ChatState>,
+    mut chat_commands: ResMut<ChatCommands>,
+    host: Option<Res<HostSession>>,
+    guest: Option<Res<GuestSession>>,
+) {
+    if !chat_state.active || !keys.just_pressed(KeyCode::Enter) {
+        return;
+    }
+
+    let line = chat_state.buffer.trim().to_string();
+    chat_state.close_input();
+    if line.is_empty() {
+        return;
+    }
+    chat_state.remember_sent(&line);
+
+    if commands::is_command(&line) {
+        chat_commands.queue(line);
+        return;
+    }
+
+    let Some(content) = protocol::clean_chat(&line) else {
+        return;
+    };
+    let user = chat_state.username.clone();
+    if let Some(host) = &host {
+        host.send_chat(&user, &content);
+    } else if let Some(guest) = &guest {
+        guest.send_chat(&content);
+    }
+    chat_state.push_message(ChatMessage { user, content });
+}
+
+fn update_chat_log(chat_state: Res<ChatState>, mut query: Query<&mut Text, With<ChatLogText>>) {
+    if !chat_state.is_changed() {
+        return;
+    }
+
+    if let Ok(mut text) = query.single_mut() {
+        let mut body = chat_state
+            .visible_messages()
+            .iter()
+            .map(|msg| format!("{}: {}", msg.user, msg.content))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if chat_state.scroll > 0 {
+            body.push_str(&format!("\n... {} newer (Page Down)", chat_state.scroll));
+        }
+
+        text.0 = body;
+    }
+}
+
+fn update_chat_prompt(
+    chat_state: Res<ChatState>,
+    mut query: Query<&mut Text, With<ChatInputText>>,
+) {
+    if !chat_state.is_changed() {
+        return;
+    }
+
+    if let Ok(mut text) = query.single_mut() {
+        if chat_state.active {
+            text.0 = format!("{}: {}", chat_state.username, chat_state.buffer);
+        } else {
+            text.0 = "Press Ctrl+A to chat".to_string();
+        }
+    }
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            user: "System".to_string(),
+            content: content.into(),
+        }
+    }
+}
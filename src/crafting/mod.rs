@@ -0,0 +1,337 @@
+//! Survival crafting: a 3x3 grid filled from the hotbar, matched against the
+//! recipes in `recipes.yaml`. Press I to open it.
+
+use crate::camera::controller::PlayerCamera;
+use crate::chat::ChatState;
+use crate::entity::Inventory;
+use crate::interaction::palette::PlacementPaletteState;
+use crate::interaction::GameMode;
+use crate::menu::PauseMenuState;
+use crate::voxel::registry::VoxelRegistry;
+use crate::voxel::types::VoxelType;
+use crate::voxel::world::VoxelWorld;
+use bevy::prelude::*;
+use recipes::{CraftingGrid, Recipe, RecipeBook, GRID_SIZE};
+
+pub mod recipes;
+
+/// How far (in blocks, per axis) a crafting station may be from the player
+pub const STATION_RANGE: i32 = 4;
+
+const CELL_COLOR: Color = Color::srgba(0.12, 0.12, 0.15, 0.9);
+const OUTPUT_READY_COLOR: Color = Color::srgba(0.2, 0.35, 0.2, 0.9);
+const OUTPUT_BLOCKED_COLOR: Color = Color::srgba(0.2, 0.2, 0.22, 0.9);
+
+pub struct CraftingPlugin;
+
+impl Plugin for CraftingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RecipeBook::load_or_empty(VoxelRegistry::global()))
+            .init_resource::<CraftingState>()
+            .add_systems(
+                Update,
+                (
+                    toggle_crafting,
+                    handle_crafting_cell_click,
+                    handle_crafting_output_click,
+                    refresh_crafting_ui,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct CraftingState {
+    pub open: bool,
+    pub grid: CraftingGrid,
+    pub needs_redraw: bool,
+    pub root: Option<Entity>,
+}
+
+#[derive(Component)]
+pub struct CraftingRoot;
+
+/// Grid cell button, indexed row by row
+#[derive(Component)]
+pub struct CraftingCellButton(pub usize);
+
+#[derive(Component)]
+pub struct CraftingCellText(pub usize);
+
+#[derive(Component)]
+pub struct CraftingOutputButton;
+
+#[derive(Component)]
+pub struct CraftingOutputText;
+
+/// Whether a station block is within `STATION_RANGE` of `center`
+pub fn station_nearby(world: &VoxelWorld, center: IVec3, station: VoxelType) -> bool {
+    let range = -STATION_RANGE..=STATION_RANGE;
+    range.clone().any(|x| {
+        range.clone().any(|y| {
+            range
+                .clone()
+                .any(|z| world.get_voxel(center + IVec3::new(x, y, z)) == Some(station))
+        })
+    })
+}
+
+/// Why the grid cannot be crafted right now, or the recipe if it can
+fn craftable<'a>(
+    book: &'a RecipeBook,
+    grid: &CraftingGrid,
+    world: &VoxelWorld,
+    player: Option<IVec3>,
+) -> Result<&'a Recipe, String> {
+    let recipe = book.find(grid).ok_or_else(|| "No matching recipe".to_string())?;
+    if let Some(station) = recipe.station {
+        let near = player.is_some_and(|pos| station_nearby(world, pos, station));
+        if !near {
+            return Err(format!("Needs a {} nearby", station.name()));
+        }
+    }
+    Ok(recipe)
+}
+
+fn player_block(camera_query: &Query<&Transform, With<PlayerCamera>>) -> Option<IVec3> {
+    camera_query
+        .single()
+        .ok()
+        .map(|transform| transform.translation.floor().as_ivec3())
+}
+
+/// Open and close the crafting grid with I; closing hands back what is in
+/// it, and whatever doesn't fit in the inventory stays in the grid
+#[allow(clippy::too_many_arguments)]
+pub fn toggle_crafting(
+    keys: Res<ButtonInput<KeyCode>>,
+    pause_state: Res<PauseMenuState>,
+    chat_state: Option<Res<ChatState>>,
+    palette: Res<PlacementPaletteState>,
+    game_mode: Res<GameMode>,
+    mut crafting: ResMut<CraftingState>,
+    mut inventory: ResMut<Inventory>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    if pause_state.open || palette.open || chat_state.is_some_and(|chat| chat.active) {
+        return;
+    }
+
+    let close = crafting.open
+        && (keys.just_pressed(KeyCode::KeyI)
+            || keys.just_pressed(KeyCode::Escape)
+            || *game_mode != GameMode::Survival);
+    let open = !crafting.open && keys.just_pressed(KeyCode::KeyI) && *game_mode == GameMode::Survival;
+
+    if open {
+        crafting.open = true;
+        crafting.needs_redraw = true;
+        spawn_crafting_ui(&mut commands, &asset_server, &mut crafting);
+    } else if close {
+        crafting.open = false;
+        let left = crafting.grid.return_to(&mut inventory);
+        if left > 0 {
+            warn!("No room to return {} items; they stay in the crafting grid", left);
+        }
+        if let Some(root) = crafting.root.take() {
+            commands.entity(root).despawn();
+        }
+    }
+}
+
+/// Clicking an empty cell puts one item from the selected hotbar slot in it;
+/// clicking a filled cell takes the item back
+pub fn handle_crafting_cell_click(
+    interactions: Query<(&Interaction, &CraftingCellButton), Changed<Interaction>>,
+    mut crafting: ResMut<CraftingState>,
+    mut inventory: ResMut<Inventory>,
+) {
+    if !crafting.open {
+        return;
+    }
+
+    for (interaction, cell) in interactions.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let slot = crafting.grid.cell_mut(cell.0);
+        match *slot {
+            Some(item) => {
+                if inventory.add_item(item) {
+                    *slot = None;
+                }
+            }
+            None => *slot = inventory.take_selected(),
+        }
+        crafting.needs_redraw = true;
+    }
+}
+
+/// Craft the matching recipe once, using up one item from every cell
+pub fn handle_crafting_output_click(
+    interactions: Query<&Interaction, (Changed<Interaction>, With<CraftingOutputButton>)>,
+    book: Res<RecipeBook>,
+    world: Res<VoxelWorld>,
+    camera_query: Query<&Transform, With<PlayerCamera>>,
+    mut crafting: ResMut<CraftingState>,
+    mut inventory: ResMut<Inventory>,
+) {
+    if !crafting.open || !interactions.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        return;
+    }
+
+    let result = match craftable(&book, &crafting.grid, &world, player_block(&camera_query)) {
+        Ok(recipe) => recipe.result,
+        Err(reason) => {
+            info!("Cannot craft: {}", reason);
+            return;
+        }
+    };
+    if inventory.space_for(result.item) < result.count {
+        info!("No room for {}", result.item.name());
+        return;
+    }
+
+    crafting.grid.take_all();
+    inventory.add_items(result.item, result.count);
+    crafting.needs_redraw = true;
+}
+
+/// Redraw the grid and the output preview
+#[allow(clippy::too_many_arguments)]
+pub fn refresh_crafting_ui(
+    mut crafting: ResMut<CraftingState>,
+    book: Res<RecipeBook>,
+    world: Res<VoxelWorld>,
+    camera_query: Query<&Transform, With<PlayerCamera>>,
+    moved: Query<(), (With<PlayerCamera>, Changed<Transform>)>,
+    mut cell_texts: Query<(&CraftingCellText, &mut Text), Without<CraftingOutputText>>,
+    mut output_text: Query<&mut Text, With<CraftingOutputText>>,
+    mut output_button: Query<&mut BackgroundColor, With<CraftingOutputButton>>,
+) {
+    // Stations come into range as the player walks or builds one
+    if !crafting.open || !(crafting.needs_redraw || world.is_changed() || !moved.is_empty()) {
+        return;
+    }
+    crafting.needs_redraw = false;
+
+    for (cell, mut text) in cell_texts.iter_mut() {
+        **text = crafting.grid.cells()[cell.0]
+            .map(|item| item.name())
+            .unwrap_or_default();
+    }
+
+    let status = craftable(&book, &crafting.grid, &world, player_block(&camera_query));
+    if let Ok(mut text) = output_text.single_mut() {
+        **text = match &status {
+            Ok(recipe) => format!("Craft {} x{}", recipe.result.item.name(), recipe.result.count),
+            Err(_) if crafting.grid.is_empty() => "Place items from the hotbar".to_string(),
+            Err(reason) => reason.clone(),
+        };
+    }
+    if let Ok(mut background) = output_button.single_mut() {
+        background.0 = if status.is_ok() {
+            OUTPUT_READY_COLOR
+        } else {
+            OUTPUT_BLOCKED_COLOR
+        };
+    }
+}
+
+fn spawn_crafting_ui(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    crafting: &mut ResMut<CraftingState>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let text_font = |size: f32| TextFont {
+        font: font.clone(),
+        font_size: size,
+        ..default()
+    };
+
+    let root = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(120.0),
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-140.0)),
+                width: Val::Px(280.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                row_gap: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.07, 0.9)),
+            CraftingRoot,
+        ))
+        .with_children(|root| {
+            root.spawn((
+                Text::new("Crafting (I to close)"),
+                text_font(18.0),
+                TextColor(Color::WHITE),
+            ));
+
+            root.spawn(Node {
+                display: Display::Grid,
+                grid_template_columns: RepeatedGridTrack::px(GRID_SIZE as u16, 80.0),
+                row_gap: Val::Px(4.0),
+                column_gap: Val::Px(4.0),
+                ..default()
+            })
+            .with_children(|grid| {
+                for index in 0..GRID_SIZE * GRID_SIZE {
+                    grid.spawn((
+                        Button,
+                        Node {
+                            height: Val::Px(56.0),
+                            padding: UiRect::all(Val::Px(4.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(CELL_COLOR),
+                        CraftingCellButton(index),
+                    ))
+                    .with_children(|cell| {
+                        cell.spawn((
+                            Text::new(""),
+                            text_font(12.0),
+                            TextColor(Color::WHITE),
+                            CraftingCellText(index),
+                        ));
+                    });
+                }
+            });
+
+            root.spawn((
+                Button,
+                Node {
+                    padding: UiRect::axes(Val::Px(10.0), Val::Px(8.0)),
+                    ..default()
+                },
+                BackgroundColor(OUTPUT_BLOCKED_COLOR),
+                CraftingOutputButton,
+            ))
+            .with_children(|button| {
+                button.spawn((
+                    Text::new(""),
+                    text_font(14.0),
+                    TextColor(Color::WHITE),
+                    CraftingOutputText,
+                ));
+            });
+
+            root.spawn((
+                Text::new("Click a cell to add one item from the selected hotbar slot, click again to take it back."),
+                text_font(12.0),
+                TextColor(Color::srgba(0.8, 0.8, 0.8, 0.8)),
+            ));
+        })
+        .id();
+
+    crafting.root = Some(root);
+}
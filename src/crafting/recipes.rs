@@ -0,0 +1,367 @@
+//! Crafting recipes loaded from `recipes.yaml` and matched against the
+//! crafting grid.
+
+use crate::config::loader::{load_config, ConfigError};
+use crate::entity::{Inventory, ItemStack, ItemType};
+use crate::voxel::registry::VoxelRegistry;
+use crate::voxel::types::VoxelType;
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use thiserror::Error;
+
+pub const RECIPES_CONFIG_PATH: &str = "assets/config/recipes.yaml";
+
+/// Width and height of the crafting grid
+pub const GRID_SIZE: usize = 3;
+
+#[derive(Error, Debug)]
+pub enum RecipeError {
+    #[error("failed to read recipes: {0}")]
+    Config(#[from] ConfigError),
+    #[error("duplicate recipe id '{0}'")]
+    DuplicateId(String),
+    #[error("recipe '{recipe}': {reason}")]
+    Invalid { recipe: String, reason: String },
+}
+
+/// One entry of `recipes.yaml`
+#[derive(Deserialize, Clone, Debug)]
+pub struct RecipeDef {
+    pub id: String,
+    /// Rows of key characters for shaped recipes; spaces are empty cells
+    #[serde(default)]
+    pub pattern: Vec<String>,
+    #[serde(default)]
+    pub key: HashMap<String, String>,
+    /// Items for shapeless recipes, one per grid cell
+    #[serde(default)]
+    pub ingredients: Vec<String>,
+    pub result: String,
+    #[serde(default = "default_count")]
+    pub count: u32,
+    /// Voxel id that must be near the player
+    #[serde(default)]
+    pub station: Option<String>,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+#[derive(Deserialize)]
+struct RecipesFile {
+    recipes: Vec<RecipeDef>,
+}
+
+/// What goes into the grid
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecipeInput {
+    /// Cells row by row, trimmed to the pattern's bounds
+    Shaped {
+        width: usize,
+        height: usize,
+        cells: Vec<Option<ItemType>>,
+    },
+    /// Items in any arrangement
+    Shapeless(Vec<ItemType>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Recipe {
+    pub id: String,
+    pub input: RecipeInput,
+    pub result: ItemStack,
+    pub station: Option<VoxelType>,
+}
+
+impl Recipe {
+    pub fn matches(&self, grid: &CraftingGrid) -> bool {
+        match &self.input {
+            RecipeInput::Shaped {
+                width,
+                height,
+                cells,
+            } => {
+                let Some(((min_x, min_y), (max_x, max_y))) = grid.bounds() else {
+                    return false;
+                };
+                if max_x - min_x + 1 != *width || max_y - min_y + 1 != *height {
+                    return false;
+                }
+                let matches_with = |mirrored: bool| {
+                    (0..*height).all(|y| {
+                        (0..*width).all(|x| {
+                            let pattern_x = if mirrored { width - 1 - x } else { x };
+                            grid.get(min_x + x, min_y + y) == cells[y * width + pattern_x]
+                        })
+                    })
+                };
+                matches_with(false) || matches_with(true)
+            }
+            RecipeInput::Shapeless(ingredients) => {
+                let mut remaining = ingredients.clone();
+                for item in grid.items() {
+                    match remaining.iter().position(|ingredient| *ingredient == item) {
+                        Some(index) => {
+                            remaining.swap_remove(index);
+                        }
+                        None => return false,
+                    }
+                }
+                remaining.is_empty()
+            }
+        }
+    }
+}
+
+/// Items placed in the 3x3 crafting grid, one per cell
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CraftingGrid {
+    cells: [Option<ItemType>; GRID_SIZE * GRID_SIZE],
+}
+
+impl CraftingGrid {
+    pub fn get(&self, x: usize, y: usize) -> Option<ItemType> {
+        self.cells[y * GRID_SIZE + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, item: Option<ItemType>) {
+        self.cells[y * GRID_SIZE + x] = item;
+    }
+
+    /// Cells row by row
+    pub fn cells(&self) -> &[Option<ItemType>] {
+        &self.cells
+    }
+
+    pub fn cell_mut(&mut self, index: usize) -> &mut Option<ItemType> {
+        &mut self.cells[index]
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = ItemType> + '_ {
+        self.cells.iter().flatten().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items().next().is_none()
+    }
+
+    /// Remove and return everything in the grid
+    pub fn take_all(&mut self) -> Vec<ItemType> {
+        self.cells.iter_mut().filter_map(Option::take).collect()
+    }
+
+    /// Move everything into `inventory`; items that don't fit stay in their
+    /// cells. Returns how many were left behind.
+    pub fn return_to(&mut self, inventory: &mut Inventory) -> usize {
+        let mut left = 0;
+        for cell in self.cells.iter_mut() {
+            if let Some(item) = *cell {
+                if inventory.add_item(item) {
+                    *cell = None;
+                } else {
+                    left += 1;
+                }
+            }
+        }
+        left
+    }
+
+    /// Smallest and largest occupied cell as (x, y), `None` when empty
+    fn bounds(&self) -> Option<((usize, usize), (usize, usize))> {
+        let occupied = (0..GRID_SIZE * GRID_SIZE)
+            .filter(|index| self.cells[*index].is_some())
+            .map(|index| (index % GRID_SIZE, index / GRID_SIZE));
+        occupied.fold(None, |bounds, (x, y)| {
+            Some(match bounds {
+                None => ((x, y), (x, y)),
+                Some(((min_x, min_y), (max_x, max_y))) => {
+                    ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+                }
+            })
+        })
+    }
+}
+
+/// All known recipes
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RecipeBook {
+    recipes: Vec<Recipe>,
+}
+
+impl RecipeBook {
+    /// Load and validate recipes from a YAML file
+    pub fn load<P: AsRef<Path>>(path: P, registry: &VoxelRegistry) -> Result<Self, RecipeError> {
+        let file: RecipesFile = load_config(path)?;
+        Self::from_defs(file.recipes, registry)
+    }
+
+    /// Load from the default config path; a broken file leaves the book
+    /// empty so the error is noticed rather than half the recipes vanishing
+    pub fn load_or_empty(registry: &VoxelRegistry) -> Self {
+        match Self::load(RECIPES_CONFIG_PATH, registry) {
+            Ok(book) => {
+                info!("Loaded {} recipes from {}", book.len(), RECIPES_CONFIG_PATH);
+                book
+            }
+            Err(e) => {
+                error!("Failed to load recipes: {}. Crafting is disabled.", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn from_defs(defs: Vec<RecipeDef>, registry: &VoxelRegistry) -> Result<Self, RecipeError> {
+        let mut ids = HashSet::new();
+        let mut recipes = Vec::with_capacity(defs.len());
+
+        for def in defs {
+            if !ids.insert(def.id.clone()) {
+                return Err(RecipeError::DuplicateId(def.id));
+            }
+            let invalid = |reason: String| RecipeError::Invalid {
+                recipe: def.id.clone(),
+                reason,
+            };
+            let item = |name: &str| {
+                ItemType::from_name(name, registry).ok_or_else(|| invalid(format!("unknown item '{}'", name)))
+            };
+
+            let input = match (def.pattern.is_empty(), def.ingredients.is_empty()) {
+                (false, true) => parse_pattern(&def, registry).map_err(invalid)?,
+                (true, false) => {
+                    if !def.key.is_empty() {
+                        return Err(invalid("shapeless recipes take no key".to_string()));
+                    }
+                    if def.ingredients.len() > GRID_SIZE * GRID_SIZE {
+                        return Err(invalid(format!(
+                            "{} ingredients do not fit the {}x{} grid",
+                            def.ingredients.len(),
+                            GRID_SIZE,
+                            GRID_SIZE
+                        )));
+                    }
+                    let ingredients = def
+                        .ingredients
+                        .iter()
+                        .map(|name| item(name))
+                        .collect::<Result<_, _>>()?;
+                    RecipeInput::Shapeless(ingredients)
+                }
+                (false, false) => {
+                    return Err(invalid("give either a pattern or ingredients, not both".to_string()));
+                }
+                (true, true) => return Err(invalid("needs a pattern or ingredients".to_string())),
+            };
+
+            let result = item(&def.result)?;
+            if def.count == 0 || def.count > result.max_stack() {
+                return Err(invalid(format!(
+                    "count {} is outside 1..={} for '{}'",
+                    def.count,
+                    result.max_stack(),
+                    def.result
+                )));
+            }
+
+            let station = match &def.station {
+                None => None,
+                Some(name) => Some(
+                    registry
+                        .by_name(name)
+                        .filter(|voxel| registry.info(*voxel).solid)
+                        .ok_or_else(|| invalid(format!("unknown station block '{}'", name)))?,
+                ),
+            };
+
+            recipes.push(Recipe {
+                id: def.id.clone(),
+                input,
+                result: ItemStack {
+                    item: result,
+                    count: def.count,
+                },
+                station,
+            });
+        }
+
+        Ok(Self { recipes })
+    }
+
+    pub fn recipes(&self) -> &[Recipe] {
+        &self.recipes
+    }
+
+    pub fn len(&self) -> usize {
+        self.recipes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+
+    /// First recipe matching the grid, regardless of stations
+    pub fn find(&self, grid: &CraftingGrid) -> Option<&Recipe> {
+        self.recipes.iter().find(|recipe| recipe.matches(grid))
+    }
+}
+
+/// Check a shaped recipe's pattern and key and trim it to its bounds
+fn parse_pattern(def: &RecipeDef, registry: &VoxelRegistry) -> Result<RecipeInput, String> {
+    if def.pattern.len() > GRID_SIZE || def.pattern.iter().any(|row| row.chars().count() > GRID_SIZE) {
+        return Err(format!("pattern is larger than {}x{}", GRID_SIZE, GRID_SIZE));
+    }
+
+    let mut key = HashMap::new();
+    for (symbol, name) in &def.key {
+        let mut chars = symbol.chars();
+        let (Some(symbol), None) = (chars.next(), chars.next()) else {
+            return Err(format!("key '{}' must be a single character", symbol));
+        };
+        if symbol == ' ' {
+            return Err("space is reserved for empty cells".to_string());
+        }
+        let item = ItemType::from_name(name, registry).ok_or_else(|| format!("unknown item '{}'", name))?;
+        key.insert(symbol, item);
+    }
+
+    let rows: Vec<Vec<char>> = def.pattern.iter().map(|row| row.chars().collect()).collect();
+    let mut used = HashSet::new();
+    let mut occupied = Vec::new();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, symbol) in row.iter().enumerate() {
+            if *symbol == ' ' {
+                continue;
+            }
+            if !key.contains_key(symbol) {
+                return Err(format!("pattern uses '{}' which is not in the key", symbol));
+            }
+            used.insert(*symbol);
+            occupied.push((x, y));
+        }
+    }
+    if occupied.is_empty() {
+        return Err("pattern is empty".to_string());
+    }
+    if let Some(unused) = key.keys().find(|symbol| !used.contains(*symbol)) {
+        return Err(format!("key '{}' is not used in the pattern", unused));
+    }
+
+    let min_x = occupied.iter().map(|(x, _)| *x).min().unwrap_or(0);
+    let max_x = occupied.iter().map(|(x, _)| *x).max().unwrap_or(0);
+    let min_y = occupied.iter().map(|(_, y)| *y).min().unwrap_or(0);
+    let max_y = occupied.iter().map(|(_, y)| *y).max().unwrap_or(0);
+    let (width, height) = (max_x - min_x + 1, max_y - min_y + 1);
+
+    let mut cells = vec![None; width * height];
+    for (x, y) in occupied {
+        cells[(y - min_y) * width + (x - min_x)] = key.get(&rows[y][x]).copied();
+    }
+    Ok(RecipeInput::Shaped {
+        width,
+        height,
+        cells,
+    })
+}
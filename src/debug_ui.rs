@@ -1,7 +1,9 @@
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts, EguiPlugin};
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use crate::voxel::lod::MAX_LOD_LEVEL;
 use crate::voxel::plugin::LodSettings;
+use crate::voxel::visibility::{ChunkCullingSettings, ChunkVisibility};
 use crate::vegetation::VegetationConfig;
 
 #[derive(Resource, Default)]
@@ -48,6 +50,8 @@ fn debug_settings_ui(
     mut contexts: EguiContexts,
     state: Res<DebugUiState>,
     mut lod_settings: ResMut<LodSettings>,
+    mut culling: ResMut<ChunkCullingSettings>,
+    chunk_visibility: Res<ChunkVisibility>,
     veg_config: Option<ResMut<VegetationConfig>>,
 ) {
     if !state.show_settings {
@@ -57,7 +61,14 @@ fn debug_settings_ui(
     egui::Window::new("Game Tweaks").show(contexts.ctx_mut().ok().expect("Failed to get Egui context"), |ui| {
         ui.heading("LOD Settings");
         ui.add(egui::Slider::new(&mut lod_settings.high_detail_distance, 32.0..=512.0).text("High Detail Dist"));
-        ui.add(egui::Slider::new(&mut lod_settings.cull_distance, 64.0..=1024.0).text("Cull Dist"));
+        ui.add(egui::Slider::new(&mut lod_settings.cull_distance, 64.0..=4096.0).text("Cull Dist"));
+        ui.add(egui::Slider::new(&mut lod_settings.max_level, 1..=MAX_LOD_LEVEL).text("Max LOD Level"));
+
+        ui.separator();
+        ui.heading("Chunk Culling");
+        ui.checkbox(&mut culling.frustum, "Frustum");
+        ui.checkbox(&mut culling.occlusion, "Cave Occlusion");
+        ui.label(format!("Visible chunks: {}", chunk_visibility.visible_count()));
         
         ui.separator();
         if let Some(mut veg) = veg_config {
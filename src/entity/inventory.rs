@@ -1,35 +1,201 @@
+use crate::voxel::registry::VoxelRegistry;
+use crate::voxel::types::{ToolTier, ToolType, VoxelType};
 use bevy::prelude::*;
-use std::collections::HashMap;
+
+/// Number of inventory slots; the first `HOTBAR_SLOTS` form the hotbar
+pub const INVENTORY_SLOTS: usize = 36;
+
+/// Slots reachable from the hotbar (number keys 1-9)
+pub const HOTBAR_SLOTS: usize = 9;
 
 /// Types of items that can be collected
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ItemType {
     Fur,
+    /// A mined voxel that can be placed again
+    Block(VoxelType),
+    /// A crafted tool, used for mining while selected on the hotbar
+    Tool { kind: ToolType, tier: ToolTier },
+}
+
+impl ItemType {
+    /// Most items one slot can hold
+    pub fn max_stack(self) -> u32 {
+        match self {
+            ItemType::Fur => 16,
+            ItemType::Block(_) => 64,
+            ItemType::Tool { .. } => 1,
+        }
+    }
+
+    /// Name used in configs and the UI: "fur", a voxel id, or a tool as
+    /// "<tier>_<kind>" (e.g. "stone_pickaxe")
+    pub fn name(self) -> String {
+        match self {
+            ItemType::Fur => "fur".to_string(),
+            ItemType::Block(voxel) => voxel.name().to_string(),
+            ItemType::Tool { kind, tier } => format!("{}_{}", tier.name(), kind.name()),
+        }
+    }
+
+    /// Inverse of `name`
+    pub fn from_name(name: &str, registry: &VoxelRegistry) -> Option<Self> {
+        if name == "fur" {
+            return Some(ItemType::Fur);
+        }
+        if let Some((tier, kind)) = name.split_once('_') {
+            let tier = ToolTier::ALL.into_iter().find(|t| t.name() == tier);
+            let kind = [ToolType::Shovel, ToolType::Pickaxe]
+                .into_iter()
+                .find(|k| k.name() == kind);
+            if let (Some(tier), Some(kind)) = (tier, kind) {
+                return Some(ItemType::Tool { kind, tier });
+            }
+        }
+        registry
+            .by_name(name)
+            .filter(|voxel| *voxel != VoxelType::Air)
+            .map(ItemType::Block)
+    }
+}
+
+/// Items of one type sharing a slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: ItemType,
+    pub count: u32,
 }
 
 /// Player inventory resource
-#[derive(Resource, Default, Debug)]
+#[derive(Resource, Debug)]
 pub struct Inventory {
-    pub items: HashMap<ItemType, u32>,
+    slots: Vec<Option<ItemStack>>,
+    /// Selected hotbar slot
+    selected: usize,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            slots: vec![None; INVENTORY_SLOTS],
+            selected: 0,
+        }
+    }
 }
 
 impl Inventory {
-    pub fn add_item(&mut self, item_type: ItemType) {
-        *self.items.entry(item_type).or_insert(0) += 1;
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    pub fn hotbar(&self) -> &[Option<ItemStack>] {
+        &self.slots[..HOTBAR_SLOTS]
+    }
+
+    pub fn add_item(&mut self, item_type: ItemType) -> bool {
+        self.add_items(item_type, 1) == 0
+    }
+
+    /// Add items, topping up existing stacks before starting new ones.
+    /// Returns how many did not fit.
+    pub fn add_items(&mut self, item_type: ItemType, mut count: u32) -> u32 {
+        let max = item_type.max_stack();
+        for stack in self.slots.iter_mut().flatten() {
+            if count == 0 {
+                return 0;
+            }
+            if stack.item == item_type && stack.count < max {
+                let added = count.min(max - stack.count);
+                stack.count += added;
+                count -= added;
+            }
+        }
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            if count == 0 {
+                return 0;
+            }
+            let added = count.min(max);
+            *slot = Some(ItemStack {
+                item: item_type,
+                count: added,
+            });
+            count -= added;
+        }
+        count
+    }
+
+    /// How many more of an item fit
+    pub fn space_for(&self, item_type: ItemType) -> u32 {
+        let max = item_type.max_stack();
+        self.slots
+            .iter()
+            .map(|slot| match slot {
+                None => max,
+                Some(stack) if stack.item == item_type => max.saturating_sub(stack.count),
+                Some(_) => 0,
+            })
+            .sum()
     }
 
     pub fn get_count(&self, item_type: ItemType) -> u32 {
-        *self.items.get(&item_type).unwrap_or(&0)
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.item == item_type)
+            .map(|stack| stack.count)
+            .sum()
     }
 
-    pub fn remove_item(&mut self, item_type: ItemType, count: u32) -> bool {
-        if let Some(current) = self.items.get_mut(&item_type) {
-            if *current >= count {
-                *current -= count;
-                return true;
+    /// Remove `count` items, taking from the last stacks first so the
+    /// hotbar keeps its items. Nothing is removed if there are too few.
+    pub fn remove_item(&mut self, item_type: ItemType, mut count: u32) -> bool {
+        if self.get_count(item_type) < count {
+            return false;
+        }
+        for slot in self.slots.iter_mut().rev() {
+            if count == 0 {
+                break;
+            }
+            if let Some(stack) = slot.filter(|stack| stack.item == item_type) {
+                let removed = count.min(stack.count);
+                count -= removed;
+                *slot = (stack.count > removed).then_some(ItemStack {
+                    count: stack.count - removed,
+                    ..stack
+                });
             }
         }
-        false
+        true
+    }
+
+    pub fn selected_slot(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select_slot(&mut self, index: usize) {
+        if index < HOTBAR_SLOTS {
+            self.selected = index;
+        }
+    }
+
+    /// Move the hotbar selection, wrapping around at either end
+    pub fn scroll_selection(&mut self, steps: i32) {
+        self.selected = (self.selected as i32 + steps).rem_euclid(HOTBAR_SLOTS as i32) as usize;
+    }
+
+    pub fn selected_stack(&self) -> Option<ItemStack> {
+        self.slots[self.selected]
+    }
+
+    /// Take one item from the selected hotbar slot
+    pub fn take_selected(&mut self) -> Option<ItemType> {
+        let slot = &mut self.slots[self.selected];
+        let stack = (*slot)?;
+        *slot = (stack.count > 1).then_some(ItemStack {
+            count: stack.count - 1,
+            ..stack
+        });
+        Some(stack.item)
     }
 }
 
@@ -2,11 +2,13 @@ pub mod wolf;
 pub mod rabbit;
 pub mod inventory;
 
+use crate::navigation::NavigationPlugin;
+use crate::player::Player;
 use bevy::prelude::*;
 
 pub use wolf::{Wolf, WolfSpawned};
 pub use rabbit::{Rabbit, RabbitSpawned};
-pub use inventory::{Inventory, ItemType, ItemDrop};
+pub use inventory::{Inventory, ItemDrop, ItemStack, ItemType};
 
 /// Component for entities with health
 #[derive(Component)]
@@ -36,10 +38,11 @@ impl Health {
 #[derive(Component)]
 pub struct Dead;
 
-/// System to handle entity death
+/// System to handle entity death; the player dies through `crate::player::vitals`
+#[allow(clippy::type_complexity)]
 pub fn handle_death(
     mut commands: Commands,
-    query: Query<(Entity, &Health, &Transform), (Without<Dead>, Changed<Health>)>,
+    query: Query<(Entity, &Health, &Transform), (Without<Dead>, Without<Player>, Changed<Health>)>,
 ) {
     for (entity, health, transform) in query.iter() {
         if health.is_dead() {
@@ -82,25 +85,40 @@ pub fn despawn_dead(
     }
 }
 
-/// Plugin for entity system
-pub struct EntityPlugin;
+/// Entity spawning, AI, death and drops; runs on the dedicated server too
+pub struct EntitySimulationPlugin;
 
-impl Plugin for EntityPlugin {
+impl Plugin for EntitySimulationPlugin {
     fn build(&self, app: &mut App) {
         app
+            .add_plugins(NavigationPlugin)
             .init_resource::<Inventory>()
             .init_resource::<WolfSpawned>()
             .init_resource::<RabbitSpawned>()
-            .add_systems(Startup, rabbit::setup_rabbit_assets)
             .add_systems(Update, (
                 wolf::spawn_wolves,
                 wolf::animate_wolves,
                 rabbit::spawn_rabbits,
                 rabbit::animate_rabbits,
-                rabbit::fix_rabbit_textures,
                 handle_death,
                 process_item_drops,
                 despawn_dead.after(process_item_drops),
             ));
     }
 }
+
+/// Plugin for entity system
+pub struct EntityPlugin;
+
+impl Plugin for EntityPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(EntitySimulationPlugin)
+            .add_systems(Startup, rabbit::setup_rabbit_assets)
+            .add_systems(Update, (
+                wolf::attach_wolf_visuals.after(wolf::spawn_wolves),
+                rabbit::attach_rabbit_visuals.after(rabbit::spawn_rabbits),
+                rabbit::fix_rabbit_textures,
+            ));
+    }
+}
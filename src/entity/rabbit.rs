@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 use bevy_mesh::VertexAttributeValues;
+use crate::navigation::{wander_path, NavAgent, NavLimits, NavMesh};
 use crate::voxel::world::VoxelWorld;
 use crate::voxel::types::{VoxelType, Voxel};
 use super::Health;
@@ -8,6 +9,7 @@ use super::Health;
 #[derive(Component)]
 pub struct Rabbit {
     pub hop_timer: f32,
+    /// Horizontal velocity of the current hop, scaled to land on the next waypoint
     pub hop_direction: Vec3,
     pub is_hopping: bool,
     pub hop_progress: f32,
@@ -230,22 +232,34 @@ pub fn fix_rabbit_textures(
     }
 }
 
+/// Give spawned rabbits their model once it is loading; the server runs
+/// without one
+pub fn attach_rabbit_visuals(
+    mut commands: Commands,
+    rabbits: Query<Entity, (With<Rabbit>, Without<SceneRoot>)>,
+    handles: Option<Res<RabbitHandles>>,
+) {
+    let Some(handles) = handles else {
+        return;
+    };
+
+    for entity in rabbits.iter() {
+        commands
+            .entity(entity)
+            .insert((SceneRoot(handles.scene.clone()), Visibility::Visible));
+    }
+}
+
 /// Spawn rabbits on the terrain
 pub fn spawn_rabbits(
     mut commands: Commands,
     world: Res<VoxelWorld>,
     mut spawned: ResMut<RabbitSpawned>,
-    handles: Option<Res<RabbitHandles>>,
 ) {
     if spawned.spawned {
         return;
     }
 
-    // Wait for rabbit assets to be loaded
-    let Some(handles) = handles else {
-        return;
-    };
-
     // Wait for chunks to be fully generated
     spawned.frame_counter += 1;
     if spawned.frame_counter < 120 {
@@ -311,15 +325,12 @@ pub fn spawn_rabbits(
                             info!("  Spawning rabbit #{} at {:?} on {:?}", rabbit_count + 1, spawn_pos, current_voxel);
 
                             commands.spawn((
-                                SceneRoot(handles.scene.clone()),
                                 Transform::from_translation(spawn_pos)
                                     .with_rotation(Quat::from_rotation_y(rotation))
                                     .with_scale(Vec3::splat(0.5)),  // Scale down the model
                                 GlobalTransform::default(),
-                                Visibility::Visible,
-                                InheritedVisibility::VISIBLE,
-                                ViewVisibility::default(),
                                 Rabbit::default(),
+                                NavAgent::new(RABBIT_NAV_LIMITS),
                                 Health::new(10.0),
                             ));
                             rabbit_count += 1;
@@ -352,15 +363,28 @@ fn simple_hash(x: i32, z: i32) -> f32 {
     (n as u32 as f32) / (u32::MAX as f32)
 }
 
-/// Animate rabbits with hopping behavior
+/// Rabbits hop up single blocks but won't jump off anything taller than two
+const RABBIT_NAV_LIMITS: NavLimits = NavLimits {
+    max_step_up: 1,
+    max_drop: 2,
+};
+
+/// Blocks a rabbit wanders from where it stands
+const WANDER_DISTANCE: f32 = 8.0;
+
+/// Blocks covered by a full-length hop
+const HOP_LENGTH: f32 = 2.0 / 3.0;
+
+/// Animate rabbits hopping along navmesh paths
 pub fn animate_rabbits(
     time: Res<Time>,
     world: Res<VoxelWorld>,
-    mut rabbits: Query<(&mut Rabbit, &mut Transform), Without<super::Dead>>,
+    nav: Res<NavMesh>,
+    mut rabbits: Query<(&mut Rabbit, &mut NavAgent, &mut Transform), Without<super::Dead>>,
 ) {
     let dt = time.delta_secs();
 
-    for (mut rabbit, mut transform) in rabbits.iter_mut() {
+    for (mut rabbit, mut agent, mut transform) in rabbits.iter_mut() {
         rabbit.hop_timer -= dt;
 
         // Apply horizontal movement if hopping
@@ -380,23 +404,37 @@ pub fn animate_rabbits(
                 transform.translation.z += forward_motion.z;
             }
         } else if rabbit.hop_timer <= 0.0 {
-            // Start new hop
-            rabbit.is_hopping = true;
-            rabbit.hop_progress = 0.0;
-
-            let angle = simple_hash(
-                (time.elapsed_secs() * 100.0) as i32,
-                (transform.translation.x * 50.0) as i32,
-            ) * std::f32::consts::TAU;
+            agent.clear_stale_path(&nav);
+            if !agent.has_path() {
+                let angle = simple_hash(
+                    (time.elapsed_secs() * 100.0) as i32,
+                    (transform.translation.x * 50.0) as i32,
+                ) * std::f32::consts::TAU;
+
+                let limits = agent.limits;
+                if let Some(path) = wander_path(&nav, transform.translation, angle, WANDER_DISTANCE, limits) {
+                    agent.set_path(path, &nav);
+                }
+            }
 
-            rabbit.hop_direction = Vec3::new(angle.cos(), 0.0, angle.sin());
+            // Start new hop toward the next waypoint
+            if let Some(waypoint) = agent.steer(transform.translation, 0.1) {
+                let offset = Vec3::new(
+                    waypoint.x - transform.translation.x,
+                    0.0,
+                    waypoint.z - transform.translation.z,
+                );
+                rabbit.is_hopping = true;
+                rabbit.hop_progress = 0.0;
+                rabbit.hop_direction = (offset / HOP_LENGTH).clamp_length_max(1.0);
 
-            // Rotate to face hop direction
-            if rabbit.hop_direction.length() > 0.01 {
+                // Rotate to face hop direction
                 let target_rotation = Quat::from_rotation_y(
-                    rabbit.hop_direction.z.atan2(rabbit.hop_direction.x) - std::f32::consts::FRAC_PI_2
+                    offset.z.atan2(offset.x) - std::f32::consts::FRAC_PI_2
                 );
                 transform.rotation = target_rotation;
+            } else {
+                rabbit.hop_timer = 1.0;
             }
         }
 
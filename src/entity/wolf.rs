@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 use bevy::asset::RenderAssetUsages;
 use bevy_mesh::{Indices, PrimitiveTopology};
+use crate::navigation::{wander_path, NavAgent, NavMesh};
 use crate::voxel::world::VoxelWorld;
 use crate::voxel::types::VoxelType;
 use super::Health;
@@ -9,6 +10,7 @@ use super::Health;
 #[derive(Component)]
 pub struct Wolf {
     pub wander_timer: f32,
+    /// Direction the wolf is walking toward its next waypoint
     pub wander_direction: Vec3,
 }
 
@@ -31,8 +33,6 @@ pub struct WolfSpawned {
 /// Spawn wolves on the terrain
 pub fn spawn_wolves(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
     world: Res<VoxelWorld>,
     mut spawned: ResMut<WolfSpawned>,
 ) {
@@ -87,17 +87,6 @@ pub fn spawn_wolves(
     }
     info!("==================");
 
-    // Create wolf mesh
-    let wolf_mesh = meshes.add(create_wolf_mesh());
-
-    // Create wolf material - Realistic gray-brown fur
-    let wolf_material = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.45, 0.40, 0.35), // Natural gray-brown
-        perceptual_roughness: 0.9, // Furry texture
-        metallic: 0.0,
-        ..default()
-    });
-
     let mut wolf_count = 0;
     let max_wolves = 50;
     let mut positions_checked = 0;
@@ -162,15 +151,11 @@ pub fn spawn_wolves(
                     );
 
                     commands.spawn((
-                        Mesh3d(wolf_mesh.clone()),
-                        MeshMaterial3d(wolf_material.clone()),
                         Transform::from_translation(spawn_pos)
                             .with_rotation(Quat::from_rotation_y(rotation)),
                         GlobalTransform::default(),
-                        Visibility::Visible,
-                        InheritedVisibility::VISIBLE,
-                        ViewVisibility::default(),
                         Wolf::default(),
+                        NavAgent::default(),
                         Health::new(30.0),
                     ));
                     wolf_count += 1;
@@ -196,6 +181,40 @@ pub fn spawn_wolves(
     }
 }
 
+/// Give newly spawned wolves their mesh; the server runs without one
+pub fn attach_wolf_visuals(
+    mut commands: Commands,
+    wolves: Query<Entity, (With<Wolf>, Without<Mesh3d>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut handles: Local<Option<(Handle<Mesh>, Handle<StandardMaterial>)>>,
+) {
+    if wolves.is_empty() {
+        return;
+    }
+
+    let (wolf_mesh, wolf_material) = handles
+        .get_or_insert_with(|| {
+            // Realistic gray-brown fur
+            let material = materials.add(StandardMaterial {
+                base_color: Color::srgb(0.45, 0.40, 0.35),
+                perceptual_roughness: 0.9, // Furry texture
+                metallic: 0.0,
+                ..default()
+            });
+            (meshes.add(create_wolf_mesh()), material)
+        })
+        .clone();
+
+    for entity in wolves.iter() {
+        commands.entity(entity).insert((
+            Mesh3d(wolf_mesh.clone()),
+            MeshMaterial3d(wolf_material.clone()),
+            Visibility::Visible,
+        ));
+    }
+}
+
 /// Create a simple wolf mesh (box-based model)
 fn create_wolf_mesh() -> Mesh {
     let mut positions = Vec::new();
@@ -294,18 +313,30 @@ fn simple_hash(x: i32, z: i32) -> f32 {
     (n as u32 as f32) / (u32::MAX as f32)
 }
 
-/// Animate wolves with simple idle behavior
+/// Blocks a wolf wanders from where it stands
+const WANDER_DISTANCE: f32 = 12.0;
+
+/// Walking speed in blocks per second
+const WOLF_SPEED: f32 = 1.5;
+
+/// Wolves stand half a block above the cell their feet are in
+const WOLF_FEET_OFFSET: f32 = 0.5;
+
+/// Walk wolves along navmesh paths to wander goals
 pub fn animate_wolves(
     time: Res<Time>,
-    mut wolves: Query<(&mut Wolf, &mut Transform), Without<super::Dead>>,
+    nav: Res<NavMesh>,
+    mut wolves: Query<(&mut Wolf, &mut NavAgent, &mut Transform), Without<super::Dead>>,
 ) {
     let dt = time.delta_secs();
 
-    for (mut wolf, mut transform) in wolves.iter_mut() {
+    for (mut wolf, mut agent, mut transform) in wolves.iter_mut() {
         wolf.wander_timer -= dt;
 
-        // Pick new wander direction every few seconds
-        if wolf.wander_timer <= 0.0 {
+        agent.clear_stale_path(&nav);
+
+        // Pick a new goal every few seconds once the last one is reached
+        if wolf.wander_timer <= 0.0 && !agent.has_path() {
             wolf.wander_timer = 2.0 + simple_hash(
                 (transform.translation.x * 100.0) as i32,
                 (transform.translation.z * 100.0) as i32,
@@ -316,18 +347,31 @@ pub fn animate_wolves(
                 (transform.translation.x * 50.0) as i32,
             ) * std::f32::consts::TAU;
 
-            wolf.wander_direction = Vec3::new(angle.cos(), 0.0, angle.sin());
+            let limits = agent.limits;
+            if let Some(path) = wander_path(&nav, transform.translation, angle, WANDER_DISTANCE, limits) {
+                agent.set_path(path, &nav);
+            }
         }
 
-        // Move slowly in wander direction
-        transform.translation += wolf.wander_direction * dt * 0.5;
+        let feet = transform.translation - Vec3::Y * WOLF_FEET_OFFSET;
+        let Some(waypoint) = agent.steer(feet, 0.05) else {
+            wolf.wander_direction = Vec3::ZERO;
+            continue;
+        };
+
+        // Walk toward the waypoint, climbing or dropping to its height on the way
+        let to_waypoint = waypoint - feet;
+        let horizontal = Vec3::new(to_waypoint.x, 0.0, to_waypoint.z);
+        wolf.wander_direction = horizontal.normalize_or_zero();
+        transform.translation += wolf.wander_direction * (WOLF_SPEED * dt).min(horizontal.length());
+        transform.translation.y += to_waypoint.y.clamp(-WOLF_SPEED * 4.0 * dt, WOLF_SPEED * 2.0 * dt);
 
         // Rotate to face movement direction
         if wolf.wander_direction.length() > 0.01 {
             let target_rotation = Quat::from_rotation_y(
                 wolf.wander_direction.z.atan2(wolf.wander_direction.x) - std::f32::consts::FRAC_PI_2
             );
-            transform.rotation = transform.rotation.slerp(target_rotation, dt * 2.0);
+            transform.rotation = transform.rotation.slerp(target_rotation, dt * 4.0);
         }
     }
 }
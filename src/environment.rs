@@ -3,6 +3,8 @@ use bevy::pbr::{DistanceFog, FogFalloff};
 use bevy::prelude::*;
 use bevy_water::*;
 
+use crate::chat::commands::{ChatCommand, ChatCommandAppExt};
+
 /// Water level constant - matches terrain generation
 pub const SEA_LEVEL: f32 = 18.0;
 
@@ -52,6 +54,17 @@ impl Default for AtmosphereSettings {
     }
 }
 
+impl AtmosphereSettings {
+    /// Clock time in hours (0..24); the cycle starts at sunrise, 6:00
+    pub fn hour(&self) -> f32 {
+        (self.time / self.day_length * 24.0 + 6.0).rem_euclid(24.0)
+    }
+
+    pub fn set_hour(&mut self, hour: f32) {
+        self.time = ((hour - 6.0) / 24.0).rem_euclid(1.0) * self.day_length;
+    }
+}
+
 #[derive(Component)]
 pub struct Sun;
 
@@ -78,8 +91,75 @@ impl Plugin for AtmospherePlugin {
                 Startup,
                 (setup_atmosphere, seed_atmosphere.after(setup_atmosphere)),
             )
-            .add_systems(Update, animate_atmosphere);
+            .add_systems(Update, animate_atmosphere)
+            .add_chat_command(TimeCommand);
+    }
+}
+
+/// `/time [day|noon|night|midnight|<hour>|stop|start]`
+pub struct TimeCommand;
+
+impl ChatCommand for TimeCommand {
+    fn name(&self) -> &str {
+        "time"
+    }
+
+    fn usage(&self) -> &str {
+        "[day|noon|night|midnight|<hour>|stop|start]"
     }
+
+    fn description(&self) -> &str {
+        "Show or set the time of day, or pause the day cycle"
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        let mut settings = world
+            .get_resource_mut::<AtmosphereSettings>()
+            .ok_or("There is no sky here")?;
+        let hour = match args.first().copied() {
+            None => {
+                return Ok(format!(
+                    "It is {} (day cycle {})",
+                    format_hour(settings.hour()),
+                    if settings.cycle_enabled { "running" } else { "stopped" }
+                ));
+            }
+            Some("stop") => {
+                settings.cycle_enabled = false;
+                return Ok("Day cycle stopped".to_string());
+            }
+            Some("start") => {
+                settings.cycle_enabled = true;
+                return Ok("Day cycle running".to_string());
+            }
+            Some("day") => 8.0,
+            Some("noon") => 12.0,
+            Some("night") => 20.0,
+            Some("midnight") => 0.0,
+            Some(other) => parse_hour(other)
+                .ok_or_else(|| format!("'{}' is not a time; try 14 or 14:30", other))?,
+        };
+
+        settings.set_hour(hour);
+        Ok(format!("Time set to {}", format_hour(settings.hour())))
+    }
+}
+
+/// `14`, `14.5` or `14:30`
+fn parse_hour(text: &str) -> Option<f32> {
+    let hour = match text.split_once(':') {
+        Some((hours, minutes)) => {
+            let minutes: f32 = minutes.parse().ok().filter(|m| (0.0..60.0).contains(m))?;
+            hours.parse::<f32>().ok()? + minutes / 60.0
+        }
+        None => text.parse().ok()?,
+    };
+    (0.0..24.0).contains(&hour).then_some(hour)
+}
+
+fn format_hour(hour: f32) -> String {
+    let minutes = (hour * 60.0).round() as u32 % (24 * 60);
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
 }
 
 fn setup_atmosphere(mut commands: Commands) {
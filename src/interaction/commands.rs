@@ -0,0 +1,157 @@
+use super::mining::HeldTool;
+use super::{GameMode, HeldBlock};
+use crate::chat::commands::ChatCommand;
+use crate::entity::{Inventory, ItemType};
+use crate::voxel::registry::VoxelRegistry;
+use crate::voxel::types::{ToolTier, ToolType, VoxelType};
+use bevy::prelude::*;
+
+/// `/give <block>` gives a stack of a block type, or holds it in creative mode
+pub struct GiveCommand;
+
+impl ChatCommand for GiveCommand {
+    fn name(&self) -> &str {
+        "give"
+    }
+
+    fn usage(&self) -> &str {
+        "<block>"
+    }
+
+    fn description(&self) -> &str {
+        "Get a stack of a block type (hold it in creative), e.g. /give sand"
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        let [name] = args else {
+            return Err("Usage: /give <block>".to_string());
+        };
+        let registry = world
+            .get_resource::<VoxelRegistry>()
+            .ok_or("Block types are not loaded")?;
+        let voxel = registry
+            .by_name(&name.to_ascii_lowercase())
+            .filter(|voxel| *voxel != VoxelType::Air)
+            .ok_or_else(|| {
+                let names: Vec<&str> = registry
+                    .placeable()
+                    .filter(|(voxel, _)| *voxel != VoxelType::Air)
+                    .map(|(_, info)| info.name.as_str())
+                    .collect();
+                format!("Unknown block '{}'; try {}", name, names.join(", "))
+            })?;
+        let block_name = registry.info(voxel).name.clone();
+
+        // Survival puts a full stack in the inventory instead
+        if world.get_resource::<GameMode>() == Some(&GameMode::Survival) {
+            let item = ItemType::Block(voxel);
+            let count = item.max_stack();
+            let lost = world
+                .get_resource_or_init::<Inventory>()
+                .add_items(item, count);
+            if lost == count {
+                return Err("Inventory is full".to_string());
+            }
+            return Ok(format!("Gave {} {}", count - lost, block_name));
+        }
+
+        world.get_resource_or_init::<HeldBlock>().block_type = voxel;
+        Ok(format!("Holding {}", block_name))
+    }
+}
+
+/// `/tool <kind> [tier]` gives a tool, or changes the one used for mining in
+/// creative mode
+pub struct ToolCommand;
+
+impl ChatCommand for ToolCommand {
+    fn name(&self) -> &str {
+        "tool"
+    }
+
+    fn usage(&self) -> &str {
+        "<pickaxe|shovel|none> [wood|stone|iron]"
+    }
+
+    fn description(&self) -> &str {
+        "Get a mining tool (hold it in creative), e.g. /tool shovel iron"
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        let usage = || format!("Usage: /tool {}", self.usage());
+        let (kind, tier) = match args {
+            [kind] => (*kind, None),
+            [kind, tier] => (*kind, Some(*tier)),
+            _ => return Err(usage()),
+        };
+        let kind = match kind.to_ascii_lowercase().as_str() {
+            "pickaxe" => ToolType::Pickaxe,
+            "shovel" => ToolType::Shovel,
+            "none" => ToolType::None,
+            _ => return Err(usage()),
+        };
+
+        let tier = match tier.map(|tier| tier.to_ascii_lowercase()).as_deref() {
+            None => None,
+            Some("wood") => Some(ToolTier::Wood),
+            Some("stone") => Some(ToolTier::Stone),
+            Some("iron") => Some(ToolTier::Iron),
+            Some(_) => return Err(usage()),
+        };
+
+        // Survival mines with the tool selected on the hotbar, so the tool
+        // goes into the inventory
+        if world.get_resource::<GameMode>() == Some(&GameMode::Survival) {
+            if kind == ToolType::None {
+                return Err("Select an empty hotbar slot to dig bare-handed".to_string());
+            }
+            let item = ItemType::Tool {
+                kind,
+                tier: tier.unwrap_or_default(),
+            };
+            if !world.get_resource_or_init::<Inventory>().add_item(item) {
+                return Err("Inventory is full".to_string());
+            }
+            return Ok(format!("Gave {}", item.name()));
+        }
+
+        let mut tool = world.get_resource_or_init::<HeldTool>();
+        tool.kind = kind;
+        if let Some(tier) = tier {
+            tool.tier = tier;
+        }
+        Ok(format!("Holding {:?} ({:?})", tool.kind, tool.tier))
+    }
+}
+
+/// `/gamemode <survival|creative>` switches between using the inventory and
+/// free building
+pub struct GameModeCommand;
+
+impl ChatCommand for GameModeCommand {
+    fn name(&self) -> &str {
+        "gamemode"
+    }
+
+    fn usage(&self) -> &str {
+        "<survival|creative>"
+    }
+
+    fn description(&self) -> &str {
+        "Switch between survival and creative mode"
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        let mode = match args {
+            [mode] => match mode.to_ascii_lowercase().as_str() {
+                "survival" => GameMode::Survival,
+                "creative" => GameMode::Creative,
+                _ => return Err(format!("Usage: /gamemode {}", self.usage())),
+            },
+            _ => return Err(format!("Usage: /gamemode {}", self.usage())),
+        };
+
+        world.insert_resource(mode);
+        Ok(format!("Game mode set to {:?}", mode))
+    }
+}
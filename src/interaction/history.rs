@@ -0,0 +1,339 @@
+//! Reversible block edits with grouped undo/redo.
+//!
+//! Every edit made by the interaction systems goes through [`EditHistory`],
+//! which applies it to the [`VoxelWorld`] and records the voxel it replaced.
+//! Edits made between [`EditHistory::begin`] and [`EditHistory::commit`] are
+//! undone and redone as one step.
+
+use super::{mark_neighbors_dirty, DirtyChunks, EditMode, GameMode};
+use crate::voxel::persistence::{ActiveWorld, SaveSlot};
+use crate::voxel::types::VoxelType;
+use crate::voxel::world::VoxelWorld;
+use bevy::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+/// Number of undo steps kept by default
+pub const DEFAULT_HISTORY_DEPTH: usize = 256;
+
+/// A single voxel change
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VoxelEdit {
+    pub position: IVec3,
+    pub old: VoxelType,
+    pub new: VoxelType,
+}
+
+/// Edits that are undone and redone together
+#[derive(Clone, Debug, Default)]
+pub struct EditTransaction {
+    pub label: String,
+    pub edits: Vec<VoxelEdit>,
+}
+
+impl EditTransaction {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            edits: Vec::new(),
+        }
+    }
+
+    /// Collapse repeated edits of a position into one and drop edits that
+    /// end where they started, so a block moved back to its origin is a no-op
+    fn coalesce(&mut self) {
+        let mut merged: Vec<VoxelEdit> = Vec::with_capacity(self.edits.len());
+        let mut index: HashMap<IVec3, usize> = HashMap::with_capacity(self.edits.len());
+        for edit in self.edits.drain(..) {
+            match index.get(&edit.position) {
+                Some(&i) => merged[i].new = edit.new,
+                None => {
+                    index.insert(edit.position, merged.len());
+                    merged.push(edit);
+                }
+            }
+        }
+        merged.retain(|edit| edit.old != edit.new);
+        self.edits = merged;
+    }
+}
+
+/// Outcome of an undo or redo step
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryStep {
+    pub label: String,
+    /// Edits written back to the world
+    pub applied: usize,
+    /// Edits whose chunk is no longer loaded
+    pub skipped: usize,
+}
+
+/// Result of applying a batch of voxel changes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EditSummary {
+    /// Voxels that changed type
+    pub changed: usize,
+    /// Changes dropped because the voxel isn't loaded or can't be edited
+    pub skipped: usize,
+    /// Chunks marked dirty for remeshing
+    pub chunks: usize,
+}
+
+/// Bounded undo/redo stacks of block edits
+#[derive(Resource)]
+pub struct EditHistory {
+    undo: VecDeque<EditTransaction>,
+    redo: Vec<EditTransaction>,
+    open: Option<EditTransaction>,
+    max_depth: usize,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_DEPTH)
+    }
+}
+
+impl EditHistory {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            open: None,
+            max_depth: max_depth.max(1),
+        }
+    }
+
+    /// Start grouping edits into one undo step; edits made while a
+    /// transaction is already open join that transaction
+    pub fn begin(&mut self, label: impl Into<String>) {
+        if self.open.is_none() {
+            self.open = Some(EditTransaction::new(label));
+        }
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Close the open transaction and push it onto the undo stack
+    pub fn commit(&mut self) {
+        if let Some(transaction) = self.open.take() {
+            self.push(transaction);
+        }
+    }
+
+    /// Set a voxel and record the change. Returns false when the chunk isn't
+    /// loaded or the voxel already has that type.
+    pub fn set_voxel(&mut self, world: &mut VoxelWorld, position: IVec3, voxel: VoxelType) -> bool {
+        let Some(old) = world.get_voxel(position) else {
+            return false;
+        };
+        if old == voxel || !world.set_voxel(position, voxel) {
+            return false;
+        }
+        mark_neighbors_dirty(world, position);
+
+        self.record(vec![VoxelEdit {
+            position,
+            old,
+            new: voxel,
+        }]);
+        true
+    }
+
+    /// Set many voxels and record them, marking every affected chunk dirty
+    /// once at the end rather than once per voxel
+    pub fn set_voxels(
+        &mut self,
+        world: &mut VoxelWorld,
+        changes: impl IntoIterator<Item = (IVec3, VoxelType)>,
+    ) -> EditSummary {
+        let mut dirty = DirtyChunks::default();
+        let mut edits = Vec::new();
+        let mut summary = EditSummary::default();
+
+        for (position, voxel) in changes {
+            match world.get_voxel(position) {
+                Some(old) if old == voxel => {}
+                Some(old) if world.set_voxel(position, voxel) => {
+                    dirty.add(position);
+                    edits.push(VoxelEdit {
+                        position,
+                        old,
+                        new: voxel,
+                    });
+                }
+                _ => summary.skipped += 1,
+            }
+        }
+
+        dirty.mark(world);
+        summary.changed = edits.len();
+        summary.chunks = dirty.len();
+        self.record(edits);
+        summary
+    }
+
+    /// Apply one edit as its own labelled undo step, or as part of the open
+    /// transaction if there is one
+    pub fn edit(
+        &mut self,
+        world: &mut VoxelWorld,
+        label: &str,
+        position: IVec3,
+        voxel: VoxelType,
+    ) -> bool {
+        if self.in_transaction() {
+            return self.set_voxel(world, position, voxel);
+        }
+        self.begin(label);
+        let changed = self.set_voxel(world, position, voxel);
+        self.commit();
+        changed
+    }
+
+    /// Revert the most recent step
+    pub fn undo(&mut self, world: &mut VoxelWorld) -> Option<HistoryStep> {
+        self.commit();
+        let transaction = self.undo.pop_back()?;
+        let step = Self::apply(
+            world,
+            transaction.edits.iter().rev().map(|e| (e.position, e.old)),
+        );
+        let label = transaction.label.clone();
+        self.redo.push(transaction);
+        Some(HistoryStep { label, ..step })
+    }
+
+    /// Reapply the most recently undone step
+    pub fn redo(&mut self, world: &mut VoxelWorld) -> Option<HistoryStep> {
+        self.commit();
+        let transaction = self.redo.pop()?;
+        let step = Self::apply(world, transaction.edits.iter().map(|e| (e.position, e.new)));
+        let label = transaction.label.clone();
+        self.undo.push_back(transaction);
+        Some(HistoryStep { label, ..step })
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.open.as_ref().is_some_and(|t| !t.edits.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Forget all history, e.g. when another world is loaded
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.open = None;
+    }
+
+    fn record(&mut self, edits: Vec<VoxelEdit>) {
+        match self.open.as_mut() {
+            Some(transaction) => transaction.edits.extend(edits),
+            None => self.push(EditTransaction {
+                label: "Edit".to_string(),
+                edits,
+            }),
+        }
+    }
+
+    fn push(&mut self, mut transaction: EditTransaction) {
+        transaction.coalesce();
+        if transaction.edits.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push_back(transaction);
+        while self.undo.len() > self.max_depth {
+            self.undo.pop_front();
+        }
+    }
+
+    fn apply(
+        world: &mut VoxelWorld,
+        changes: impl Iterator<Item = (IVec3, VoxelType)>,
+    ) -> HistoryStep {
+        let mut dirty = DirtyChunks::default();
+        let mut step = HistoryStep {
+            label: String::new(),
+            applied: 0,
+            skipped: 0,
+        };
+        for (position, voxel) in changes {
+            if world.set_voxel(position, voxel) {
+                dirty.add(position);
+                step.applied += 1;
+            } else {
+                step.skipped += 1;
+            }
+        }
+        dirty.mark(world);
+        step
+    }
+}
+
+/// Undo with Ctrl+Z, redo with Ctrl+Y or Ctrl+Shift+Z. Only in creative
+/// mode or edit mode: survival edits are paid for with items.
+pub fn undo_redo_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    game_mode: Res<GameMode>,
+    edit_mode: Res<EditMode>,
+    mut history: ResMut<EditHistory>,
+    mut world: ResMut<VoxelWorld>,
+) {
+    let ctrl = keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if !ctrl || (*game_mode != GameMode::Creative && !edit_mode.enabled) {
+        return;
+    }
+
+    // Leave an in-progress drag alone; it commits when the block is dropped
+    if history.in_transaction() {
+        return;
+    }
+
+    let step = if keyboard.just_pressed(KeyCode::KeyY)
+        || (shift && keyboard.just_pressed(KeyCode::KeyZ))
+    {
+        history.redo(&mut world).map(|step| ("Redo", step))
+    } else if keyboard.just_pressed(KeyCode::KeyZ) {
+        history.undo(&mut world).map(|step| ("Undo", step))
+    } else {
+        return;
+    };
+
+    match step {
+        Some((action, step)) if step.skipped > 0 => warn!(
+            "{} '{}': {} edits applied, {} skipped in unloaded chunks",
+            action, step.label, step.applied, step.skipped
+        ),
+        Some((action, step)) => info!("{} '{}' ({} edits)", action, step.label, step.applied),
+        None => info!("Nothing to undo or redo"),
+    }
+}
+
+/// Drop the history when another save slot is loaded, since its edits refer
+/// to a different world
+pub fn clear_history_on_world_switch(
+    active_world: Res<ActiveWorld>,
+    mut last_slot: Local<Option<SaveSlot>>,
+    mut history: ResMut<EditHistory>,
+) {
+    if last_slot.as_ref() != Some(&active_world.slot) {
+        if last_slot.is_some() {
+            history.clear();
+        }
+        *last_slot = Some(active_world.slot.clone());
+    }
+}
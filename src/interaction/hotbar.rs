@@ -0,0 +1,192 @@
+//! Survival hotbar: the first `HOTBAR_SLOTS` inventory slots along the
+//! bottom of the screen, picked with the number keys or the mouse wheel.
+//! A tool in the selected slot is the one used for mining.
+
+use crate::chat::ChatState;
+use crate::entity::inventory::HOTBAR_SLOTS;
+use crate::entity::{Inventory, ItemType};
+use crate::interaction::mining::HeldTool;
+use crate::interaction::palette::PlacementPaletteState;
+use crate::interaction::{EditMode, GameMode};
+use crate::voxel::types::{ToolTier, ToolType};
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+
+const SLOT_KEYS: [KeyCode; HOTBAR_SLOTS] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+const SLOT_COLOR: Color = Color::srgba(0.05, 0.05, 0.07, 0.75);
+const SELECTED_SLOT_COLOR: Color = Color::srgba(0.35, 0.35, 0.4, 0.9);
+
+/// Component to mark the hotbar root node
+#[derive(Component)]
+pub struct HotbarRoot;
+
+/// Component for a hotbar slot and its label
+#[derive(Component)]
+pub struct HotbarSlot(pub usize);
+
+#[derive(Component)]
+pub struct HotbarSlotText(pub usize);
+
+pub fn setup_hotbar(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(16.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(4.0),
+                ..default()
+            },
+            HotbarRoot,
+        ))
+        .with_children(|root| {
+            for index in 0..HOTBAR_SLOTS {
+                root.spawn((
+                    Node {
+                        width: Val::Px(64.0),
+                        height: Val::Px(64.0),
+                        padding: UiRect::all(Val::Px(4.0)),
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::SpaceBetween,
+                        ..default()
+                    },
+                    BackgroundColor(SLOT_COLOR),
+                    HotbarSlot(index),
+                ))
+                .with_children(|slot| {
+                    slot.spawn((
+                        Text::new(format!("{}", index + 1)),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 11.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgba(0.7, 0.7, 0.7, 1.0)),
+                    ));
+                    slot.spawn((
+                        Text::new(""),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 12.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                        HotbarSlotText(index),
+                    ));
+                });
+            }
+        });
+}
+
+/// Pick the hotbar slot with the number keys or the mouse wheel
+pub fn select_hotbar_slot(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut mouse_wheel: MessageReader<MouseWheel>,
+    game_mode: Res<GameMode>,
+    edit_mode: Res<EditMode>,
+    palette: Res<PlacementPaletteState>,
+    chat_state: Option<Res<ChatState>>,
+    mut inventory: ResMut<Inventory>,
+) {
+    let wheel: f32 = mouse_wheel.read().map(|wheel| wheel.y).sum();
+
+    // The wheel rotates dragged blocks in edit mode, and keys type into the
+    // palette search and chat
+    if *game_mode != GameMode::Survival
+        || edit_mode.enabled
+        || palette.open
+        || chat_state.is_some_and(|chat| chat.active)
+    {
+        return;
+    }
+
+    if let Some(index) = SLOT_KEYS.iter().position(|key| keys.just_pressed(*key)) {
+        inventory.select_slot(index);
+    }
+    if wheel != 0.0 {
+        // Scrolling down moves to the right
+        inventory.scroll_selection(-wheel.signum() as i32);
+    }
+}
+
+/// The tool mining uses: in survival the one in the selected slot, or
+/// bare hands without one; creative always digs with the default tool
+pub fn held_tool_for(game_mode: GameMode, inventory: &Inventory) -> HeldTool {
+    if game_mode == GameMode::Creative {
+        return HeldTool::default();
+    }
+    match inventory.selected_stack().map(|stack| stack.item) {
+        Some(ItemType::Tool { kind, tier }) => HeldTool { kind, tier },
+        _ => HeldTool {
+            kind: ToolType::None,
+            tier: ToolTier::default(),
+        },
+    }
+}
+
+/// Keep `HeldTool` in step with the selected slot in survival. Creative
+/// only resets it when the mode changes, so a `/tool` survives pickups.
+pub fn sync_held_tool(
+    inventory: Res<Inventory>,
+    game_mode: Res<GameMode>,
+    mut tool: ResMut<HeldTool>,
+) {
+    let changed = match *game_mode {
+        GameMode::Survival => inventory.is_changed() || game_mode.is_changed(),
+        GameMode::Creative => game_mode.is_changed(),
+    };
+    if changed {
+        tool.set_if_neq(held_tool_for(*game_mode, &inventory));
+    }
+}
+
+/// Redraw the hotbar when the inventory changes; hidden in creative mode
+pub fn update_hotbar_ui(
+    inventory: Res<Inventory>,
+    game_mode: Res<GameMode>,
+    mut root_query: Query<&mut Visibility, With<HotbarRoot>>,
+    mut slot_query: Query<(&HotbarSlot, &mut BackgroundColor)>,
+    mut text_query: Query<(&HotbarSlotText, &mut Text)>,
+) {
+    if game_mode.is_changed() {
+        let visibility = match *game_mode {
+            GameMode::Survival => Visibility::Inherited,
+            GameMode::Creative => Visibility::Hidden,
+        };
+        for mut root_visibility in root_query.iter_mut() {
+            *root_visibility = visibility;
+        }
+    }
+
+    if !inventory.is_changed() {
+        return;
+    }
+
+    for (slot, mut background) in slot_query.iter_mut() {
+        background.0 = if slot.0 == inventory.selected_slot() {
+            SELECTED_SLOT_COLOR
+        } else {
+            SLOT_COLOR
+        };
+    }
+    for (slot, mut text) in text_query.iter_mut() {
+        **text = match inventory.hotbar()[slot.0] {
+            Some(stack) => format!("{}\n{}", stack.item.name(), stack.count),
+            None => String::new(),
+        };
+    }
+}
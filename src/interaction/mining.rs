@@ -0,0 +1,394 @@
+//! Hold-to-mine block breaking.
+//!
+//! Holding the left button on a block builds up break progress; how long a
+//! block takes comes from its `hardness` and whether the held tool is the
+//! one it asks for (`tool_required`). Progress is drawn as cracks on the
+//! targeted face, and every `MINING_TICK_SECONDS` a `MiningTick` is sent so
+//! the pickaxe swing keeps time with the digging. Voxels with negative
+//! hardness never break.
+
+use crate::crafting::CraftingState;
+use crate::entity::{Inventory, ItemType};
+use crate::interaction::history::EditHistory;
+use crate::interaction::{
+    set_block_unrecorded, EditMode, GameMode, HeldBlock, TargetedBlock, TargetedEntity,
+};
+use crate::particles::{ParticleType, SpawnParticleEvent};
+use crate::voxel::registry::VoxelRegistry;
+use crate::voxel::types::{ToolTier, ToolType, VoxelType, VoxelTypeInfo};
+use crate::voxel::world::VoxelWorld;
+use bevy::asset::RenderAssetUsages;
+use bevy::image::{ImageFilterMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+/// Seconds to break one point of hardness bare-handed
+pub const SECONDS_PER_HARDNESS: f32 = 1.5;
+
+/// Interval between mining ticks (one pickaxe swing each)
+pub const MINING_TICK_SECONDS: f32 = 0.25;
+
+/// Number of crack overlay stages drawn while a block breaks
+pub const CRACK_STAGES: usize = 8;
+
+const CRACK_TEXTURE_SIZE: u32 = 16;
+
+/// How far the crack overlay floats above the face to avoid z-fighting
+const CRACK_OFFSET: f32 = 0.003;
+
+/// Resource for the tool in the player's hand
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeldTool {
+    pub kind: ToolType,
+    pub tier: ToolTier,
+}
+
+impl Default for HeldTool {
+    fn default() -> Self {
+        Self {
+            kind: ToolType::Pickaxe,
+            tier: ToolTier::default(),
+        }
+    }
+}
+
+impl HeldTool {
+    /// Seconds to break a voxel, `None` for unbreakable ones. Only the
+    /// required tool speeds the work up; anything else digs bare-handed.
+    pub fn break_seconds(&self, info: &VoxelTypeInfo) -> Option<f32> {
+        if !info.is_breakable() {
+            return None;
+        }
+        let speed = if info.tool_required != ToolType::None && info.tool_required == self.kind {
+            self.tier.speed()
+        } else {
+            1.0
+        };
+        Some(info.hardness * SECONDS_PER_HARDNESS / speed)
+    }
+}
+
+/// Sent on every mining tick, and when the block finally breaks
+#[derive(Message, Clone, Copy, Debug)]
+pub struct MiningTick {
+    pub position: IVec3,
+    pub broken: bool,
+}
+
+/// What a frame of mining did
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum MiningStep {
+    Mining,
+    Tick,
+    Broken,
+}
+
+/// Resource tracking the block being mined
+#[derive(Resource, Default)]
+pub struct MiningState {
+    target: Option<(IVec3, VoxelType)>,
+    elapsed: f32,
+    break_seconds: f32,
+    next_tick: f32,
+}
+
+impl MiningState {
+    pub fn target(&self) -> Option<IVec3> {
+        self.target.map(|(position, _)| position)
+    }
+
+    /// Break progress from 0 to 1
+    pub fn progress(&self) -> f32 {
+        if self.target.is_none() {
+            0.0
+        } else if self.break_seconds <= 0.0 {
+            1.0
+        } else {
+            (self.elapsed / self.break_seconds).min(1.0)
+        }
+    }
+
+    /// Crack overlay stage for the current progress
+    pub fn crack_stage(&self) -> Option<usize> {
+        self.target?;
+        Some(((self.progress() * CRACK_STAGES as f32) as usize).min(CRACK_STAGES - 1))
+    }
+
+    pub fn stop(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Keep mining `voxel` at `position` for `dt` seconds. A new target
+    /// starts over from no progress.
+    pub fn mine(
+        &mut self,
+        position: IVec3,
+        voxel: VoxelType,
+        break_seconds: f32,
+        dt: f32,
+    ) -> MiningStep {
+        if self.target == Some((position, voxel)) {
+            self.elapsed += dt;
+        } else {
+            *self = Self {
+                target: Some((position, voxel)),
+                elapsed: 0.0,
+                break_seconds,
+                next_tick: 0.0,
+            };
+        }
+
+        if self.elapsed >= self.break_seconds {
+            self.stop();
+            MiningStep::Broken
+        } else if self.elapsed >= self.next_tick {
+            self.next_tick += MINING_TICK_SECONDS;
+            MiningStep::Tick
+        } else {
+            MiningStep::Mining
+        }
+    }
+}
+
+/// System to mine the targeted block while the left button is held
+#[allow(clippy::too_many_arguments)]
+pub fn mine_block_system(
+    time: Res<Time>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    edit_mode: Res<EditMode>,
+    targeted_block: Res<TargetedBlock>,
+    targeted_entity: Res<TargetedEntity>,
+    tool: Res<HeldTool>,
+    registry: Res<VoxelRegistry>,
+    mut mining: ResMut<MiningState>,
+    mut world: ResMut<VoxelWorld>,
+    mut history: ResMut<EditHistory>,
+    game_mode: Res<GameMode>,
+    mut held: ResMut<HeldBlock>,
+    mut inventory: ResMut<Inventory>,
+    mut ticks: MessageWriter<MiningTick>,
+    mut particle_events: MessageWriter<SpawnParticleEvent>,
+    crafting: Res<CraftingState>,
+) {
+    // Only mine blocks if not targeting an entity or clicking the crafting grid
+    if edit_mode.enabled
+        || crafting.open
+        || !mouse.pressed(MouseButton::Left)
+        || targeted_entity.entity.is_some()
+    {
+        mining.stop();
+        return;
+    }
+    let (Some(pos), Some(voxel_type)) = (targeted_block.position, targeted_block.voxel_type) else {
+        mining.stop();
+        return;
+    };
+    let Some(break_seconds) = tool.break_seconds(registry.info(voxel_type)) else {
+        mining.stop();
+        return;
+    };
+
+    match mining.mine(pos, voxel_type, break_seconds, time.delta_secs()) {
+        MiningStep::Mining => {}
+        MiningStep::Tick => {
+            ticks.write(MiningTick {
+                position: pos,
+                broken: false,
+            });
+        }
+        MiningStep::Broken => {
+            ticks.write(MiningTick {
+                position: pos,
+                broken: true,
+            });
+
+            // Creative mode holds the broken block type for placing and can
+            // undo the break; survival collects it, so the break is kept out
+            // of the history (undoing it would duplicate the item). Both mark
+            // neighboring chunks dirty.
+            match *game_mode {
+                GameMode::Creative => {
+                    held.block_type = voxel_type;
+                    history.edit(&mut world, "Break block", pos, VoxelType::Air);
+                }
+                GameMode::Survival => {
+                    if !inventory.add_item(ItemType::Block(voxel_type)) {
+                        info!("Inventory full, {} lost", voxel_type.name());
+                    }
+                    set_block_unrecorded(&mut world, pos, VoxelType::Air);
+                }
+            }
+
+            // Spawn digging particles
+            let center = Vec3::new(pos.x as f32 + 0.5, pos.y as f32 + 0.5, pos.z as f32 + 0.5);
+            particle_events.write(SpawnParticleEvent {
+                position: center,
+                particle_type: ParticleType::Dig,
+            });
+        }
+    }
+}
+
+/// Component to mark the crack overlay entity
+#[derive(Component)]
+pub struct CrackOverlay;
+
+/// One material per crack stage
+#[derive(Resource)]
+pub struct CrackMaterials {
+    pub stages: Vec<Handle<StandardMaterial>>,
+}
+
+/// Pixels of the crack pattern in the order they appear. Branches grow
+/// from the middle of the face together, so every stage adds to all of them.
+fn crack_pixels() -> Vec<UVec2> {
+    const DIRECTIONS: [IVec2; 6] = [
+        IVec2::new(1, 0),
+        IVec2::new(1, 1),
+        IVec2::new(-1, 1),
+        IVec2::new(-1, 0),
+        IVec2::new(-1, -1),
+        IVec2::new(1, -1),
+    ];
+
+    // xorshift keeps the pattern the same on every run
+    let mut state: u32 = 0x9e37_79b9;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+
+    let size = CRACK_TEXTURE_SIZE as i32;
+    let mut heads = DIRECTIONS.map(|_| IVec2::splat(size / 2));
+    let mut pixels = vec![UVec2::splat(CRACK_TEXTURE_SIZE / 2)];
+    for _ in 0..size / 2 {
+        for (head, direction) in heads.iter_mut().zip(DIRECTIONS) {
+            // Wander sideways now and then for a jagged line
+            let jitter = match next() % 4 {
+                0 => direction.perp(),
+                1 => -direction.perp(),
+                _ => IVec2::ZERO,
+            };
+            *head = (*head + direction + jitter).clamp(IVec2::ZERO, IVec2::splat(size - 1));
+            let pixel = head.as_uvec2();
+            if !pixels.contains(&pixel) {
+                pixels.push(pixel);
+            }
+        }
+    }
+    pixels
+}
+
+/// Texture for a crack stage: dark crack pixels on a transparent face
+fn crack_image(pixels: &[UVec2]) -> Image {
+    let mut data = vec![0u8; (CRACK_TEXTURE_SIZE * CRACK_TEXTURE_SIZE * 4) as usize];
+    for pixel in pixels {
+        let idx = ((pixel.y * CRACK_TEXTURE_SIZE + pixel.x) * 4) as usize;
+        data[idx..idx + 4].copy_from_slice(&[24, 20, 18, 210]);
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: CRACK_TEXTURE_SIZE,
+            height: CRACK_TEXTURE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        mag_filter: ImageFilterMode::Nearest,
+        min_filter: ImageFilterMode::Nearest,
+        ..default()
+    });
+    image
+}
+
+/// Build the crack stage materials and the (hidden) overlay quad
+pub fn setup_crack_overlay(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let pixels = crack_pixels();
+    let stages: Vec<Handle<StandardMaterial>> = (1..=CRACK_STAGES)
+        .map(|stage| {
+            let shown = (pixels.len() * stage).div_ceil(CRACK_STAGES);
+            materials.add(StandardMaterial {
+                base_color_texture: Some(images.add(crack_image(&pixels[..shown]))),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })
+        })
+        .collect();
+
+    commands.spawn((
+        Mesh3d(meshes.add(Rectangle::new(1.0, 1.0))),
+        MeshMaterial3d(stages[0].clone()),
+        Transform::default(),
+        Visibility::Hidden,
+        CrackOverlay,
+    ));
+    commands.insert_resource(CrackMaterials { stages });
+}
+
+/// Move the crack overlay onto the face being mined and show its stage
+#[allow(clippy::type_complexity)]
+pub fn update_crack_overlay(
+    mining: Res<MiningState>,
+    targeted: Res<TargetedBlock>,
+    crack_materials: Option<Res<CrackMaterials>>,
+    mut overlay_query: Query<
+        (
+            &mut Transform,
+            &mut Visibility,
+            &mut MeshMaterial3d<StandardMaterial>,
+        ),
+        With<CrackOverlay>,
+    >,
+) {
+    let Some(crack_materials) = crack_materials else {
+        return;
+    };
+    let Ok((mut transform, mut visibility, mut material)) = overlay_query.single_mut() else {
+        return;
+    };
+
+    let face = mining
+        .target()
+        .filter(|pos| targeted.position == Some(*pos))
+        .zip(targeted.normal.and_then(face_normal))
+        .zip(mining.crack_stage());
+    let Some(((pos, normal), stage)) = face else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    let center = pos.as_vec3() + Vec3::splat(0.5);
+    *transform = Transform::from_translation(center + normal * (0.5 + CRACK_OFFSET))
+        .with_rotation(Quat::from_rotation_arc(Vec3::Z, normal));
+    if material.0 != crack_materials.stages[stage] {
+        material.0 = crack_materials.stages[stage].clone();
+    }
+    visibility.set_if_neq(Visibility::Visible);
+}
+
+/// Axis-aligned face normal for a raycast hit normal, which can point along
+/// two axes when the ray slips past an edge
+fn face_normal(normal: IVec3) -> Option<Vec3> {
+    if normal.x != 0 {
+        Some(Vec3::X * normal.x.signum() as f32)
+    } else if normal.y != 0 {
+        Some(Vec3::Y * normal.y.signum() as f32)
+    } else if normal.z != 0 {
+        Some(Vec3::Z * normal.z.signum() as f32)
+    } else {
+        None
+    }
+}
@@ -1,14 +1,25 @@
-use crate::entity::{Health, Wolf};
+use crate::chat::commands::ChatCommandAppExt;
+use crate::entity::{Health, Inventory, ItemType, Wolf};
 use crate::interaction::palette::{PlacementPaletteState, PlacementSelection};
 use crate::menu::PauseMenuState;
 use crate::network::NetworkSession;
+use crate::player::player_alive;
+use crate::voxel::registry::VoxelRegistry;
 use crate::voxel::types::{Voxel, VoxelType};
 use crate::voxel::world::VoxelWorld;
-use crate::particles::{SpawnParticleEvent, ParticleType};
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
+use history::EditHistory;
+use std::collections::HashSet;
+use world_edit::{Clipboard, Selection};
+pub mod commands;
+pub mod history;
+pub mod hotbar;
+pub mod mining;
 pub mod palette;
+pub mod schematic;
+pub mod world_edit;
 
 /// Component to mark the block highlight entity
 #[derive(Component)]
@@ -77,7 +88,18 @@ pub struct TargetedEntity {
     pub distance: f32,
 }
 
-/// Resource for the player's held block type
+/// Whether blocks come from the inventory or are free to use. The game
+/// starts in creative; `/gamemode survival` switches over.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameMode {
+    /// Mined blocks go to the inventory and placing uses them up
+    Survival,
+    /// Mined blocks become the held block, which never runs out
+    #[default]
+    Creative,
+}
+
+/// Resource for the player's held block type (creative mode and the palette)
 #[derive(Resource)]
 pub struct HeldBlock {
     pub block_type: VoxelType,
@@ -224,46 +246,6 @@ pub fn attack_entity_system(
     }
 }
 
-/// System to handle block breaking (left click)
-pub fn break_block_system(
-    mouse: Res<ButtonInput<MouseButton>>,
-    edit_mode: Res<EditMode>,
-    targeted_block: Res<TargetedBlock>,
-    targeted_entity: Res<TargetedEntity>,
-    mut world: ResMut<VoxelWorld>,
-    mut held: ResMut<HeldBlock>,
-    mut particle_events: MessageWriter<SpawnParticleEvent>,
-) {
-    if edit_mode.enabled {
-        return;
-    }
-
-    // Only break blocks if not targeting an entity
-    if mouse.just_pressed(MouseButton::Left) && targeted_entity.entity.is_none() {
-        if let (Some(pos), Some(voxel_type)) = (targeted_block.position, targeted_block.voxel_type)
-        {
-            // Don't break bedrock
-            if voxel_type != VoxelType::Bedrock {
-                // Store the broken block type for placing
-                held.block_type = voxel_type;
-
-                // Set to air
-                world.set_voxel(pos, VoxelType::Air);
-
-                // Mark neighboring chunks dirty too (for proper mesh updates at edges)
-                mark_neighbors_dirty(&mut world, pos);
-
-                // Spawn digging particles
-                let center = Vec3::new(pos.x as f32 + 0.5, pos.y as f32 + 0.5, pos.z as f32 + 0.5);
-                particle_events.write(SpawnParticleEvent {
-                    position: center,
-                    particle_type: ParticleType::Dig,
-                });
-            }
-        }
-    }
-}
-
 /// System to handle block placing (right click)
 pub fn place_block_system(
     mouse: Res<ButtonInput<MouseButton>>,
@@ -271,10 +253,14 @@ pub fn place_block_system(
     delete_mode: Res<DeleteMode>,
     targeted: Res<TargetedBlock>,
     mut world: ResMut<VoxelWorld>,
+    mut history: ResMut<EditHistory>,
     held: Res<HeldBlock>,
+    game_mode: Res<GameMode>,
+    mut inventory: ResMut<Inventory>,
     camera_query: Query<&Transform, With<crate::camera::controller::PlayerCamera>>,
     drag_state: Res<DragState>,
     palette: Res<PlacementPaletteState>,
+    crafting: Res<crate::crafting::CraftingState>,
 ) {
     let placing_in_edit_mode = edit_mode.enabled
         && palette
@@ -287,7 +273,7 @@ pub fn place_block_system(
         return;
     }
 
-    if delete_mode.enabled || drag_state.dragged_block.is_some() {
+    if delete_mode.enabled || drag_state.dragged_block.is_some() || crafting.open {
         return;
     }
 
@@ -316,25 +302,51 @@ pub fn place_block_system(
             }
 
             // Check if the position is valid (air or water)
-            if let Some(existing) = world.get_voxel(place_pos) {
-                if existing == VoxelType::Air || existing == VoxelType::Water {
-                    world.set_voxel(place_pos, held.block_type);
-                    mark_neighbors_dirty(&mut world, place_pos);
-                }
+            let Some(existing) = world.get_voxel(place_pos) else {
+                return;
+            };
+            if existing != VoxelType::Air && existing != VoxelType::Water {
+                return;
+            }
+
+            // The palette and creative mode place the held block for free;
+            // survival uses up a block from the selected hotbar slot
+            if placing_in_edit_mode || *game_mode == GameMode::Creative {
+                history.edit(&mut world, "Place block", place_pos, held.block_type);
+            } else if let Some(ItemType::Block(voxel)) =
+                inventory.selected_stack().map(|stack| stack.item)
+            {
+                inventory.take_selected();
+                set_block_unrecorded(&mut world, place_pos, voxel);
             }
         }
     }
 }
 
+/// Set a block without recording it in the edit history. Survival edits
+/// cost or yield items, so undoing them would duplicate or lose items.
+pub fn set_block_unrecorded(world: &mut VoxelWorld, pos: IVec3, voxel: VoxelType) -> bool {
+    if world.get_voxel(pos).is_none_or(|old| old == voxel) || !world.set_voxel(pos, voxel) {
+        return false;
+    }
+    mark_neighbors_dirty(world, pos);
+    true
+}
+
 /// Mark a block and its neighbors as dirty for mesh regeneration
-fn mark_neighbors_dirty(world: &mut VoxelWorld, pos: IVec3) {
-    // Mark the chunk containing this block
-    let chunk_pos = VoxelWorld::world_to_chunk(pos);
-    if let Some(chunk) = world.get_chunk_mut(chunk_pos) {
-        chunk.mark_dirty();
+pub fn mark_neighbors_dirty(world: &mut VoxelWorld, pos: IVec3) {
+    for chunk_pos in affected_chunks(pos) {
+        if let Some(chunk) = world.get_chunk_mut(chunk_pos) {
+            chunk.mark_dirty();
+        }
     }
+}
+
+/// The chunk containing a block plus any neighbor chunk it borders
+fn affected_chunks(pos: IVec3) -> impl Iterator<Item = IVec3> {
+    let chunk_pos = VoxelWorld::world_to_chunk(pos);
 
-    // Check if we're at a chunk boundary and mark neighbor chunks
+    // Check if we're at a chunk boundary and include the neighbor chunks
     let local = VoxelWorld::world_to_local(pos);
 
     let offsets = [
@@ -346,10 +358,37 @@ fn mark_neighbors_dirty(world: &mut VoxelWorld, pos: IVec3) {
         (local.z == 15, IVec3::new(0, 0, 1)),
     ];
 
-    for (at_edge, offset) in offsets {
-        if at_edge {
-            let neighbor_chunk = chunk_pos + offset;
-            if let Some(chunk) = world.get_chunk_mut(neighbor_chunk) {
+    std::iter::once(chunk_pos).chain(
+        offsets
+            .into_iter()
+            .filter(|(at_edge, _)| *at_edge)
+            .map(move |(_, offset)| chunk_pos + offset),
+    )
+}
+
+/// Chunks touched by a bulk edit, collected so each is marked dirty once
+/// instead of once per voxel
+#[derive(Default)]
+pub struct DirtyChunks {
+    chunks: HashSet<IVec3>,
+}
+
+impl DirtyChunks {
+    pub fn add(&mut self, pos: IVec3) {
+        self.chunks.extend(affected_chunks(pos));
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub fn mark(&self, world: &mut VoxelWorld) {
+        for chunk_pos in &self.chunks {
+            if let Some(chunk) = world.get_chunk_mut(*chunk_pos) {
                 chunk.mark_dirty();
             }
         }
@@ -363,6 +402,7 @@ pub fn toggle_edit_mode(
     mut delete_mode: ResMut<DeleteMode>,
     mut drag_state: ResMut<DragState>,
     mut world: ResMut<VoxelWorld>,
+    mut history: ResMut<EditHistory>,
 ) {
     let shift_pressed =
         keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
@@ -375,8 +415,8 @@ pub fn toggle_edit_mode(
             info!("Edit mode enabled - click and drag a block to move it");
         } else {
             if let Some(dragged) = drag_state.dragged_block.take() {
-                world.set_voxel(dragged.original_position, dragged.block_type);
-                mark_neighbors_dirty(&mut world, dragged.original_position);
+                history.set_voxel(&mut world, dragged.original_position, dragged.block_type);
+                history.commit();
             }
             drag_state.rotation_degrees = 0.0;
             info!("Edit mode disabled");
@@ -391,6 +431,7 @@ pub fn toggle_delete_mode(
     mut delete_mode: ResMut<DeleteMode>,
     mut drag_state: ResMut<DragState>,
     mut world: ResMut<VoxelWorld>,
+    mut history: ResMut<EditHistory>,
 ) {
     if !edit_mode.enabled {
         delete_mode.enabled = false;
@@ -402,8 +443,8 @@ pub fn toggle_delete_mode(
 
         if delete_mode.enabled {
             if let Some(dragged) = drag_state.dragged_block.take() {
-                world.set_voxel(dragged.original_position, dragged.block_type);
-                mark_neighbors_dirty(&mut world, dragged.original_position);
+                history.set_voxel(&mut world, dragged.original_position, dragged.block_type);
+                history.commit();
             }
             drag_state.rotation_degrees = 0.0;
             info!("Delete mode enabled - left click a block to remove it");
@@ -421,6 +462,8 @@ pub fn start_dragging_block(
     targeted_block: Res<TargetedBlock>,
     mut drag_state: ResMut<DragState>,
     mut world: ResMut<VoxelWorld>,
+    mut history: ResMut<EditHistory>,
+    registry: Res<VoxelRegistry>,
 ) {
     if !edit_mode.enabled || delete_mode.enabled || !mouse.just_pressed(MouseButton::Left) {
         return;
@@ -431,12 +474,13 @@ pub fn start_dragging_block(
     }
 
     if let (Some(pos), Some(voxel_type)) = (targeted_block.position, targeted_block.voxel_type) {
-        if voxel_type == VoxelType::Bedrock {
+        if !registry.info(voxel_type).is_breakable() {
             return;
         }
 
-        world.set_voxel(pos, VoxelType::Air);
-        mark_neighbors_dirty(&mut world, pos);
+        // The pickup and the drop are undone together as one move
+        history.begin("Move block");
+        history.set_voxel(&mut world, pos, VoxelType::Air);
         drag_state.dragged_block = Some(DraggedBlock {
             block_type: voxel_type,
             original_position: pos,
@@ -453,6 +497,7 @@ pub fn finish_dragging_block(
     mut drag_state: ResMut<DragState>,
     camera_query: Query<&Transform, With<crate::camera::controller::PlayerCamera>>,
     mut world: ResMut<VoxelWorld>,
+    mut history: ResMut<EditHistory>,
 ) {
     if !edit_mode.enabled || !mouse.just_released(MouseButton::Left) {
         return;
@@ -465,8 +510,8 @@ pub fn finish_dragging_block(
     if let (Some(block_pos), Some(normal)) = (targeted_block.position, targeted_block.normal) {
         let place_pos = block_pos + normal;
         let Some(grounded_pos) = find_grounded_position(place_pos, &world) else {
-            world.set_voxel(dragged.original_position, dragged.block_type);
-            mark_neighbors_dirty(&mut world, dragged.original_position);
+            history.set_voxel(&mut world, dragged.original_position, dragged.block_type);
+            history.commit();
             return;
         };
 
@@ -483,24 +528,24 @@ pub fn finish_dragging_block(
             );
 
             if grounded_pos == player_block || grounded_pos == player_feet {
-                world.set_voxel(dragged.original_position, dragged.block_type);
-                mark_neighbors_dirty(&mut world, dragged.original_position);
+                history.set_voxel(&mut world, dragged.original_position, dragged.block_type);
+                history.commit();
                 return;
             }
         }
 
         if let Some(existing) = world.get_voxel(grounded_pos) {
             if existing == VoxelType::Air || existing == VoxelType::Water {
-                world.set_voxel(grounded_pos, dragged.block_type);
-                mark_neighbors_dirty(&mut world, grounded_pos);
+                history.set_voxel(&mut world, grounded_pos, dragged.block_type);
+                history.commit();
                 return;
             }
         }
     }
 
     // Restore to the original position if we couldn't place it elsewhere
-    world.set_voxel(dragged.original_position, dragged.block_type);
-    mark_neighbors_dirty(&mut world, dragged.original_position);
+    history.set_voxel(&mut world, dragged.original_position, dragged.block_type);
+    history.commit();
     drag_state.rotation_degrees = 0.0;
 }
 
@@ -551,6 +596,8 @@ pub fn delete_block_in_edit_mode(
     mouse: Res<ButtonInput<MouseButton>>,
     targeted_block: Res<TargetedBlock>,
     mut world: ResMut<VoxelWorld>,
+    mut history: ResMut<EditHistory>,
+    registry: Res<VoxelRegistry>,
 ) {
     if !edit_mode.enabled || !delete_mode.enabled {
         return;
@@ -559,9 +606,8 @@ pub fn delete_block_in_edit_mode(
     if mouse.just_pressed(MouseButton::Left) {
         if let (Some(pos), Some(voxel_type)) = (targeted_block.position, targeted_block.voxel_type)
         {
-            if voxel_type != VoxelType::Bedrock {
-                world.set_voxel(pos, VoxelType::Air);
-                mark_neighbors_dirty(&mut world, pos);
+            if registry.info(voxel_type).is_breakable() {
+                history.edit(&mut world, "Delete block", pos, VoxelType::Air);
             }
         }
     }
@@ -603,9 +649,12 @@ pub fn render_block_highlight(
     targeted: Res<TargetedBlock>,
     drag_state: Res<DragState>,
     edit_mode: Res<EditMode>,
+    selection: Res<Selection>,
     world: Res<VoxelWorld>,
     mut gizmos: Gizmos,
 ) {
+    world_edit::draw_selection(&selection, &mut gizmos);
+
     if let Some(pos) = targeted.position {
         let center = Vec3::new(pos.x as f32 + 0.5, pos.y as f32 + 0.5, pos.z as f32 + 0.5);
         let half_size = Vec3::splat(0.505); // Slightly larger than block
@@ -962,6 +1011,11 @@ pub fn toggle_debug_details(
     keyboard: Res<ButtonInput<KeyCode>>,
     mut toggles: ResMut<DebugDetailToggles>,
 ) {
+    // Ctrl+V pastes the world-edit clipboard
+    if keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
     if keyboard.just_pressed(KeyCode::KeyV) {
         toggles.show_vertex_corners = !toggles.show_vertex_corners;
     }
@@ -1123,6 +1177,10 @@ pub fn update_debug_overlay(
             text_content.push_str(&format!("Peer: {}:{}\n", ip, port));
         }
 
+        if network.server_running || network.client_connected {
+            text_content.push_str(&format!("Players: {}\n", network.player_count));
+        }
+
         let latency = network
             .last_latency_ms
             .map(|ms| format!("{ms} ms"))
@@ -1203,16 +1261,33 @@ impl Plugin for InteractionPlugin {
         app.init_resource::<TargetedBlock>()
             .init_resource::<TargetedEntity>()
             .init_resource::<HeldBlock>()
+            .init_resource::<GameMode>()
+            .init_resource::<Inventory>()
+            .init_resource::<mining::HeldTool>()
+            .init_resource::<mining::MiningState>()
+            .add_message::<mining::MiningTick>()
             .init_resource::<EditMode>()
             .init_resource::<DeleteMode>()
             .init_resource::<DragState>()
+            .init_resource::<EditHistory>()
+            .init_resource::<Selection>()
+            .init_resource::<Clipboard>()
+            .insert_resource(schematic::SchematicMapping::load_or_default())
+            .init_resource::<schematic::SchematicLibrary>()
             .init_resource::<DebugOverlayState>()
             .init_resource::<DebugDetailToggles>()
             .init_resource::<palette::PaletteItems>()
             .init_resource::<PlacementPaletteState>()
             .init_resource::<palette::BookmarkStore>()
-            .add_systems(Startup, setup_debug_overlay)
+            .add_chat_command(commands::GiveCommand)
+            .add_chat_command(commands::ToolCommand)
+            .add_chat_command(commands::GameModeCommand)
+            .add_systems(
+                Startup,
+                (setup_debug_overlay, mining::setup_crack_overlay, hotbar::setup_hotbar),
+            )
             .add_systems(Startup, palette::load_bookmarks)
+            .add_systems(Update, history::clear_history_on_world_switch)
             .add_systems(
                 Update,
                 (
@@ -1221,6 +1296,8 @@ impl Plugin for InteractionPlugin {
                     palette::initialize_palette_items,
                     palette::toggle_palette,
                     palette::handle_palette_input,
+                    hotbar::select_hotbar_slot,
+                    hotbar::sync_held_tool,
                     palette::handle_bookmark_buttons,
                     toggle_edit_mode,
                     toggle_delete_mode,
@@ -1228,6 +1305,8 @@ impl Plugin for InteractionPlugin {
                     palette::handle_palette_item_click,
                     delete_block_in_edit_mode,
                     update_drag_rotation,
+                    history::undo_redo_system,
+                    world_edit::world_edit_input_system,
                 )
                     .run_if(|state: Res<PauseMenuState>| !state.open),
             )
@@ -1236,10 +1315,14 @@ impl Plugin for InteractionPlugin {
                 (
                     finish_dragging_block,
                     palette::place_prop_from_palette,
+                    palette::place_schematic_from_palette,
+                    schematic::save_selection_system,
                     palette::persist_bookmarks,
-                    attack_entity_system,
-                    break_block_system,
-                    place_block_system,
+                    attack_entity_system.run_if(player_alive),
+                    mining::mine_block_system.run_if(player_alive),
+                    mining::update_crack_overlay,
+                    place_block_system.run_if(player_alive),
+                    hotbar::update_hotbar_ui,
                     palette::refresh_palette_ui,
                     render_block_highlight,
                     debug_voxel_info_system,
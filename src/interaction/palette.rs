@@ -1,8 +1,12 @@
 use crate::camera::controller::PlayerCamera;
 use crate::chat::ChatState;
+use crate::interaction::history::EditHistory;
+use crate::interaction::schematic::{self, SchematicLibrary, SchematicMapping};
 use crate::menu::PauseMenuState;
 use crate::props::{Prop, PropAssets, PropConfig, PropType};
+use crate::voxel::registry::VoxelRegistry;
 use crate::voxel::types::VoxelType;
+use crate::voxel::world::VoxelWorld;
 use bevy::prelude::*;
 use bevy::input::keyboard::{Key, KeyboardInput};
 use bevy::ui::{
@@ -17,6 +21,7 @@ use std::path::PathBuf;
 pub enum PlacementSelection {
     Voxel(VoxelType),
     Prop { id: String, prop_type: PropType },
+    Schematic { name: String, path: PathBuf },
 }
 
 #[derive(Clone)]
@@ -95,6 +100,7 @@ pub fn initialize_palette_items(
     mut items: ResMut<PaletteItems>,
     mut palette: ResMut<PlacementPaletteState>,
     config: Res<PropConfig>,
+    registry: Res<VoxelRegistry>,
 ) {
     if palette.items_initialized {
         return;
@@ -102,21 +108,10 @@ pub fn initialize_palette_items(
 
     let mut all_items = Vec::new();
 
-    for voxel in [
-        VoxelType::TopSoil,
-        VoxelType::SubSoil,
-        VoxelType::Rock,
-        VoxelType::Sand,
-        VoxelType::Clay,
-        VoxelType::Water,
-        VoxelType::Wood,
-        VoxelType::Leaves,
-        VoxelType::DungeonWall,
-        VoxelType::DungeonFloor,
-    ] {
+    for (voxel, info) in registry.placeable() {
         all_items.push(PaletteItem {
             label: format!("{:?}", voxel),
-            tags: voxel_tags(voxel),
+            tags: info.tags.clone(),
             selection: PlacementSelection::Voxel(voxel),
         });
     }
@@ -143,6 +138,22 @@ pub fn initialize_palette_items(
         }
     }
 
+    for path in schematic::list_schematics(schematic::SCHEMATICS_DIR) {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut tags = vec!["schematic".to_string()];
+        if let Some(extension) = path.extension() {
+            tags.push(extension.to_string_lossy().to_lowercase());
+        }
+        all_items.push(PaletteItem {
+            label: name.clone(),
+            tags,
+            selection: PlacementSelection::Schematic { name, path },
+        });
+    }
+
     items.0 = all_items;
     palette.items_initialized = true;
     palette.needs_redraw = palette.open;
@@ -347,6 +358,9 @@ pub fn refresh_palette_ui(
             Some(PlacementSelection::Prop { id, prop_type }) => {
                 format!("Selected: {} ({:?})", id, prop_type)
             }
+            Some(PlacementSelection::Schematic { name, .. }) => {
+                format!("Selected: {} (schematic)", name)
+            }
             None => "Selected: (none)".to_string(),
         };
     }
@@ -564,6 +578,47 @@ pub fn place_prop_from_palette(
     ));
 }
 
+pub fn place_schematic_from_palette(
+    mouse: Res<ButtonInput<MouseButton>>,
+    edit_mode: Res<crate::interaction::EditMode>,
+    delete_mode: Res<crate::interaction::DeleteMode>,
+    drag_state: Res<crate::interaction::DragState>,
+    targeted: Res<crate::interaction::TargetedBlock>,
+    palette: Res<PlacementPaletteState>,
+    mapping: Res<SchematicMapping>,
+    registry: Res<VoxelRegistry>,
+    mut library: ResMut<SchematicLibrary>,
+    mut history: ResMut<EditHistory>,
+    mut world: ResMut<VoxelWorld>,
+) {
+    if !edit_mode.enabled || delete_mode.enabled || drag_state.dragged_block.is_some() {
+        return;
+    }
+
+    if !mouse.just_pressed(MouseButton::Right) {
+        return;
+    }
+
+    let Some(PlacementSelection::Schematic { path, .. }) = &palette.active_selection else {
+        return;
+    };
+
+    let (Some(block_pos), Some(normal)) = (targeted.position, targeted.normal) else {
+        return;
+    };
+
+    let result = library
+        .get_or_load(path, &mapping, &registry)
+        .and_then(|schematic| schematic.place(&mut world, &mut history, block_pos + normal));
+    match result {
+        Ok(summary) => info!(
+            "Placed schematic {:?}: {} voxels changed",
+            path, summary.changed
+        ),
+        Err(err) => warn!("Failed to place schematic: {}", err),
+    }
+}
+
 fn spawn_palette_ui(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
@@ -676,7 +731,7 @@ fn spawn_palette_ui(
             ));
 
             root.spawn((
-                Text::new("Right click while editing to place props. Voxels use the held block."),
+                Text::new("Right click while editing to place props and schematics. Voxels use the held block."),
                 TextFont {
                     font: font.clone(),
                     font_size: 12.0,
@@ -716,19 +771,3 @@ pub fn persist_bookmarks(mut store: ResMut<BookmarkStore>) {
         Err(err) => warn!("Failed to serialize bookmarks: {err}"),
     }
 }
-
-fn voxel_tags(voxel: VoxelType) -> Vec<String> {
-    match voxel {
-        VoxelType::TopSoil => vec!["material".into(), "soil".into(), "ground".into()],
-        VoxelType::SubSoil => vec!["material".into(), "soil".into()],
-        VoxelType::Rock => vec!["material".into(), "stone".into()],
-        VoxelType::Sand => vec!["material".into(), "sand".into()],
-        VoxelType::Clay => vec!["material".into(), "clay".into()],
-        VoxelType::Water => vec!["liquid".into(), "water".into()],
-        VoxelType::Wood => vec!["material".into(), "wood".into(), "tree".into()],
-        VoxelType::Leaves => vec!["material".into(), "foliage".into()],
-        VoxelType::DungeonWall => vec!["material".into(), "dungeon".into()],
-        VoxelType::DungeonFloor => vec!["material".into(), "dungeon".into()],
-        VoxelType::Air | VoxelType::Bedrock => vec!["hidden".into()],
-    }
-}
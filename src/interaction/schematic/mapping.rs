@@ -0,0 +1,245 @@
+use crate::config::loader::{load_config, ConfigError};
+use crate::voxel::registry::VoxelRegistry;
+use crate::voxel::types::VoxelType;
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use thiserror::Error;
+
+pub const SCHEMATIC_MAPPING_PATH: &str = "assets/config/schematic_mapping.yaml";
+
+/// Sponge block ids mapped by the built-in table
+const BUILTIN_BLOCKS: &[(&str, &str)] = &[
+    ("minecraft:air", "air"),
+    ("minecraft:cave_air", "air"),
+    ("minecraft:void_air", "air"),
+    ("minecraft:grass_block", "topsoil"),
+    ("minecraft:dirt", "subsoil"),
+    ("minecraft:coarse_dirt", "subsoil"),
+    ("minecraft:podzol", "subsoil"),
+    ("minecraft:rooted_dirt", "subsoil"),
+    ("minecraft:stone", "rock"),
+    ("minecraft:cobblestone", "rock"),
+    ("minecraft:andesite", "rock"),
+    ("minecraft:diorite", "rock"),
+    ("minecraft:granite", "rock"),
+    ("minecraft:deepslate", "rock"),
+    ("minecraft:gravel", "rock"),
+    ("minecraft:bedrock", "bedrock"),
+    ("minecraft:sand", "sand"),
+    ("minecraft:red_sand", "sand"),
+    ("minecraft:sandstone", "sand"),
+    ("minecraft:clay", "clay"),
+    ("minecraft:terracotta", "clay"),
+    ("minecraft:water", "water"),
+    ("minecraft:oak_log", "wood"),
+    ("minecraft:spruce_log", "wood"),
+    ("minecraft:birch_log", "wood"),
+    ("minecraft:oak_planks", "wood"),
+    ("minecraft:spruce_planks", "wood"),
+    ("minecraft:birch_planks", "wood"),
+    ("minecraft:oak_leaves", "leaves"),
+    ("minecraft:spruce_leaves", "leaves"),
+    ("minecraft:birch_leaves", "leaves"),
+    ("minecraft:stone_bricks", "dungeon_wall"),
+    ("minecraft:mossy_stone_bricks", "dungeon_wall"),
+    ("minecraft:mossy_cobblestone", "dungeon_floor"),
+    ("minecraft:polished_andesite", "dungeon_floor"),
+];
+
+const BUILTIN_EXPORT_BLOCKS: &[(&str, &str)] = &[
+    ("wood", "minecraft:oak_planks"),
+    ("leaves", "minecraft:oak_leaves[persistent=true]"),
+];
+
+const BUILTIN_VOX_COLORS: &[([u8; 3], &str)] = &[
+    ([96, 160, 64], "topsoil"),
+    ([121, 85, 58], "subsoil"),
+    ([128, 128, 128], "rock"),
+    ([40, 40, 40], "bedrock"),
+    ([219, 207, 142], "sand"),
+    ([160, 166, 179], "clay"),
+    ([64, 96, 220], "water"),
+    ([150, 110, 60], "wood"),
+    ([60, 120, 40], "leaves"),
+    ([90, 90, 100], "dungeon_wall"),
+    ([110, 100, 85], "dungeon_floor"),
+];
+
+/// Colour written for voxels without a `vox_colors` entry
+const UNMAPPED_COLOR: [u8; 3] = [200, 0, 200];
+
+#[derive(Error, Debug)]
+pub enum SchematicMappingError {
+    #[error("failed to read schematic mapping: {0}")]
+    Config(#[from] ConfigError),
+    #[error("invalid schematic mapping: {0}")]
+    Invalid(String),
+}
+
+/// One MagicaVoxel colour and the voxel it stands for
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct VoxColor {
+    pub color: [u8; 3],
+    pub voxel: String,
+}
+
+/// How third-party block types map to voxels (`schematic_mapping.yaml`)
+#[derive(Resource, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct SchematicMapping {
+    /// Voxel used for blocks and colours with no mapping
+    pub fallback_voxel: String,
+    /// Block written for voxels with no mapping
+    pub fallback_block: String,
+    /// Sponge block ids, without block states, to voxel names
+    pub blocks: BTreeMap<String, String>,
+    /// Block written for a voxel, overriding the reverse of `blocks`
+    pub export_blocks: BTreeMap<String, String>,
+    /// MagicaVoxel colours, matched to the nearest entry on import
+    pub vox_colors: Vec<VoxColor>,
+    /// MagicaVoxel palette indices mapped directly, checked before colours
+    pub vox_indices: BTreeMap<u8, String>,
+}
+
+impl Default for SchematicMapping {
+    fn default() -> Self {
+        let owned = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(a, b)| (a.to_string(), b.to_string()))
+                .collect()
+        };
+        Self {
+            fallback_voxel: "rock".to_string(),
+            fallback_block: "minecraft:stone".to_string(),
+            blocks: owned(BUILTIN_BLOCKS),
+            export_blocks: owned(BUILTIN_EXPORT_BLOCKS),
+            vox_colors: BUILTIN_VOX_COLORS
+                .iter()
+                .map(|(color, voxel)| VoxColor {
+                    color: *color,
+                    voxel: voxel.to_string(),
+                })
+                .collect(),
+            vox_indices: BTreeMap::new(),
+        }
+    }
+}
+
+impl SchematicMapping {
+    /// Load and validate a mapping from a YAML file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SchematicMappingError> {
+        let mapping: SchematicMapping = load_config(path)?;
+        mapping.validate()?;
+        Ok(mapping)
+    }
+
+    /// Load from the default config path, falling back to the built-in table
+    pub fn load_or_default() -> Self {
+        match Self::load(SCHEMATIC_MAPPING_PATH) {
+            Ok(mapping) => {
+                info!("Loaded schematic mapping from {}", SCHEMATIC_MAPPING_PATH);
+                mapping
+            }
+            Err(e) => {
+                warn!("{}. Using the built-in schematic mapping.", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), SchematicMappingError> {
+        if self.fallback_voxel.is_empty() || self.fallback_block.is_empty() {
+            return Err(SchematicMappingError::Invalid(
+                "fallback_voxel and fallback_block must be set".to_string(),
+            ));
+        }
+        if self.vox_indices.contains_key(&0) {
+            return Err(SchematicMappingError::Invalid(
+                "vox_indices start at 1; index 0 is empty space".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Voxel for a block state such as `minecraft:oak_log[axis=y]`
+    pub fn voxel_for_block(&self, block: &str, registry: &VoxelRegistry) -> VoxelType {
+        let id = block.split('[').next().unwrap_or(block);
+        let id = if id.contains(':') {
+            id.to_string()
+        } else {
+            format!("minecraft:{}", id)
+        };
+        match self.blocks.get(&id) {
+            Some(name) => self.voxel_named(name, registry),
+            None => self.fallback(registry),
+        }
+    }
+
+    /// Block state written for a voxel
+    pub fn block_for_voxel(&self, voxel: VoxelType, registry: &VoxelRegistry) -> String {
+        if voxel == VoxelType::Air {
+            return "minecraft:air".to_string();
+        }
+        let name = &registry.info(voxel).name;
+        if let Some(block) = self.export_blocks.get(name) {
+            return block.clone();
+        }
+        self.blocks
+            .iter()
+            .find(|(_, mapped)| *mapped == name)
+            .map(|(block, _)| block.clone())
+            .unwrap_or_else(|| self.fallback_block.clone())
+    }
+
+    /// Voxel for a MagicaVoxel palette entry; `color` is None for files
+    /// without their own palette
+    pub fn voxel_for_vox(
+        &self,
+        index: u8,
+        color: Option<[u8; 3]>,
+        registry: &VoxelRegistry,
+    ) -> VoxelType {
+        if let Some(name) = self.vox_indices.get(&index) {
+            return self.voxel_named(name, registry);
+        }
+        let nearest = color.and_then(|color| {
+            self.vox_colors.iter().min_by_key(|entry| {
+                entry
+                    .color
+                    .iter()
+                    .zip(color)
+                    .map(|(a, b)| (*a as i32 - b as i32).pow(2))
+                    .sum::<i32>()
+            })
+        });
+        match nearest {
+            Some(entry) => self.voxel_named(&entry.voxel, registry),
+            None => self.fallback(registry),
+        }
+    }
+
+    /// Colour written for a voxel in MagicaVoxel files
+    pub fn color_for_voxel(&self, voxel: VoxelType, registry: &VoxelRegistry) -> [u8; 3] {
+        let name = &registry.info(voxel).name;
+        self.vox_colors
+            .iter()
+            .find(|entry| &entry.voxel == name)
+            .map_or(UNMAPPED_COLOR, |entry| entry.color)
+    }
+
+    /// Registry voxel by name, or the fallback if the registry lacks it
+    pub fn voxel_named(&self, name: &str, registry: &VoxelRegistry) -> VoxelType {
+        registry
+            .by_name(name)
+            .unwrap_or_else(|| self.fallback(registry))
+    }
+
+    fn fallback(&self, registry: &VoxelRegistry) -> VoxelType {
+        registry
+            .by_name(&self.fallback_voxel)
+            .unwrap_or(VoxelType::Rock)
+    }
+}
@@ -0,0 +1,359 @@
+//! Reusable prefabs saved from world regions.
+//!
+//! Schematics are stored in `schematics/` in their own format (`.vxs`) and can
+//! be imported from and exported to MagicaVoxel `.vox` and Sponge `.schem`
+//! files. Foreign block types are translated through [`SchematicMapping`].
+
+mod mapping;
+mod nbt;
+mod sponge;
+mod vox;
+
+pub use mapping::{SchematicMapping, SchematicMappingError, VoxColor, SCHEMATIC_MAPPING_PATH};
+
+use super::history::{EditHistory, EditSummary};
+use super::palette::PlacementPaletteState;
+use super::world_edit::{self, Region, Selection, VoxelBuffer};
+use super::EditMode;
+use crate::voxel::registry::VoxelRegistry;
+use crate::voxel::types::VoxelType;
+use crate::voxel::world::VoxelWorld;
+use bevy::prelude::*;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+pub const SCHEMATICS_DIR: &str = "schematics";
+
+/// Header of native schematic files
+const MAGIC: &[u8; 4] = b"VXSC";
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchematicFormat {
+    /// This game's own format
+    Native,
+    /// MagicaVoxel
+    Vox,
+    /// Sponge schematic (WorldEdit)
+    Sponge,
+}
+
+impl SchematicFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "vxs" => Some(Self::Native),
+            "vox" => Some(Self::Vox),
+            "schem" => Some(Self::Sponge),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Native => "vxs",
+            Self::Vox => "vox",
+            Self::Sponge => "schem",
+        }
+    }
+}
+
+/// Voxels are stored as indices into a palette of registry names, so the
+/// file stays valid when voxel ids change
+#[derive(Serialize, Deserialize)]
+struct SchematicData {
+    name: String,
+    size: [i32; 3],
+    palette: Vec<String>,
+    voxels: Vec<u16>,
+}
+
+/// A named box of voxels
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schematic {
+    pub name: String,
+    pub buffer: VoxelBuffer,
+}
+
+impl Schematic {
+    pub fn new(name: impl Into<String>, buffer: VoxelBuffer) -> Self {
+        Self {
+            name: name.into(),
+            buffer,
+        }
+    }
+
+    /// Copy a region of the world
+    pub fn from_region(
+        world: &VoxelWorld,
+        region: Region,
+        name: impl Into<String>,
+    ) -> Result<Self, String> {
+        Ok(Self::new(name, world_edit::copy(world, region)?))
+    }
+
+    pub fn size(&self) -> IVec3 {
+        self.buffer.size()
+    }
+
+    /// Distinct voxel types in order of first appearance
+    pub fn palette(&self) -> Vec<VoxelType> {
+        let mut palette = Vec::new();
+        for voxel in self.buffer.voxels() {
+            if !palette.contains(voxel) {
+                palette.push(*voxel);
+            }
+        }
+        palette
+    }
+
+    /// Paste with the minimum corner at `origin` as one undo step
+    pub fn place(
+        &self,
+        world: &mut VoxelWorld,
+        history: &mut EditHistory,
+        origin: IVec3,
+    ) -> Result<EditSummary, String> {
+        world_edit::paste(world, history, &self.buffer, origin)
+    }
+
+    /// Load any supported format, chosen by file extension
+    pub fn load<P: AsRef<Path>>(
+        path: P,
+        mapping: &SchematicMapping,
+        registry: &VoxelRegistry,
+    ) -> Result<Self, String> {
+        let path = path.as_ref();
+        let format = SchematicFormat::from_path(path)
+            .ok_or_else(|| format!("{:?} is not a schematic file", path))?;
+        let bytes = fs::read(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let result = match format {
+            SchematicFormat::Native => Self::from_native(&bytes, mapping, registry),
+            SchematicFormat::Vox => {
+                vox::read(&bytes, mapping, registry).map(|b| Self::new(name, b))
+            }
+            SchematicFormat::Sponge => {
+                sponge::read(&bytes, mapping, registry).map(|b| Self::new(name, b))
+            }
+        };
+        result.map_err(|e| format!("{:?}: {}", path, e))
+    }
+
+    /// Save in the format matching the file extension
+    pub fn save<P: AsRef<Path>>(
+        &self,
+        path: P,
+        mapping: &SchematicMapping,
+        registry: &VoxelRegistry,
+    ) -> Result<(), String> {
+        let path = path.as_ref();
+        let format = SchematicFormat::from_path(path)
+            .ok_or_else(|| format!("{:?} is not a schematic file", path))?;
+        let bytes = match format {
+            SchematicFormat::Native => self.to_native(registry)?,
+            SchematicFormat::Vox => vox::write(&self.buffer, mapping, registry)?,
+            SchematicFormat::Sponge => sponge::write(&self.buffer, mapping, registry)?,
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+        fs::write(path, bytes).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    fn to_native(&self, registry: &VoxelRegistry) -> Result<Vec<u8>, String> {
+        let palette = self.palette();
+        let index: HashMap<VoxelType, u16> = palette
+            .iter()
+            .enumerate()
+            .map(|(i, voxel)| (*voxel, i as u16))
+            .collect();
+        let size = self.size();
+        let data = SchematicData {
+            name: self.name.clone(),
+            size: size.to_array(),
+            palette: palette
+                .iter()
+                .map(|voxel| registry.info(*voxel).name.clone())
+                .collect(),
+            voxels: self.buffer.voxels().iter().map(|v| index[v]).collect(),
+        };
+
+        let bytes = bincode::serialize(&data)
+            .map_err(|e| format!("Failed to serialize schematic: {}", e))?;
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&bytes)
+            .map_err(|e| format!("Failed to compress schematic: {}", e))?;
+        let compressed = encoder
+            .finish()
+            .map_err(|e| format!("Failed to compress schematic: {}", e))?;
+
+        let mut out = Vec::with_capacity(8 + compressed.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&compressed);
+        Ok(out)
+    }
+
+    fn from_native(
+        bytes: &[u8],
+        mapping: &SchematicMapping,
+        registry: &VoxelRegistry,
+    ) -> Result<Self, String> {
+        if bytes.len() < 8 || &bytes[0..4] != MAGIC {
+            return Err("Not a schematic file".to_string());
+        }
+        let version = u32::from_le_bytes(bytes[4..8].try_into().expect("4 bytes"));
+        if version > FORMAT_VERSION {
+            return Err(format!(
+                "Schematic format {} is newer than supported ({})",
+                version, FORMAT_VERSION
+            ));
+        }
+
+        let mut decompressed = Vec::new();
+        ZlibDecoder::new(&bytes[8..])
+            .read_to_end(&mut decompressed)
+            .map_err(|e| format!("Failed to decompress schematic: {}", e))?;
+        let data: SchematicData = bincode::deserialize(&decompressed)
+            .map_err(|e| format!("Failed to parse schematic: {}", e))?;
+
+        // Types removed from the registry fall back like unmapped blocks
+        let palette: Vec<VoxelType> = data
+            .palette
+            .iter()
+            .map(|name| mapping.voxel_named(name, registry))
+            .collect();
+        let voxels = data
+            .voxels
+            .iter()
+            .map(|i| {
+                palette
+                    .get(*i as usize)
+                    .copied()
+                    .ok_or_else(|| format!("Voxel uses unknown palette index {}", i))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let buffer = VoxelBuffer::from_voxels(IVec3::from_array(data.size), voxels)?;
+        Ok(Self::new(data.name, buffer))
+    }
+}
+
+/// Schematic files in a directory, sorted by name
+pub fn list_schematics<P: AsRef<Path>>(dir: P) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && SchematicFormat::from_path(path).is_some())
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// A file name in `dir` that isn't taken yet, like `schematic_3.vxs`
+pub fn unused_schematic_path<P: AsRef<Path>>(
+    dir: P,
+    stem: &str,
+    format: SchematicFormat,
+) -> PathBuf {
+    let dir = dir.as_ref();
+    (1..)
+        .map(|n| dir.join(format!("{}_{}.{}", stem, n, format.extension())))
+        .find(|path| !path.exists())
+        .expect("some file name is free")
+}
+
+/// Schematics loaded for placement, keyed by file
+#[derive(Resource, Default)]
+pub struct SchematicLibrary {
+    loaded: HashMap<PathBuf, Schematic>,
+}
+
+impl SchematicLibrary {
+    /// Load a schematic on first use and keep it for later placements
+    pub fn get_or_load(
+        &mut self,
+        path: &Path,
+        mapping: &SchematicMapping,
+        registry: &VoxelRegistry,
+    ) -> Result<&Schematic, String> {
+        if !self.loaded.contains_key(path) {
+            let schematic = Schematic::load(path, mapping, registry)?;
+            self.loaded.insert(path.to_path_buf(), schematic);
+        }
+        Ok(&self.loaded[path])
+    }
+
+    /// Forget cached schematics so edited files are read again
+    pub fn clear(&mut self) {
+        self.loaded.clear();
+    }
+}
+
+/// Save the box selection into `schematics/` with Ctrl+K while editing;
+/// Ctrl+Shift+K also exports `.vox` and `.schem` copies. (Not Ctrl+S: Left
+/// Ctrl is turbo fly, so flying backwards would keep saving.)
+pub fn save_selection_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    edit_mode: Res<EditMode>,
+    selection: Res<Selection>,
+    world: Res<VoxelWorld>,
+    mapping: Res<SchematicMapping>,
+    registry: Res<VoxelRegistry>,
+    mut palette: ResMut<PlacementPaletteState>,
+) {
+    let ctrl = keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if !edit_mode.enabled || !ctrl || !keyboard.just_pressed(KeyCode::KeyK) {
+        return;
+    }
+    let Some(region) = selection.region() else {
+        info!("Select two corners with [ and ] before saving a schematic");
+        return;
+    };
+
+    let path = unused_schematic_path(SCHEMATICS_DIR, "selection", SchematicFormat::Native);
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let schematic = match Schematic::from_region(&world, region, name) {
+        Ok(schematic) => schematic,
+        Err(err) => {
+            warn!("Failed to save schematic: {}", err);
+            return;
+        }
+    };
+
+    let mut paths = vec![path.clone()];
+    if keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        for format in [SchematicFormat::Vox, SchematicFormat::Sponge] {
+            paths.push(path.with_extension(format.extension()));
+        }
+    }
+    for path in paths {
+        match schematic.save(&path, &mapping, &registry) {
+            Ok(()) => info!("Saved {:?} schematic to {:?}", schematic.size(), path),
+            Err(err) => warn!("Failed to save schematic: {}", err),
+        }
+    }
+
+    // List the new file in the placement palette
+    palette.items_initialized = false;
+}
@@ -0,0 +1,285 @@
+//! Minimal reader and writer for Minecraft's NBT format, enough for Sponge
+//! schematics. Files are gzip-compressed big-endian tag trees.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+/// Nesting deeper than this is rejected instead of overflowing the stack
+const MAX_DEPTH: usize = 256;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<u8>),
+    String(String),
+    List(Vec<Tag>),
+    /// Named children in file order
+    Compound(Vec<(String, Tag)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+
+    /// Child of a compound by name
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(children) => children
+                .iter()
+                .find(|(child, _)| child == name)
+                .map(|(_, tag)| tag),
+            _ => None,
+        }
+    }
+
+    /// Any integer tag widened to i64
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Tag::Byte(v) => Some(*v as i64),
+            Tag::Short(v) => Some(*v as i64),
+            Tag::Int(v) => Some(*v as i64),
+            Tag::Long(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&[(String, Tag)]> {
+        match self {
+            Tag::Compound(children) => Some(children),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Tag::ByteArray(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a (possibly gzip-compressed) NBT file into its root name and tag
+pub fn read(bytes: &[u8]) -> Result<(String, Tag), String> {
+    let mut raw = Vec::new();
+    let data = if bytes.starts_with(&[0x1f, 0x8b]) {
+        GzDecoder::new(bytes)
+            .read_to_end(&mut raw)
+            .map_err(|e| format!("Failed to decompress NBT: {}", e))?;
+        raw.as_slice()
+    } else {
+        bytes
+    };
+
+    let mut reader = Reader { data, pos: 0 };
+    let id = reader.u8()?;
+    if id != 10 {
+        return Err(format!("NBT root must be a compound, found tag {}", id));
+    }
+    let name = reader.string()?;
+    let root = reader.payload(id, 0)?;
+    Ok((name, root))
+}
+
+/// Serialize a named root compound as gzip-compressed NBT
+pub fn write(name: &str, root: &Tag) -> Result<Vec<u8>, String> {
+    if root.id() != 10 {
+        return Err("NBT root must be a compound".to_string());
+    }
+    let mut out = Vec::new();
+    out.push(root.id());
+    write_string(&mut out, name)?;
+    write_payload(&mut out, root)?;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&out)
+        .map_err(|e| format!("Failed to compress NBT: {}", e))?;
+    encoder
+        .finish()
+        .map_err(|e| format!("Failed to compress NBT: {}", e))
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| format!("NBT data ends early at byte {}", self.pos))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.take(N)?.try_into().expect("slice has N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    /// Array or list length, checked against the bytes left
+    fn length(&mut self, element_size: usize) -> Result<usize, String> {
+        let len = self.i32()?;
+        let len = usize::try_from(len).map_err(|_| format!("Negative NBT length {}", len))?;
+        if len.saturating_mul(element_size) > self.data.len() - self.pos {
+            return Err(format!("NBT length {} exceeds the data", len));
+        }
+        Ok(len)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = u16::from_be_bytes(self.array()?) as usize;
+        let bytes = self.take(len)?;
+        // Java's modified UTF-8 only differs for NUL and astral characters
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    fn payload(&mut self, id: u8, depth: usize) -> Result<Tag, String> {
+        if depth > MAX_DEPTH {
+            return Err("NBT nesting is too deep".to_string());
+        }
+
+        Ok(match id {
+            1 => Tag::Byte(self.u8()? as i8),
+            2 => Tag::Short(i16::from_be_bytes(self.array()?)),
+            3 => Tag::Int(self.i32()?),
+            4 => Tag::Long(i64::from_be_bytes(self.array()?)),
+            5 => Tag::Float(f32::from_be_bytes(self.array()?)),
+            6 => Tag::Double(f64::from_be_bytes(self.array()?)),
+            7 => {
+                let len = self.length(1)?;
+                Tag::ByteArray(self.take(len)?.to_vec())
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let element = self.u8()?;
+                let len = self.length(1)?;
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(self.payload(element, depth + 1)?);
+                }
+                Tag::List(items)
+            }
+            10 => {
+                let mut children = Vec::new();
+                loop {
+                    let child = self.u8()?;
+                    if child == 0 {
+                        break;
+                    }
+                    let name = self.string()?;
+                    children.push((name, self.payload(child, depth + 1)?));
+                }
+                Tag::Compound(children)
+            }
+            11 => {
+                let len = self.length(4)?;
+                Tag::IntArray((0..len).map(|_| self.i32()).collect::<Result<_, _>>()?)
+            }
+            12 => {
+                let len = self.length(8)?;
+                let mut values = Vec::with_capacity(len);
+                for _ in 0..len {
+                    values.push(i64::from_be_bytes(self.array()?));
+                }
+                Tag::LongArray(values)
+            }
+            other => return Err(format!("Unknown NBT tag type {}", other)),
+        })
+    }
+}
+
+fn write_string(out: &mut Vec<u8>, value: &str) -> Result<(), String> {
+    let len = u16::try_from(value.len()).map_err(|_| "NBT string is too long".to_string())?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(value.as_bytes());
+    Ok(())
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) -> Result<(), String> {
+    let len = i32::try_from(len).map_err(|_| "NBT array is too long".to_string())?;
+    out.extend_from_slice(&len.to_be_bytes());
+    Ok(())
+}
+
+fn write_payload(out: &mut Vec<u8>, tag: &Tag) -> Result<(), String> {
+    match tag {
+        Tag::Byte(v) => out.push(*v as u8),
+        Tag::Short(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Int(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Long(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Float(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Double(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::ByteArray(bytes) => {
+            write_len(out, bytes.len())?;
+            out.extend_from_slice(bytes);
+        }
+        Tag::String(value) => write_string(out, value)?,
+        Tag::List(items) => {
+            let element = items.first().map_or(0, Tag::id);
+            if items.iter().any(|item| item.id() != element) {
+                return Err("NBT list items must share one type".to_string());
+            }
+            out.push(element);
+            write_len(out, items.len())?;
+            for item in items {
+                write_payload(out, item)?;
+            }
+        }
+        Tag::Compound(children) => {
+            for (name, child) in children {
+                out.push(child.id());
+                write_string(out, name)?;
+                write_payload(out, child)?;
+            }
+            out.push(0);
+        }
+        Tag::IntArray(values) => {
+            write_len(out, values.len())?;
+            for v in values {
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+        Tag::LongArray(values) => {
+            write_len(out, values.len())?;
+            for v in values {
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+    }
+    Ok(())
+}
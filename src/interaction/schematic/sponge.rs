@@ -0,0 +1,171 @@
+//! Sponge `.schem` files as written by WorldEdit.
+//!
+//! Versions 1 to 3 are read; files are written as version 2, which current
+//! tools still load. Block entities and biomes are not carried over.
+
+use super::mapping::SchematicMapping;
+use super::nbt::{self, Tag};
+use crate::interaction::world_edit::VoxelBuffer;
+use crate::voxel::registry::VoxelRegistry;
+use crate::voxel::types::VoxelType;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+const WRITE_VERSION: i32 = 2;
+
+/// Minecraft 1.20.1, the data version written into exported files
+const DATA_VERSION: i32 = 3465;
+
+fn dimension(root: &Tag, name: &str) -> Result<i32, String> {
+    root.get(name)
+        .and_then(Tag::as_int)
+        // Dimensions are unsigned shorts stored in signed tags
+        .map(|v| (v as i16) as u16 as i32)
+        .filter(|v| *v > 0)
+        .ok_or_else(|| format!("Schematic is missing its {}", name))
+}
+
+fn read_varints(bytes: &[u8], count: usize) -> Result<Vec<u32>, String> {
+    let mut values = Vec::with_capacity(count.min(bytes.len()));
+    let mut value = 0u32;
+    let mut shift = 0;
+    for byte in bytes {
+        value |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            values.push(value);
+            value = 0;
+            shift = 0;
+        } else {
+            shift += 7;
+            if shift > 28 {
+                return Err("Block data varint is too long".to_string());
+            }
+        }
+    }
+    if values.len() != count {
+        return Err(format!(
+            "Schematic has {} blocks of data for {} positions",
+            values.len(),
+            count
+        ));
+    }
+    Ok(values)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+pub fn read(
+    bytes: &[u8],
+    mapping: &SchematicMapping,
+    registry: &VoxelRegistry,
+) -> Result<VoxelBuffer, String> {
+    let (_, root) = nbt::read(bytes)?;
+    // Version 3 nests everything under a "Schematic" compound
+    let root = root.get("Schematic").unwrap_or(&root);
+    let version = root.get("Version").and_then(Tag::as_int).unwrap_or(1);
+
+    let (palette, data) = if version >= 3 {
+        let blocks = root.get("Blocks").ok_or("Schematic has no blocks")?;
+        (blocks.get("Palette"), blocks.get("Data"))
+    } else {
+        (root.get("Palette"), root.get("BlockData"))
+    };
+    let palette = palette
+        .and_then(Tag::as_compound)
+        .ok_or("Schematic has no block palette")?;
+    let data = data
+        .and_then(Tag::as_bytes)
+        .ok_or("Schematic has no block data")?;
+
+    let size = IVec3::new(
+        dimension(root, "Width")?,
+        dimension(root, "Height")?,
+        dimension(root, "Length")?,
+    );
+
+    let mut voxels_by_index: HashMap<u32, VoxelType> = HashMap::new();
+    for (block, index) in palette {
+        let index = index
+            .as_int()
+            .and_then(|i| u32::try_from(i).ok())
+            .ok_or_else(|| format!("Palette entry {} has no index", block))?;
+        voxels_by_index.insert(index, mapping.voxel_for_block(block, registry));
+    }
+
+    let count = size.x as usize * size.y as usize * size.z as usize;
+    let indices = read_varints(data, count)?;
+
+    // Sponge orders blocks y, z, x from slowest to fastest
+    let mut buffer = VoxelBuffer::new(size);
+    let mut indices = indices.into_iter();
+    for y in 0..size.y {
+        for z in 0..size.z {
+            for x in 0..size.x {
+                let index = indices.next().expect("one index per position");
+                let voxel = voxels_by_index
+                    .get(&index)
+                    .copied()
+                    .ok_or_else(|| format!("Block data uses unknown palette index {}", index))?;
+                buffer.set(IVec3::new(x, y, z), voxel);
+            }
+        }
+    }
+    Ok(buffer)
+}
+
+pub fn write(
+    buffer: &VoxelBuffer,
+    mapping: &SchematicMapping,
+    registry: &VoxelRegistry,
+) -> Result<Vec<u8>, String> {
+    let size = buffer.size();
+    if size.cmpgt(IVec3::splat(u16::MAX as i32)).any() {
+        return Err(format!(
+            "Schematic of size {:?} is too large for .schem",
+            size
+        ));
+    }
+
+    // Voxels without their own mapping can share the fallback block, so
+    // palette entries are keyed by block rather than by voxel
+    let mut palette: Vec<(String, Tag)> = Vec::new();
+    let mut block_indices: HashMap<String, u32> = HashMap::new();
+    let mut indices: HashMap<VoxelType, u32> = HashMap::new();
+    let mut data = Vec::new();
+    for y in 0..size.y {
+        for z in 0..size.z {
+            for x in 0..size.x {
+                let voxel = buffer.get(IVec3::new(x, y, z)).unwrap_or(VoxelType::Air);
+                let index = *indices.entry(voxel).or_insert_with(|| {
+                    let block = mapping.block_for_voxel(voxel, registry);
+                    *block_indices.entry(block.clone()).or_insert_with(|| {
+                        let index = palette.len() as u32;
+                        palette.push((block, Tag::Int(index as i32)));
+                        index
+                    })
+                });
+                write_varint(&mut data, index);
+            }
+        }
+    }
+
+    let root = Tag::Compound(vec![
+        ("Version".to_string(), Tag::Int(WRITE_VERSION)),
+        ("DataVersion".to_string(), Tag::Int(DATA_VERSION)),
+        ("Width".to_string(), Tag::Short(size.x as u16 as i16)),
+        ("Height".to_string(), Tag::Short(size.y as u16 as i16)),
+        ("Length".to_string(), Tag::Short(size.z as u16 as i16)),
+        ("Offset".to_string(), Tag::IntArray(vec![0, 0, 0])),
+        ("PaletteMax".to_string(), Tag::Int(palette.len() as i32)),
+        ("Palette".to_string(), Tag::Compound(palette)),
+        ("BlockData".to_string(), Tag::ByteArray(data)),
+        ("BlockEntities".to_string(), Tag::List(Vec::new())),
+    ]);
+    nbt::write("Schematic", &root)
+}
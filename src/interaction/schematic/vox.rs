@@ -0,0 +1,184 @@
+//! MagicaVoxel `.vox` files.
+//!
+//! Only the first model is read; scene graph transforms and materials are
+//! ignored. MagicaVoxel is Z-up, so its axes are turned to Y-up on the way
+//! in and back on the way out.
+
+use super::mapping::SchematicMapping;
+use crate::interaction::world_edit::VoxelBuffer;
+use crate::voxel::registry::VoxelRegistry;
+use crate::voxel::types::VoxelType;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+const MAGIC: &[u8; 4] = b"VOX ";
+const VERSION: i32 = 150;
+
+/// Models can't be larger than this along any axis
+const MAX_MODEL_SIZE: i32 = 256;
+
+struct RawChunk<'a> {
+    id: [u8; 4],
+    content: &'a [u8],
+    children: &'a [u8],
+}
+
+fn read_i32(bytes: &[u8], offset: usize) -> Result<i32, String> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| i32::from_le_bytes(b.try_into().expect("4 bytes")))
+        .ok_or_else(|| "VOX data ends early".to_string())
+}
+
+/// Split a run of chunks into id, content and children
+fn read_chunks(mut bytes: &[u8]) -> Result<Vec<RawChunk<'_>>, String> {
+    let mut chunks = Vec::new();
+    while !bytes.is_empty() {
+        if bytes.len() < 12 {
+            return Err("Truncated VOX chunk header".to_string());
+        }
+        let id: [u8; 4] = bytes[0..4].try_into().expect("4 bytes");
+        let content_len =
+            usize::try_from(read_i32(bytes, 4)?).map_err(|_| "Negative VOX chunk size")?;
+        let children_len =
+            usize::try_from(read_i32(bytes, 8)?).map_err(|_| "Negative VOX chunk size")?;
+        let end = 12usize
+            .checked_add(content_len)
+            .and_then(|n| n.checked_add(children_len))
+            .filter(|end| *end <= bytes.len())
+            .ok_or_else(|| {
+                format!(
+                    "VOX chunk {} overruns the file",
+                    String::from_utf8_lossy(&id)
+                )
+            })?;
+
+        chunks.push(RawChunk {
+            id,
+            content: &bytes[12..12 + content_len],
+            children: &bytes[12 + content_len..end],
+        });
+        bytes = &bytes[end..];
+    }
+    Ok(chunks)
+}
+
+pub fn read(
+    bytes: &[u8],
+    mapping: &SchematicMapping,
+    registry: &VoxelRegistry,
+) -> Result<VoxelBuffer, String> {
+    if bytes.len() < 8 || &bytes[0..4] != MAGIC {
+        return Err("Not a MagicaVoxel file".to_string());
+    }
+    let main = read_chunks(&bytes[8..])?
+        .into_iter()
+        .find(|chunk| &chunk.id == b"MAIN")
+        .ok_or("VOX file has no MAIN chunk")?;
+    let children = read_chunks(main.children)?;
+
+    let size_chunk = children
+        .iter()
+        .find(|chunk| &chunk.id == b"SIZE")
+        .ok_or("VOX file has no model")?;
+    let voxel_chunk = children
+        .iter()
+        .find(|chunk| &chunk.id == b"XYZI")
+        .ok_or("VOX file has no voxel data")?;
+    let palette = children
+        .iter()
+        .find(|chunk| &chunk.id == b"RGBA")
+        .map(|chunk| chunk.content);
+
+    let vox_size = IVec3::new(
+        read_i32(size_chunk.content, 0)?,
+        read_i32(size_chunk.content, 4)?,
+        read_i32(size_chunk.content, 8)?,
+    );
+    if vox_size.cmplt(IVec3::ONE).any() || vox_size.cmpgt(IVec3::splat(MAX_MODEL_SIZE)).any() {
+        return Err(format!("Invalid VOX model size {:?}", vox_size));
+    }
+
+    // Z-up to Y-up, keeping the model's handedness
+    let mut buffer = VoxelBuffer::new(IVec3::new(vox_size.x, vox_size.z, vox_size.y));
+    let count =
+        usize::try_from(read_i32(voxel_chunk.content, 0)?).map_err(|_| "Negative voxel count")?;
+    let data = voxel_chunk
+        .content
+        .get(4..4 + count.saturating_mul(4))
+        .ok_or("VOX voxel data ends early")?;
+
+    let mut resolved: HashMap<u8, VoxelType> = HashMap::new();
+    for voxel in data.chunks_exact(4) {
+        let (x, y, z, index) = (voxel[0] as i32, voxel[1] as i32, voxel[2] as i32, voxel[3]);
+        let voxel_type = *resolved.entry(index).or_insert_with(|| {
+            let color = palette.and_then(|rgba| {
+                let offset = (index as usize).checked_sub(1)? * 4;
+                rgba.get(offset..offset + 3).map(|c| [c[0], c[1], c[2]])
+            });
+            mapping.voxel_for_vox(index, color, registry)
+        });
+        buffer.set(IVec3::new(x, z, vox_size.y - 1 - y), voxel_type);
+    }
+
+    Ok(buffer)
+}
+
+fn push_chunk(out: &mut Vec<u8>, id: &[u8; 4], content: &[u8], children: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(content.len() as i32).to_le_bytes());
+    out.extend_from_slice(&(children.len() as i32).to_le_bytes());
+    out.extend_from_slice(content);
+    out.extend_from_slice(children);
+}
+
+pub fn write(
+    buffer: &VoxelBuffer,
+    mapping: &SchematicMapping,
+    registry: &VoxelRegistry,
+) -> Result<Vec<u8>, String> {
+    let size = buffer.size();
+    if size.cmpgt(IVec3::splat(MAX_MODEL_SIZE)).any() {
+        return Err(format!(
+            "MagicaVoxel models are limited to {} voxels per side, schematic is {:?}",
+            MAX_MODEL_SIZE, size
+        ));
+    }
+
+    // One palette slot per voxel type, starting at index 1
+    let mut indices: HashMap<VoxelType, u8> = HashMap::new();
+    let mut rgba = vec![0u8; 256 * 4];
+    let mut voxels = Vec::new();
+    for (pos, voxel) in buffer.iter() {
+        if voxel == VoxelType::Air {
+            continue;
+        }
+        let next = indices.len() + 1;
+        let index = *indices.entry(voxel).or_insert_with(|| {
+            let [r, g, b] = mapping.color_for_voxel(voxel, registry);
+            rgba[(next - 1) * 4..next * 4].copy_from_slice(&[r, g, b, 255]);
+            next as u8
+        });
+        // Y-up back to Z-up
+        voxels.extend_from_slice(&[pos.x as u8, (size.z - 1 - pos.z) as u8, pos.y as u8, index]);
+    }
+
+    let mut size_content = Vec::with_capacity(12);
+    for v in [size.x, size.z, size.y] {
+        size_content.extend_from_slice(&v.to_le_bytes());
+    }
+    let mut xyzi = Vec::with_capacity(4 + voxels.len());
+    xyzi.extend_from_slice(&((voxels.len() / 4) as i32).to_le_bytes());
+    xyzi.extend_from_slice(&voxels);
+
+    let mut children = Vec::new();
+    push_chunk(&mut children, b"SIZE", &size_content, &[]);
+    push_chunk(&mut children, b"XYZI", &xyzi, &[]);
+    push_chunk(&mut children, b"RGBA", &rgba, &[]);
+
+    let mut out = Vec::with_capacity(20 + children.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    push_chunk(&mut out, b"MAIN", &[], &children);
+    Ok(out)
+}
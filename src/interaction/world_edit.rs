@@ -0,0 +1,484 @@
+//! Box selection and bulk edits: fill, replace, hollow, walls, clear,
+//! copy/paste with rotation and mirroring.
+//!
+//! Edits are applied through [`EditHistory::set_voxels`], so each operation is
+//! one undo step and every touched chunk is marked dirty once.
+
+use super::history::{EditHistory, EditSummary};
+use super::{DeleteMode, DragState, EditMode, HeldBlock, TargetedBlock};
+use crate::camera::controller::PlayerCamera;
+use crate::voxel::types::VoxelType;
+use crate::voxel::world::VoxelWorld;
+use bevy::prelude::*;
+
+/// Largest region a single operation may touch (128³ voxels)
+pub const MAX_EDIT_VOLUME: i64 = 128 * 128 * 128;
+
+/// An axis-aligned box of voxels, inclusive on both corners
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub min: IVec3,
+    pub max: IVec3,
+}
+
+impl Region {
+    pub fn new(a: IVec3, b: IVec3) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    pub fn size(&self) -> IVec3 {
+        self.max - self.min + IVec3::ONE
+    }
+
+    pub fn volume(&self) -> i64 {
+        let size = self.size().as_i64vec3();
+        size.x * size.y * size.z
+    }
+
+    pub fn contains(&self, pos: IVec3) -> bool {
+        pos.cmpge(self.min).all() && pos.cmple(self.max).all()
+    }
+
+    /// Every position in the box, x fastest
+    pub fn positions(self) -> impl Iterator<Item = IVec3> {
+        let (min, max) = (self.min, self.max);
+        (min.z..=max.z).flat_map(move |z| {
+            (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec3::new(x, y, z)))
+        })
+    }
+
+    /// On one of the six faces of the box
+    pub fn is_shell(&self, pos: IVec3) -> bool {
+        self.is_wall(pos) || pos.y == self.min.y || pos.y == self.max.y
+    }
+
+    /// On one of the four vertical sides of the box
+    pub fn is_wall(&self, pos: IVec3) -> bool {
+        pos.x == self.min.x || pos.x == self.max.x || pos.z == self.min.z || pos.z == self.max.z
+    }
+
+    fn check_volume(&self) -> Result<(), String> {
+        if self.volume() > MAX_EDIT_VOLUME {
+            return Err(format!(
+                "Selection of {} voxels is larger than the limit of {}",
+                self.volume(),
+                MAX_EDIT_VOLUME
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The two corners picked for the box selection
+#[derive(Resource, Default)]
+pub struct Selection {
+    pub first: Option<IVec3>,
+    pub second: Option<IVec3>,
+}
+
+impl Selection {
+    pub fn region(&self) -> Option<Region> {
+        Some(Region::new(self.first?, self.second?))
+    }
+
+    pub fn clear(&mut self) {
+        self.first = None;
+        self.second = None;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MirrorAxis {
+    X,
+    Y,
+    Z,
+}
+
+/// A box of voxels lifted out of the world, indexed x + y*sx + z*sx*sy
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoxelBuffer {
+    size: IVec3,
+    voxels: Vec<VoxelType>,
+}
+
+impl VoxelBuffer {
+    /// An all-air buffer
+    pub fn new(size: IVec3) -> Self {
+        let size = size.max(IVec3::ONE);
+        Self {
+            size,
+            voxels: vec![VoxelType::Air; (size.x * size.y * size.z) as usize],
+        }
+    }
+
+    pub fn from_voxels(size: IVec3, voxels: Vec<VoxelType>) -> Result<Self, String> {
+        if size.cmplt(IVec3::ONE).any() {
+            return Err(format!("Invalid buffer size {:?}", size));
+        }
+        let expected = size.x as usize * size.y as usize * size.z as usize;
+        if voxels.len() != expected {
+            return Err(format!(
+                "Buffer of size {:?} needs {} voxels, got {}",
+                size,
+                expected,
+                voxels.len()
+            ));
+        }
+        Ok(Self { size, voxels })
+    }
+
+    pub fn size(&self) -> IVec3 {
+        self.size
+    }
+
+    pub fn voxels(&self) -> &[VoxelType] {
+        &self.voxels
+    }
+
+    pub fn get(&self, local: IVec3) -> Option<VoxelType> {
+        self.index(local).map(|i| self.voxels[i])
+    }
+
+    pub fn set(&mut self, local: IVec3, voxel: VoxelType) -> bool {
+        match self.index(local) {
+            Some(i) => {
+                self.voxels[i] = voxel;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Local positions and voxels, x fastest
+    pub fn iter(&self) -> impl Iterator<Item = (IVec3, VoxelType)> + '_ {
+        Region::new(IVec3::ZERO, self.size - IVec3::ONE)
+            .positions()
+            .zip(self.voxels.iter().copied())
+    }
+
+    /// Rotated by quarter turns about the vertical axis; negative turns go
+    /// the other way
+    pub fn rotated_y(&self, quarter_turns: i32) -> Self {
+        let mut rotated = self.clone();
+        for _ in 0..quarter_turns.rem_euclid(4) {
+            let size = rotated.size;
+            let mut next = VoxelBuffer::new(IVec3::new(size.z, size.y, size.x));
+            for (pos, voxel) in rotated.iter() {
+                next.set(IVec3::new(size.z - 1 - pos.z, pos.y, pos.x), voxel);
+            }
+            rotated = next;
+        }
+        rotated
+    }
+
+    pub fn mirrored(&self, axis: MirrorAxis) -> Self {
+        let mut mirrored = VoxelBuffer::new(self.size);
+        let max = self.size - IVec3::ONE;
+        for (pos, voxel) in self.iter() {
+            let flipped = match axis {
+                MirrorAxis::X => IVec3::new(max.x - pos.x, pos.y, pos.z),
+                MirrorAxis::Y => IVec3::new(pos.x, max.y - pos.y, pos.z),
+                MirrorAxis::Z => IVec3::new(pos.x, pos.y, max.z - pos.z),
+            };
+            mirrored.set(flipped, voxel);
+        }
+        mirrored
+    }
+
+    fn index(&self, local: IVec3) -> Option<usize> {
+        if local.cmplt(IVec3::ZERO).any() || local.cmpge(self.size).any() {
+            return None;
+        }
+        Some((local.x + local.y * self.size.x + local.z * self.size.x * self.size.y) as usize)
+    }
+}
+
+/// The last copied region, pasted relative to its minimum corner
+#[derive(Resource, Default)]
+pub struct Clipboard {
+    pub buffer: Option<VoxelBuffer>,
+}
+
+/// Set every position the predicate picks to the voxel it returns, skipping
+/// unbreakable voxels, as one undo step
+fn apply_region(
+    world: &mut VoxelWorld,
+    history: &mut EditHistory,
+    label: &str,
+    region: Region,
+    mut change: impl FnMut(IVec3, VoxelType) -> Option<VoxelType>,
+) -> Result<EditSummary, String> {
+    region.check_volume()?;
+
+    let mut skipped = 0;
+    let mut changes = Vec::new();
+    for pos in region.positions() {
+        let Some(existing) = world.get_voxel(pos) else {
+            skipped += 1;
+            continue;
+        };
+        match change(pos, existing) {
+            Some(voxel) if voxel == existing => {}
+            Some(voxel) if existing.info().is_breakable() => changes.push((pos, voxel)),
+            Some(_) => skipped += 1,
+            None => {}
+        }
+    }
+
+    let open = history.in_transaction();
+    history.begin(label);
+    let mut summary = history.set_voxels(world, changes);
+    if !open {
+        history.commit();
+    }
+    summary.skipped += skipped;
+    Ok(summary)
+}
+
+pub fn fill(
+    world: &mut VoxelWorld,
+    history: &mut EditHistory,
+    region: Region,
+    voxel: VoxelType,
+) -> Result<EditSummary, String> {
+    apply_region(world, history, "Fill", region, |_, _| Some(voxel))
+}
+
+pub fn replace(
+    world: &mut VoxelWorld,
+    history: &mut EditHistory,
+    region: Region,
+    from: VoxelType,
+    to: VoxelType,
+) -> Result<EditSummary, String> {
+    apply_region(world, history, "Replace", region, |_, existing| {
+        (existing == from).then_some(to)
+    })
+}
+
+/// Empty the inside of the box, keeping its six faces
+pub fn hollow(
+    world: &mut VoxelWorld,
+    history: &mut EditHistory,
+    region: Region,
+) -> Result<EditSummary, String> {
+    apply_region(world, history, "Hollow", region, |pos, _| {
+        (!region.is_shell(pos)).then_some(VoxelType::Air)
+    })
+}
+
+/// Build the four vertical sides of the box
+pub fn walls(
+    world: &mut VoxelWorld,
+    history: &mut EditHistory,
+    region: Region,
+    voxel: VoxelType,
+) -> Result<EditSummary, String> {
+    apply_region(world, history, "Walls", region, |pos, _| {
+        region.is_wall(pos).then_some(voxel)
+    })
+}
+
+pub fn clear(
+    world: &mut VoxelWorld,
+    history: &mut EditHistory,
+    region: Region,
+) -> Result<EditSummary, String> {
+    apply_region(world, history, "Clear", region, |_, _| Some(VoxelType::Air))
+}
+
+/// Copy a region; voxels in unloaded chunks come out as air
+pub fn copy(world: &VoxelWorld, region: Region) -> Result<VoxelBuffer, String> {
+    region.check_volume()?;
+    let voxels = region
+        .positions()
+        .map(|pos| world.get_voxel(pos).unwrap_or(VoxelType::Air))
+        .collect();
+    VoxelBuffer::from_voxels(region.size(), voxels)
+}
+
+/// Paste a buffer with its minimum corner at `origin`
+pub fn paste(
+    world: &mut VoxelWorld,
+    history: &mut EditHistory,
+    buffer: &VoxelBuffer,
+    origin: IVec3,
+) -> Result<EditSummary, String> {
+    let region = Region::new(origin, origin + buffer.size() - IVec3::ONE);
+    apply_region(world, history, "Paste", region, |pos, _| {
+        buffer.get(pos - origin)
+    })
+}
+
+/// Selection corners, bulk operations and clipboard keys while edit mode is on:
+/// `[`/`]` set the corners on the targeted block, `\` deselects,
+/// Ctrl+F fills and Ctrl+B builds walls with the held block, Ctrl+E replaces
+/// the targeted block type with the held block, Ctrl+H hollows,
+/// Ctrl+X clears (after copying), Ctrl+C copies, Ctrl+V pastes onto the
+/// targeted face, Ctrl+R rotates and Ctrl+M mirrors the clipboard along the
+/// view direction
+pub fn world_edit_input_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    edit_mode: Res<EditMode>,
+    delete_mode: Res<DeleteMode>,
+    drag_state: Res<DragState>,
+    targeted: Res<TargetedBlock>,
+    held: Res<HeldBlock>,
+    camera_query: Query<&Transform, With<PlayerCamera>>,
+    mut selection: ResMut<Selection>,
+    mut clipboard: ResMut<Clipboard>,
+    mut history: ResMut<EditHistory>,
+    mut world: ResMut<VoxelWorld>,
+) {
+    if !edit_mode.enabled || delete_mode.enabled || drag_state.dragged_block.is_some() {
+        return;
+    }
+
+    if keyboard.just_pressed(KeyCode::BracketLeft) {
+        selection.first = targeted.position;
+    }
+    if keyboard.just_pressed(KeyCode::BracketRight) {
+        selection.second = targeted.position;
+    }
+    // Not a Ctrl shortcut: Left Ctrl is turbo fly, and Ctrl+D would fire
+    // while strafing right
+    if keyboard.just_pressed(KeyCode::Backslash) {
+        selection.clear();
+        return;
+    }
+
+    if !keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let pressed = |key| keyboard.just_pressed(key);
+
+    if pressed(KeyCode::KeyR) || pressed(KeyCode::KeyM) {
+        let Some(buffer) = clipboard.buffer.as_mut() else {
+            info!("Clipboard is empty");
+            return;
+        };
+        if pressed(KeyCode::KeyR) {
+            *buffer = buffer.rotated_y(1);
+        } else {
+            let forward = camera_query
+                .single()
+                .map(|transform| transform.forward().as_vec3())
+                .unwrap_or(Vec3::X);
+            *buffer = buffer.mirrored(facing_axis(forward));
+        }
+        info!("Clipboard is now {:?}", buffer.size());
+        return;
+    }
+
+    if pressed(KeyCode::KeyV) {
+        let (Some(block), Some(normal)) = (targeted.position, targeted.normal) else {
+            return;
+        };
+        let Some(buffer) = clipboard.buffer.as_ref() else {
+            info!("Clipboard is empty");
+            return;
+        };
+        report(
+            "Paste",
+            paste(&mut world, &mut history, buffer, block + normal),
+        );
+        return;
+    }
+
+    let Some(region) = selection.region() else {
+        if [
+            KeyCode::KeyF,
+            KeyCode::KeyE,
+            KeyCode::KeyH,
+            KeyCode::KeyB,
+            KeyCode::KeyX,
+            KeyCode::KeyC,
+        ]
+        .into_iter()
+        .any(pressed)
+        {
+            info!("Select two corners with [ and ] first");
+        }
+        return;
+    };
+
+    if pressed(KeyCode::KeyC) || pressed(KeyCode::KeyX) {
+        match copy(&world, region) {
+            Ok(buffer) => {
+                info!("Copied {:?} voxels", buffer.size());
+                clipboard.buffer = Some(buffer);
+            }
+            Err(err) => warn!("Copy failed: {}", err),
+        }
+    }
+
+    if pressed(KeyCode::KeyF) {
+        report(
+            "Fill",
+            fill(&mut world, &mut history, region, held.block_type),
+        );
+    } else if pressed(KeyCode::KeyE) {
+        if let Some(from) = targeted.voxel_type {
+            report(
+                "Replace",
+                replace(&mut world, &mut history, region, from, held.block_type),
+            );
+        }
+    } else if pressed(KeyCode::KeyH) {
+        report("Hollow", hollow(&mut world, &mut history, region));
+    } else if pressed(KeyCode::KeyB) {
+        report(
+            "Walls",
+            walls(&mut world, &mut history, region, held.block_type),
+        );
+    } else if pressed(KeyCode::KeyX) {
+        report("Clear", clear(&mut world, &mut history, region));
+    }
+}
+
+/// The axis the camera is looking along most
+fn facing_axis(forward: Vec3) -> MirrorAxis {
+    let abs = forward.abs();
+    if abs.y > abs.x && abs.y > abs.z {
+        MirrorAxis::Y
+    } else if abs.x >= abs.z {
+        MirrorAxis::X
+    } else {
+        MirrorAxis::Z
+    }
+}
+
+fn report(operation: &str, result: Result<EditSummary, String>) {
+    match result {
+        Ok(summary) => info!(
+            "{}: {} voxels changed in {} chunks ({} skipped)",
+            operation, summary.changed, summary.chunks, summary.skipped
+        ),
+        Err(err) => warn!("{} failed: {}", operation, err),
+    }
+}
+
+/// Outline the selection corners and box, drawn with the block highlight
+pub fn draw_selection(selection: &Selection, gizmos: &mut Gizmos) {
+    let color = Color::srgba(0.3, 0.8, 1.0, 0.9);
+
+    for corner in [selection.first, selection.second].into_iter().flatten() {
+        gizmos.cuboid(
+            Transform::from_translation(corner.as_vec3() + Vec3::splat(0.5))
+                .with_scale(Vec3::splat(1.02)),
+            color,
+        );
+    }
+
+    if let Some(region) = selection.region() {
+        let min = region.min.as_vec3();
+        let size = region.size().as_vec3();
+        gizmos.cuboid(
+            Transform::from_translation(min + size * 0.5).with_scale(size + Vec3::splat(0.02)),
+            color,
+        );
+    }
+}
@@ -2,12 +2,14 @@ pub mod atmosphere;
 pub mod camera;
 pub mod chat;
 pub mod config;
+pub mod crafting;
 pub mod constants;
 pub mod entity;
 pub mod environment;
 pub mod interaction;
 pub mod map;
 pub mod menu;
+pub mod navigation;
 pub mod network;
 pub mod physics;
 pub mod props;
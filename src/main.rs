@@ -4,12 +4,14 @@ use bevy::render::settings::{Backends, RenderCreation, WgpuSettings};
 use bevy::render::RenderPlugin;
 use voxel_builder::camera::plugin::CameraPlugin;
 use voxel_builder::chat::ChatPlugin;
+use voxel_builder::crafting::CraftingPlugin;
 use voxel_builder::entity::EntityPlugin;
 use voxel_builder::environment::AtmospherePlugin;
 use voxel_builder::atmosphere::FogPlugin;
 use voxel_builder::interaction::InteractionPlugin;
 use voxel_builder::map::MapPlugin;
 use voxel_builder::menu::PauseMenuPlugin;
+use voxel_builder::network::NetworkPlugin;
 use voxel_builder::props::PropsPlugin;
 use voxel_builder::physics::PhysicsPlugin;
 use voxel_builder::player::PlayerPlugin;
@@ -49,11 +51,13 @@ fn main() {
         .add_plugins(RenderingPlugin)
         .add_plugins(CameraPlugin)
         .add_plugins(InteractionPlugin)
+        .add_plugins(CraftingPlugin)
         .add_plugins(PickaxePlugin)
         .add_plugins(MapPlugin)
         .add_plugins(VegetationPlugin)
         .add_plugins(ChatPlugin)
         .add_plugins(PauseMenuPlugin)
+        .add_plugins(NetworkPlugin)
         .add_plugins(PropsPlugin)
         .add_plugins(AtmospherePlugin)
         .add_plugins(FogPlugin)
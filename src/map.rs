@@ -52,7 +52,10 @@ fn toggle_map_overlay(
     world: Res<VoxelWorld>,
     pause_menu: Res<PauseMenuState>,
 ) {
-    if !keys.just_pressed(KeyCode::KeyM) {
+    // Ctrl+M mirrors the world-edit clipboard
+    if !keys.just_pressed(KeyCode::KeyM)
+        || keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+    {
         return;
     }
 
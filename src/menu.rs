@@ -1,12 +1,29 @@
+use crate::camera::controller::PlayerCamera;
 use crate::chat::ChatState;
 use crate::environment::AtmosphereSettings;
-use crate::network::NetworkSession;
+use crate::network::{
+    client::NetClient,
+    guest::GuestSession,
+    host::HostSession,
+    protocol::{Welcome, DEFAULT_PORT},
+    server::ServerSettings,
+    NetworkSession,
+};
 use crate::player::{Player, PlayerConfig};
 use crate::rendering::{capabilities::GraphicsCapabilities, ray_tracing::RayTracingSettings};
-use crate::voxel::{meshing::ChunkMesh, persistence, world::VoxelWorld};
+use crate::voxel::{
+    meshing::ChunkMesh,
+    persistence::{self, ActiveWorld, ChunkStore, SaveSlot, WorldMetadata, SAVES_DIR},
+    streaming::{chunk_column, enter_world, ChunkStreamingSettings, StreamingAnchors},
+    world::VoxelWorld,
+    worldgen::WorldGen,
+};
 use bevy::{
+    asset::RenderAssetUsages,
+    image::{CompressedImageFormats, ImageSampler, ImageType},
     input::keyboard::{Key, KeyboardInput},
     prelude::*,
+    render::view::screenshot::{save_to_disk, Screenshot},
     window::{PrimaryWindow, WindowMode, WindowResolution, MonitorSelection, VideoModeSelection},
 };
 // use bevy::prelude::ChildBuilder;
@@ -15,11 +32,13 @@ use bevy::ui::{
     UiRect, Val,
 };
 
+use std::fs;
 use std::net::ToSocketAddrs;
+use std::path::Path;
 use std::sync::mpsc::{self, Receiver, TryRecvError};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 #[derive(Resource, Default, Clone)]
 pub struct FavoriteServer {
@@ -31,13 +50,50 @@ pub struct FavoriteServer {
 #[derive(Resource, Default)]
 pub struct MultiplayerFormState {
     pub host_password: String,
+    pub host_port: String,
     pub join_ip: String,
     pub join_port: String,
     pub join_password: String,
     pub favorites: Vec<FavoriteServer>,
+    /// Name and seed inputs of the world browser
+    pub world_name: String,
+    pub world_seed: String,
     pub active_field: Option<MultiplayerField>,
 }
 
+/// Save slots listed in the world browser
+#[derive(Resource, Default)]
+pub struct WorldBrowserState {
+    pub slots: Vec<(SaveSlot, WorldMetadata)>,
+    pub thumbnails: Vec<Option<Handle<Image>>>,
+    pub selected: Option<usize>,
+}
+
+impl WorldBrowserState {
+    fn refresh(&mut self, images: &mut Assets<Image>) {
+        let selected_id = self
+            .selected
+            .and_then(|i| self.slots.get(i))
+            .map(|(slot, _)| slot.id.clone());
+
+        self.slots = persistence::list_save_slots(Path::new(SAVES_DIR));
+        self.thumbnails = self
+            .slots
+            .iter()
+            .map(|(slot, _)| load_thumbnail(slot, images))
+            .collect();
+        self.selected = selected_id.and_then(|id| self.slots.iter().position(|(slot, _)| slot.id == id));
+    }
+
+    fn select_id(&mut self, id: &str) {
+        self.selected = self.slots.iter().position(|(slot, _)| slot.id == id);
+    }
+
+    fn selected_slot(&self) -> Option<&(SaveSlot, WorldMetadata)> {
+        self.selected.and_then(|i| self.slots.get(i))
+    }
+}
+
 #[derive(Resource, Default)]
 struct ConnectTaskState {
     receiver: Option<Arc<Mutex<Receiver<ConnectOutcome>>>>,
@@ -49,6 +105,8 @@ enum ConnectOutcome {
         port: String,
         address: String,
         latency_ms: u128,
+        client: NetClient,
+        welcome: Welcome,
     },
     Failure {
         message: String,
@@ -77,6 +135,7 @@ impl Default for PauseMenuState {
 pub enum MenuScreen {
     Main,
     Multiplayer,
+    Worlds,
 }
 
 #[derive(Resource, Clone)]
@@ -364,12 +423,12 @@ struct PauseMenuRoot;
 
 #[derive(Component, Copy, Clone)]
 enum PauseMenuButton {
-    Save,
-    Load,
     Settings,
     Multiplayer,
     StartServer,
+    StopServer,
     Connect,
+    Disconnect,
     SaveFavorite,
     BackToMain,
     Resume,
@@ -378,11 +437,28 @@ enum PauseMenuButton {
 #[derive(Component, Copy, Clone, Eq, PartialEq)]
 pub enum MultiplayerField {
     HostPassword,
+    HostPort,
     JoinIp,
     JoinPort,
     JoinPassword,
+    WorldName,
+    WorldSeed,
 }
 
+#[derive(Component, Copy, Clone)]
+enum WorldBrowserButton {
+    Open,
+    SaveActive,
+    Create,
+    Load,
+    Duplicate,
+    Rename,
+    Delete,
+}
+
+#[derive(Component, Copy, Clone)]
+struct WorldSlotButton(usize);
+
 #[derive(Component, Copy, Clone)]
 struct InputField {
     field: MultiplayerField,
@@ -407,6 +483,7 @@ impl Plugin for PauseMenuPlugin {
             .init_resource::<SettingsState>()
             .init_resource::<MultiplayerFormState>()
             .init_resource::<ConnectTaskState>()
+            .init_resource::<WorldBrowserState>()
             .init_resource::<ChatState>()
             .init_resource::<NetworkSession>()
             .add_systems(
@@ -427,6 +504,15 @@ impl Plugin for PauseMenuPlugin {
                     poll_connect_task_results,
                 ),
             )
+            .add_systems(
+                Update,
+                (
+                    handle_world_browser_buttons,
+                    handle_world_slot_buttons,
+                    update_world_slot_backgrounds,
+                    capture_world_thumbnail,
+                ),
+            )
             .add_systems(
                 Update,
                 (
@@ -510,6 +596,7 @@ fn toggle_pause_menu(
     mut state: ResMut<PauseMenuState>,
     mut form_state: ResMut<MultiplayerFormState>,
     mut settings_state: ResMut<SettingsState>,
+    browser: Res<WorldBrowserState>,
 ) {
     if !keys.just_pressed(KeyCode::Escape) {
         return;
@@ -523,7 +610,7 @@ fn toggle_pause_menu(
             &mut settings_state,
         );
     } else {
-        open_menu(&mut commands, &asset_server, &mut state, &form_state);
+        open_menu(&mut commands, &asset_server, &mut state, &form_state, &browser);
     }
 }
 
@@ -532,6 +619,7 @@ fn open_menu(
     asset_server: &Res<AssetServer>,
     state: &mut PauseMenuState,
     form_state: &MultiplayerFormState,
+    browser: &WorldBrowserState,
 ) {
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
 
@@ -555,6 +643,9 @@ fn open_menu(
                 MenuScreen::Multiplayer => {
                     spawn_multiplayer_menu(parent, &font, form_state);
                 }
+                MenuScreen::Worlds => {
+                    spawn_world_browser(parent, &font, browser);
+                }
             }
         })
         .id();
@@ -588,8 +679,8 @@ fn spawn_main_menu(parent: &mut ChildSpawnerCommands, font: &Handle<Font>) {
             ));
 
             // Main menu buttons
-            spawn_button(menu, font, "Load", PauseMenuButton::Load);
-            spawn_button(menu, font, "Save", PauseMenuButton::Save);
+            spawn_button(menu, font, "Worlds", WorldBrowserButton::Open);
+            spawn_button(menu, font, "Save", WorldBrowserButton::SaveActive);
             spawn_button(menu, font, "Multiplayer", PauseMenuButton::Multiplayer);
             spawn_button(menu, font, "Settings", PauseMenuButton::Settings);
             spawn_button(menu, font, "Resume", PauseMenuButton::Resume);
@@ -653,8 +744,24 @@ fn spawn_multiplayer_menu(
                     "Required for clients",
                     MultiplayerField::HostPassword,
                 );
+                spawn_labeled_input(
+                    section,
+                    font,
+                    "Port",
+                    "7777",
+                    MultiplayerField::HostPort,
+                );
 
-                spawn_button(section, font, "Start Server", PauseMenuButton::StartServer);
+                section
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        spawn_button(row, font, "Start Server", PauseMenuButton::StartServer);
+                        spawn_button(row, font, "Stop Server", PauseMenuButton::StopServer);
+                    });
             });
 
             // Join Game Section
@@ -708,6 +815,7 @@ fn spawn_multiplayer_menu(
                     })
                     .with_children(|row| {
                         spawn_button(row, font, "Connect", PauseMenuButton::Connect);
+                        spawn_button(row, font, "Disconnect", PauseMenuButton::Disconnect);
                         spawn_button(
                             row,
                             font,
@@ -803,11 +911,11 @@ fn spawn_labeled_input(
         });
 }
 
-fn spawn_button(
+fn spawn_button<A: Component>(
     parent: &mut ChildSpawnerCommands,
     font: &Handle<Font>,
     label: &str,
-    action: PauseMenuButton,
+    action: A,
 ) {
     parent
         .spawn((
@@ -1711,13 +1819,14 @@ fn handle_menu_buttons(
         (&Interaction, &PauseMenuButton),
         (Changed<Interaction>, With<Button>),
     >,
-    mut world: ResMut<VoxelWorld>,
-    chunk_meshes: Query<Entity, With<ChunkMesh>>,
     mut state: ResMut<PauseMenuState>,
     mut settings_state: ResMut<SettingsState>,
     mut form_state: ResMut<MultiplayerFormState>,
     mut connect_tasks: ResMut<ConnectTaskState>,
     mut network: ResMut<NetworkSession>,
+    host: Option<Res<HostSession>>,
+    mut guest: Option<ResMut<GuestSession>>,
+    mut anchors: ResMut<StreamingAnchors>,
     mut chat: ResMut<ChatState>,
     favorites_list: Query<Entity, With<FavoritesList>>,
     asset_server: Res<AssetServer>,
@@ -1730,34 +1839,65 @@ fn handle_menu_buttons(
         }
 
         match action {
-            PauseMenuButton::Save => match persistence::save_world(&world) {
-                Ok(()) => info!("World saved via pause menu"),
-                Err(err) => warn!("Failed to save world: {}", err),
-            },
-            PauseMenuButton::Load => {
-                for entity in chunk_meshes.iter() {
-                    commands.entity(entity).despawn();
+            PauseMenuButton::StartServer => {
+                if host.is_some() {
+                    chat.push_system("Server already running");
+                    continue;
+                }
+                if guest.is_some() || connect_tasks.receiver.is_some() {
+                    chat.push_system("Disconnect before starting a server");
+                    continue;
                 }
 
-                match persistence::load_world() {
-                    Ok(loaded_world) => {
-                        *world = loaded_world;
-                        info!("World loaded from disk via pause menu");
+                let port = if form_state.host_port.is_empty() {
+                    DEFAULT_PORT
+                } else {
+                    match form_state.host_port.parse::<u16>() {
+                        Ok(port) => port,
+                        Err(err) => {
+                            warn!("Cannot start server: invalid port - {}", err);
+                            chat.push_system("Cannot start server: invalid port");
+                            continue;
+                        }
+                    }
+                };
+
+                let settings = ServerSettings {
+                    password: form_state.host_password.clone(),
+                    ..default()
+                };
+                match HostSession::start(([0, 0, 0, 0], port).into(), settings) {
+                    Ok(session) => {
+                        info!("Server listening on {}", session.local_addr());
+                        commands.insert_resource(session);
+                        network.reset_client();
+                        network.server_running = true;
+                        network.host_password = form_state.host_password.clone();
+                        network.player_count = 1;
+                        chat.push_system(format!("Server started on port {}", port));
+                    }
+                    Err(e) => {
+                        warn!("Cannot start server: {}", e);
+                        chat.push_system(format!("Cannot start server: {}", e));
                     }
-                    Err(err) => warn!("Failed to load world: {}", err),
                 }
             }
-            PauseMenuButton::StartServer => {
-                info!(
-                    "Starting server with password '{}'",
-                    form_state.host_password
-                );
-                network.server_running = true;
-                network.host_password = form_state.host_password.clone();
-                network.reset_client();
-
-                chat.push_system("Server started");
+            PauseMenuButton::StopServer => {
+                if host.is_none() {
+                    chat.push_system("No server running");
+                    continue;
+                }
+                // Dropping the session kicks every guest
+                commands.remove_resource::<HostSession>();
+                anchors.positions.clear();
+                network.server_running = false;
+                network.player_count = 0;
+                chat.push_system("Server stopped");
             }
+            PauseMenuButton::Disconnect => match guest.as_mut() {
+                Some(guest) => guest.leave(),
+                None => chat.push_system("Not connected"),
+            },
             PauseMenuButton::Connect => {
                 if connect_tasks.receiver.is_some() {
                     warn!("Connection attempt already in progress");
@@ -1771,12 +1911,12 @@ fn handle_menu_buttons(
                     continue;
                 }
 
-                if network.server_running
-                    && !network.host_password.is_empty()
-                    && form_state.join_password != network.host_password
-                {
-                    warn!("Cannot connect: password mismatch");
-                    chat.push_system("Connection rejected: incorrect password");
+                if host.is_some() {
+                    chat.push_system("Stop the server before joining another");
+                    continue;
+                }
+                if guest.is_some() {
+                    chat.push_system("Already connected; disconnect first");
                     continue;
                 }
 
@@ -1792,6 +1932,8 @@ fn handle_menu_buttons(
                 let address = format!("{}:{}", form_state.join_ip, port);
                 let join_ip = form_state.join_ip.clone();
                 let join_port = form_state.join_port.clone();
+                let join_password = form_state.join_password.clone();
+                let username = chat.username.clone();
                 let (tx, rx) = mpsc::channel();
 
                 connect_tasks.receiver = Some(Arc::new(Mutex::new(rx)));
@@ -1811,10 +1953,12 @@ fn handle_menu_buttons(
                         };
 
                         let start = Instant::now();
-                        std::net::TcpStream::connect_timeout(&target_addr, Duration::from_secs(3))
-                            .map_err(|err| {
-                                format!("Cannot connect: ping/health check failed - {}", err)
-                            })?;
+                        let (client, welcome) = NetClient::connect(
+                            target_addr,
+                            &username,
+                            &join_password,
+                            Duration::from_secs(3),
+                        )?;
 
                         let latency_ms = start.elapsed().as_millis();
 
@@ -1823,6 +1967,8 @@ fn handle_menu_buttons(
                             port: join_port,
                             address,
                             latency_ms,
+                            client,
+                            welcome,
                         })
                     })();
 
@@ -1951,6 +2097,7 @@ fn handle_menu_buttons(
 }
 
 fn poll_connect_task_results(
+    mut commands: Commands,
     mut connect_tasks: ResMut<ConnectTaskState>,
     mut network: ResMut<NetworkSession>,
     mut chat: ResMut<ChatState>,
@@ -1973,7 +2120,10 @@ fn poll_connect_task_results(
             port,
             address,
             latency_ms,
+            client,
+            welcome,
         }) => {
+            commands.insert_resource(GuestSession::new(client, welcome));
             network.client_connected = true;
             network.connection_ip = Some(ip);
             network.connection_port = Some(port);
@@ -2843,7 +2993,9 @@ fn process_input_characters(
                 let target = get_field_value_mut(&mut form_state, field);
 
                 match field {
-                    MultiplayerField::JoinPort => {
+                    MultiplayerField::HostPort
+                    | MultiplayerField::JoinPort
+                    | MultiplayerField::WorldSeed => {
                         if ch.is_ascii_digit() {
                             target.push(ch);
                         }
@@ -2863,9 +3015,12 @@ fn get_field_value_mut<'a>(
 ) -> &'a mut String {
     match field {
         MultiplayerField::HostPassword => &mut form_state.host_password,
+        MultiplayerField::HostPort => &mut form_state.host_port,
         MultiplayerField::JoinIp => &mut form_state.join_ip,
         MultiplayerField::JoinPort => &mut form_state.join_port,
         MultiplayerField::JoinPassword => &mut form_state.join_password,
+        MultiplayerField::WorldName => &mut form_state.world_name,
+        MultiplayerField::WorldSeed => &mut form_state.world_seed,
     }
 }
 
@@ -2881,17 +3036,23 @@ fn update_input_texts(
     for (field, mut text) in query.iter_mut() {
         let value = match field.field {
             MultiplayerField::HostPassword => &form_state.host_password,
+            MultiplayerField::HostPort => &form_state.host_port,
             MultiplayerField::JoinIp => &form_state.join_ip,
             MultiplayerField::JoinPort => &form_state.join_port,
             MultiplayerField::JoinPassword => &form_state.join_password,
+            MultiplayerField::WorldName => &form_state.world_name,
+            MultiplayerField::WorldSeed => &form_state.world_seed,
         };
 
         let display_value = if value.is_empty() {
             match field.field {
                 MultiplayerField::HostPassword => "Required for clients",
+                MultiplayerField::HostPort => "7777",
                 MultiplayerField::JoinIp => "Enter IPv4 or IPv6",
                 MultiplayerField::JoinPort => "e.g. 7777",
                 MultiplayerField::JoinPassword => "Session password",
+                MultiplayerField::WorldName => "New World",
+                MultiplayerField::WorldSeed => "Random",
             }
         } else {
             value
@@ -2976,3 +3137,468 @@ fn handle_favorite_buttons(
         }
     }
 }
+
+fn spawn_world_browser(
+    parent: &mut ChildSpawnerCommands,
+    font: &Handle<Font>,
+    browser: &WorldBrowserState,
+) {
+    parent
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Stretch,
+                row_gap: Val::Px(16.0),
+                padding: UiRect::all(Val::Px(30.0)),
+                max_width: Val::Px(560.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.9)),
+        ))
+        .with_children(|menu| {
+            menu.spawn((
+                Text::new("Worlds"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 32.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+
+            // Saved worlds, most recently played first
+            menu.spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    max_height: Val::Px(320.0),
+                    overflow: Overflow::scroll_y(),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.8)),
+            ))
+            .with_children(|list| {
+                if browser.slots.is_empty() {
+                    list.spawn((
+                        Text::new("No saved worlds yet"),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgba(0.8, 0.8, 0.8, 0.9)),
+                    ));
+                }
+
+                for (index, (_, meta)) in browser.slots.iter().enumerate() {
+                    let thumbnail = browser.thumbnails.get(index).cloned().flatten();
+                    spawn_world_slot_button(list, font, index, meta, thumbnail);
+                }
+            });
+
+            spawn_labeled_input(
+                menu,
+                font,
+                "World Name",
+                "New World",
+                MultiplayerField::WorldName,
+            );
+            spawn_labeled_input(menu, font, "Seed", "Random", MultiplayerField::WorldSeed);
+
+            menu.spawn(Node {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                column_gap: Val::Px(10.0),
+                row_gap: Val::Px(10.0),
+                ..default()
+            })
+            .with_children(|row| {
+                spawn_button(row, font, "Create", WorldBrowserButton::Create);
+                spawn_button(row, font, "Load", WorldBrowserButton::Load);
+                spawn_button(row, font, "Duplicate", WorldBrowserButton::Duplicate);
+                spawn_button(row, font, "Rename", WorldBrowserButton::Rename);
+                spawn_button(row, font, "Delete", WorldBrowserButton::Delete);
+            });
+
+            spawn_button(menu, font, "Back", PauseMenuButton::BackToMain);
+        });
+}
+
+fn spawn_world_slot_button(
+    parent: &mut ChildSpawnerCommands,
+    font: &Handle<Font>,
+    index: usize,
+    meta: &WorldMetadata,
+    thumbnail: Option<Handle<Image>>,
+) {
+    let details = format!(
+        "Seed {}  |  {} chunks  |  Played {}  |  {}",
+        meta.seed,
+        meta.world_size_chunks.x * meta.world_size_chunks.z,
+        format_play_time(meta.play_time_secs),
+        format_last_played(meta.last_played)
+    );
+
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                column_gap: Val::Px(10.0),
+                justify_content: JustifyContent::FlexStart,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.18, 0.18, 0.18, 0.9)),
+            WorldSlotButton(index),
+        ))
+        .with_children(|button: &mut ChildSpawnerCommands| {
+            let thumbnail_node = Node {
+                width: Val::Px(96.0),
+                height: Val::Px(54.0),
+                ..default()
+            };
+            match thumbnail {
+                Some(image) => {
+                    button.spawn((ImageNode::new(image), thumbnail_node));
+                }
+                None => {
+                    button.spawn((thumbnail_node, BackgroundColor(Color::srgba(0.3, 0.3, 0.3, 0.9))));
+                }
+            }
+
+            button
+                .spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(2.0),
+                    ..default()
+                })
+                .with_children(|column| {
+                    column.spawn((
+                        Text::new(meta.name.clone()),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 18.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                    column.spawn((
+                        Text::new(details),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 13.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgba(0.8, 0.8, 0.8, 0.9)),
+                    ));
+                });
+        });
+}
+
+/// Read a slot's screenshot into an image asset for the browser
+fn load_thumbnail(slot: &SaveSlot, images: &mut Assets<Image>) -> Option<Handle<Image>> {
+    let bytes = fs::read(slot.thumbnail_path()).ok()?;
+    let image = Image::from_buffer(
+        &bytes,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .map_err(|err| warn!("Failed to read thumbnail {:?}: {}", slot.thumbnail_path(), err))
+    .ok()?;
+    Some(images.add(image))
+}
+
+fn format_play_time(secs: u64) -> String {
+    let minutes = secs / 60;
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+fn format_last_played(timestamp: u64) -> String {
+    let age = persistence::unix_now().saturating_sub(timestamp);
+    match age {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min ago", age / 60),
+        3600..=86399 => format!("{} h ago", age / 3600),
+        _ => format!("{} days ago", age / 86400),
+    }
+}
+
+fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Despawn the current menu screen and show `screen` instead
+fn show_menu_screen(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    state: &mut PauseMenuState,
+    form_state: &MultiplayerFormState,
+    browser: &WorldBrowserState,
+    screen: MenuScreen,
+) {
+    if let Some(root) = state.root_entity.take() {
+        commands.entity(root).despawn();
+    }
+    state.current_screen = screen;
+    open_menu(commands, asset_server, state, form_state, browser);
+}
+
+fn handle_world_browser_buttons(
+    mut interaction_query: Query<
+        (&Interaction, &WorldBrowserButton),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut browser: ResMut<WorldBrowserState>,
+    mut form_state: ResMut<MultiplayerFormState>,
+    mut state: ResMut<PauseMenuState>,
+    mut chat: ResMut<ChatState>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut world: ResMut<VoxelWorld>,
+    mut active_world: ResMut<ActiveWorld>,
+    mut chunk_store: ResMut<ChunkStore>,
+    mut world_gen: ResMut<WorldGen>,
+    streaming: Res<ChunkStreamingSettings>,
+    chunk_meshes: Query<Entity, With<ChunkMesh>>,
+    camera_query: Query<&Transform, With<PlayerCamera>>,
+    network: Res<NetworkSession>,
+) {
+    for (interaction, action) in interaction_query.iter_mut() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        // The loaded world belongs to the host until we disconnect
+        if network.is_connected() && !matches!(action, WorldBrowserButton::Open) {
+            chat.push_system("Disconnect before managing local worlds");
+            continue;
+        }
+
+        let root = Path::new(SAVES_DIR);
+        let name_input = form_state.world_name.trim().to_string();
+
+        match action {
+            WorldBrowserButton::Open => {}
+            WorldBrowserButton::SaveActive => {
+                active_world.metadata.thumbnail = Some(
+                    active_world
+                        .slot
+                        .thumbnail_path()
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                );
+                match active_world.save(&mut world) {
+                    Ok(()) => {
+                        active_world.thumbnail_pending = true;
+                        chat.push_system(format!("Saved world '{}'", active_world.metadata.name));
+                    }
+                    Err(err) => {
+                        warn!("Failed to save world: {}", err);
+                        chat.push_system(format!("Failed to save world: {}", err));
+                    }
+                }
+                // Stay on the main screen
+                continue;
+            }
+            WorldBrowserButton::Create => {
+                let seed = form_state.world_seed.parse().unwrap_or_else(|_| random_seed());
+                let name = if name_input.is_empty() { "New World" } else { name_input.as_str() };
+                match persistence::create_save_slot(root, name, seed, world.world_size_chunks()) {
+                    Ok((slot, meta)) => {
+                        chat.push_system(format!("Created world '{}' (seed {})", meta.name, meta.seed));
+                        browser.refresh(&mut images);
+                        browser.select_id(&slot.id);
+                        form_state.world_name.clear();
+                        form_state.world_seed.clear();
+                    }
+                    Err(err) => chat.push_system(format!("Failed to create world: {}", err)),
+                }
+            }
+            WorldBrowserButton::Load => {
+                let Some((slot, meta)) = browser.selected_slot().cloned() else {
+                    chat.push_system("Select a world to load");
+                    continue;
+                };
+
+                // Keep the edits of the world we are leaving
+                if let Err(err) = active_world.save(&mut world) {
+                    warn!("Failed to save world before switching: {}", err);
+                }
+
+                let center = camera_query
+                    .iter()
+                    .next()
+                    .map(|transform| chunk_column(transform.translation))
+                    .unwrap_or(IVec3::ZERO);
+                match enter_world(
+                    slot,
+                    &mut world,
+                    &mut active_world,
+                    &mut chunk_store,
+                    &mut world_gen,
+                    &streaming,
+                    center,
+                ) {
+                    Ok(()) => {
+                        // Meshes of the old world are rebuilt from the new chunks
+                        for entity in chunk_meshes.iter() {
+                            commands.entity(entity).despawn();
+                        }
+                        chat.push_system(format!("Loaded world '{}'", meta.name));
+                    }
+                    Err(err) => chat.push_system(format!("Failed to load world: {}", err)),
+                }
+                browser.refresh(&mut images);
+            }
+            WorldBrowserButton::Duplicate => {
+                let Some((slot, meta)) = browser.selected_slot().cloned() else {
+                    chat.push_system("Select a world to duplicate");
+                    continue;
+                };
+
+                if slot == active_world.slot {
+                    if let Err(err) = active_world.save(&mut world) {
+                        warn!("Failed to save world before duplicating: {}", err);
+                    }
+                }
+
+                let name = if name_input.is_empty() {
+                    format!("{} Copy", meta.name)
+                } else {
+                    name_input
+                };
+                match persistence::duplicate_save_slot(&slot, &name) {
+                    Ok(copy) => {
+                        chat.push_system(format!("Duplicated '{}' as '{}'", meta.name, name));
+                        browser.refresh(&mut images);
+                        browser.select_id(&copy.id);
+                    }
+                    Err(err) => chat.push_system(format!("Failed to duplicate world: {}", err)),
+                }
+            }
+            WorldBrowserButton::Rename => {
+                let Some((slot, meta)) = browser.selected_slot().cloned() else {
+                    chat.push_system("Select a world to rename");
+                    continue;
+                };
+
+                match persistence::rename_save_slot(&slot, &name_input) {
+                    Ok(()) => {
+                        if slot == active_world.slot {
+                            active_world.metadata.name = name_input.clone();
+                        }
+                        chat.push_system(format!("Renamed '{}' to '{}'", meta.name, name_input));
+                        form_state.world_name.clear();
+                        browser.refresh(&mut images);
+                    }
+                    Err(err) => chat.push_system(format!("Failed to rename world: {}", err)),
+                }
+            }
+            WorldBrowserButton::Delete => {
+                let Some((slot, meta)) = browser.selected_slot().cloned() else {
+                    chat.push_system("Select a world to delete");
+                    continue;
+                };
+
+                if slot == active_world.slot {
+                    chat.push_system("Cannot delete the world that is currently loaded");
+                    continue;
+                }
+
+                match persistence::delete_saved_world(&slot) {
+                    Ok(()) => {
+                        chat.push_system(format!("Deleted world '{}'", meta.name));
+                        browser.selected = None;
+                        browser.refresh(&mut images);
+                    }
+                    Err(err) => chat.push_system(format!("Failed to delete world: {}", err)),
+                }
+            }
+        }
+
+        if matches!(action, WorldBrowserButton::Open) {
+            browser.refresh(&mut images);
+        }
+        form_state.active_field = None;
+        show_menu_screen(
+            &mut commands,
+            &asset_server,
+            &mut state,
+            &form_state,
+            &browser,
+            MenuScreen::Worlds,
+        );
+    }
+}
+
+fn handle_world_slot_buttons(
+    mut browser: ResMut<WorldBrowserState>,
+    mut form_state: ResMut<MultiplayerFormState>,
+    state: Res<PauseMenuState>,
+    mut query: Query<(&Interaction, &WorldSlotButton), (Changed<Interaction>, With<Button>)>,
+) {
+    if !state.open {
+        return;
+    }
+
+    for (interaction, slot_button) in query.iter_mut() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        browser.selected = Some(slot_button.0);
+        if let Some((_, meta)) = browser.slots.get(slot_button.0) {
+            // Prefill the name field so Rename/Duplicate start from the current name
+            form_state.world_name = meta.name.clone();
+        }
+    }
+}
+
+fn update_world_slot_backgrounds(
+    browser: Res<WorldBrowserState>,
+    state: Res<PauseMenuState>,
+    mut query: Query<(&WorldSlotButton, &mut BackgroundColor)>,
+) {
+    if !state.open {
+        return;
+    }
+
+    for (slot_button, mut background) in query.iter_mut() {
+        *background = if browser.selected == Some(slot_button.0) {
+            Color::srgba(0.3, 0.35, 0.45, 0.95).into()
+        } else {
+            Color::srgba(0.18, 0.18, 0.18, 0.9).into()
+        };
+    }
+}
+
+/// Screenshot the world for the browser after a save, once the menu is out of the way
+fn capture_world_thumbnail(
+    mut commands: Commands,
+    state: Res<PauseMenuState>,
+    mut active_world: ResMut<ActiveWorld>,
+) {
+    if !active_world.thumbnail_pending || state.open {
+        return;
+    }
+
+    active_world.thumbnail_pending = false;
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(active_world.slot.thumbnail_path()));
+}
@@ -0,0 +1,110 @@
+pub mod navmesh;
+
+use crate::voxel::world::VoxelWorld;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+pub use navmesh::{NavLimits, NavMesh, AGENT_HEIGHT, MAX_SEARCH_NODES};
+
+/// Chunks whose walkable surfaces are rebuilt per frame, so a freshly
+/// loaded world doesn't stall a single frame
+pub const NAV_CHUNKS_PER_FRAME: usize = 32;
+
+/// A creature following a path through the `NavMesh`
+#[derive(Component, Default, Debug)]
+pub struct NavAgent {
+    pub limits: NavLimits,
+    path: VecDeque<IVec3>,
+    /// Chunks the path crosses and the surfaces it was planned on
+    crossed: Vec<(IVec3, Option<[u64; 3]>)>,
+}
+
+impl NavAgent {
+    pub fn new(limits: NavLimits) -> Self {
+        Self {
+            limits,
+            path: VecDeque::new(),
+            crossed: Vec::new(),
+        }
+    }
+
+    /// Follow `path` as returned by `nav.find_path`; the first cell is
+    /// where the agent already stands
+    pub fn set_path(&mut self, path: Vec<IVec3>, nav: &NavMesh) {
+        self.crossed.clear();
+        for cell in &path {
+            let chunk_pos = VoxelWorld::world_to_chunk(*cell);
+            if !self.crossed.iter().any(|(pos, _)| *pos == chunk_pos) {
+                self.crossed
+                    .push((chunk_pos, nav.chunk_revision(chunk_pos)));
+            }
+        }
+        self.path = path.into_iter().skip(1).collect();
+    }
+
+    pub fn clear_path(&mut self) {
+        self.path.clear();
+        self.crossed.clear();
+    }
+
+    /// Drop the path once the surfaces of a chunk it crosses were rebuilt,
+    /// so the agent plans again instead of walking into new walls
+    pub fn clear_stale_path(&mut self, nav: &NavMesh) {
+        if self
+            .crossed
+            .iter()
+            .any(|(pos, revision)| nav.chunk_revision(*pos) != *revision)
+        {
+            self.clear_path();
+        }
+    }
+
+    pub fn has_path(&self) -> bool {
+        !self.path.is_empty()
+    }
+
+    /// Feet position of the next waypoint, dropping waypoints that are
+    /// within `reach` horizontally of `position`
+    pub fn steer(&mut self, position: Vec3, reach: f32) -> Option<Vec3> {
+        while let Some(cell) = self.path.front() {
+            let target = cell.as_vec3() + Vec3::new(0.5, 0.0, 0.5);
+            if Vec2::new(target.x - position.x, target.z - position.z).length() > reach {
+                return Some(target);
+            }
+            self.path.pop_front();
+        }
+        None
+    }
+}
+
+/// Pick a walkable goal about `distance` blocks from `position` in the
+/// direction of `angle` and find a path there
+pub fn wander_path(
+    nav: &NavMesh,
+    position: Vec3,
+    angle: f32,
+    distance: f32,
+    limits: NavLimits,
+) -> Option<Vec<IVec3>> {
+    let start = nav.nearest_walkable(position.floor().as_ivec3(), 1)?;
+    let offset = Vec3::new(angle.cos(), 0.0, angle.sin()) * distance;
+    let search = limits.max_drop.max(limits.max_step_up);
+    let goal = nav.nearest_walkable((position + offset).floor().as_ivec3(), search)?;
+    nav.find_path(start, goal, limits)
+}
+
+/// Keep walkable surfaces in step with loaded and edited chunks
+pub fn update_navmesh(world: Res<VoxelWorld>, mut nav: ResMut<NavMesh>) {
+    nav.update(&world, NAV_CHUNKS_PER_FRAME);
+}
+
+/// Walkable surfaces and path queries for creatures; runs on the dedicated
+/// server too
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavMesh>()
+            .add_systems(Update, update_navmesh);
+    }
+}
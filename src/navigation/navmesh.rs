@@ -0,0 +1,314 @@
+//! Walkable surfaces derived from `VoxelWorld` and A* path queries over them.
+//!
+//! A cell is walkable when the voxel below it is solid and it has
+//! `AGENT_HEIGHT` cells of headroom that are neither solid nor liquid.
+//! Surfaces are cached per chunk and rebuilt when the chunk, or one of the
+//! chunks directly above or below it, changes revision.
+
+use crate::constants::{CHUNK_SIZE_I32, CHUNK_VOLUME};
+use crate::voxel::types::{Voxel, VoxelType};
+use crate::voxel::world::VoxelWorld;
+use bevy::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Cells of headroom a creature needs to stand somewhere
+pub const AGENT_HEIGHT: i32 = 2;
+
+/// Nodes expanded before a path query gives up
+pub const MAX_SEARCH_NODES: usize = 4096;
+
+/// Cost of one horizontal step; each block climbed or dropped adds half
+const STEP_COST: u32 = 10;
+const VERTICAL_COST: u32 = 5;
+
+const HORIZONTAL: [IVec3; 4] = [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z];
+
+const WORDS: usize = CHUNK_VOLUME / 64;
+
+/// How far a creature can climb or fall in one step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NavLimits {
+    /// Blocks it can step up onto
+    pub max_step_up: i32,
+    /// Blocks it is willing to drop down
+    pub max_drop: i32,
+}
+
+impl Default for NavLimits {
+    fn default() -> Self {
+        Self {
+            max_step_up: 1,
+            max_drop: 3,
+        }
+    }
+}
+
+/// Walkable and open cells of one chunk
+#[derive(Clone)]
+struct ChunkNav {
+    walkable: [u64; WORDS],
+    /// Cells a creature's body can pass through
+    open: [u64; WORDS],
+    /// Revisions of the chunk below, the chunk itself and the chunk above
+    built_from: [u64; 3],
+}
+
+impl ChunkNav {
+    fn index(local: IVec3) -> usize {
+        ((local.y * CHUNK_SIZE_I32 + local.z) * CHUNK_SIZE_I32 + local.x) as usize
+    }
+
+    fn get(bits: &[u64; WORDS], local: IVec3) -> bool {
+        let index = Self::index(local);
+        bits[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    fn set(bits: &mut [u64; WORDS], local: IVec3) {
+        let index = Self::index(local);
+        bits[index / 64] |= 1 << (index % 64);
+    }
+}
+
+fn revisions(world: &VoxelWorld, chunk_pos: IVec3) -> [u64; 3] {
+    [-IVec3::Y, IVec3::ZERO, IVec3::Y].map(|offset| {
+        world
+            .get_chunk(chunk_pos + offset)
+            .map_or(0, |chunk| chunk.revision())
+    })
+}
+
+fn build_chunk(world: &VoxelWorld, chunk_pos: IVec3) -> Option<ChunkNav> {
+    let chunk = world.get_chunk(chunk_pos)?;
+    let below = world.get_chunk(chunk_pos - IVec3::Y);
+    let above = world.get_chunk(chunk_pos + IVec3::Y);
+    let size = CHUNK_SIZE_I32;
+
+    // Floors sit one layer below the chunk and headroom reaches into the one above
+    let voxel = |x: i32, y: i32, z: i32| -> Option<VoxelType> {
+        let (source, y) = if y < 0 {
+            (below, y + size)
+        } else if y >= size {
+            (above, y - size)
+        } else {
+            (Some(chunk), y)
+        };
+        source.map(|source| source.get(UVec3::new(x as u32, y as u32, z as u32)))
+    };
+    // Nothing loaded above is open sky; nothing below is no floor
+    let passable = |voxel: Option<VoxelType>| {
+        voxel.is_none_or(|voxel| !voxel.is_solid() && !voxel.is_liquid())
+    };
+
+    let mut nav = ChunkNav {
+        walkable: [0; WORDS],
+        open: [0; WORDS],
+        built_from: revisions(world, chunk_pos),
+    };
+    for y in 0..size {
+        for z in 0..size {
+            for x in 0..size {
+                let local = IVec3::new(x, y, z);
+                if voxel(x, y, z).is_some_and(|voxel| voxel.is_solid()) {
+                    continue;
+                }
+                ChunkNav::set(&mut nav.open, local);
+
+                let floor = voxel(x, y - 1, z).is_some_and(|voxel| voxel.is_solid());
+                let headroom = (0..AGENT_HEIGHT).all(|dy| passable(voxel(x, y + dy, z)));
+                if floor && headroom {
+                    ChunkNav::set(&mut nav.walkable, local);
+                }
+            }
+        }
+    }
+    Some(nav)
+}
+
+#[derive(PartialEq, Eq)]
+struct Candidate {
+    estimate: u32,
+    cell: IVec3,
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so the heap pops the cheapest estimate first
+        other.estimate.cmp(&self.estimate)
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn heuristic(from: IVec3, to: IVec3) -> u32 {
+    let delta = (to - from).abs();
+    (delta.x + delta.z) as u32 * STEP_COST + delta.y as u32 * VERTICAL_COST
+}
+
+/// Walkable surfaces of every loaded chunk
+#[derive(Resource, Default)]
+pub struct NavMesh {
+    chunks: HashMap<IVec3, ChunkNav>,
+}
+
+impl NavMesh {
+    /// Surfaces for every loaded chunk at once
+    pub fn build(world: &VoxelWorld) -> Self {
+        let mut nav = Self::default();
+        nav.update(world, usize::MAX);
+        nav
+    }
+
+    /// Drop unloaded chunks and rebuild up to `budget` chunks whose voxels,
+    /// or whose neighbors above and below, changed. Returns how many were
+    /// rebuilt.
+    pub fn update(&mut self, world: &VoxelWorld, budget: usize) -> usize {
+        self.chunks.retain(|pos, _| world.chunk_exists(*pos));
+
+        let stale: Vec<IVec3> = world
+            .loaded_chunk_positions()
+            .filter(|pos| {
+                self.chunks
+                    .get(pos)
+                    .is_none_or(|nav| nav.built_from != revisions(world, *pos))
+            })
+            .take(budget)
+            .collect();
+
+        for pos in &stale {
+            if let Some(nav) = build_chunk(world, *pos) {
+                self.chunks.insert(*pos, nav);
+            }
+        }
+        stale.len()
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Voxel revisions the surfaces of `chunk_pos` were built from, or
+    /// `None` if the chunk has no surfaces yet
+    pub fn chunk_revision(&self, chunk_pos: IVec3) -> Option<[u64; 3]> {
+        self.chunks.get(&chunk_pos).map(|nav| nav.built_from)
+    }
+
+    fn lookup(&self, cell: IVec3, bits: impl Fn(&ChunkNav) -> &[u64; WORDS]) -> bool {
+        let chunk_pos = VoxelWorld::world_to_chunk(cell);
+        let local = cell - VoxelWorld::chunk_to_world(chunk_pos);
+        self.chunks
+            .get(&chunk_pos)
+            .is_some_and(|nav| ChunkNav::get(bits(nav), local))
+    }
+
+    /// Whether a creature can stand in `cell` (its feet, not the floor)
+    pub fn is_walkable(&self, cell: IVec3) -> bool {
+        self.lookup(cell, |nav| &nav.walkable)
+    }
+
+    fn is_open(&self, cell: IVec3) -> bool {
+        self.lookup(cell, |nav| &nav.open)
+    }
+
+    /// Every cell of the column at `x`/`z` from `bottom` to `top` is open
+    fn column_open(&self, x: i32, z: i32, bottom: i32, top: i32) -> bool {
+        (bottom..=top).all(|y| self.is_open(IVec3::new(x, y, z)))
+    }
+
+    /// Closest walkable cell in the column of `position`, within `max_dy`
+    /// blocks up or down
+    pub fn nearest_walkable(&self, position: IVec3, max_dy: i32) -> Option<IVec3> {
+        (0..=max_dy)
+            .flat_map(|dy| [position - IVec3::Y * dy, position + IVec3::Y * dy])
+            .find(|cell| self.is_walkable(*cell))
+    }
+
+    /// Cells reachable in one step from `cell`
+    fn neighbors(&self, cell: IVec3, limits: NavLimits) -> impl Iterator<Item = IVec3> + '_ {
+        HORIZONTAL.into_iter().flat_map(move |direction| {
+            let next = cell + direction;
+            (-limits.max_drop..=limits.max_step_up).filter_map(move |dy| {
+                let target = next + IVec3::Y * dy;
+                if !self.is_walkable(target) {
+                    return None;
+                }
+                let clear = match dy.cmp(&0) {
+                    // Rise inside the current column first, then step across
+                    Ordering::Greater => self.column_open(
+                        cell.x,
+                        cell.z,
+                        cell.y + AGENT_HEIGHT,
+                        cell.y + AGENT_HEIGHT + dy - 1,
+                    ),
+                    Ordering::Equal => true,
+                    // Step across at the current height, then fall
+                    Ordering::Less => {
+                        self.column_open(next.x, next.z, target.y, cell.y + AGENT_HEIGHT - 1)
+                    }
+                };
+                clear.then_some(target)
+            })
+        })
+    }
+
+    /// Shortest path from `start` to `goal`, both walkable cells, as every
+    /// cell along the way including both ends. `None` when the goal can't
+    /// be reached within `MAX_SEARCH_NODES` expansions.
+    pub fn find_path(&self, start: IVec3, goal: IVec3, limits: NavLimits) -> Option<Vec<IVec3>> {
+        if !self.is_walkable(start) || !self.is_walkable(goal) {
+            return None;
+        }
+
+        let mut open = BinaryHeap::new();
+        let mut cost: HashMap<IVec3, u32> = HashMap::new();
+        let mut came_from: HashMap<IVec3, IVec3> = HashMap::new();
+        open.push(Candidate {
+            estimate: heuristic(start, goal),
+            cell: start,
+        });
+        cost.insert(start, 0);
+
+        let mut expanded = 0;
+        while let Some(Candidate { estimate, cell }) = open.pop() {
+            if cell == goal {
+                let mut path = vec![goal];
+                let mut current = goal;
+                while let Some(previous) = came_from.get(&current) {
+                    path.push(*previous);
+                    current = *previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            let cell_cost = cost[&cell];
+            // Skip entries made stale by a cheaper route found later
+            if estimate > cell_cost + heuristic(cell, goal) {
+                continue;
+            }
+            expanded += 1;
+            if expanded > MAX_SEARCH_NODES {
+                return None;
+            }
+
+            for next in self.neighbors(cell, limits) {
+                let next_cost =
+                    cell_cost + STEP_COST + (next.y - cell.y).unsigned_abs() * VERTICAL_COST;
+                if cost.get(&next).is_some_and(|known| *known <= next_cost) {
+                    continue;
+                }
+                cost.insert(next, next_cost);
+                came_from.insert(next, cell);
+                open.push(Candidate {
+                    estimate: next_cost + heuristic(next, goal),
+                    cell: next,
+                });
+            }
+        }
+        None
+    }
+}
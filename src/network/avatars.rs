@@ -0,0 +1,84 @@
+use super::guest::GuestSession;
+use super::host::HostSession;
+use super::protocol::{PlayerId, PlayerState};
+use bevy::prelude::*;
+
+/// Transforms arrive 20 times a second; ease towards them in between
+const SMOOTHING: f32 = 15.0;
+
+/// Eye height above the middle of the avatar's body
+const EYE_OFFSET: Vec3 = Vec3::new(0.0, 0.8, 0.0);
+
+/// Another player's body in the world
+#[derive(Component)]
+pub struct RemoteAvatar {
+    pub id: PlayerId,
+}
+
+/// Spawn, move and despawn avatars to match the connected players
+pub fn sync_remote_avatars(
+    mut commands: Commands,
+    host: Option<Res<HostSession>>,
+    guest: Option<Res<GuestSession>>,
+    mut avatars: Query<(Entity, &RemoteAvatar, &mut Transform)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut handles: Local<Option<(Handle<Mesh>, Handle<StandardMaterial>)>>,
+    time: Res<Time>,
+) {
+    let players: Vec<(PlayerId, PlayerState)> = match (&host, &guest) {
+        (Some(host), _) => host
+            .players()
+            .filter_map(|(id, player)| player.state.map(|state| (id, state)))
+            .collect(),
+        (None, Some(guest)) => guest
+            .players()
+            .filter_map(|(id, player)| player.state.map(|state| (id, state)))
+            .collect(),
+        (None, None) => Vec::new(),
+    };
+
+    let blend = 1.0 - (-SMOOTHING * time.delta_secs()).exp();
+    let mut present = Vec::new();
+    for (entity, avatar, mut transform) in avatars.iter_mut() {
+        match players.iter().find(|(id, _)| *id == avatar.id) {
+            Some((_, state)) => {
+                let (translation, rotation) = body_transform(state);
+                transform.translation = transform.translation.lerp(translation, blend);
+                transform.rotation = transform.rotation.slerp(rotation, blend);
+                present.push(avatar.id);
+            }
+            None => commands.entity(entity).despawn(),
+        }
+    }
+
+    for (id, state) in players {
+        if present.contains(&id) {
+            continue;
+        }
+        let (mesh, material) = handles
+            .get_or_insert_with(|| {
+                (
+                    meshes.add(Capsule3d::new(0.4, 1.0)),
+                    materials.add(StandardMaterial {
+                        base_color: Color::srgb(0.85, 0.45, 0.2),
+                        ..default()
+                    }),
+                )
+            })
+            .clone();
+        let (translation, rotation) = body_transform(&state);
+        commands.spawn((
+            RemoteAvatar { id },
+            Mesh3d(mesh),
+            MeshMaterial3d(material),
+            Transform::from_translation(translation).with_rotation(rotation),
+        ));
+    }
+}
+
+/// Body position and yaw for a camera transform
+fn body_transform(state: &PlayerState) -> (Vec3, Quat) {
+    let (yaw, _, _) = state.rotation.to_euler(EulerRot::YXZ);
+    (state.position - EYE_OFFSET, Quat::from_rotation_y(yaw))
+}
@@ -0,0 +1,232 @@
+//! Client side of the transport.
+//!
+//! [`NetClient::connect`] blocks through the handshake, so the game runs it
+//! on a worker thread. Afterwards reads happen on background threads and
+//! [`NetClient::poll`] hands their results to the game.
+
+use super::protocol::{
+    self, ClientMessage, Datagram, PlayerId, PlayerState, ServerMessage, Welcome,
+    MAX_DATAGRAM_SIZE, PROTOCOL_VERSION,
+};
+use std::io::ErrorKind;
+use std::net::{Shutdown, SocketAddr, TcpStream, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How often the UDP reader checks whether the client is closing
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug)]
+pub enum ClientEvent {
+    Message(ServerMessage),
+    States(Vec<(PlayerId, PlayerState)>),
+    /// The connection is gone; no further events follow
+    Disconnected(String),
+}
+
+pub struct NetClient {
+    server_addr: SocketAddr,
+    stream: TcpStream,
+    outgoing: Sender<ClientMessage>,
+    incoming: Mutex<Receiver<ClientEvent>>,
+    udp: UdpSocket,
+    token: u64,
+    state_sequence: u32,
+    running: Arc<AtomicBool>,
+}
+
+impl NetClient {
+    /// Connect, check versions and log in. Blocks for up to `timeout` per step.
+    pub fn connect(
+        addr: SocketAddr,
+        name: &str,
+        password: &str,
+        timeout: Duration,
+    ) -> Result<(Self, Welcome), String> {
+        let mut stream = TcpStream::connect_timeout(&addr, timeout)
+            .map_err(|e| format!("Cannot connect to {}: {}", addr, e))?;
+        stream
+            .set_nodelay(true)
+            .and_then(|_| stream.set_read_timeout(Some(timeout)))
+            .map_err(|e| format!("Failed to configure connection: {}", e))?;
+
+        protocol::write_prelude(&mut stream)?;
+        let version = protocol::read_prelude(&mut stream)?;
+        if version != PROTOCOL_VERSION {
+            return Err(format!(
+                "Server uses protocol v{}, this game uses v{}",
+                version, PROTOCOL_VERSION
+            ));
+        }
+
+        protocol::write_frame(
+            &mut stream,
+            &ClientMessage::Hello {
+                name: name.to_string(),
+                password: password.to_string(),
+            },
+        )?;
+        let welcome = match protocol::read_frame(&mut stream)? {
+            ServerMessage::Welcome(welcome) => welcome,
+            ServerMessage::Rejected { reason } => {
+                return Err(format!("Connection rejected: {}", reason));
+            }
+            other => return Err(format!("Unexpected reply to hello: {:?}", other)),
+        };
+
+        let bind_addr: SocketAddr = if addr.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let udp = UdpSocket::bind(bind_addr)
+            .and_then(|udp| udp.connect(addr).map(|_| udp))
+            .map_err(|e| format!("Failed to open UDP socket: {}", e))?;
+        udp.set_read_timeout(Some(POLL_INTERVAL))
+            .and_then(|_| stream.set_read_timeout(None))
+            .map_err(|e| format!("Failed to configure connection: {}", e))?;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let (events, incoming) = mpsc::channel();
+        let (outgoing, queue) = mpsc::channel();
+        let reader = stream
+            .try_clone()
+            .map_err(|e| format!("Failed to share connection: {}", e))?;
+        let writer = stream
+            .try_clone()
+            .map_err(|e| format!("Failed to share connection: {}", e))?;
+        let udp_reader = udp
+            .try_clone()
+            .map_err(|e| format!("Failed to share UDP socket: {}", e))?;
+
+        {
+            let events = events.clone();
+            thread::spawn(move || read_loop(reader, events));
+        }
+        thread::spawn(move || write_loop(writer, queue));
+        {
+            let running = running.clone();
+            thread::spawn(move || udp_loop(udp_reader, running, events));
+        }
+
+        let client = Self {
+            server_addr: addr,
+            stream,
+            outgoing,
+            incoming: Mutex::new(incoming),
+            udp,
+            token: welcome.token,
+            state_sequence: 0,
+            running,
+        };
+        Ok((client, welcome))
+    }
+
+    pub fn server_addr(&self) -> SocketAddr {
+        self.server_addr
+    }
+
+    /// Queue a reliable message
+    pub fn send(&self, message: ClientMessage) {
+        let _ = self.outgoing.send(message);
+    }
+
+    /// Send the local player's transform; lost packets are simply replaced
+    /// by the next one
+    pub fn send_state(&mut self, state: PlayerState) {
+        self.state_sequence = self.state_sequence.wrapping_add(1);
+        let datagram = Datagram::ClientState {
+            token: self.token,
+            sequence: self.state_sequence,
+            state,
+        };
+        if let Ok(bytes) = protocol::encode_datagram(&datagram) {
+            let _ = self.udp.send(&bytes);
+        }
+    }
+
+    /// Everything that arrived since the last call
+    pub fn poll(&self) -> Vec<ClientEvent> {
+        match self.incoming.lock() {
+            Ok(receiver) => receiver.try_iter().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Say goodbye and close the connection
+    pub fn disconnect(self) {
+        self.send(ClientMessage::Goodbye);
+    }
+}
+
+impl Drop for NetClient {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        // The writer still sends anything queued (like a goodbye) and closes
+        // the connection once `outgoing` is dropped
+        let _ = self.stream.shutdown(Shutdown::Read);
+    }
+}
+
+fn read_loop(mut stream: TcpStream, events: Sender<ClientEvent>) {
+    loop {
+        match protocol::read_frame::<_, ServerMessage>(&mut stream) {
+            Ok(ServerMessage::Kicked { reason }) => {
+                let _ = events.send(ClientEvent::Disconnected(format!("Kicked: {}", reason)));
+                return;
+            }
+            Ok(message) => {
+                if events.send(ClientEvent::Message(message)).is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                let _ = events.send(ClientEvent::Disconnected(e));
+                return;
+            }
+        }
+    }
+}
+
+fn write_loop(mut stream: TcpStream, queue: Receiver<ClientMessage>) {
+    for message in queue {
+        if protocol::write_frame(&mut stream, &message).is_err() {
+            break;
+        }
+    }
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+fn udp_loop(socket: UdpSocket, running: Arc<AtomicBool>, events: Sender<ClientEvent>) {
+    let mut buffer = [0u8; MAX_DATAGRAM_SIZE];
+    let mut last_sequence: Option<u32> = None;
+    while running.load(Ordering::Relaxed) {
+        match socket.recv(&mut buffer) {
+            Ok(length) => {
+                let Ok(Datagram::PlayerStates { sequence, players }) =
+                    protocol::decode_datagram(&buffer[..length])
+                else {
+                    continue;
+                };
+                // Large lists arrive as several packets with one sequence
+                if last_sequence
+                    .is_some_and(|last| sequence != last && !protocol::is_newer(sequence, last))
+                {
+                    continue;
+                }
+                last_sequence = Some(sequence);
+                if events.send(ClientEvent::States(players)).is_err() {
+                    return;
+                }
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            // Nothing listens on the server's UDP port (yet); keep trying
+            Err(e) if e.kind() == ErrorKind::ConnectionRefused => {}
+            Err(e) if e.kind() == ErrorKind::ConnectionReset => {}
+            Err(_) => thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use super::guest::GuestSession;
+use super::host::HostSession;
+use crate::chat::commands::ChatCommand;
+use bevy::prelude::*;
+
+/// `/players`
+pub struct PlayersCommand;
+
+impl ChatCommand for PlayersCommand {
+    fn name(&self) -> &str {
+        "players"
+    }
+
+    fn description(&self) -> &str {
+        "List the connected players"
+    }
+
+    fn run(&self, _args: &[&str], world: &mut World) -> Result<String, String> {
+        if let Some(guest) = world.get_resource::<GuestSession>() {
+            let mut names: Vec<&str> = guest
+                .players()
+                .map(|(_, player)| player.name.as_str())
+                .collect();
+            names.sort_unstable();
+            return Ok(format!("Also playing: {}", list_or_none(&names)));
+        }
+        let host = world
+            .get_resource::<HostSession>()
+            .ok_or("Not hosting or connected")?;
+        let mut names: Vec<&str> = host
+            .players()
+            .map(|(_, player)| player.name.as_str())
+            .collect();
+        names.sort_unstable();
+        Ok(format!(
+            "{} connected: {}",
+            names.len(),
+            list_or_none(&names)
+        ))
+    }
+}
+
+fn list_or_none(names: &[&str]) -> String {
+    if names.is_empty() {
+        "nobody".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+/// `/kick <name> [reason]`
+pub struct KickCommand;
+
+impl ChatCommand for KickCommand {
+    fn name(&self) -> &str {
+        "kick"
+    }
+
+    fn usage(&self) -> &str {
+        "<name> [reason]"
+    }
+
+    fn description(&self) -> &str {
+        "Disconnect a player"
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        let Some((name, reason)) = args.split_first() else {
+            return Err("Usage: /kick <name> [reason]".to_string());
+        };
+        let mut host = world
+            .get_resource_mut::<HostSession>()
+            .ok_or("Only the host can kick players")?;
+
+        let ids: Vec<_> = host
+            .players()
+            .filter(|(_, player)| player.name.eq_ignore_ascii_case(name))
+            .map(|(id, _)| id)
+            .collect();
+        if ids.is_empty() {
+            return Err(format!("No player named {}", name));
+        }
+
+        let reason = if reason.is_empty() {
+            "Kicked by the host".to_string()
+        } else {
+            reason.join(" ")
+        };
+        for id in ids {
+            host.kick(id, &reason);
+        }
+        Ok(format!("Kicked {} ({})", name, reason))
+    }
+}
@@ -0,0 +1,86 @@
+//! Settings for the dedicated server (`server.yaml`).
+
+use super::protocol::DEFAULT_PORT;
+use super::server::ServerSettings;
+use crate::config::loader::{load_config, ConfigError};
+use serde::Deserialize;
+use std::path::Path;
+use thiserror::Error;
+
+pub const SERVER_CONFIG_PATH: &str = "assets/config/server.yaml";
+
+#[derive(Error, Debug)]
+pub enum ServerConfigError {
+    #[error("failed to read server config: {0}")]
+    Config(#[from] ConfigError),
+    #[error("invalid server config: {0}")]
+    Invalid(String),
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// TCP and UDP port to listen on
+    pub port: u16,
+    /// Empty means anyone may join
+    pub password: String,
+    /// Save slot id inside `saves/`; created when missing
+    pub world: String,
+    /// Seconds between automatic saves; 0 only saves on /save and /stop
+    pub autosave_interval_secs: f32,
+    pub max_players: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        let settings = ServerSettings::default();
+        Self {
+            port: DEFAULT_PORT,
+            password: settings.password,
+            world: "world".to_string(),
+            autosave_interval_secs: 300.0,
+            max_players: settings.max_players,
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ServerConfigError> {
+        let config: ServerConfig = load_config(path)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ServerConfigError> {
+        let invalid = |message: String| Err(ServerConfigError::Invalid(message));
+
+        if self.max_players == 0 {
+            return invalid("max_players must be at least 1".to_string());
+        }
+        if self.autosave_interval_secs.is_nan() || self.autosave_interval_secs < 0.0 {
+            return invalid(format!(
+                "autosave_interval_secs must not be negative, got {}",
+                self.autosave_interval_secs
+            ));
+        }
+        let slot_id_ok = !self.world.is_empty()
+            && self
+                .world
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !slot_id_ok {
+            return invalid(format!(
+                "world '{}' must be a save slot id (letters, digits, '_' and '-')",
+                self.world
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn settings(&self) -> ServerSettings {
+        ServerSettings {
+            password: self.password.clone(),
+            max_players: self.max_players,
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! The client side of a multiplayer session.
+//!
+//! While connected the local `VoxelWorld` mirrors the host's: chunks arrive
+//! from the host instead of being generated or loaded, local edits are sent
+//! to the host, and the local save is left untouched until we disconnect.
+
+use super::client::{ClientEvent, NetClient};
+use super::protocol::{
+    self, ClientMessage, PlayerId, PlayerState, ServerMessage, VoxelChange, Welcome,
+};
+use super::{NetworkSession, RemotePlayer, MAX_CHANGES_PER_MESSAGE, STATE_SEND_INTERVAL};
+use crate::camera::controller::PlayerCamera;
+use crate::chat::{ChatMessage, ChatState};
+use crate::interaction::history::EditHistory;
+use crate::interaction::DirtyChunks;
+use crate::player::Player;
+use crate::voxel::chunk::Chunk;
+use crate::voxel::meshing::ChunkMesh;
+use crate::voxel::persistence::{ActiveWorld, ChunkStore};
+use crate::voxel::streaming::{
+    chunk_column, enter_world, mark_neighbor_chunks_dirty, ChunkStreamingSettings,
+};
+use crate::voxel::world::VoxelWorld;
+use crate::voxel::worldgen::WorldGen;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GuestEvent {
+    Joined { id: PlayerId, name: String },
+    Left { id: PlayerId, name: String },
+    Chat { user: String, content: String },
+    Disconnected(String),
+}
+
+#[derive(Resource)]
+pub struct GuestSession {
+    client: Option<NetClient>,
+    welcome: Welcome,
+    players: HashMap<PlayerId, RemotePlayer>,
+    /// The host's world has replaced the local one
+    world_swapped: bool,
+    /// Local chunk streaming setting to restore when leaving
+    streaming_was_enabled: bool,
+    leave_requested: bool,
+}
+
+impl GuestSession {
+    pub fn new(client: NetClient, welcome: Welcome) -> Self {
+        let players = welcome
+            .players
+            .iter()
+            .map(|(id, name)| (*id, RemotePlayer::new(name.clone())))
+            .collect();
+        Self {
+            client: Some(client),
+            welcome,
+            players,
+            world_swapped: false,
+            streaming_was_enabled: true,
+            leave_requested: false,
+        }
+    }
+
+    pub fn welcome(&self) -> &Welcome {
+        &self.welcome
+    }
+
+    /// Other players by id, including the host
+    pub fn players(&self) -> impl Iterator<Item = (PlayerId, &RemotePlayer)> {
+        self.players.iter().map(|(id, player)| (*id, player))
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.client.is_some()
+    }
+
+    /// Disconnect at the next update
+    pub fn leave(&mut self) {
+        self.leave_requested = true;
+    }
+
+    pub fn send_state(&mut self, state: PlayerState) {
+        if let Some(client) = &mut self.client {
+            client.send_state(state);
+        }
+    }
+
+    /// Send a chat message to everyone else
+    pub fn send_chat(&self, content: &str) {
+        if let (Some(client), Some(content)) = (&self.client, protocol::clean_chat(content)) {
+            client.send(ClientMessage::Chat(content));
+        }
+    }
+
+    /// Send local edits to the host and apply what the host sent
+    pub fn update(&mut self, world: &mut VoxelWorld) -> Vec<GuestEvent> {
+        let Some(client) = &self.client else {
+            return Vec::new();
+        };
+
+        world.record_changes(true);
+        let local_changes: Vec<VoxelChange> = world
+            .take_changes()
+            .into_iter()
+            .map(|(position, voxel)| VoxelChange {
+                position,
+                voxel,
+                level: None,
+            })
+            .collect();
+        for batch in local_changes.chunks(MAX_CHANGES_PER_MESSAGE) {
+            client.send(ClientMessage::EditVoxels(batch.to_vec()));
+        }
+
+        let mut events = Vec::new();
+        let mut dirty = DirtyChunks::default();
+        let mut disconnected = None;
+        for event in client.poll() {
+            match event {
+                ClientEvent::Message(ServerMessage::Chunk(data)) => {
+                    if let Err(e) = data.validate() {
+                        warn!(
+                            "Ignoring corrupt chunk {:?} from host: {}",
+                            data.position, e
+                        );
+                        continue;
+                    }
+                    let chunk_pos = data.position;
+                    world.insert_chunk(Chunk::from_data(data));
+                    mark_neighbor_chunks_dirty(world, chunk_pos);
+                }
+                ClientEvent::Message(ServerMessage::VoxelChanges(changes)) => {
+                    for change in changes {
+                        if world.set_voxel(change.position, change.voxel) {
+                            world.set_fluid_level(change.position, change.level);
+                            dirty.add(change.position);
+                        }
+                    }
+                }
+                ClientEvent::Message(ServerMessage::PlayerJoined { id, name }) => {
+                    self.players.insert(id, RemotePlayer::new(name.clone()));
+                    events.push(GuestEvent::Joined { id, name });
+                }
+                ClientEvent::Message(ServerMessage::PlayerLeft { id }) => {
+                    if let Some(player) = self.players.remove(&id) {
+                        events.push(GuestEvent::Left {
+                            id,
+                            name: player.name,
+                        });
+                    }
+                }
+                ClientEvent::Message(ServerMessage::Chat { user, content }) => {
+                    events.push(GuestEvent::Chat { user, content });
+                }
+                ClientEvent::Message(ServerMessage::Kicked { reason }) => {
+                    disconnected = Some(format!("Kicked: {}", reason));
+                }
+                ClientEvent::Message(_) => {}
+                ClientEvent::States(states) => {
+                    for (id, state) in states {
+                        if let Some(player) = self.players.get_mut(&id) {
+                            player.state = Some(state);
+                        }
+                    }
+                }
+                ClientEvent::Disconnected(reason) => disconnected = Some(reason),
+            }
+        }
+        dirty.mark(world);
+        // The host's changes are already authoritative; don't echo them back
+        world.take_changes();
+
+        if let Some(reason) = disconnected {
+            self.client = None;
+            self.players.clear();
+            events.push(GuestEvent::Disconnected(reason));
+        }
+        events
+    }
+
+    /// Say goodbye to the host
+    pub fn disconnect(&mut self) {
+        if let Some(client) = self.client.take() {
+            client.disconnect();
+        }
+        self.players.clear();
+    }
+}
+
+/// Swap in the host's world when joining, keep it in sync while connected
+/// and bring the local save back when leaving
+pub fn guest_session_system(
+    mut commands: Commands,
+    mut guest: ResMut<GuestSession>,
+    mut world: ResMut<VoxelWorld>,
+    mut active_world: ResMut<ActiveWorld>,
+    mut chunk_store: ResMut<ChunkStore>,
+    mut world_gen: ResMut<WorldGen>,
+    mut streaming: ResMut<ChunkStreamingSettings>,
+    mut history: ResMut<EditHistory>,
+    mut network: ResMut<NetworkSession>,
+    mut chat: ResMut<ChatState>,
+    chunk_meshes: Query<Entity, With<ChunkMesh>>,
+    mut camera_query: Query<&mut Transform, (With<PlayerCamera>, Without<Player>)>,
+    mut player_query: Query<&mut Transform, (With<Player>, Without<PlayerCamera>)>,
+    time: Res<Time>,
+    mut since_state: Local<f32>,
+) {
+    if !guest.world_swapped {
+        // Keep local edits before the host's chunks take over
+        if let Err(e) = active_world.save(&mut world) {
+            warn!("Failed to save world before joining: {}", e);
+        }
+        let welcome = guest.welcome().clone();
+        *world = VoxelWorld::new(welcome.world_size_chunks);
+        world.set_border_enabled(welcome.world_border);
+        for entity in chunk_meshes.iter() {
+            commands.entity(entity).despawn();
+        }
+        guest.streaming_was_enabled = streaming.enabled;
+        streaming.enabled = false;
+        history.clear();
+
+        for mut transform in camera_query.iter_mut() {
+            transform.translation = welcome.spawn;
+        }
+        for mut transform in player_query.iter_mut() {
+            transform.translation = welcome.spawn - Vec3::Y * 1.6;
+        }
+        guest.world_swapped = true;
+    }
+
+    if guest.leave_requested {
+        guest.disconnect();
+        chat.push_system("Disconnected");
+    }
+
+    for event in guest.update(&mut world) {
+        match event {
+            GuestEvent::Joined { name, .. } => {
+                chat.push_system(format!("{} joined the game", name))
+            }
+            GuestEvent::Left { name, .. } => chat.push_system(format!("{} left the game", name)),
+            GuestEvent::Chat { user, content } => chat.push_message(ChatMessage { user, content }),
+            GuestEvent::Disconnected(reason) => {
+                chat.push_system(format!("Disconnected: {}", reason))
+            }
+        }
+    }
+    network.player_count = guest.players.len() + 1;
+
+    if !guest.is_connected() {
+        // Back to the local save, where we left it
+        streaming.enabled = guest.streaming_was_enabled;
+        history.clear();
+        let center = camera_query
+            .iter()
+            .next()
+            .map(|transform| chunk_column(transform.translation))
+            .unwrap_or(IVec3::ZERO);
+        let slot = active_world.slot.clone();
+        if let Err(e) = enter_world(
+            slot,
+            &mut world,
+            &mut active_world,
+            &mut chunk_store,
+            &mut world_gen,
+            &streaming,
+            center,
+        ) {
+            warn!("Failed to reload local world: {}", e);
+            chat.push_system(format!("Failed to reload local world: {}", e));
+        }
+        for entity in chunk_meshes.iter() {
+            commands.entity(entity).despawn();
+        }
+        network.reset_client();
+        commands.remove_resource::<GuestSession>();
+        return;
+    }
+
+    *since_state += time.delta_secs();
+    if *since_state >= STATE_SEND_INTERVAL {
+        *since_state = 0.0;
+        if let Some(transform) = camera_query.iter().next() {
+            guest.send_state(PlayerState {
+                position: transform.translation,
+                rotation: transform.rotation,
+            });
+        }
+    }
+}
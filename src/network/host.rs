@@ -0,0 +1,374 @@
+//! The authoritative side of a multiplayer session.
+//!
+//! The host's `VoxelWorld` is the real one: clients send the voxels they
+//! changed, the host applies what it allows and everyone else hears about
+//! the result. Clients that tried something the host refused get the actual
+//! voxels back.
+
+use super::protocol::{
+    self, ClientMessage, PlayerId, PlayerState, ServerMessage, VoxelChange, HOST_PLAYER_ID,
+};
+use super::server::{NetServer, ServerEvent, ServerSettings, WorldInfo};
+use super::{NetworkSession, RemotePlayer, MAX_CHANGES_PER_MESSAGE, STATE_SEND_INTERVAL};
+use crate::camera::controller::PlayerCamera;
+use crate::chat::{ChatMessage, ChatState};
+use crate::interaction::DirtyChunks;
+use crate::voxel::persistence::{ActiveWorld, SaveSlot};
+use crate::voxel::registry::VoxelRegistry;
+use crate::voxel::streaming::{chunk_column, ChunkStreamingSettings, StreamingAnchors};
+use crate::voxel::types::VoxelType;
+use crate::voxel::world::VoxelWorld;
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+
+/// Chunks sent to one player per update, nearest first
+const CHUNKS_PER_UPDATE: usize = 8;
+
+/// Streaming to a guest pauses while this many messages wait for its socket,
+/// so a slow link is not cut off for falling behind while it loads in
+const MAX_QUEUED_CHUNKS: usize = 64;
+
+/// Edits touching this many voxels of one chunk resend the whole chunk
+const RESEND_CHUNK_THRESHOLD: usize = 512;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostEvent {
+    Joined {
+        id: PlayerId,
+        name: String,
+    },
+    Left {
+        id: PlayerId,
+        name: String,
+        reason: String,
+    },
+    Chat {
+        id: PlayerId,
+        name: String,
+        content: String,
+    },
+}
+
+/// A connected player as the host sees them
+struct Guest {
+    player: RemotePlayer,
+    /// Chunks this player has received and gets edits for
+    sent_chunks: HashSet<IVec3>,
+}
+
+#[derive(Resource)]
+pub struct HostSession {
+    server: NetServer,
+    guests: HashMap<PlayerId, Guest>,
+}
+
+impl HostSession {
+    pub fn start(addr: SocketAddr, settings: ServerSettings) -> Result<Self, String> {
+        Ok(Self {
+            server: NetServer::bind(addr, settings)?,
+            guests: HashMap::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.server.local_addr()
+    }
+
+    pub fn set_world_info(&mut self, info: WorldInfo) {
+        self.server.set_world_info(info);
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.guests.len()
+    }
+
+    /// Connected players by id
+    pub fn players(&self) -> impl Iterator<Item = (PlayerId, &RemotePlayer)> {
+        self.guests.iter().map(|(id, guest)| (*id, &guest.player))
+    }
+
+    /// Where each player is, or the spawn point until they report in
+    pub fn player_positions(&self) -> Vec<Vec3> {
+        let spawn = self.server.world_info().spawn;
+        self.guests
+            .values()
+            .map(|guest| guest.player.state.map_or(spawn, |state| state.position))
+            .collect()
+    }
+
+    /// Exchange everything since the last update: broadcast local edits,
+    /// apply and relay guests' edits and stream chunks to guests
+    pub fn update(
+        &mut self,
+        world: &mut VoxelWorld,
+        registry: &VoxelRegistry,
+        streaming: &ChunkStreamingSettings,
+    ) -> Vec<HostEvent> {
+        world.record_changes(true);
+        let local_changes = world.take_changes();
+        self.broadcast_changes(world, &local_changes, None);
+
+        let mut events = Vec::new();
+        for event in self.server.poll() {
+            match event {
+                ServerEvent::Joined { id, name } => {
+                    self.guests.insert(
+                        id,
+                        Guest {
+                            player: RemotePlayer::new(name.clone()),
+                            sent_chunks: HashSet::new(),
+                        },
+                    );
+                    events.push(HostEvent::Joined { id, name });
+                }
+                ServerEvent::Left { id, name, reason } => {
+                    self.guests.remove(&id);
+                    events.push(HostEvent::Left { id, name, reason });
+                }
+                ServerEvent::Message {
+                    id,
+                    message: ClientMessage::EditVoxels(changes),
+                } => self.apply_edits(world, registry, id, &changes),
+                ServerEvent::Message {
+                    id,
+                    message: ClientMessage::Chat(content),
+                } => {
+                    let (Some(guest), Some(content)) =
+                        (self.guests.get(&id), protocol::clean_chat(&content))
+                    else {
+                        continue;
+                    };
+                    let name = guest.player.name.clone();
+                    self.server.broadcast(
+                        ServerMessage::Chat {
+                            user: name.clone(),
+                            content: content.clone(),
+                        },
+                        Some(id),
+                    );
+                    events.push(HostEvent::Chat { id, name, content });
+                }
+                ServerEvent::Message { .. } => {}
+                ServerEvent::State { id, state } => {
+                    if let Some(guest) = self.guests.get_mut(&id) {
+                        guest.player.state = Some(state);
+                    }
+                }
+            }
+        }
+
+        self.stream_chunks(world, streaming);
+        events
+    }
+
+    /// Send every player the others' transforms; `host` is the host's own
+    /// player, if there is one
+    pub fn send_states(&mut self, host: Option<PlayerState>) {
+        let states: Vec<(PlayerId, PlayerState)> = host
+            .map(|state| (HOST_PLAYER_ID, state))
+            .into_iter()
+            .chain(
+                self.guests
+                    .iter()
+                    .filter_map(|(id, guest)| guest.player.state.map(|state| (*id, state))),
+            )
+            .collect();
+        self.server.send_states(&states);
+    }
+
+    /// Send a chat message from this side (the host or the server console)
+    /// to every player
+    pub fn send_chat(&self, user: &str, content: &str) {
+        if let Some(content) = protocol::clean_chat(content) {
+            self.server.broadcast(
+                ServerMessage::Chat {
+                    user: user.to_string(),
+                    content,
+                },
+                None,
+            );
+        }
+    }
+
+    pub fn kick(&mut self, id: PlayerId, reason: &str) {
+        self.server.kick(id, reason);
+        self.guests.remove(&id);
+    }
+
+    pub fn kick_all(&mut self, reason: &str) {
+        self.server.kick_all(reason);
+        self.guests.clear();
+    }
+
+    fn apply_edits(
+        &mut self,
+        world: &mut VoxelWorld,
+        registry: &VoxelRegistry,
+        id: PlayerId,
+        changes: &[VoxelChange],
+    ) {
+        let mut dirty = DirtyChunks::default();
+        let mut rejected = Vec::new();
+        for change in changes {
+            if !edit_allowed(world, registry, change) {
+                rejected.push(change.position);
+            } else if world.set_voxel(change.position, change.voxel) {
+                dirty.add(change.position);
+            }
+        }
+        dirty.mark(world);
+
+        let applied = world.take_changes();
+        self.broadcast_changes(world, &applied, Some(id));
+
+        // Put the sender's copy back in line with ours
+        let corrections: Vec<VoxelChange> = rejected
+            .into_iter()
+            .filter_map(|position| VoxelChange::read(world, position))
+            .collect();
+        for batch in corrections.chunks(MAX_CHANGES_PER_MESSAGE) {
+            self.server
+                .send(id, ServerMessage::VoxelChanges(batch.to_vec()));
+        }
+    }
+
+    /// Relay changes to players holding the affected chunks. Chunks with
+    /// many changes are sent whole, which is smaller than the change list.
+    fn broadcast_changes(
+        &self,
+        world: &VoxelWorld,
+        changes: &[(IVec3, VoxelType)],
+        except: Option<PlayerId>,
+    ) {
+        if changes.is_empty() {
+            return;
+        }
+
+        let mut by_chunk: HashMap<IVec3, Vec<VoxelChange>> = HashMap::new();
+        for (position, voxel) in changes {
+            by_chunk
+                .entry(VoxelWorld::world_to_chunk(*position))
+                .or_default()
+                .push(VoxelChange {
+                    position: *position,
+                    voxel: *voxel,
+                    level: world.flow_level(*position),
+                });
+        }
+
+        for (id, guest) in &self.guests {
+            if Some(*id) == except {
+                continue;
+            }
+            let mut batch = Vec::new();
+            for (chunk_pos, chunk_changes) in &by_chunk {
+                if !guest.sent_chunks.contains(chunk_pos) {
+                    continue;
+                }
+                match world.get_chunk(*chunk_pos) {
+                    Some(chunk) if chunk_changes.len() >= RESEND_CHUNK_THRESHOLD => {
+                        self.server.send(*id, ServerMessage::Chunk(chunk.to_data()));
+                    }
+                    _ => batch.extend_from_slice(chunk_changes),
+                }
+            }
+            for changes in batch.chunks(MAX_CHANGES_PER_MESSAGE) {
+                self.server
+                    .send(*id, ServerMessage::VoxelChanges(changes.to_vec()));
+            }
+        }
+    }
+
+    /// Send loaded chunks around each player that they don't have yet
+    fn stream_chunks(&mut self, world: &VoxelWorld, streaming: &ChunkStreamingSettings) {
+        let spawn = self.server.world_info().spawn;
+        for (id, guest) in &mut self.guests {
+            let room = MAX_QUEUED_CHUNKS.saturating_sub(self.server.queued_messages(*id));
+            if room == 0 {
+                continue;
+            }
+            let position = guest.player.state.map_or(spawn, |state| state.position);
+            let missing: Vec<IVec3> = streaming
+                .positions_around(chunk_column(position))
+                .into_iter()
+                .filter(|pos| world.chunk_exists(*pos) && !guest.sent_chunks.contains(pos))
+                .take(CHUNKS_PER_UPDATE.min(room))
+                .collect();
+
+            for chunk_pos in missing {
+                if let Some(chunk) = world.get_chunk(chunk_pos) {
+                    self.server.send(*id, ServerMessage::Chunk(chunk.to_data()));
+                    guest.sent_chunks.insert(chunk_pos);
+                }
+            }
+        }
+    }
+}
+
+/// Guests may change any loaded, breakable voxel into a known type
+fn edit_allowed(world: &VoxelWorld, registry: &VoxelRegistry, change: &VoxelChange) -> bool {
+    let Some(existing) = world.get_voxel(change.position) else {
+        return false;
+    };
+    (change.voxel.id() as usize) < registry.len()
+        && (existing == change.voxel || registry.info(existing).is_breakable())
+}
+
+/// Run the host session: relay edits, stream chunks, keep the streamed area
+/// around every player loaded and share transforms
+pub fn host_session_system(
+    mut host: ResMut<HostSession>,
+    mut world: ResMut<VoxelWorld>,
+    registry: Res<VoxelRegistry>,
+    streaming: Res<ChunkStreamingSettings>,
+    active_world: Res<ActiveWorld>,
+    mut anchors: ResMut<StreamingAnchors>,
+    mut network: ResMut<NetworkSession>,
+    mut chat: ResMut<ChatState>,
+    camera_query: Query<&Transform, With<PlayerCamera>>,
+    time: Res<Time>,
+    mut last_slot: Local<Option<SaveSlot>>,
+    mut since_states: Local<f32>,
+) {
+    // Guests hold chunks of the old world
+    if last_slot.as_ref() != Some(&active_world.slot) {
+        if last_slot.is_some() {
+            host.kick_all("The host switched worlds");
+        }
+        *last_slot = Some(active_world.slot.clone());
+    }
+
+    let host_state = camera_query.iter().next().map(|transform| PlayerState {
+        position: transform.translation,
+        rotation: transform.rotation,
+    });
+    host.set_world_info(WorldInfo {
+        seed: active_world.metadata.seed,
+        world_size_chunks: world.world_size_chunks(),
+        world_border: world.border_enabled(),
+        spawn: host_state.map_or(Vec3::new(0.0, 50.0, 0.0), |state| state.position),
+        host_name: host_state.map(|_| chat.username.clone()),
+    });
+
+    for event in host.update(&mut world, &registry, &streaming) {
+        match event {
+            HostEvent::Joined { name, .. } => chat.push_system(format!("{} joined the game", name)),
+            HostEvent::Left { name, reason, .. } => {
+                chat.push_system(format!("{} left the game ({})", name, reason))
+            }
+            HostEvent::Chat { name, content, .. } => chat.push_message(ChatMessage {
+                user: name,
+                content,
+            }),
+        }
+    }
+    network.player_count = host.player_count() + usize::from(host_state.is_some());
+    anchors.positions = host.player_positions();
+
+    *since_states += time.delta_secs();
+    if *since_states >= STATE_SEND_INTERVAL {
+        *since_states = 0.0;
+        host.send_states(host_state);
+    }
+}
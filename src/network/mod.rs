@@ -0,0 +1,91 @@
+//! Multiplayer: one player hosts the authoritative world, others join it.
+//!
+//! [`protocol`] defines the wire format, [`server`] and [`client`] move it
+//! over TCP (reliable messages) and UDP (player transforms), and [`host`] and
+//! [`guest`] connect those to the game world. The dedicated server binary
+//! (`src/bin/server.rs`) runs the host side without a player of its own.
+
+pub mod avatars;
+pub mod client;
+pub mod commands;
+pub mod config;
+pub mod guest;
+pub mod host;
+pub mod protocol;
+pub mod server;
+
+use crate::chat::commands::ChatCommandAppExt;
+use bevy::prelude::*;
+use commands::{KickCommand, PlayersCommand};
+use guest::GuestSession;
+use host::HostSession;
+use protocol::PlayerState;
+
+/// Voxel changes per message, so bulk edits don't hit the frame size limit
+pub const MAX_CHANGES_PER_MESSAGE: usize = 4096;
+
+/// Seconds between player transform updates
+pub const STATE_SEND_INTERVAL: f32 = 0.05;
+
+#[derive(Resource, Default, Debug, Clone)]
+pub struct NetworkSession {
+    pub server_running: bool,
+    pub client_connected: bool,
+    pub host_password: String,
+    pub connection_ip: Option<String>,
+    pub connection_port: Option<String>,
+    pub last_latency_ms: Option<u128>,
+    pub last_health_ok: bool,
+    /// Players in the session, including this one
+    pub player_count: usize,
+}
+
+impl NetworkSession {
+    pub fn reset_client(&mut self) {
+        self.client_connected = false;
+        self.connection_ip = None;
+        self.connection_port = None;
+        self.last_latency_ms = None;
+        self.last_health_ok = false;
+        self.player_count = 0;
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.client_connected
+    }
+}
+
+/// Another player in the session
+#[derive(Clone, Debug)]
+pub struct RemotePlayer {
+    pub name: String,
+    /// Latest transform, once one has arrived
+    pub state: Option<PlayerState>,
+}
+
+impl RemotePlayer {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            state: None,
+        }
+    }
+}
+
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetworkSession>()
+            .add_chat_command(PlayersCommand)
+            .add_chat_command(KickCommand)
+            .add_systems(
+                Update,
+                (
+                    host::host_session_system.run_if(resource_exists::<HostSession>),
+                    guest::guest_session_system.run_if(resource_exists::<GuestSession>),
+                    avatars::sync_remote_avatars,
+                ),
+            );
+    }
+}
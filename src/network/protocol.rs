@@ -0,0 +1,197 @@
+//! Wire format shared by the server and clients.
+//!
+//! A connection opens with a fixed prelude in both directions: [`MAGIC`]
+//! followed by the little-endian [`PROTOCOL_VERSION`]. It is not bincode, so
+//! peers of any version can tell each other apart. After that the TCP stream
+//! carries length-prefixed bincode frames of [`ClientMessage`] and
+//! [`ServerMessage`], and UDP carries one [`Datagram`] per packet.
+
+use crate::voxel::chunk::ChunkData;
+use crate::voxel::types::VoxelType;
+use crate::voxel::world::VoxelWorld;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+/// Bump whenever a message changes shape
+pub const PROTOCOL_VERSION: u32 = 3;
+
+pub const MAGIC: &[u8; 4] = b"VXNP";
+
+pub const DEFAULT_PORT: u16 = 7777;
+
+/// Largest TCP frame accepted; guards against corrupt length prefixes
+pub const MAX_FRAME_SIZE: usize = 4 * 1024 * 1024;
+
+/// Large enough for a full player list; transforms are tiny
+pub const MAX_DATAGRAM_SIZE: usize = 1200;
+
+/// Longest chat message in characters; longer ones are cut off
+pub const MAX_CHAT_LENGTH: usize = 256;
+
+pub type PlayerId = u32;
+
+/// The host's own player on a listen server
+pub const HOST_PLAYER_ID: PlayerId = 0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PlayerState {
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VoxelChange {
+    pub position: IVec3,
+    pub voxel: VoxelType,
+    /// Level of flowing liquid; `None` for sources and everything else
+    pub level: Option<u8>,
+}
+
+impl VoxelChange {
+    /// A voxel as it currently is, `None` if its chunk isn't loaded
+    pub fn read(world: &VoxelWorld, position: IVec3) -> Option<Self> {
+        Some(Self {
+            position,
+            voxel: world.get_voxel(position)?,
+            level: world.flow_level(position),
+        })
+    }
+}
+
+/// Sent in reply to an accepted [`ClientMessage::Hello`]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Welcome {
+    pub player_id: PlayerId,
+    /// Identifies this client's UDP packets
+    pub token: u64,
+    pub seed: u64,
+    pub world_size_chunks: IVec3,
+    pub world_border: bool,
+    pub spawn: Vec3,
+    /// Players already connected, including the host
+    pub players: Vec<(PlayerId, String)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ClientMessage {
+    Hello {
+        name: String,
+        password: String,
+    },
+    /// Voxels changed locally; the server applies what it allows
+    EditVoxels(Vec<VoxelChange>),
+    Chat(String),
+    Goodbye,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum ServerMessage {
+    Welcome(Welcome),
+    Rejected { reason: String },
+    Chunk(ChunkData),
+    VoxelChanges(Vec<VoxelChange>),
+    PlayerJoined { id: PlayerId, name: String },
+    PlayerLeft { id: PlayerId },
+    Chat { user: String, content: String },
+    Kicked { reason: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Datagram {
+    /// Client to server; `sequence` drops reordered packets
+    ClientState {
+        token: u64,
+        sequence: u32,
+        state: PlayerState,
+    },
+    /// Server to client, everyone but the receiver
+    PlayerStates {
+        sequence: u32,
+        players: Vec<(PlayerId, PlayerState)>,
+    },
+}
+
+pub fn write_prelude<W: Write>(writer: &mut W) -> Result<(), String> {
+    let mut prelude = [0u8; 8];
+    prelude[..4].copy_from_slice(MAGIC);
+    prelude[4..].copy_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+    writer
+        .write_all(&prelude)
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Failed to send handshake: {}", e))
+}
+
+/// Read the peer's prelude and return its protocol version
+pub fn read_prelude<R: Read>(reader: &mut R) -> Result<u32, String> {
+    let mut prelude = [0u8; 8];
+    reader
+        .read_exact(&mut prelude)
+        .map_err(|e| format!("Failed to read handshake: {}", e))?;
+    if &prelude[..4] != MAGIC {
+        return Err("Peer is not a voxel_builder server or client".to_string());
+    }
+    Ok(u32::from_le_bytes(
+        prelude[4..].try_into().expect("4 bytes"),
+    ))
+}
+
+pub fn write_frame<W: Write, T: Serialize>(writer: &mut W, message: &T) -> Result<(), String> {
+    let payload =
+        bincode::serialize(message).map_err(|e| format!("Failed to encode message: {}", e))?;
+    if payload.len() > MAX_FRAME_SIZE {
+        return Err(format!("Message of {} bytes is too large", payload.len()));
+    }
+    writer
+        .write_all(&(payload.len() as u32).to_le_bytes())
+        .and_then(|_| writer.write_all(&payload))
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Failed to send message: {}", e))
+}
+
+pub fn read_frame<R: Read, T: for<'de> Deserialize<'de>>(reader: &mut R) -> Result<T, String> {
+    let mut length = [0u8; 4];
+    reader
+        .read_exact(&mut length)
+        .map_err(|e| format!("Connection lost: {}", e))?;
+    let length = u32::from_le_bytes(length) as usize;
+    if length > MAX_FRAME_SIZE {
+        return Err(format!("Message of {} bytes is too large", length));
+    }
+
+    let mut payload = vec![0u8; length];
+    reader
+        .read_exact(&mut payload)
+        .map_err(|e| format!("Connection lost: {}", e))?;
+    bincode::deserialize(&payload).map_err(|e| format!("Malformed message: {}", e))
+}
+
+pub fn encode_datagram(datagram: &Datagram) -> Result<Vec<u8>, String> {
+    let bytes =
+        bincode::serialize(datagram).map_err(|e| format!("Failed to encode datagram: {}", e))?;
+    if bytes.len() > MAX_DATAGRAM_SIZE {
+        return Err(format!("Datagram of {} bytes is too large", bytes.len()));
+    }
+    Ok(bytes)
+}
+
+pub fn decode_datagram(bytes: &[u8]) -> Result<Datagram, String> {
+    bincode::deserialize(bytes).map_err(|e| format!("Malformed datagram: {}", e))
+}
+
+/// Whether `sequence` is newer than `last`, allowing for wrap-around
+pub fn is_newer(sequence: u32, last: u32) -> bool {
+    sequence != last && sequence.wrapping_sub(last) < u32::MAX / 2
+}
+
+/// Chat text as it may be sent: trimmed, without control characters and at
+/// most [`MAX_CHAT_LENGTH`] characters. `None` when nothing is left.
+pub fn clean_chat(content: &str) -> Option<String> {
+    let cleaned: String = content
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_CHAT_LENGTH)
+        .collect();
+    (!cleaned.is_empty()).then_some(cleaned)
+}
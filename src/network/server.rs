@@ -0,0 +1,610 @@
+//! Server side of the transport: accepts connections, runs the handshake and
+//! moves messages between sockets and the game thread.
+//!
+//! Every connection gets a reader thread and a writer thread; the game only
+//! talks to [`NetServer`] through channels, so a slow client never blocks a
+//! frame. Each writer's queue is capped; a client that falls that far
+//! behind is disconnected rather than buffered without limit.
+
+use super::protocol::{
+    self, ClientMessage, Datagram, PlayerId, PlayerState, ServerMessage, Welcome, HOST_PLAYER_ID,
+    MAX_DATAGRAM_SIZE, PROTOCOL_VERSION,
+};
+use bevy::prelude::*;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::io::ErrorKind;
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Time a new connection gets to finish the handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often blocking loops check whether the server is stopping
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+const MAX_NAME_LENGTH: usize = 32;
+
+/// Player states fit comfortably below `MAX_DATAGRAM_SIZE` in groups this size
+const STATES_PER_DATAGRAM: usize = 24;
+
+/// Reliable messages waiting for one client's writer before the client is
+/// dropped for falling behind
+pub const MAX_QUEUED_MESSAGES: usize = 1024;
+
+#[derive(Clone, Debug)]
+pub struct ServerSettings {
+    /// Empty means anyone may join
+    pub password: String,
+    pub max_players: usize,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            password: String::new(),
+            max_players: 8,
+        }
+    }
+}
+
+/// World details handed to joining players
+#[derive(Clone, Debug, Default)]
+pub struct WorldInfo {
+    pub seed: u64,
+    pub world_size_chunks: IVec3,
+    pub world_border: bool,
+    pub spawn: Vec3,
+    /// Name of the host's own player; None on a dedicated server
+    pub host_name: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ServerEvent {
+    Joined {
+        id: PlayerId,
+        name: String,
+    },
+    Left {
+        id: PlayerId,
+        name: String,
+        reason: String,
+    },
+    Message {
+        id: PlayerId,
+        message: ClientMessage,
+    },
+    State {
+        id: PlayerId,
+        state: PlayerState,
+    },
+}
+
+/// Sent from connection and UDP threads to the game thread
+enum Incoming {
+    Authenticated {
+        id: PlayerId,
+        name: String,
+        stream: TcpStream,
+    },
+    Message {
+        id: PlayerId,
+        message: ClientMessage,
+    },
+    Closed {
+        id: PlayerId,
+        reason: String,
+    },
+    Datagram {
+        from: SocketAddr,
+        datagram: Datagram,
+    },
+}
+
+struct Connection {
+    name: String,
+    token: u64,
+    outgoing: Sender<ServerMessage>,
+    /// Messages sent to the writer and not yet written
+    queued: Arc<AtomicUsize>,
+    /// Shut down to drop a client that stopped reading
+    stream: TcpStream,
+    udp_addr: Option<SocketAddr>,
+    last_sequence: Option<u32>,
+}
+
+pub struct NetServer {
+    local_addr: SocketAddr,
+    settings: ServerSettings,
+    world_info: WorldInfo,
+    running: Arc<AtomicBool>,
+    incoming: Mutex<Receiver<Incoming>>,
+    udp: UdpSocket,
+    connections: HashMap<PlayerId, Connection>,
+    state_sequence: u32,
+}
+
+impl NetServer {
+    /// Listen for TCP and UDP on the same address; port 0 picks a free one
+    pub fn bind(addr: SocketAddr, settings: ServerSettings) -> Result<Self, String> {
+        let listener =
+            TcpListener::bind(addr).map_err(|e| format!("Failed to listen on {}: {}", addr, e))?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read listen address: {}", e))?;
+        let udp = UdpSocket::bind(local_addr)
+            .map_err(|e| format!("Failed to open UDP port {}: {}", local_addr.port(), e))?;
+        listener
+            .set_nonblocking(true)
+            .and_then(|_| udp.set_read_timeout(Some(POLL_INTERVAL)))
+            .map_err(|e| format!("Failed to configure sockets: {}", e))?;
+        let udp_reader = udp
+            .try_clone()
+            .map_err(|e| format!("Failed to share UDP socket: {}", e))?;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let (sender, receiver) = mpsc::channel();
+        let password = Arc::new(settings.password.clone());
+
+        {
+            let running = running.clone();
+            let sender = sender.clone();
+            thread::spawn(move || accept_loop(listener, password, running, sender));
+        }
+        {
+            let running = running.clone();
+            thread::spawn(move || udp_loop(udp_reader, running, sender));
+        }
+
+        info!(
+            "Server listening on {} (protocol v{})",
+            local_addr, PROTOCOL_VERSION
+        );
+        Ok(Self {
+            local_addr,
+            settings,
+            world_info: WorldInfo::default(),
+            running,
+            incoming: Mutex::new(receiver),
+            udp,
+            connections: HashMap::new(),
+            state_sequence: 0,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn set_world_info(&mut self, info: WorldInfo) {
+        self.world_info = info;
+    }
+
+    pub fn world_info(&self) -> &WorldInfo {
+        &self.world_info
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// Connected players by id
+    pub fn players(&self) -> impl Iterator<Item = (PlayerId, &str)> {
+        self.connections
+            .iter()
+            .map(|(id, connection)| (*id, connection.name.as_str()))
+    }
+
+    pub fn player_name(&self, id: PlayerId) -> Option<&str> {
+        self.connections.get(&id).map(|c| c.name.as_str())
+    }
+
+    /// Everything that arrived since the last call. Joining players are
+    /// welcomed (or turned away when full) before their event is returned.
+    pub fn poll(&mut self) -> Vec<ServerEvent> {
+        let incoming: Vec<Incoming> = match self.incoming.lock() {
+            Ok(receiver) => receiver.try_iter().collect(),
+            Err(_) => Vec::new(),
+        };
+
+        let mut events = Vec::new();
+        for item in incoming {
+            match item {
+                Incoming::Authenticated { id, name, stream } => {
+                    if let Some(event) = self.accept(id, name, stream) {
+                        events.push(event);
+                    }
+                }
+                Incoming::Message { id, message } => {
+                    if self.connections.contains_key(&id) {
+                        events.push(ServerEvent::Message { id, message });
+                    }
+                }
+                Incoming::Closed { id, mut reason } => {
+                    if let Some(connection) = self.connections.remove(&id) {
+                        if connection.queued.load(Ordering::Relaxed) >= MAX_QUEUED_MESSAGES {
+                            reason = "fell too far behind".to_string();
+                        }
+                        self.broadcast(ServerMessage::PlayerLeft { id }, None);
+                        events.push(ServerEvent::Left {
+                            id,
+                            name: connection.name,
+                            reason,
+                        });
+                    }
+                }
+                Incoming::Datagram { from, datagram } => {
+                    if let Some(event) = self.receive_datagram(from, datagram) {
+                        events.push(event);
+                    }
+                }
+            }
+        }
+        events
+    }
+
+    fn accept(&mut self, id: PlayerId, name: String, stream: TcpStream) -> Option<ServerEvent> {
+        let (outgoing, queue) = mpsc::channel();
+        let writer = match stream.try_clone() {
+            Ok(writer) => writer,
+            Err(e) => {
+                warn!("Dropping player {}: {}", name, e);
+                return None;
+            }
+        };
+        let queued = Arc::new(AtomicUsize::new(0));
+        {
+            let queued = queued.clone();
+            thread::spawn(move || write_loop(writer, queue, queued));
+        }
+
+        if self.connections.len() >= self.settings.max_players {
+            let _ = outgoing.send(ServerMessage::Rejected {
+                reason: format!("Server is full ({} players)", self.settings.max_players),
+            });
+            return None;
+        }
+
+        let mut players: Vec<(PlayerId, String)> = self
+            .connections
+            .iter()
+            .map(|(id, connection)| (*id, connection.name.clone()))
+            .collect();
+        if let Some(host_name) = &self.world_info.host_name {
+            players.push((HOST_PLAYER_ID, host_name.clone()));
+        }
+        players.sort();
+
+        let token = random_token();
+        let welcome = Welcome {
+            player_id: id,
+            token,
+            seed: self.world_info.seed,
+            world_size_chunks: self.world_info.world_size_chunks,
+            world_border: self.world_info.world_border,
+            spawn: self.world_info.spawn,
+            players,
+        };
+        queued.fetch_add(1, Ordering::Relaxed);
+        if outgoing.send(ServerMessage::Welcome(welcome)).is_err() {
+            return None;
+        }
+
+        self.broadcast(
+            ServerMessage::PlayerJoined {
+                id,
+                name: name.clone(),
+            },
+            None,
+        );
+        self.connections.insert(
+            id,
+            Connection {
+                name: name.clone(),
+                token,
+                outgoing,
+                queued,
+                stream,
+                udp_addr: None,
+                last_sequence: None,
+            },
+        );
+        info!("{} joined as player {}", name, id);
+        Some(ServerEvent::Joined { id, name })
+    }
+
+    fn receive_datagram(&mut self, from: SocketAddr, datagram: Datagram) -> Option<ServerEvent> {
+        let Datagram::ClientState {
+            token,
+            sequence,
+            state,
+        } = datagram
+        else {
+            return None;
+        };
+
+        let (id, connection) = self
+            .connections
+            .iter_mut()
+            .find(|(_, connection)| connection.token == token)?;
+        if connection
+            .last_sequence
+            .is_some_and(|last| !protocol::is_newer(sequence, last))
+        {
+            return None;
+        }
+        connection.last_sequence = Some(sequence);
+        // Clients behind NAT may change port; follow their latest packet
+        connection.udp_addr = Some(from);
+
+        let valid = state.position.is_finite() && state.rotation.is_finite();
+        valid.then_some(ServerEvent::State { id: *id, state })
+    }
+
+    /// Reliable messages queued for a player and not yet written
+    pub fn queued_messages(&self, id: PlayerId) -> usize {
+        self.connections
+            .get(&id)
+            .map_or(0, |connection| connection.queued.load(Ordering::Relaxed))
+    }
+
+    /// Queue a reliable message for one player
+    pub fn send(&self, id: PlayerId, message: ServerMessage) {
+        if let Some(connection) = self.connections.get(&id) {
+            connection.queue(message);
+        }
+    }
+
+    /// Queue a message for every player, optionally skipping one
+    pub fn broadcast(&self, message: ServerMessage, except: Option<PlayerId>) {
+        for (id, connection) in &self.connections {
+            if Some(*id) != except {
+                connection.queue(message.clone());
+            }
+        }
+    }
+
+    /// Send each player everyone else's state over UDP
+    pub fn send_states(&mut self, states: &[(PlayerId, PlayerState)]) {
+        self.state_sequence = self.state_sequence.wrapping_add(1);
+        for (id, connection) in &self.connections {
+            let Some(addr) = connection.udp_addr else {
+                continue;
+            };
+            let others: Vec<(PlayerId, PlayerState)> = states
+                .iter()
+                .filter(|(other, _)| other != id)
+                .copied()
+                .collect();
+            for players in others.chunks(STATES_PER_DATAGRAM) {
+                let datagram = Datagram::PlayerStates {
+                    sequence: self.state_sequence,
+                    players: players.to_vec(),
+                };
+                match protocol::encode_datagram(&datagram) {
+                    Ok(bytes) => {
+                        let _ = self.udp.send_to(&bytes, addr);
+                    }
+                    Err(e) => warn!("{}", e),
+                }
+            }
+        }
+    }
+
+    /// Disconnect a player, telling them why
+    pub fn kick(&mut self, id: PlayerId, reason: &str) {
+        if let Some(connection) = self.connections.remove(&id) {
+            connection.queue(ServerMessage::Kicked {
+                reason: reason.to_string(),
+            });
+            self.broadcast(ServerMessage::PlayerLeft { id }, None);
+            info!("Kicked {}: {}", connection.name, reason);
+        }
+    }
+
+    pub fn kick_all(&mut self, reason: &str) {
+        let ids: Vec<PlayerId> = self.connections.keys().copied().collect();
+        for id in ids {
+            self.kick(id, reason);
+        }
+    }
+}
+
+impl Connection {
+    /// Hand a message to the writer, or drop the client once its queue is
+    /// full; the reader then reports the connection closed
+    fn queue(&self, message: ServerMessage) {
+        let queued = self.queued.fetch_add(1, Ordering::Relaxed);
+        if queued >= MAX_QUEUED_MESSAGES {
+            if queued == MAX_QUEUED_MESSAGES {
+                warn!(
+                    "Dropping {}: {} messages waiting to be sent",
+                    self.name, queued
+                );
+                let _ = self.stream.shutdown(Shutdown::Both);
+            }
+            return;
+        }
+        let _ = self.outgoing.send(message);
+    }
+}
+
+impl Drop for NetServer {
+    fn drop(&mut self) {
+        self.kick_all("Server stopped");
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+fn random_token() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0),
+    );
+    hasher.finish()
+}
+
+fn accept_loop(
+    listener: TcpListener,
+    password: Arc<String>,
+    running: Arc<AtomicBool>,
+    sender: Sender<Incoming>,
+) {
+    // Player 0 is the host
+    let next_id = Arc::new(AtomicU32::new(HOST_PLAYER_ID + 1));
+    while running.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, addr)) => {
+                let password = password.clone();
+                let running = running.clone();
+                let sender = sender.clone();
+                let next_id = next_id.clone();
+                thread::spawn(move || {
+                    if let Err(reason) =
+                        handle_connection(stream, &password, &running, &sender, &next_id)
+                    {
+                        info!("Connection from {} closed: {}", addr, reason);
+                    }
+                });
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                warn!("Failed to accept connection: {}", e);
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Handshake, then forward messages until the connection drops
+fn handle_connection(
+    mut stream: TcpStream,
+    password: &str,
+    running: &AtomicBool,
+    sender: &Sender<Incoming>,
+    next_id: &AtomicU32,
+) -> Result<(), String> {
+    stream
+        .set_nonblocking(false)
+        .and_then(|_| stream.set_nodelay(true))
+        .and_then(|_| stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)))
+        .map_err(|e| format!("Failed to configure connection: {}", e))?;
+
+    let version = protocol::read_prelude(&mut stream)?;
+    protocol::write_prelude(&mut stream)?;
+    if version != PROTOCOL_VERSION {
+        let _ = stream.shutdown(Shutdown::Both);
+        return Err(format!(
+            "client uses protocol v{}, server v{}",
+            version, PROTOCOL_VERSION
+        ));
+    }
+
+    let (name, client_password) = match protocol::read_frame(&mut stream)? {
+        ClientMessage::Hello { name, password } => (name, password),
+        _ => return Err("expected a hello".to_string()),
+    };
+    let reject = |stream: &mut TcpStream, reason: &str| {
+        let _ = protocol::write_frame(
+            stream,
+            &ServerMessage::Rejected {
+                reason: reason.to_string(),
+            },
+        );
+        let _ = stream.shutdown(Shutdown::Both);
+        Err(reason.to_string())
+    };
+    if client_password != password {
+        return reject(&mut stream, "Incorrect password");
+    }
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return reject(
+            &mut stream,
+            &format!("Names must be 1 to {} characters", MAX_NAME_LENGTH),
+        );
+    }
+
+    stream
+        .set_read_timeout(None)
+        .map_err(|e| format!("Failed to configure connection: {}", e))?;
+    let id = next_id.fetch_add(1, Ordering::Relaxed);
+    let registered = stream
+        .try_clone()
+        .map_err(|e| format!("Failed to share connection: {}", e))?;
+    sender
+        .send(Incoming::Authenticated {
+            id,
+            name: name.to_string(),
+            stream: registered,
+        })
+        .map_err(|_| "server stopped".to_string())?;
+
+    let reason = loop {
+        if !running.load(Ordering::Relaxed) {
+            break "server stopped".to_string();
+        }
+        match protocol::read_frame::<_, ClientMessage>(&mut stream) {
+            Ok(ClientMessage::Goodbye) => break "left the game".to_string(),
+            Ok(ClientMessage::Hello { .. }) => break "sent a second hello".to_string(),
+            Ok(message) => {
+                if sender.send(Incoming::Message { id, message }).is_err() {
+                    break "server stopped".to_string();
+                }
+            }
+            Err(e) => break e,
+        }
+    };
+    let _ = stream.shutdown(Shutdown::Both);
+    let _ = sender.send(Incoming::Closed {
+        id,
+        reason: reason.clone(),
+    });
+    Err(reason)
+}
+
+/// Write queued messages until the game drops the sender
+fn write_loop(mut stream: TcpStream, queue: Receiver<ServerMessage>, queued: Arc<AtomicUsize>) {
+    for message in queue {
+        let last = matches!(
+            message,
+            ServerMessage::Rejected { .. } | ServerMessage::Kicked { .. }
+        );
+        if protocol::write_frame(&mut stream, &message).is_err() || last {
+            break;
+        }
+        queued.fetch_sub(1, Ordering::Relaxed);
+    }
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+fn udp_loop(socket: UdpSocket, running: Arc<AtomicBool>, sender: Sender<Incoming>) {
+    let mut buffer = [0u8; MAX_DATAGRAM_SIZE];
+    while running.load(Ordering::Relaxed) {
+        match socket.recv_from(&mut buffer) {
+            Ok((length, from)) => {
+                // Malformed packets are dropped quietly; UDP is best effort
+                if let Ok(datagram) = protocol::decode_datagram(&buffer[..length]) {
+                    if sender.send(Incoming::Datagram { from, datagram }).is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            // Windows reports ICMP port unreachable from earlier sends here
+            Err(e) if e.kind() == ErrorKind::ConnectionReset => {}
+            Err(e) => {
+                warn!("UDP receive failed: {}", e);
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
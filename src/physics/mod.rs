@@ -3,5 +3,5 @@ mod plugin;
 mod terrain_collider;
 
 pub use layers::CollisionLayer as PhysicsLayer;
-pub use plugin::PhysicsPlugin;
+pub use plugin::{PhysicsPlugin, PhysicsSimulationPlugin};
 pub use terrain_collider::*;
@@ -5,11 +5,23 @@ use bevy_tnua_avian3d::*;
 
 use super::terrain_collider::{generate_chunk_colliders, handle_chunk_modification};
 
+/// Rigid-body simulation and gravity; runs on the dedicated server too
+pub struct PhysicsSimulationPlugin;
+
+impl Plugin for PhysicsSimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(PhysicsPlugins::default());
+
+        app.insert_resource(Gravity(Vec3::new(0.0, -20.0, 0.0)));
+        app.insert_resource(PhysicsLengthUnit(1.0));
+    }
+}
+
 pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(PhysicsPlugins::default());
+        app.add_plugins(PhysicsSimulationPlugin);
 
         app.add_plugins(TnuaAvian3dPlugin::new(PhysicsSchedule));
         app.add_plugins(TnuaControllerPlugin::new(PhysicsSchedule));
@@ -17,9 +29,7 @@ impl Plugin for PhysicsPlugin {
         #[cfg(debug_assertions)]
         app.add_plugins(PhysicsDebugPlugin::default());
 
-        app.insert_resource(Gravity(Vec3::new(0.0, -20.0, 0.0)));
-        app.insert_resource(PhysicsLengthUnit(1.0));
-
+        // Terrain colliders are built from the chunks' render meshes
         app.add_systems(
             Update,
             (generate_chunk_colliders, handle_chunk_modification),
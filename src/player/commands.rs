@@ -0,0 +1,173 @@
+use avian3d::prelude::LinearVelocity;
+use bevy::prelude::*;
+
+use crate::camera::controller::PlayerCamera;
+use crate::chat::commands::ChatCommand;
+use crate::voxel::persistence::{ActiveWorld, GameRules};
+
+use super::{Player, PlayerVitals, SpawnPoint};
+
+/// Camera height above the player's origin
+const EYE_HEIGHT: f32 = 1.6;
+
+/// `/tp <x> <y> <z>`; `~` is relative to the current position
+pub struct TeleportCommand;
+
+impl ChatCommand for TeleportCommand {
+    fn name(&self) -> &str {
+        "tp"
+    }
+
+    fn usage(&self) -> &str {
+        "<x> <y> <z>"
+    }
+
+    fn description(&self) -> &str {
+        "Teleport to a position; ~ means relative, as in ~ ~10 ~"
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        if args.len() != 3 {
+            return Err("Usage: /tp <x> <y> <z>".to_string());
+        }
+
+        let current = world
+            .query_filtered::<&Transform, With<Player>>()
+            .iter(world)
+            .next()
+            .map(|transform| transform.translation)
+            .ok_or("There is no player to teleport")?;
+        let target = Vec3::new(
+            coordinate(args[0], current.x)?,
+            coordinate(args[1], current.y)?,
+            coordinate(args[2], current.z)?,
+        );
+
+        let mut players = world.query_filtered::<(
+            &mut Transform,
+            Option<&mut LinearVelocity>,
+            Option<&mut PlayerVitals>,
+        ), With<Player>>();
+        for (mut transform, velocity, vitals) in players.iter_mut(world) {
+            transform.translation = target;
+            if let Some(mut velocity) = velocity {
+                velocity.0 = Vec3::ZERO;
+            }
+            if let Some(mut vitals) = vitals {
+                vitals.teleported();
+            }
+        }
+        let mut cameras =
+            world.query_filtered::<&mut Transform, (With<PlayerCamera>, Without<Player>)>();
+        for mut transform in cameras.iter_mut(world) {
+            transform.translation = target + Vec3::Y * EYE_HEIGHT;
+        }
+
+        Ok(format!(
+            "Teleported to {:.1} {:.1} {:.1}",
+            target.x, target.y, target.z
+        ))
+    }
+}
+
+/// `/spawnpoint`: respawn where the player stands now
+pub struct SpawnPointCommand;
+
+impl ChatCommand for SpawnPointCommand {
+    fn name(&self) -> &str {
+        "spawnpoint"
+    }
+
+    fn usage(&self) -> &str {
+        ""
+    }
+
+    fn description(&self) -> &str {
+        "Set the respawn point to the current position"
+    }
+
+    fn run(&self, _args: &[&str], world: &mut World) -> Result<String, String> {
+        let position = world
+            .query_filtered::<&Transform, With<Player>>()
+            .iter(world)
+            .next()
+            .map(|transform| transform.translation)
+            .ok_or("There is no player")?;
+        world.insert_resource(SpawnPoint(position));
+        if let Some(mut active_world) = world.get_resource_mut::<ActiveWorld>() {
+            active_world.metadata.spawn_point = Some(position);
+        }
+        Ok(format!(
+            "Spawn point set to {:.1} {:.1} {:.1}",
+            position.x, position.y, position.z
+        ))
+    }
+}
+
+/// `/gamerule [rule] [on|off]`, saved with the world
+pub struct GameRuleCommand;
+
+impl ChatCommand for GameRuleCommand {
+    fn name(&self) -> &str {
+        "gamerule"
+    }
+
+    fn usage(&self) -> &str {
+        "[fall_damage|drowning] [on|off]"
+    }
+
+    fn description(&self) -> &str {
+        "Show or switch this world's damage rules"
+    }
+
+    fn run(&self, args: &[&str], world: &mut World) -> Result<String, String> {
+        let mut active_world = world
+            .get_resource_mut::<ActiveWorld>()
+            .ok_or("No world is loaded")?;
+        let rules = &mut active_world.metadata.game_rules;
+        let state = |enabled: bool| if enabled { "on" } else { "off" };
+
+        let (name, enabled) = match args {
+            [] => {
+                return Ok(GameRules::NAMES
+                    .iter()
+                    .map(|name| format!("{}: {}", name, state(rules.get(name).unwrap_or_default())))
+                    .collect::<Vec<_>>()
+                    .join(", "));
+            }
+            [name] => {
+                let enabled = rules
+                    .get(name)
+                    .ok_or_else(|| format!("Unknown rule '{}'", name))?;
+                return Ok(format!("{} is {}", name, state(enabled)));
+            }
+            [name, "on"] => (*name, true),
+            [name, "off"] => (*name, false),
+            _ => return Err(format!("Usage: /gamerule {}", self.usage())),
+        };
+
+        if !rules.set(name, enabled) {
+            return Err(format!("Unknown rule '{}'", name));
+        }
+        active_world
+            .slot
+            .store()
+            .write_meta(&active_world.metadata)?;
+        Ok(format!("{} is now {}", name, state(enabled)))
+    }
+}
+
+/// An absolute coordinate, or `~`/`~offset` relative to `current`
+fn coordinate(arg: &str, current: f32) -> Result<f32, String> {
+    let (base, number) = match arg.strip_prefix('~') {
+        Some("") => return Ok(current),
+        Some(offset) => (current, offset),
+        None => (0.0, arg),
+    };
+    number
+        .parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .map(|value| base + value)
+        .ok_or_else(|| format!("'{}' is not a valid coordinate", arg))
+}
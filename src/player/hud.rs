@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+
+use crate::entity::Health;
+use crate::interaction::GameMode;
+
+use super::vitals::{DamageSource, PlayerDamaged, PlayerVitals, MAX_BREATH_SECONDS};
+use super::Player;
+
+const BAR_WIDTH: f32 = 240.0;
+const BAR_HEIGHT: f32 = 10.0;
+const HEALTH_COLOR: Color = Color::srgb(0.8, 0.15, 0.15);
+const BREATH_COLOR: Color = Color::srgb(0.3, 0.6, 0.95);
+
+/// Red tint shown right after taking damage
+const FLASH_ALPHA: f32 = 0.35;
+/// Seconds the damage tint takes to fade out
+const FLASH_SECONDS: f32 = 0.4;
+
+/// Component to mark the vitals bars above the hotbar
+#[derive(Component)]
+pub struct VitalsHudRoot;
+
+#[derive(Component)]
+pub struct HealthBarFill;
+
+#[derive(Component)]
+pub struct BreathBar;
+
+#[derive(Component)]
+pub struct BreathBarFill;
+
+/// Full-screen tint that flashes when the player is hurt
+#[derive(Component)]
+pub struct DamageFlash;
+
+#[derive(Component)]
+pub struct RespawnButton;
+
+fn bar(parent: &mut ChildSpawnerCommands, color: Color, fill: impl Bundle, extra: impl Bundle) {
+    parent
+        .spawn((
+            Node {
+                width: Val::Px(BAR_WIDTH),
+                height: Val::Px(BAR_HEIGHT),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.05, 0.05, 0.07, 0.75)),
+            extra,
+        ))
+        .with_children(|bar| {
+            bar.spawn((
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(color),
+                fill,
+            ));
+        });
+}
+
+pub fn setup_vitals_hud(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        BackgroundColor(Color::NONE),
+        DamageFlash,
+    ));
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(88.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            VitalsHudRoot,
+        ))
+        .with_children(|root| {
+            bar(root, BREATH_COLOR, BreathBarFill, (BreathBar, Visibility::Hidden));
+            bar(root, HEALTH_COLOR, HealthBarFill, ());
+        });
+}
+
+/// Keep the bars in step with the player; hidden in creative mode
+#[allow(clippy::type_complexity)]
+pub fn update_vitals_hud(
+    game_mode: Res<GameMode>,
+    player_query: Query<(&Health, &PlayerVitals), With<Player>>,
+    mut root_query: Query<&mut Visibility, (With<VitalsHudRoot>, Without<BreathBar>)>,
+    mut breath_bar: Query<&mut Visibility, (With<BreathBar>, Without<VitalsHudRoot>)>,
+    mut health_fill: Query<&mut Node, (With<HealthBarFill>, Without<BreathBarFill>)>,
+    mut breath_fill: Query<&mut Node, (With<BreathBarFill>, Without<HealthBarFill>)>,
+) {
+    let visibility = match *game_mode {
+        GameMode::Survival => Visibility::Inherited,
+        GameMode::Creative => Visibility::Hidden,
+    };
+    for mut root_visibility in root_query.iter_mut() {
+        root_visibility.set_if_neq(visibility);
+    }
+
+    let Ok((health, vitals)) = player_query.single() else {
+        return;
+    };
+    let health_width = Val::Percent(100.0 * health.current / health.max);
+    for mut node in health_fill.iter_mut() {
+        if node.width != health_width {
+            node.width = health_width;
+        }
+    }
+    let breath_width = Val::Percent(100.0 * vitals.breath / MAX_BREATH_SECONDS);
+    for mut node in breath_fill.iter_mut() {
+        if node.width != breath_width {
+            node.width = breath_width;
+        }
+    }
+    let breath_visibility = if vitals.breath < MAX_BREATH_SECONDS {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for mut visibility in breath_bar.iter_mut() {
+        visibility.set_if_neq(breath_visibility);
+    }
+}
+
+/// Tint the screen red on damage and fade it back out
+pub fn update_damage_flash(
+    time: Res<Time>,
+    mut damaged: MessageReader<PlayerDamaged>,
+    mut flash_query: Query<&mut BackgroundColor, With<DamageFlash>>,
+) {
+    let hurt = damaged.read().count() > 0;
+    for mut background in flash_query.iter_mut() {
+        let alpha = if hurt {
+            FLASH_ALPHA
+        } else {
+            (background.0.alpha() - FLASH_ALPHA * time.delta_secs() / FLASH_SECONDS).max(0.0)
+        };
+        if alpha != background.0.alpha() {
+            background.0 = Color::srgba(0.8, 0.0, 0.0, alpha);
+        }
+    }
+}
+
+pub fn spawn_death_screen(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    cause: DamageSource,
+) -> Entity {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.3, 0.0, 0.0, 0.6)),
+            GlobalZIndex(10),
+        ))
+        .with_children(|root| {
+            root.spawn((
+                Text::new("You died"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 48.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            root.spawn((
+                Text::new(cause.death_message()),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::srgba(0.9, 0.9, 0.9, 1.0)),
+            ));
+            root.spawn((
+                Button,
+                Node {
+                    padding: UiRect::axes(Val::Px(24.0), Val::Px(10.0)),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.2, 0.2, 0.22, 0.9)),
+                RespawnButton,
+            ))
+            .with_children(|button| {
+                button.spawn((
+                    Text::new("Respawn"),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ));
+            });
+        })
+        .id()
+}
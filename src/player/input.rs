@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use bevy_tnua::prelude::*;
 
-use super::{Player, PlayerConfig};
+use super::{Player, PlayerConfig, PlayerDeath};
 
 /// Player input state.
 #[derive(Resource, Default)]
@@ -15,7 +15,13 @@ pub struct PlayerInput {
 pub fn read_player_input(
     keyboard: Res<ButtonInput<KeyCode>>,
     mut input: ResMut<PlayerInput>,
+    death: Res<PlayerDeath>,
 ) {
+    if death.dead {
+        *input = PlayerInput::default();
+        return;
+    }
+
     let mut movement = Vec2::ZERO;
     if keyboard.pressed(KeyCode::KeyW) {
         movement.y += 1.0;
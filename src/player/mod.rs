@@ -1,9 +1,15 @@
+mod commands;
 mod controller;
+mod hud;
 mod input;
 mod plugin;
 mod spawn;
+mod vitals;
 
+pub use commands::*;
 pub use controller::*;
+pub use hud::*;
 pub use input::*;
 pub use plugin::*;
 pub use spawn::*;
+pub use vitals::*;
@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 use bevy_tnua::prelude::*;
 
+use crate::chat::commands::ChatCommandAppExt;
+
 use super::*;
 
 pub struct PlayerPlugin;
@@ -9,8 +11,14 @@ impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<PlayerConfig>();
         app.init_resource::<PlayerInput>();
+        app.init_resource::<SpawnPoint>();
+        app.init_resource::<PlayerDeath>();
+        app.add_message::<PlayerDamaged>();
+        app.add_chat_command(TeleportCommand);
+        app.add_chat_command(SpawnPointCommand);
+        app.add_chat_command(GameRuleCommand);
 
-        app.add_systems(Startup, spawn_player);
+        app.add_systems(Startup, (spawn_player, setup_vitals_hud));
 
         app.add_systems(
             Update,
@@ -20,5 +28,19 @@ impl Plugin for PlayerPlugin {
             )
                 .chain(),
         );
+
+        app.add_systems(
+            Update,
+            (
+                load_spawn_point,
+                attach_player_vitals,
+                player_damage_system,
+                player_death_system,
+                respawn_player,
+                update_vitals_hud,
+                update_damage_flash,
+            )
+                .chain(),
+        );
     }
 }
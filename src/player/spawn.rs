@@ -1,9 +1,8 @@
 use bevy::prelude::*;
 
-use super::{PlayerBundle, PlayerConfig};
+use super::{PlayerBundle, PlayerConfig, SpawnPoint};
 
 /// Spawn the player at game start.
-pub fn spawn_player(mut commands: Commands, config: Res<PlayerConfig>) {
-    let spawn_position = Vec3::new(0.0, 50.0, 0.0);
-    commands.spawn(PlayerBundle::new(spawn_position, config.clone()));
+pub fn spawn_player(mut commands: Commands, config: Res<PlayerConfig>, spawn: Res<SpawnPoint>) {
+    commands.spawn(PlayerBundle::new(spawn.0, config.clone()));
 }
@@ -0,0 +1,315 @@
+use avian3d::prelude::LinearVelocity;
+use bevy::prelude::*;
+use bevy_tnua::prelude::*;
+
+use crate::camera::controller::{CameraMode, PlayerCamera};
+use crate::entity::Health;
+use crate::interaction::GameMode;
+use crate::voxel::persistence::{ActiveWorld, GameRules};
+use crate::voxel::types::{Voxel, VoxelType};
+use crate::voxel::world::VoxelWorld;
+
+use super::hud::{spawn_death_screen, RespawnButton};
+use super::{Player, PlayerConfig};
+
+pub const PLAYER_MAX_HEALTH: f32 = 20.0;
+
+/// Landing slower than this (m/s) does not hurt; about a 3.5 block drop at
+/// the physics gravity of 20 m/s²
+pub const SAFE_FALL_SPEED: f32 = 12.0;
+
+/// Health lost per m/s of landing speed above `SAFE_FALL_SPEED`
+pub const FALL_DAMAGE_PER_SPEED: f32 = 1.0;
+
+/// Seconds the player can stay under water before drowning
+pub const MAX_BREATH_SECONDS: f32 = 10.0;
+
+/// Breath regained per second above water
+const BREATH_RECOVERY_RATE: f32 = 5.0;
+
+/// Health lost every `DROWNING_INTERVAL` seconds once out of breath
+pub const DROWNING_DAMAGE: f32 = 2.0;
+pub const DROWNING_INTERVAL: f32 = 1.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DamageSource {
+    Fall,
+    Drowning,
+}
+
+impl DamageSource {
+    pub fn death_message(self) -> &'static str {
+        match self {
+            DamageSource::Fall => "You hit the ground too hard",
+            DamageSource::Drowning => "You drowned",
+        }
+    }
+}
+
+/// Sent whenever the player loses health
+#[derive(Message, Clone, Copy, Debug)]
+pub struct PlayerDamaged {
+    pub amount: f32,
+    pub source: DamageSource,
+}
+
+/// Where the player comes back after dying, set with `/spawnpoint` and
+/// saved with the world
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct SpawnPoint(pub Vec3);
+
+impl Default for SpawnPoint {
+    fn default() -> Self {
+        Self(Vec3::new(0.0, 50.0, 0.0))
+    }
+}
+
+/// Whether the player is dead and waiting on the death screen
+#[derive(Resource, Default)]
+pub struct PlayerDeath {
+    pub dead: bool,
+    pub cause: Option<DamageSource>,
+    pub root: Option<Entity>,
+}
+
+/// Run condition for gameplay input that a dead player can't do
+pub fn player_alive(death: Option<Res<PlayerDeath>>) -> bool {
+    death.is_none_or(|death| !death.dead)
+}
+
+/// Breath and fall tracking for the player
+#[derive(Component, Debug)]
+pub struct PlayerVitals {
+    /// Seconds of air left
+    pub breath: f32,
+    /// Fastest downward speed since the player last stood on something
+    fall_speed: f32,
+    /// Falls only count once the player has stood on something since the
+    /// last spawn or teleport
+    grounded_since_spawn: bool,
+    drowning_timer: f32,
+}
+
+impl Default for PlayerVitals {
+    fn default() -> Self {
+        Self {
+            breath: MAX_BREATH_SECONDS,
+            fall_speed: 0.0,
+            grounded_since_spawn: false,
+            drowning_timer: 0.0,
+        }
+    }
+}
+
+impl PlayerVitals {
+    /// Feed the ground state and vertical velocity every frame. Returns the
+    /// landing speed on the frame the player touches down after a fall.
+    pub fn track_fall(&mut self, airborne: bool, vertical_velocity: f32) -> Option<f32> {
+        if !self.grounded_since_spawn {
+            self.grounded_since_spawn = !airborne;
+            return None;
+        }
+        if airborne {
+            self.fall_speed = self.fall_speed.max(-vertical_velocity);
+            return None;
+        }
+        let speed = std::mem::take(&mut self.fall_speed);
+        (speed > 0.0).then_some(speed)
+    }
+
+    /// Forget the current fall, e.g. after splashing into water
+    pub fn reset_fall(&mut self) {
+        self.fall_speed = 0.0;
+    }
+
+    /// The player was placed somewhere new; the drop from there to the
+    /// ground doesn't hurt
+    pub fn teleported(&mut self) {
+        self.fall_speed = 0.0;
+        self.grounded_since_spawn = false;
+    }
+
+    /// Use up breath under water or recover it above; returns the drowning
+    /// damage taken this frame
+    pub fn breathe(&mut self, submerged: bool, dt: f32) -> f32 {
+        if !submerged {
+            self.breath = (self.breath + dt * BREATH_RECOVERY_RATE).min(MAX_BREATH_SECONDS);
+            self.drowning_timer = 0.0;
+            return 0.0;
+        }
+
+        let out_of_breath = (dt - self.breath).max(0.0);
+        self.breath = (self.breath - dt).max(0.0);
+        self.drowning_timer += out_of_breath;
+
+        let mut damage = 0.0;
+        while self.drowning_timer >= DROWNING_INTERVAL {
+            self.drowning_timer -= DROWNING_INTERVAL;
+            damage += DROWNING_DAMAGE;
+        }
+        damage
+    }
+}
+
+/// Whole points of health lost landing at `landing_speed`
+pub fn fall_damage(landing_speed: f32) -> f32 {
+    ((landing_speed - SAFE_FALL_SPEED) * FALL_DAMAGE_PER_SPEED)
+        .max(0.0)
+        .floor()
+}
+
+/// Where a player placed at `point` stands: on top of the first solid voxel
+/// at or below it, with the controller floating `float_height` above.
+/// `None` when there is no loaded ground under the point.
+pub fn ground_spawn_position(world: &VoxelWorld, point: Vec3, float_height: f32) -> Option<Vec3> {
+    let column = point.floor().as_ivec3();
+    (0..=column.y)
+        .rev()
+        .find(|y| {
+            world
+                .get_voxel(IVec3::new(column.x, *y, column.z))
+                .is_some_and(|voxel| voxel.is_solid())
+        })
+        .map(|y| Vec3::new(point.x, (y + 1) as f32 + float_height, point.z))
+}
+
+/// Give a freshly spawned player its health and vitals
+pub fn attach_player_vitals(
+    mut commands: Commands,
+    players: Query<Entity, (With<Player>, Without<PlayerVitals>)>,
+) {
+    for entity in players.iter() {
+        commands
+            .entity(entity)
+            .insert((Health::new(PLAYER_MAX_HEALTH), PlayerVitals::default()));
+    }
+}
+
+/// Fall damage from Tnua's ground contact and avian's velocity, drowning
+/// while the camera is in water. Only survival players in walk mode get hurt.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn player_damage_system(
+    time: Res<Time>,
+    world: Res<VoxelWorld>,
+    active_world: Option<Res<ActiveWorld>>,
+    game_mode: Res<GameMode>,
+    death: Res<PlayerDeath>,
+    camera_query: Query<(&Transform, &PlayerCamera), Without<Player>>,
+    mut player_query: Query<
+        (&Transform, &LinearVelocity, &TnuaController, &mut PlayerVitals, &mut Health),
+        With<Player>,
+    >,
+    mut damaged: MessageWriter<PlayerDamaged>,
+) {
+    if death.dead {
+        return;
+    }
+    let Ok((camera_transform, camera)) = camera_query.single() else {
+        return;
+    };
+    let rules: GameRules = active_world
+        .map(|active_world| active_world.metadata.game_rules)
+        .unwrap_or_default();
+    let vulnerable = *game_mode == GameMode::Survival && camera.mode == CameraMode::Walk;
+    let dt = time.delta_secs();
+
+    let is_water = |position: Vec3| world.get_voxel(position.floor().as_ivec3()) == Some(VoxelType::Water);
+
+    for (transform, velocity, controller, mut vitals, mut health) in player_query.iter_mut() {
+        if !vulnerable {
+            vitals.reset_fall();
+            vitals.breathe(false, dt);
+            continue;
+        }
+
+        let mut hurt = |amount: f32, source: DamageSource| {
+            if amount > 0.0 {
+                health.damage(amount);
+                damaged.write(PlayerDamaged { amount, source });
+            }
+        };
+
+        // Water breaks the fall
+        let landing = if is_water(transform.translation) {
+            vitals.reset_fall();
+            None
+        } else {
+            vitals.track_fall(controller.is_airborne().unwrap_or(false), velocity.y)
+        };
+        if rules.fall_damage {
+            hurt(landing.map_or(0.0, fall_damage), DamageSource::Fall);
+        }
+
+        let submerged = rules.drowning && is_water(camera_transform.translation);
+        let drowning = vitals.breathe(submerged, dt);
+        hurt(drowning, DamageSource::Drowning);
+    }
+}
+
+/// Show the death screen once the player's health runs out
+pub fn player_death_system(
+    mut damaged: MessageReader<PlayerDamaged>,
+    player_query: Query<&Health, With<Player>>,
+    mut death: ResMut<PlayerDeath>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(last) = damaged.read().last().copied() else {
+        return;
+    };
+    if death.dead || !player_query.iter().any(|health| health.is_dead()) {
+        return;
+    }
+
+    death.dead = true;
+    death.cause = Some(last.source);
+    death.root = Some(spawn_death_screen(&mut commands, &asset_server, last.source));
+    info!("Player died: {}", last.source.death_message());
+}
+
+/// Use the spawn point saved with the active world, or the default one in a
+/// world without
+pub fn load_spawn_point(active_world: Option<Res<ActiveWorld>>, mut spawn: ResMut<SpawnPoint>) {
+    let Some(active_world) = active_world.filter(|active_world| active_world.is_changed()) else {
+        return;
+    };
+    let point = active_world
+        .metadata
+        .spawn_point
+        .map_or_else(SpawnPoint::default, SpawnPoint);
+    spawn.set_if_neq(point);
+}
+
+/// Bring the player back at the spawn point with full health, standing on
+/// the ground below it
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn respawn_player(
+    interactions: Query<&Interaction, (Changed<Interaction>, With<RespawnButton>)>,
+    mut death: ResMut<PlayerDeath>,
+    spawn: Res<SpawnPoint>,
+    world: Res<VoxelWorld>,
+    config: Res<PlayerConfig>,
+    mut player_query: Query<
+        (&mut Transform, &mut LinearVelocity, &mut Health, &mut PlayerVitals),
+        With<Player>,
+    >,
+    mut commands: Commands,
+) {
+    if !death.dead || !interactions.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        return;
+    }
+
+    death.dead = false;
+    death.cause = None;
+    if let Some(root) = death.root.take() {
+        commands.entity(root).despawn();
+    }
+
+    let position = ground_spawn_position(&world, spawn.0, config.float_height).unwrap_or(spawn.0);
+    for (mut transform, mut velocity, mut health, mut vitals) in player_query.iter_mut() {
+        transform.translation = position;
+        velocity.0 = Vec3::ZERO;
+        health.current = health.max;
+        *vitals = PlayerVitals::default();
+    }
+}
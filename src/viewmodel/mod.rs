@@ -1,3 +1,4 @@
+use crate::interaction::mining::{MiningTick, MINING_TICK_SECONDS};
 use bevy::prelude::*;
 
 /// Component marking the pickaxe viewmodel
@@ -81,21 +82,23 @@ pub fn spawn_pickaxe(
     }
 }
 
-/// System to trigger pickaxe swing when breaking blocks
+/// System to swing the pickaxe on attacks and on every mining tick
 pub fn trigger_swing_system(
     mouse: Res<ButtonInput<MouseButton>>,
+    mut ticks: MessageReader<MiningTick>,
     mut pickaxe_query: Query<&mut PickaxeViewModel>,
     mut state: ResMut<PickaxeState>,
 ) {
-    // Start swing on left click
-    if mouse.just_pressed(MouseButton::Left) {
-        for mut pickaxe in pickaxe_query.iter_mut() {
-            if !pickaxe.is_swinging {
-                pickaxe.is_swinging = true;
-                pickaxe.swing_progress = 0.0;
-                state.swing_timer = 0.0;
-                state.swing_duration = 0.25; // Quarter second swing
-            }
+    // Mining ticks restart the swing so it stays in step with the digging
+    let mining_tick = ticks.read().count() > 0;
+    let attack = mouse.just_pressed(MouseButton::Left);
+
+    for mut pickaxe in pickaxe_query.iter_mut() {
+        if mining_tick || (attack && !pickaxe.is_swinging) {
+            pickaxe.is_swinging = true;
+            pickaxe.swing_progress = 0.0;
+            state.swing_timer = 0.0;
+            state.swing_duration = MINING_TICK_SECONDS; // One swing per tick
         }
     }
 }
@@ -1,27 +1,93 @@
 use crate::constants::{CHUNK_SIZE, CHUNK_VOLUME};
+use crate::voxel::fluid::FluidLevels;
+use crate::voxel::light::{ChunkLight, LightChannel};
+use crate::voxel::storage::ChunkStorage;
 use crate::voxel::types::VoxelType;
+use crate::voxel::visibility::FaceConnectivity;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
 
-/// Serializable chunk data (voxels only)
-#[derive(Serialize, Deserialize)]
+/// Source of chunk revisions, shared so no two chunks ever get the same one
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(1);
+
+fn next_revision() -> u64 {
+    NEXT_REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Serializable chunk data (voxels and flowing liquid levels)
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ChunkData {
+    pub position: IVec3,
+    pub storage: ChunkStorage,
+    pub fluid: FluidLevels,
+}
+
+impl ChunkData {
+    /// Check deserialized data before building a chunk from it
+    pub fn validate(&self) -> Result<(), String> {
+        self.storage.validate()?;
+        self.fluid.validate()
+    }
+}
+
+/// Paletted layout without liquid levels, used by version 2 region files
+#[derive(Serialize, Deserialize)]
+pub struct PalettedChunkData {
+    pub position: IVec3,
+    pub storage: ChunkStorage,
+}
+
+impl From<PalettedChunkData> for ChunkData {
+    fn from(data: PalettedChunkData) -> Self {
+        Self {
+            position: data.position,
+            storage: data.storage,
+            fluid: FluidLevels::default(),
+        }
+    }
+}
+
+/// Flat voxel array layout used by `world_data.bin` and version 1 region files
+#[derive(Serialize, Deserialize)]
+pub struct FlatChunkData {
     pub voxels: Vec<VoxelType>,
     pub position: IVec3,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+impl From<FlatChunkData> for ChunkData {
+    fn from(data: FlatChunkData) -> Self {
+        Self {
+            position: data.position,
+            storage: ChunkStorage::from_voxels(&data.voxels),
+            fluid: FluidLevels::default(),
+        }
+    }
+}
+
+/// How coarsely a chunk or LOD tile is meshed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum LodLevel {
-    High,
-    Low,
+    /// Meshed from voxels downsampled 2^n times along each axis; level 0 is
+    /// the chunk's own data
+    Level(u8),
+    /// Not drawn
     Culled,
 }
 
 impl LodLevel {
+    /// Full resolution, used by every loaded chunk that is drawn
+    pub const FULL: LodLevel = LodLevel::Level(0);
+
+    /// Whether a loaded chunk at this level is drawn from its own mesh.
+    /// Coarser levels are drawn by the LOD tile covering the chunk instead.
+    pub fn draws_chunk_mesh(self) -> bool {
+        self == LodLevel::FULL
+    }
+
     pub fn detail_value(&self) -> u8 {
         match self {
-            LodLevel::High => 2,
-            LodLevel::Low => 1,
+            LodLevel::Level(level) => u8::MAX - level,
             LodLevel::Culled => 0,
         }
     }
@@ -33,42 +99,148 @@ impl LodLevel {
     pub fn is_higher_detail_than(self, other: LodLevel) -> bool {
         self.detail_value() > other.detail_value()
     }
+
+    /// How many levels coarser this is than `other`; 0 unless both are drawn
+    pub fn levels_coarser_than(self, other: LodLevel) -> u8 {
+        match (self, other) {
+            (LodLevel::Level(level), LodLevel::Level(other)) => level.saturating_sub(other),
+            _ => 0,
+        }
+    }
 }
 
 pub struct Chunk {
-    voxels: [VoxelType; CHUNK_VOLUME],
+    voxels: ChunkStorage,
+    /// Levels of flowing liquid voxels; liquid without one is a source
+    fluid: FluidLevels,
+    /// Sky and block light, derived from the voxels and never saved
+    light: ChunkLight,
+    /// Light has been propagated into the chunk since it loaded
+    lit: bool,
     dirty: bool,
+    /// Voxels changed since the chunk was last written to disk
+    modified: bool,
     mesh_entity: Option<Entity>,
     water_mesh_entity: Option<Entity>,
     position: IVec3, // Chunk coords (not world)
     lod_level: LodLevel,
+    /// Which faces see each other through the chunk, `None` until computed
+    connectivity: Option<FaceConnectivity>,
+    /// Changes with every voxel edit so derived data (navigation) can tell
+    /// it is stale; unique across chunks, never 0
+    revision: u64,
 }
 
 impl Chunk {
     pub fn new(position: IVec3) -> Self {
         Self {
-            voxels: [VoxelType::Air; CHUNK_VOLUME],
+            voxels: ChunkStorage::Uniform(VoxelType::Air),
+            fluid: FluidLevels::default(),
+            light: ChunkLight::default(),
+            lit: false,
             dirty: true,
+            modified: true,
             mesh_entity: None,
             water_mesh_entity: None,
             position,
-            lod_level: LodLevel::High,
+            lod_level: LodLevel::FULL,
+            connectivity: None,
+            revision: next_revision(),
         }
     }
 
     pub fn get(&self, local: UVec3) -> VoxelType {
         let index = Self::index(local.x as usize, local.y as usize, local.z as usize);
-        self.voxels[index]
+        self.voxels.get(index)
     }
 
     pub fn set(&mut self, local: UVec3, voxel: VoxelType) {
         let index = Self::index(local.x as usize, local.y as usize, local.z as usize);
-        if self.voxels[index] != voxel {
-            self.voxels[index] = voxel;
+        if self.voxels.set(index, voxel) {
+            // A new voxel starts out as a source
+            self.fluid.set(index, None);
             self.dirty = true;
+            self.modified = true;
+            self.connectivity = None;
+            self.revision = next_revision();
         }
     }
 
+    /// Level of a flowing liquid voxel, `None` for sources and other voxels
+    pub fn fluid_level(&self, local: UVec3) -> Option<u8> {
+        let index = Self::index(local.x as usize, local.y as usize, local.z as usize);
+        self.fluid.get(index)
+    }
+
+    /// Returns whether the level changed
+    pub fn set_fluid_level(&mut self, local: UVec3, level: Option<u8>) -> bool {
+        let index = Self::index(local.x as usize, local.y as usize, local.z as usize);
+        let changed = self.fluid.set(index, level);
+        if changed {
+            self.dirty = true;
+            self.modified = true;
+        }
+        changed
+    }
+
+    pub fn light(&self, local: UVec3, channel: LightChannel) -> u8 {
+        let index = Self::index(local.x as usize, local.y as usize, local.z as usize);
+        self.light.get(index, channel)
+    }
+
+    /// Set a light level without dirtying the chunk; the lighting code
+    /// marks every chunk whose mesh the change reaches
+    pub fn set_light(&mut self, local: UVec3, channel: LightChannel, level: u8) -> bool {
+        let index = Self::index(local.x as usize, local.y as usize, local.z as usize);
+        self.light.set(index, channel, level)
+    }
+
+    pub fn is_lit(&self) -> bool {
+        self.lit
+    }
+
+    pub fn mark_lit(&mut self) {
+        self.lit = true;
+    }
+
+    /// The single voxel type filling the chunk, if it is uniform
+    pub fn uniform_voxel(&self) -> Option<VoxelType> {
+        self.voxels.uniform()
+    }
+
+    /// Approximate heap memory used by the voxel storage
+    pub fn voxel_memory_bytes(&self) -> usize {
+        self.voxels.memory_bytes()
+    }
+
+    /// Voxel-only copy (no mesh entities, clean flags) for background work
+    pub fn snapshot(&self) -> Chunk {
+        Self {
+            voxels: self.voxels.clone(),
+            fluid: self.fluid.clone(),
+            light: self.light.clone(),
+            lit: self.lit,
+            dirty: false,
+            modified: false,
+            mesh_entity: None,
+            water_mesh_entity: None,
+            position: self.position,
+            lod_level: self.lod_level,
+            connectivity: self.connectivity,
+            revision: self.revision,
+        }
+    }
+
+    /// Repack the voxel storage after bulk edits (e.g. generation)
+    pub fn compact(&mut self) {
+        self.voxels.compact();
+        self.fluid.compact();
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
     pub fn is_dirty(&self) -> bool {
         self.dirty
     }
@@ -81,6 +253,15 @@ impl Chunk {
         self.dirty = false;
     }
 
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// Call after the chunk has been persisted (or when it can be regenerated)
+    pub fn clear_modified(&mut self) {
+        self.modified = false;
+    }
+
     pub fn set_mesh_entity(&mut self, entity: Entity) {
         self.mesh_entity = Some(entity);
     }
@@ -122,6 +303,17 @@ impl Chunk {
         false
     }
 
+    pub fn connectivity(&self) -> Option<FaceConnectivity> {
+        self.connectivity
+    }
+
+    /// Recompute which faces see each other, kept until a voxel changes
+    pub fn update_connectivity(&mut self) -> FaceConnectivity {
+        let connectivity = FaceConnectivity::compute(self);
+        self.connectivity = Some(connectivity);
+        connectivity
+    }
+
     // For meshing - index conversion
     fn index(x: usize, y: usize, z: usize) -> usize {
         x + (y * CHUNK_SIZE) + (z * CHUNK_SIZE * CHUNK_SIZE)
@@ -137,27 +329,40 @@ impl Chunk {
 
     /// Convert chunk to serializable data
     pub fn to_data(&self) -> ChunkData {
+        let mut storage = self.voxels.clone();
+        storage.compact();
+        let mut fluid = self.fluid.clone();
+        fluid.compact();
         ChunkData {
-            voxels: self.voxels.to_vec(),
+            position: self.position,
+            storage,
+            fluid,
+        }
+    }
+
+    /// Flat voxel array in the legacy save layout
+    pub fn to_flat_data(&self) -> FlatChunkData {
+        FlatChunkData {
+            voxels: (0..CHUNK_VOLUME).map(|i| self.voxels.get(i)).collect(),
             position: self.position,
         }
     }
 
     /// Create chunk from serializable data
     pub fn from_data(data: ChunkData) -> Self {
-        let mut voxels = [VoxelType::Air; CHUNK_VOLUME];
-        for (i, v) in data.voxels.into_iter().enumerate() {
-            if i < CHUNK_VOLUME {
-                voxels[i] = v;
-            }
-        }
         Self {
-            voxels,
+            voxels: data.storage,
+            fluid: data.fluid,
+            light: ChunkLight::default(),
+            lit: false,
             dirty: true, // Mark dirty so mesh gets generated
+            modified: false,
             mesh_entity: None,
             water_mesh_entity: None,
             position: data.position,
-            lod_level: LodLevel::High,
+            lod_level: LodLevel::FULL,
+            connectivity: None,
+            revision: next_revision(),
         }
     }
 }
@@ -0,0 +1,57 @@
+use crate::chat::commands::ChatCommand;
+use crate::network::guest::GuestSession;
+use crate::voxel::persistence::ActiveWorld;
+use crate::voxel::world::VoxelWorld;
+use bevy::prelude::*;
+
+/// `/seed`
+pub struct SeedCommand;
+
+impl ChatCommand for SeedCommand {
+    fn name(&self) -> &str {
+        "seed"
+    }
+
+    fn description(&self) -> &str {
+        "Show the world seed"
+    }
+
+    fn run(&self, _args: &[&str], world: &mut World) -> Result<String, String> {
+        // While connected the world is the host's, not the local save
+        if let Some(guest) = world.get_resource::<GuestSession>() {
+            return Ok(format!("Seed: {}", guest.welcome().seed));
+        }
+        let active_world = world
+            .get_resource::<ActiveWorld>()
+            .ok_or("No world is loaded")?;
+        Ok(format!("Seed: {}", active_world.metadata.seed))
+    }
+}
+
+/// `/save`
+pub struct SaveCommand;
+
+impl ChatCommand for SaveCommand {
+    fn name(&self) -> &str {
+        "save"
+    }
+
+    fn description(&self) -> &str {
+        "Save the world now"
+    }
+
+    fn run(&self, _args: &[&str], world: &mut World) -> Result<String, String> {
+        if world.contains_resource::<GuestSession>() {
+            return Err("Only the host can save this world".to_string());
+        }
+        if !world.contains_resource::<ActiveWorld>() || !world.contains_resource::<VoxelWorld>() {
+            return Err("No world is loaded".to_string());
+        }
+
+        world.resource_scope(|world, mut active_world: Mut<ActiveWorld>| {
+            let mut voxel_world = world.resource_mut::<VoxelWorld>();
+            active_world.save(&mut voxel_world)?;
+            Ok(format!("Saved '{}'", active_world.metadata.name))
+        })
+    }
+}
@@ -0,0 +1,402 @@
+//! Flowing liquids.
+//!
+//! Liquid voxels have a level from 1 to [`MAX_FLUID_LEVEL`]. Liquid placed by
+//! players or world generation is a source: always full and never drains.
+//! Liquid falls into open voxels below it, spreads sideways one level lower
+//! per voxel while it rests on something, dries up once nothing feeds it and
+//! settles into a new source between two sources. Only voxels near a recent
+//! change are simulated, so still water costs nothing.
+
+use crate::constants::{CHUNK_SIZE_I32, CHUNK_VOLUME};
+use crate::interaction::DirtyChunks;
+use crate::network::guest::GuestSession;
+use crate::voxel::persistence::{ActiveWorld, SaveSlot};
+use crate::voxel::types::{Voxel, VoxelType};
+use crate::voxel::world::{VoxelChanged, VoxelWorld};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Level of a full voxel (sources and falling liquid)
+pub const MAX_FLUID_LEVEL: u8 = 8;
+
+const HORIZONTAL: [IVec3; 4] = [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z];
+
+const NEIGHBORS: [IVec3; 6] = [
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Y,
+    IVec3::NEG_Y,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
+
+/// Flowing levels of a chunk's liquid voxels, by voxel index. Liquid voxels
+/// without a level are sources, so chunks without flowing liquid store nothing.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FluidLevels {
+    /// Empty, or one entry per voxel where 0 means "not flowing"
+    levels: Vec<u8>,
+}
+
+impl FluidLevels {
+    /// Flowing level at `index`, or `None` for sources and other voxels
+    pub fn get(&self, index: usize) -> Option<u8> {
+        self.levels.get(index).copied().filter(|level| *level > 0)
+    }
+
+    /// Set a flowing level, or clear it with `None`. Returns true if it changed.
+    pub fn set(&mut self, index: usize, level: Option<u8>) -> bool {
+        let value = level.unwrap_or(0);
+        if self.levels.is_empty() {
+            if value == 0 {
+                return false;
+            }
+            self.levels = vec![0; CHUNK_VOLUME];
+        }
+        if self.levels[index] == value {
+            return false;
+        }
+        self.levels[index] = value;
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels.iter().all(|level| *level == 0)
+    }
+
+    /// Drop the level array once nothing flows
+    pub fn compact(&mut self) {
+        if !self.levels.is_empty() && self.is_empty() {
+            self.levels = Vec::new();
+        }
+    }
+
+    /// Check deserialized levels before indexing into them
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.levels.is_empty() && self.levels.len() != CHUNK_VOLUME {
+            return Err(format!(
+                "expected {} fluid levels, found {}",
+                CHUNK_VOLUME,
+                self.levels.len()
+            ));
+        }
+        match self.levels.iter().find(|level| **level > MAX_FLUID_LEVEL) {
+            Some(level) => Err(format!("fluid level {} out of range", level)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct FluidSettings {
+    /// Seconds between simulation steps; liquid moves one voxel per step
+    pub step_interval: f32,
+    /// Voxels updated per step; the rest wait for the next one
+    pub max_updates_per_step: usize,
+}
+
+impl Default for FluidSettings {
+    fn default() -> Self {
+        Self {
+            step_interval: 0.2,
+            max_updates_per_step: 4096,
+        }
+    }
+}
+
+/// What a liquid voxel should be, given its neighbours
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FluidState {
+    Source,
+    Flowing(u8),
+    Dry,
+}
+
+/// Liquid voxels that may change on the next step, grouped by chunk
+#[derive(Resource, Default)]
+pub struct FluidSimulation {
+    active: HashMap<IVec3, HashSet<u16>>,
+}
+
+impl FluidSimulation {
+    /// Update the voxel at `pos` on the next step
+    pub fn activate(&mut self, pos: IVec3) {
+        let local = VoxelWorld::world_to_local(pos);
+        let size = CHUNK_SIZE_I32 as u32;
+        let index = (local.x + local.y * size + local.z * size * size) as u16;
+        self.active
+            .entry(VoxelWorld::world_to_chunk(pos))
+            .or_default()
+            .insert(index);
+    }
+
+    /// Activate the liquid at and around a voxel that changed
+    pub fn wake_around(&mut self, world: &VoxelWorld, pos: IVec3) {
+        for candidate in std::iter::once(pos).chain(NEIGHBORS.iter().map(|dir| pos + *dir)) {
+            if world.fluid_level(candidate) > 0 {
+                self.activate(candidate);
+            }
+        }
+    }
+
+    /// Voxels waiting for the next step
+    pub fn active_count(&self) -> usize {
+        self.active.values().map(HashSet::len).sum()
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    /// Forget all pending updates, e.g. when another world is loaded
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+
+    /// Update up to `max_updates` active voxels, lowest first, and return
+    /// how many were updated
+    pub fn step(&mut self, world: &mut VoxelWorld, max_updates: usize) -> usize {
+        let mut batch = Vec::new();
+        let chunk_positions: Vec<IVec3> = self.active.keys().copied().collect();
+        for chunk_pos in chunk_positions {
+            if batch.len() >= max_updates {
+                break;
+            }
+            let Some(cells) = self.active.get_mut(&chunk_pos) else {
+                continue;
+            };
+            let picked: Vec<u16> = cells
+                .iter()
+                .copied()
+                .take(max_updates - batch.len())
+                .collect();
+            for index in &picked {
+                cells.remove(index);
+            }
+            if cells.is_empty() {
+                self.active.remove(&chunk_pos);
+            }
+
+            let origin = VoxelWorld::chunk_to_world(chunk_pos);
+            batch.extend(picked.into_iter().map(|index| {
+                let index = index as i32;
+                origin
+                    + IVec3::new(
+                        index % CHUNK_SIZE_I32,
+                        (index / CHUNK_SIZE_I32) % CHUNK_SIZE_I32,
+                        index / (CHUNK_SIZE_I32 * CHUNK_SIZE_I32),
+                    )
+            }));
+        }
+
+        // Liquid below settles before the layer above decides where to go
+        batch.sort_by_key(|pos| (pos.y, pos.x, pos.z));
+
+        let mut dirty = DirtyChunks::default();
+        for pos in &batch {
+            self.update_voxel(world, *pos, &mut dirty);
+        }
+        dirty.mark(world);
+        batch.len()
+    }
+
+    fn update_voxel(&mut self, world: &mut VoxelWorld, pos: IVec3, dirty: &mut DirtyChunks) {
+        let Some(voxel) = world.get_voxel(pos) else {
+            return;
+        };
+        if !voxel.is_liquid() {
+            return;
+        }
+
+        let state = desired_state(world, pos, voxel);
+        let current = if world.is_fluid_source(pos) {
+            FluidState::Source
+        } else {
+            FluidState::Flowing(world.fluid_level(pos))
+        };
+        if state != current {
+            match state {
+                FluidState::Dry => {
+                    world.set_voxel(pos, VoxelType::Air);
+                }
+                FluidState::Source => {
+                    world.set_fluid_level(pos, None);
+                }
+                FluidState::Flowing(level) => {
+                    world.set_fluid_level(pos, Some(level));
+                }
+            }
+            dirty.add(pos);
+            self.wake_around(world, pos);
+        }
+
+        let level = match state {
+            FluidState::Dry => return,
+            FluidState::Source => MAX_FLUID_LEVEL,
+            FluidState::Flowing(level) => level,
+        };
+
+        // Fall first; only liquid resting on something spreads sideways
+        let below = pos - IVec3::Y;
+        if can_fall_into(world, below, voxel) {
+            self.flow_into(world, below, voxel, MAX_FLUID_LEVEL, dirty);
+            return;
+        }
+        if level <= 1 || !rests_on_ground(world, pos, voxel) {
+            return;
+        }
+        for dir in HORIZONTAL {
+            let neighbor = pos + dir;
+            match world.get_voxel(neighbor) {
+                Some(other) if is_open(other) => {
+                    self.flow_into(world, neighbor, voxel, level - 1, dirty);
+                }
+                // A lower neighbour of the same liquid is fed by this one
+                Some(other)
+                    if other == voxel
+                        && !world.is_fluid_source(neighbor)
+                        && world.fluid_level(neighbor) < level - 1 =>
+                {
+                    self.activate(neighbor);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn flow_into(
+        &mut self,
+        world: &mut VoxelWorld,
+        pos: IVec3,
+        voxel: VoxelType,
+        level: u8,
+        dirty: &mut DirtyChunks,
+    ) {
+        world.set_voxel(pos, voxel);
+        world.set_fluid_level(pos, Some(level));
+        dirty.add(pos);
+        self.wake_around(world, pos);
+    }
+}
+
+/// Air and other voxels that are neither solid nor liquid
+fn is_open(voxel: VoxelType) -> bool {
+    !voxel.is_solid() && !voxel.is_liquid()
+}
+
+/// Open voxels and the same liquid below full can take falling liquid
+fn can_fall_into(world: &VoxelWorld, pos: IVec3, voxel: VoxelType) -> bool {
+    match world.get_voxel(pos) {
+        Some(other) if is_open(other) => true,
+        Some(other) => {
+            other == voxel
+                && !world.is_fluid_source(pos)
+                && world.fluid_level(pos) < MAX_FLUID_LEVEL
+        }
+        None => false,
+    }
+}
+
+/// Solid voxels and sources of the same liquid hold liquid up; a falling
+/// column doesn't spread sideways until it lands
+fn rests_on_ground(world: &VoxelWorld, pos: IVec3, voxel: VoxelType) -> bool {
+    let below = pos - IVec3::Y;
+    match world.get_voxel(below) {
+        Some(other) if other == voxel => world.is_fluid_source(below),
+        Some(other) => other.is_solid(),
+        None => false,
+    }
+}
+
+fn desired_state(world: &VoxelWorld, pos: IVec3, voxel: VoxelType) -> FluidState {
+    if world.is_fluid_source(pos) {
+        return FluidState::Source;
+    }
+
+    // Settle between two sources when resting on ground or a source
+    let source_neighbors = HORIZONTAL
+        .iter()
+        .filter(|dir| {
+            let neighbor = pos + **dir;
+            world.get_voxel(neighbor) == Some(voxel) && world.is_fluid_source(neighbor)
+        })
+        .count();
+    if source_neighbors >= 2 && rests_on_ground(world, pos, voxel) {
+        return FluidState::Source;
+    }
+
+    // Falling liquid stays full
+    if world.get_voxel(pos + IVec3::Y) == Some(voxel) {
+        return FluidState::Flowing(MAX_FLUID_LEVEL);
+    }
+
+    // Fed by the highest neighbour that spreads sideways
+    let fed = HORIZONTAL
+        .iter()
+        .map(|dir| pos + *dir)
+        .filter(|neighbor| {
+            world.get_voxel(*neighbor) == Some(voxel) && rests_on_ground(world, *neighbor, voxel)
+        })
+        .map(|neighbor| world.fluid_level(neighbor).saturating_sub(1))
+        .max()
+        .unwrap_or(0);
+    if fed > 0 {
+        FluidState::Flowing(fed)
+    } else {
+        FluidState::Dry
+    }
+}
+
+pub struct FluidPlugin;
+
+impl Plugin for FluidPlugin {
+    fn build(&self, app: &mut App) {
+        // The host simulates and sends the result; guests only display it
+        app.init_resource::<FluidSettings>()
+            .init_resource::<FluidSimulation>()
+            .add_systems(
+                Update,
+                (wake_fluids, simulate_fluids)
+                    .chain()
+                    .run_if(not(resource_exists::<GuestSession>)),
+            );
+    }
+}
+
+fn wake_fluids(
+    mut changes: MessageReader<VoxelChanged>,
+    world: Res<VoxelWorld>,
+    mut simulation: ResMut<FluidSimulation>,
+) {
+    for change in changes.read() {
+        simulation.wake_around(&world, change.position);
+    }
+}
+
+fn simulate_fluids(
+    time: Res<Time>,
+    settings: Res<FluidSettings>,
+    active_world: Res<ActiveWorld>,
+    mut simulation: ResMut<FluidSimulation>,
+    mut world: ResMut<VoxelWorld>,
+    mut since_step: Local<f32>,
+    mut last_slot: Local<Option<SaveSlot>>,
+) {
+    // Pending updates belong to the world they were queued in
+    if last_slot.as_ref() != Some(&active_world.slot) {
+        simulation.clear();
+        *last_slot = Some(active_world.slot.clone());
+    }
+
+    if simulation.is_idle() {
+        *since_step = 0.0;
+        return;
+    }
+    *since_step += time.delta_secs();
+    if *since_step < settings.step_interval {
+        return;
+    }
+    *since_step = 0.0;
+    simulation.step(&mut world, settings.max_updates_per_step);
+}
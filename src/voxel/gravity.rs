@@ -1,216 +1,349 @@
+//! Structural support for voxels.
+//!
+//! Gravity only runs in response to edits. When a solid voxel is removed,
+//! the solid voxels around it flood-fill through their neighbours looking
+//! for anchored ground (bedrock or the world floor). Islands that can't find
+//! any are cut out of the world and fall as one entity, which turns back into
+//! voxels where it lands. Loose voxels (sand, clay) also fall on their own
+//! whenever nothing solid is directly below them.
+
+use crate::interaction::DirtyChunks;
+use crate::network::guest::GuestSession;
+use crate::rendering::materials::VoxelMaterial;
+use crate::rendering::AmbientOcclusionConfig;
+use crate::voxel::chunk::Chunk;
+use crate::voxel::meshing::generate_chunk_mesh;
+use crate::voxel::persistence::{ActiveWorld, SaveSlot};
+use crate::voxel::types::{Support, Voxel, VoxelType};
+use crate::voxel::world::{VoxelChanged, VoxelWorld};
 use bevy::prelude::*;
-use crate::voxel::world::VoxelWorld;
-use crate::voxel::types::VoxelType;
-// use crate::voxel::chunk::Chunk;
-use std::collections::VecDeque;
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 
-pub struct GravityPlugin;
+/// How far the support search follows solid voxels from an edit. Structures
+/// reaching further count as supported, so digging into a mountain never
+/// searches the whole mountain.
+pub const MAX_SUPPORT_DISTANCE: u32 = 32;
 
-impl Plugin for GravityPlugin {
-    fn build(&self, app: &mut App) {
-        app.add_systems(Update, gravity_system);
-    }
-}
+/// Voxels at or below this height rest on the bottom of the world
+pub const WORLD_FLOOR_Y: i32 = 0;
 
-// Configuration for gravity system
-const GRAVITY_UPDATE_INTERVAL: f32 = 0.05; // Run faster (20 times per second)
-const MAX_SUPPORT_DISTANCE: u32 = 32; // Distance in blocks
-const CHUNKS_PER_FRAME: usize = 16; // Process 16 chunks per tick (approx 320 chunks/sec)
+/// Blocks per second squared, matching the physics gravity
+const FALL_ACCELERATION: f32 = 20.0;
+const MAX_FALL_SPEED: f32 = 40.0;
 
-#[derive(Resource)]
-struct GravityState {
-    timer: Timer,
-    chunk_iterator_index: usize,
+const NEIGHBORS: [IVec3; 6] = [
+    IVec3::NEG_Y,
+    IVec3::Y,
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
+
+/// Voxels cut out of the world, falling as one rigid group
+#[derive(Component, Clone, Debug)]
+pub struct FallingBlocks {
+    /// Voxels by offset from `origin`
+    voxels: Vec<(IVec3, VoxelType)>,
+    /// Where the group was before it started falling
+    origin: IVec3,
+    /// Distance fallen so far
+    fallen: f32,
+    speed: f32,
 }
 
-impl Default for GravityState {
-    fn default() -> Self {
-        Self {
-            timer: Timer::from_seconds(GRAVITY_UPDATE_INTERVAL, TimerMode::Repeating),
-            chunk_iterator_index: 0,
+impl FallingBlocks {
+    pub fn voxels(&self) -> &[(IVec3, VoxelType)] {
+        &self.voxels
+    }
+
+    pub fn origin(&self) -> IVec3 {
+        self.origin
+    }
+
+    /// Current position of `origin`
+    pub fn translation(&self) -> Vec3 {
+        self.origin.as_vec3() - Vec3::Y * self.fallen
+    }
+
+    /// Fall for `delta` seconds. Returns true once the group has landed and
+    /// been written back into the world.
+    pub fn fall(&mut self, world: &mut VoxelWorld, delta: f32) -> bool {
+        self.speed = (self.speed + FALL_ACCELERATION * delta).min(MAX_FALL_SPEED);
+        let target = self.fallen + self.speed * delta;
+
+        // Check every whole voxel passed this frame so fast groups can't
+        // tunnel through thin floors
+        let mut drop = self.fallen.floor() as i32;
+        while (drop + 1) as f32 <= target {
+            if !self.fits(world, drop + 1) {
+                self.land(world, drop);
+                return true;
+            }
+            drop += 1;
         }
+        self.fallen = target;
+        false
+    }
+
+    /// Every voxel would be in loaded, non-solid space after dropping `drop`
+    fn fits(&self, world: &VoxelWorld, drop: i32) -> bool {
+        self.voxels.iter().all(|(offset, _)| {
+            let pos = self.origin + *offset - IVec3::Y * drop;
+            matches!(world.get_voxel(pos), Some(voxel) if !voxel.is_solid())
+        })
+    }
+
+    /// Write the voxels back `drop` below where they started. Whatever was
+    /// built into the group's path while it fell is kept instead.
+    fn land(&self, world: &mut VoxelWorld, drop: i32) {
+        let mut dirty = DirtyChunks::default();
+        for (offset, voxel) in &self.voxels {
+            let pos = self.origin + *offset - IVec3::Y * drop;
+            if matches!(world.get_voxel(pos), Some(current) if !current.is_solid()) {
+                world.set_voxel(pos, *voxel);
+                dirty.add(pos);
+            }
+        }
+        dirty.mark(world);
     }
 }
 
-fn gravity_system(
-    time: Res<Time>,
-    mut world: ResMut<VoxelWorld>,
-    mut state: Local<GravityState>,
-) {
-    state.timer.tick(time.delta());
+/// Voxels held up by nothing but themselves
+fn is_anchor(voxel: VoxelType, pos: IVec3) -> bool {
+    pos.y <= WORLD_FLOOR_Y || voxel.info().support == Support::Anchored
+}
 
-    if !state.timer.is_finished() {
-        return;
+/// A loose voxel with loaded, non-solid space below it
+fn loose_and_unsupported(world: &VoxelWorld, pos: IVec3, voxel: VoxelType) -> bool {
+    voxel.info().support == Support::Loose
+        && pos.y > WORLD_FLOOR_Y
+        && matches!(world.get_voxel(pos - IVec3::Y), Some(below) if !below.is_solid())
+}
+
+/// The solid voxels connected to `start` when none of them reaches anchored
+/// ground, or `None` if they are held up. Unloaded chunks and anything past
+/// [`MAX_SUPPORT_DISTANCE`] count as support, as does every voxel in
+/// `supported`. Voxels found to be held up are added to `supported`.
+pub fn unsupported_island(
+    world: &VoxelWorld,
+    start: IVec3,
+    supported: &mut HashSet<IVec3>,
+) -> Option<Vec<IVec3>> {
+    let voxel = world.get_voxel(start)?;
+    if !voxel.is_solid() || supported.contains(&start) {
+        return None;
     }
 
-    // Get all chunk positions
-    let chunk_positions: Vec<IVec3> = world.all_chunk_positions().collect();
-    if chunk_positions.is_empty() {
-        return;
+    let mut visited = HashSet::from([start]);
+    let mut queue = VecDeque::from([(start, voxel, 0)]);
+    let mut held_up = false;
+
+    'search: while let Some((pos, voxel, distance)) = queue.pop_front() {
+        if is_anchor(voxel, pos) {
+            held_up = true;
+            break;
+        }
+        for dir in NEIGHBORS {
+            let next = pos + dir;
+            if visited.contains(&next) {
+                continue;
+            }
+            let Some(next_voxel) = world.get_voxel(next) else {
+                held_up = true;
+                break 'search;
+            };
+            if !next_voxel.is_solid() {
+                continue;
+            }
+            if supported.contains(&next) || distance + 1 > MAX_SUPPORT_DISTANCE {
+                held_up = true;
+                break 'search;
+            }
+            visited.insert(next);
+            queue.push_back((next, next_voxel, distance + 1));
+        }
     }
 
-    // Process a subset of chunks each frame
-    let start_index = state.chunk_iterator_index % chunk_positions.len();
-    let end_index = (start_index + CHUNKS_PER_FRAME).min(chunk_positions.len());
-    
-    // Update iterator for next frame
-    state.chunk_iterator_index = (start_index + CHUNKS_PER_FRAME) % chunk_positions.len();
+    if held_up {
+        // Everything connected to a supported voxel is supported too
+        supported.extend(visited);
+        None
+    } else {
+        Some(visited.into_iter().collect())
+    }
+}
 
-    // Collect updates first to avoid borrow checker issues
-    // List of (position, new_voxel_type)
-    let mut recursive_falls = Vec::new();
+/// Cut the voxels the edits left without support out of the world and
+/// return them as falling groups
+pub fn collapse(world: &mut VoxelWorld, edits: &[VoxelChanged]) -> Vec<FallingBlocks> {
+    // (position, whether its structure lost a voxel)
+    let mut candidates = Vec::new();
+    for edit in edits {
+        if edit.voxel.is_solid() {
+            candidates.push((edit.position, false));
+        }
+        if edit.previous.is_solid() && !edit.voxel.is_solid() {
+            candidates.extend(NEIGHBORS.iter().map(|dir| (edit.position + *dir, true)));
+        }
+    }
 
-    for i in start_index..end_index {
-        let chunk_pos = chunk_positions[i];
-        
-        // Skip if chunk doesn't exist (shouldn't happen with all_chunk_positions but safe is safe)
-        if !world.chunk_exists(chunk_pos) {
+    let mut supported = HashSet::new();
+    let mut groups = Vec::new();
+    for (pos, lost_support) in candidates {
+        let Some(voxel) = world.get_voxel(pos) else {
+            continue;
+        };
+        if !voxel.is_solid() {
             continue;
         }
-
-        // We need to iterate over voxels in the chunk.
-        // Since we can't easily iterate voxels from the world wrapper without positions,
-        // we'll recalculate world positions for the chunk.
-        let chunk_world_pos = VoxelWorld::chunk_to_world(chunk_pos);
-        
-        // Check voxels bottom-up to let lower things fall first? 
-        // Or top-down? Top-down is better for "detaching", bottom-up is better for "landing".
-        // Let's do bottom-up so if something falls, it falls into empty space.
-        
-        // Actually, for a sweeper, simply checking every voxel is expensive.
-        // But let's try a naive optimization: only check non-air, non-bedrock voxels.
-        
-        // We will scan the chunk's voxels.
-        // Note: accessing chunk directly would be faster but VoxelWorld abstractions are cleaner.
-        // Let's rely on get_voxel/set_voxel for now.
-        
-        // Optimization: Iterating 16x16x16 = 4096 voxels * 4 chunks = 16k checks per 0.1s is fine.
-        
-        for y in 0..crate::constants::CHUNK_SIZE_I32 {
-            for x in 0..crate::constants::CHUNK_SIZE_I32 {
-                for z in 0..crate::constants::CHUNK_SIZE_I32 {
-                    let local_pos = IVec3::new(x, y, z);
-                    let world_pos = chunk_world_pos + local_pos;
-                    
-                    if let Some(voxel) = world.get_voxel(world_pos) {
-                        if voxel == VoxelType::Air || voxel == VoxelType::Bedrock || voxel == VoxelType::Water {
-                            continue;
-                        }
-                        
-                        // Check if it should fall
-                        if should_fall(&world, world_pos) {
-                            // It should fall. 
-                            // Check if space below is empty.
-                            let below_pos = world_pos + IVec3::new(0, -1, 0);
-                            
-                            if let Some(below_voxel) = world.get_voxel(below_pos) {
-                                if below_voxel == VoxelType::Air || below_voxel == VoxelType::Water {
-                                     recursive_falls.push((world_pos, below_pos, voxel));
-                                }
-                            } else if below_pos.y < 0 {
-                                // Destroy if it falls out of the world
-                                recursive_falls.push((world_pos, below_pos, VoxelType::Air)); // Mark to remove source, but don't place destination
-                            }
-                        }
-                    }
-                }
-            }
+        if loose_and_unsupported(world, pos, voxel) {
+            groups.push(detach(world, &[pos]));
+            continue;
+        }
+        // A placed voxel hangs on whatever it was placed against
+        if !lost_support {
+            continue;
+        }
+        if let Some(island) = unsupported_island(world, pos, &mut supported) {
+            groups.push(detach(world, &island));
         }
     }
+    groups
+}
+
+/// Replace voxels with air and return them as a falling group
+fn detach(world: &mut VoxelWorld, positions: &[IVec3]) -> FallingBlocks {
+    let origin = positions
+        .iter()
+        .copied()
+        .reduce(IVec3::min)
+        .unwrap_or_default();
 
-    // Apply updates
-    for (source_pos, dest_pos, voxel_type) in recursive_falls {
-        // Double check source is still what we think (in case of chain reactions in future)
-         if let Some(current_source) = world.get_voxel(source_pos) {
-             if current_source != voxel_type && voxel_type != VoxelType::Air {
-                 continue; 
-             }
-         }
-        
-        // Move voxel
-        world.set_voxel(source_pos, VoxelType::Air);
-        
-        // If destiny is within bounds/valid, set it
-        if dest_pos.y >= 0 {
-             // If falling into water, maybe splash? For now just replace.
-            world.set_voxel(dest_pos, voxel_type);
+    let mut dirty = DirtyChunks::default();
+    let mut voxels = Vec::with_capacity(positions.len());
+    for pos in positions {
+        if let Some(voxel) = world.get_voxel(*pos) {
+            voxels.push((*pos - origin, voxel));
+            world.set_voxel(*pos, VoxelType::Air);
+            dirty.add(*pos);
         }
     }
+    dirty.mark(world);
+
+    FallingBlocks {
+        voxels,
+        origin,
+        fallen: 0.0,
+        speed: 0.0,
+    }
 }
 
-fn should_fall(world: &VoxelWorld, pos: IVec3) -> bool {
-    // Bedrock never falls
-    if pos.y <= 0 {
-        return false;
+/// Falling blocks without any rendering; see [`attach_falling_block_visuals`]
+pub struct GravityPlugin;
+
+impl Plugin for GravityPlugin {
+    fn build(&self, app: &mut App) {
+        // The host decides what falls and sends the landed voxels; guests
+        // see blocks disappear and reappear where they land
+        app.add_systems(
+            Update,
+            (collapse_unsupported_system, fall_system)
+                .chain()
+                .run_if(not(resource_exists::<GuestSession>)),
+        );
     }
+}
 
-    // 1. Check immediate support (optimization)
-    // If voxel below is solid, we assume it's supporting us.
-    // This creates a "layer-by-layer" falling effect for floating islands,
-    // which is efficient and visually acceptable (like sand).
-    let below = pos + IVec3::new(0, -1, 0);
-    if let Some(below_voxel) = world.get_voxel(below) {
-        if below_voxel == VoxelType::Bedrock {
-            return false;
-        }
-        // If the thing below is solid, we don't fall yet.
-        // We wait for the thing below to fall first (if it's unstable).
-        // Exception: Leaves don't support things (usually).
-        if below_voxel != VoxelType::Air && below_voxel != VoxelType::Water && below_voxel != VoxelType::Leaves {
-             return false;
-        }
+fn collapse_unsupported_system(
+    mut commands: Commands,
+    mut edits: MessageReader<VoxelChanged>,
+    mut world: ResMut<VoxelWorld>,
+) {
+    let edits: Vec<VoxelChanged> = edits.read().copied().collect();
+    if edits.is_empty() {
+        return;
+    }
+    for group in collapse(&mut world, &edits) {
+        commands.spawn((Transform::from_translation(group.translation()), group));
     }
+}
 
-    // 2. BFS for support with Distance Tracking
-    let mut queue = VecDeque::new();
-    let mut visited = HashSet::new();
-    
-    // Push (position, distance)
-    queue.push_back((pos, 0));
-    visited.insert(pos);
-    
-    // Direct neighbors (6 directions)
-    let directions = [
-        IVec3::new(0, -1, 0), // Check down first
-        IVec3::new(0, 1, 0),
-        IVec3::new(-1, 0, 0),
-        IVec3::new(1, 0, 0),
-        IVec3::new(0, 0, -1),
-        IVec3::new(0, 0, 1),
-    ];
-
-    while let Some((current, dist)) = queue.pop_front() {
-        if dist > MAX_SUPPORT_DISTANCE {
-            return true; // Too far from support, assume floating
+fn fall_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    active_world: Res<ActiveWorld>,
+    mut world: ResMut<VoxelWorld>,
+    mut falling: Query<(Entity, &mut FallingBlocks, &mut Transform)>,
+    mut last_slot: Local<Option<SaveSlot>>,
+) {
+    // Blocks falling in the previous world must not land in this one
+    let slot_changed = last_slot.as_ref() != Some(&active_world.slot);
+    *last_slot = Some(active_world.slot.clone());
+
+    for (entity, mut blocks, mut transform) in &mut falling {
+        if slot_changed || blocks.fall(&mut world, time.delta_secs()) {
+            commands.entity(entity).despawn();
+        } else {
+            transform.translation = blocks.translation();
         }
+    }
+}
 
-        for dir in directions.iter() {
-            let next_pos = current + *dir;
-            
-            if visited.contains(&next_pos) {
-                continue;
-            }
+/// Give new falling groups a blocky mesh of their voxels
+pub fn attach_falling_block_visuals(
+    mut commands: Commands,
+    new_groups: Query<(Entity, &FallingBlocks), Without<Visibility>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    material: Option<Res<VoxelMaterial>>,
+    ao_config: Res<AmbientOcclusionConfig>,
+) {
+    let Some(material) = material else {
+        return;
+    };
 
-            // Check boundaries - if we hit bottom of world, it's supported
-            if next_pos.y <= 0 {
-                return false; // Found support (ground)
+    for (entity, blocks) in &new_groups {
+        // A scratch world holding just the group, with empty chunks around
+        // it so the mesher renders its outer faces
+        let mut group_world = VoxelWorld::new(IVec3::ONE);
+        group_world.set_border_enabled(false);
+        let mut group_chunks = HashSet::new();
+        for (offset, _) in blocks.voxels() {
+            let chunk_pos = VoxelWorld::world_to_chunk(*offset);
+            group_chunks.insert(chunk_pos);
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let neighbor = chunk_pos + IVec3::new(dx, dy, dz);
+                        if !group_world.chunk_exists(neighbor) {
+                            group_world.insert_chunk(Chunk::new(neighbor));
+                        }
+                    }
+                }
             }
+        }
+        for (offset, voxel) in blocks.voxels() {
+            group_world.set_voxel(*offset, *voxel);
+        }
 
-            if let Some(voxel) = world.get_voxel(next_pos) {
-                if voxel == VoxelType::Bedrock {
-                    return false; // Found support (bedrock)
-                }
-                
-                // If voxel is solid, it can transmit support
-                if voxel != VoxelType::Air && voxel != VoxelType::Water && voxel != VoxelType::Leaves { 
-                     visited.insert(next_pos);
-                     queue.push_back((next_pos, dist + 1));
-                }
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.insert(Visibility::default());
+        for chunk_pos in group_chunks {
+            let Some(chunk) = group_world.get_chunk(chunk_pos) else {
+                continue;
+            };
+            let mesh = generate_chunk_mesh(chunk, &group_world, &ao_config.baked).solid;
+            if mesh.is_empty() {
+                continue;
             }
+            let mesh = meshes.add(mesh.into_mesh());
+            entity_commands.with_children(|parent| {
+                parent.spawn((
+                    Mesh3d(mesh),
+                    MeshMaterial3d(material.handle.clone()),
+                    Transform::from_translation(VoxelWorld::chunk_to_world(chunk_pos).as_vec3()),
+                ));
+            });
         }
     }
-    
-    // BFS exhausted without finding support
-    true
 }
@@ -0,0 +1,325 @@
+//! World inspection without a running app: stats, heightmaps, save
+//! validation and batch generation. Used by the `worldgen` binary.
+
+use crate::constants::{CHUNK_SIZE, CHUNK_SIZE_I32};
+use crate::voxel::chunk::Chunk;
+use crate::voxel::persistence::{self, ChunkStore, SaveSlot, WORLD_FORMAT_VERSION};
+use crate::voxel::region::{RegionFile, REGION_FORMAT_VERSION};
+use crate::voxel::types::VoxelType;
+use crate::voxel::world::VoxelWorld;
+use crate::voxel::worldgen::{GenerationStats, WorldGenerator};
+use bevy::prelude::*;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Chunk counts and a voxel histogram for a world
+#[derive(Clone, Debug)]
+pub struct WorldStats {
+    pub stored_chunks: usize,
+    pub generated_chunks: usize,
+    /// Chunks made of a single voxel type
+    pub uniform_chunks: usize,
+    pub voxel_memory_bytes: usize,
+    histogram: Vec<u64>,
+}
+
+impl Default for WorldStats {
+    fn default() -> Self {
+        Self {
+            stored_chunks: 0,
+            generated_chunks: 0,
+            uniform_chunks: 0,
+            voxel_memory_bytes: 0,
+            histogram: vec![0; 256],
+        }
+    }
+}
+
+impl WorldStats {
+    fn add_chunk(&mut self, chunk: &Chunk, stored: bool) {
+        if stored {
+            self.stored_chunks += 1;
+        } else {
+            self.generated_chunks += 1;
+        }
+        self.voxel_memory_bytes += chunk.voxel_memory_bytes();
+
+        if let Some(voxel) = chunk.uniform_voxel() {
+            self.uniform_chunks += 1;
+            self.histogram[voxel.id() as usize] += (CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE) as u64;
+            return;
+        }
+
+        for local in chunk_positions() {
+            self.histogram[chunk.get(local).id() as usize] += 1;
+        }
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.stored_chunks + self.generated_chunks
+    }
+
+    pub fn count(&self, voxel: VoxelType) -> u64 {
+        self.histogram[voxel.id() as usize]
+    }
+
+    pub fn total_voxels(&self) -> u64 {
+        self.histogram.iter().sum()
+    }
+
+    /// Voxel types that occur, most common first
+    pub fn histogram(&self) -> Vec<(VoxelType, u64)> {
+        let mut entries: Vec<(VoxelType, u64)> = self
+            .histogram
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(id, count)| (VoxelType::from_id(id as u8), *count))
+            .collect();
+        entries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.id().cmp(&b.0.id())));
+        entries
+    }
+}
+
+fn chunk_positions() -> impl Iterator<Item = UVec3> {
+    let size = CHUNK_SIZE as u32;
+    (0..size).flat_map(move |x| (0..size).flat_map(move |y| (0..size).map(move |z| UVec3::new(x, y, z))))
+}
+
+/// Visit every chunk of a save: the ones stored in its region files, then
+/// (with a generator) the chunks inside the world bounds that were never
+/// stored. Region files are opened read-only and never upgraded.
+pub fn visit_world_chunks(
+    store: &ChunkStore,
+    world_size_chunks: IVec3,
+    generator: Option<&dyn WorldGenerator>,
+    mut visit: impl FnMut(&Chunk, bool),
+) -> Result<(), String> {
+    let mut stored = HashSet::new();
+    for (region, path) in store.region_files()? {
+        let mut file = RegionFile::open_read_only(&path)?;
+        for chunk_pos in file.stored_chunks(region) {
+            if let Some(data) = file.read_chunk(chunk_pos)? {
+                visit(&Chunk::from_data(data), true);
+                stored.insert(chunk_pos);
+            }
+        }
+    }
+
+    let Some(generator) = generator else {
+        return Ok(());
+    };
+
+    let bounds = VoxelWorld::new(world_size_chunks);
+    for chunk_pos in bounds.all_chunk_positions() {
+        if stored.contains(&chunk_pos) {
+            continue;
+        }
+        let chunk = generator.generate_chunk(chunk_pos, &mut GenerationStats::default());
+        visit(&chunk, false);
+    }
+    Ok(())
+}
+
+pub fn collect_stats(
+    store: &ChunkStore,
+    world_size_chunks: IVec3,
+    generator: Option<&dyn WorldGenerator>,
+) -> Result<WorldStats, String> {
+    let mut stats = WorldStats::default();
+    visit_world_chunks(store, world_size_chunks, generator, |chunk, stored| {
+        stats.add_chunk(chunk, stored);
+    })?;
+    Ok(stats)
+}
+
+/// Highest solid voxel per column of the bordered world area
+#[derive(Clone, Debug)]
+pub struct Heightmap {
+    pub width: usize,
+    pub depth: usize,
+    heights: Vec<Option<i32>>,
+}
+
+impl Heightmap {
+    fn new(width: usize, depth: usize) -> Self {
+        Self {
+            width,
+            depth,
+            heights: vec![None; width * depth],
+        }
+    }
+
+    /// Surface height of a column, `None` if it has no solid voxels
+    pub fn get(&self, x: usize, z: usize) -> Option<i32> {
+        self.heights.get(z * self.width + x).copied().flatten()
+    }
+
+    fn raise(&mut self, x: usize, z: usize, height: i32) {
+        let entry = &mut self.heights[z * self.width + x];
+        *entry = Some(entry.map_or(height, |h| h.max(height)));
+    }
+
+    pub fn max_height(&self) -> Option<i32> {
+        self.heights.iter().flatten().copied().max()
+    }
+
+    /// Row-major 8-bit grayscale pixels, `max_y` and above as white and
+    /// empty columns as black
+    pub fn to_grayscale(&self, max_y: i32) -> Vec<u8> {
+        let max_y = max_y.max(1) as f32;
+        self.heights
+            .iter()
+            .map(|height| match height {
+                Some(h) => ((*h + 1) as f32 / (max_y + 1.0) * 255.0).clamp(1.0, 255.0) as u8,
+                None => 0,
+            })
+            .collect()
+    }
+}
+
+pub fn build_heightmap(
+    store: &ChunkStore,
+    world_size_chunks: IVec3,
+    generator: Option<&dyn WorldGenerator>,
+) -> Result<Heightmap, String> {
+    let width = (world_size_chunks.x.max(0) * CHUNK_SIZE_I32) as usize;
+    let depth = (world_size_chunks.z.max(0) * CHUNK_SIZE_I32) as usize;
+    let mut heightmap = Heightmap::new(width, depth);
+
+    visit_world_chunks(store, world_size_chunks, generator, |chunk, _| {
+        let origin = VoxelWorld::chunk_to_world(chunk.position());
+        if chunk.uniform_voxel().is_some_and(|voxel| !voxel.info().solid) {
+            return;
+        }
+
+        for x in 0..CHUNK_SIZE as u32 {
+            for z in 0..CHUNK_SIZE as u32 {
+                let world_x = origin.x + x as i32;
+                let world_z = origin.z + z as i32;
+                if world_x < 0 || world_z < 0 || world_x as usize >= width || world_z as usize >= depth {
+                    continue;
+                }
+
+                let top = (0..CHUNK_SIZE as u32)
+                    .rev()
+                    .find(|y| chunk.get(UVec3::new(x, *y, z)).info().solid);
+                if let Some(y) = top {
+                    heightmap.raise(world_x as usize, world_z as usize, origin.y + y as i32);
+                }
+            }
+        }
+    })?;
+
+    Ok(heightmap)
+}
+
+/// Problems found in a save. Warnings don't stop the game from loading it.
+#[derive(Clone, Debug, Default)]
+pub struct ValidationReport {
+    pub regions_checked: usize,
+    pub chunks_checked: usize,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Check a slot's metadata and read back every stored chunk
+pub fn validate_save(slot: &SaveSlot) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    if !persistence::saved_world_exists(slot) {
+        report
+            .errors
+            .push(format!("{:?} has no world metadata", slot.directory()));
+        return report;
+    }
+
+    match slot.read_metadata() {
+        Ok(meta) => {
+            if meta.format_version < WORLD_FORMAT_VERSION {
+                report.warnings.push(format!(
+                    "Save format version {} is older than {} and will be upgraded on load",
+                    meta.format_version, WORLD_FORMAT_VERSION
+                ));
+            }
+            if meta.world_size_chunks.cmple(IVec3::ZERO).any() {
+                report.errors.push(format!(
+                    "World size {:?} must be positive on every axis",
+                    meta.world_size_chunks
+                ));
+            }
+            if meta.thumbnail.is_some() && !slot.thumbnail_path().exists() {
+                report.warnings.push("Thumbnail is referenced but missing".to_string());
+            }
+        }
+        Err(e) => report.errors.push(e),
+    }
+
+    let regions = match slot.store().region_files() {
+        Ok(regions) => regions,
+        Err(e) => {
+            report.errors.push(e);
+            return report;
+        }
+    };
+
+    for (region, path) in regions {
+        report.regions_checked += 1;
+        let mut file = match RegionFile::open_read_only(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                report.errors.push(e);
+                continue;
+            }
+        };
+
+        if file.version() < REGION_FORMAT_VERSION {
+            report.warnings.push(format!(
+                "{:?} uses region format {} and will be upgraded on load",
+                path,
+                file.version()
+            ));
+        }
+
+        for chunk_pos in file.stored_chunks(region) {
+            report.chunks_checked += 1;
+            if let Err(e) = file.read_chunk(chunk_pos) {
+                report.errors.push(format!("{:?}: {}", path, e));
+            }
+        }
+    }
+
+    report
+}
+
+/// Create a slot and store every chunk inside its world bounds, so the
+/// save no longer depends on the generator that produced it
+pub fn generate_world(
+    root: &Path,
+    name: &str,
+    generator: &dyn WorldGenerator,
+    world_size_chunks: IVec3,
+) -> Result<(SaveSlot, GenerationStats), String> {
+    let (slot, _) = persistence::create_save_slot(root, name, generator.seed(), world_size_chunks)?;
+    let store = slot.store();
+    let bounds = VoxelWorld::new(world_size_chunks);
+
+    let mut totals = GenerationStats::default();
+    // One x slice at a time keeps memory bounded for large worlds
+    for x in 0..world_size_chunks.x {
+        let chunks: Vec<Chunk> = bounds
+            .all_chunk_positions()
+            .filter(|pos| pos.x == x)
+            .map(|pos| generator.generate_chunk(pos, &mut totals))
+            .collect();
+        store.save_chunks(chunks.iter())?;
+    }
+
+    Ok((slot, totals))
+}
@@ -0,0 +1,364 @@
+//! Voxel light propagation.
+//!
+//! Every voxel carries two light levels from 0 to [`MAX_LIGHT`]: sky light,
+//! which falls straight down from open sky without fading, and block light
+//! given off by emissive voxels such as glowstone. Both flood outwards,
+//! losing a level per voxel (more through water and leaves), and stop at
+//! opaque voxels. Chunks are lit once when they load; after that only the
+//! light around an edit is recomputed. Meshing bakes the levels into vertex
+//! attributes. Light is derived from the voxels, so it is neither saved nor
+//! sent to guests.
+
+use crate::constants::{CHUNK_SIZE_I32, CHUNK_VOLUME};
+use crate::interaction::DirtyChunks;
+use crate::voxel::types::{Voxel, VoxelType};
+use crate::voxel::world::{VoxelChanged, VoxelWorld};
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// Brightest light level: open sky, or standing next to glowstone
+pub const MAX_LIGHT: u8 = 15;
+
+const NEIGHBORS: [IVec3; 6] = [
+    IVec3::NEG_Y,
+    IVec3::Y,
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LightChannel {
+    Sky,
+    Block,
+}
+
+impl LightChannel {
+    pub const ALL: [LightChannel; 2] = [LightChannel::Sky, LightChannel::Block];
+}
+
+/// Light levels of a chunk, two per voxel packed into one byte. Chunks that
+/// were never lit store nothing.
+#[derive(Clone, Debug, Default)]
+pub struct ChunkLight {
+    /// Empty, or one entry per voxel: sky light high nibble, block light low
+    levels: Vec<u8>,
+}
+
+impl ChunkLight {
+    pub fn get(&self, index: usize, channel: LightChannel) -> u8 {
+        let Some(packed) = self.levels.get(index) else {
+            return 0;
+        };
+        match channel {
+            LightChannel::Sky => packed >> 4,
+            LightChannel::Block => packed & 0x0f,
+        }
+    }
+
+    /// Set one channel at `index`. Returns true if it changed.
+    pub fn set(&mut self, index: usize, channel: LightChannel, level: u8) -> bool {
+        let level = level.min(MAX_LIGHT);
+        if self.levels.is_empty() {
+            if level == 0 {
+                return false;
+            }
+            self.levels = vec![0; CHUNK_VOLUME];
+        }
+        let packed = self.levels[index];
+        let updated = match channel {
+            LightChannel::Sky => (packed & 0x0f) | (level << 4),
+            LightChannel::Block => (packed & 0xf0) | level,
+        };
+        self.levels[index] = updated;
+        updated != packed
+    }
+}
+
+/// How many levels light loses entering a voxel, or `None` if it can't
+pub fn attenuation(voxel: VoxelType) -> Option<u8> {
+    if voxel.is_solid() && !voxel.is_transparent() {
+        None
+    } else if voxel.is_liquid() || voxel.is_solid() {
+        // Water and leaves dim light faster than air
+        Some(2)
+    } else {
+        Some(1)
+    }
+}
+
+fn emission(voxel: VoxelType) -> u8 {
+    voxel.info().light_emission
+}
+
+/// Light reaching `voxel` from a neighbour at `level` in direction `dir`
+fn propagated(channel: LightChannel, level: u8, dir: IVec3, voxel: VoxelType) -> u8 {
+    let Some(cost) = attenuation(voxel) else {
+        return 0;
+    };
+    // Full sky light falls through open air without fading
+    if channel == LightChannel::Sky && level == MAX_LIGHT && dir == IVec3::NEG_Y && cost == 1 {
+        MAX_LIGHT
+    } else {
+        level.saturating_sub(cost)
+    }
+}
+
+/// Whether `pos` is lit from above by sky nobody has loaded yet
+fn open_to_unloaded_sky(world: &VoxelWorld, pos: IVec3) -> bool {
+    world.get_voxel(pos + IVec3::Y).is_none()
+}
+
+/// Flood light outwards from the voxels in `queue`, brightening any
+/// neighbour that gets more light than it has
+fn spread(
+    world: &mut VoxelWorld,
+    channel: LightChannel,
+    mut queue: VecDeque<IVec3>,
+    dirty: &mut DirtyChunks,
+) {
+    while let Some(pos) = queue.pop_front() {
+        let Some(level) = world.light(pos, channel) else {
+            continue;
+        };
+        if level <= 1 {
+            continue;
+        }
+        for dir in NEIGHBORS {
+            let next = pos + dir;
+            let Some(voxel) = world.get_voxel(next) else {
+                continue;
+            };
+            let reached = propagated(channel, level, dir, voxel);
+            if reached > world.light(next, channel).unwrap_or(0) {
+                world.set_light(next, channel, reached);
+                dirty.add(next);
+                queue.push_back(next);
+            }
+        }
+    }
+}
+
+/// Darken everything that got its light from the voxels in `queue` (each
+/// with the level it had). Returns the voxels whose light has to spread
+/// back into the darkened area.
+fn unspread(
+    world: &mut VoxelWorld,
+    channel: LightChannel,
+    mut queue: VecDeque<(IVec3, u8)>,
+    dirty: &mut DirtyChunks,
+) -> VecDeque<IVec3> {
+    let mut refill = VecDeque::new();
+    while let Some((pos, level)) = queue.pop_front() {
+        for dir in NEIGHBORS {
+            let next = pos + dir;
+            let Some(next_level) = world.light(next, channel) else {
+                continue;
+            };
+            if next_level == 0 {
+                continue;
+            }
+            let fed_by_pos = next_level < level
+                || (channel == LightChannel::Sky
+                    && dir == IVec3::NEG_Y
+                    && level == MAX_LIGHT
+                    && next_level == MAX_LIGHT);
+            if fed_by_pos {
+                world.set_light(next, channel, 0);
+                dirty.add(next);
+                queue.push_back((next, next_level));
+            } else {
+                refill.push_back(next);
+            }
+        }
+        // Emitters in the darkened area keep shining
+        if channel == LightChannel::Block {
+            if let Some(voxel) = world.get_voxel(pos) {
+                let own = emission(voxel);
+                if own > 0 && world.light(pos, channel) < Some(own) {
+                    world.set_light(pos, channel, own);
+                    refill.push_back(pos);
+                }
+            }
+        }
+    }
+    refill
+}
+
+/// The light a voxel gives itself, before any neighbour shines into it
+fn own_light(world: &VoxelWorld, pos: IVec3, channel: LightChannel, voxel: VoxelType) -> u8 {
+    match channel {
+        LightChannel::Block => emission(voxel),
+        LightChannel::Sky if open_to_unloaded_sky(world, pos) => {
+            propagated(channel, MAX_LIGHT, IVec3::NEG_Y, voxel)
+        }
+        LightChannel::Sky => 0,
+    }
+}
+
+/// Recompute the light around a voxel that changed
+pub fn relight_voxel(world: &mut VoxelWorld, pos: IVec3, dirty: &mut DirtyChunks) {
+    let Some(voxel) = world.get_voxel(pos) else {
+        return;
+    };
+    for channel in LightChannel::ALL {
+        let old = world.light(pos, channel).unwrap_or(0);
+        world.set_light(pos, channel, 0);
+        dirty.add(pos);
+
+        let mut refill = if old > 0 {
+            unspread(world, channel, VecDeque::from([(pos, old)]), dirty)
+        } else {
+            VecDeque::new()
+        };
+
+        let own = own_light(world, pos, channel, voxel);
+        if own > world.light(pos, channel).unwrap_or(0) {
+            world.set_light(pos, channel, own);
+        }
+        refill.push_back(pos);
+        // Let the surrounding light back into the voxel
+        refill.extend(NEIGHBORS.iter().map(|dir| pos + *dir));
+        spread(world, channel, refill, dirty);
+    }
+}
+
+/// Light a freshly loaded chunk: open sky, its emitters and whatever shines
+/// in from lit neighbours. The chunk below loses sky light it was given
+/// while this chunk wasn't loaded and no longer gets.
+pub fn light_chunk(world: &mut VoxelWorld, chunk_pos: IVec3, dirty: &mut DirtyChunks) {
+    let Some(chunk) = world.get_chunk(chunk_pos) else {
+        return;
+    };
+    let origin = VoxelWorld::chunk_to_world(chunk_pos);
+    let size = CHUNK_SIZE_I32;
+    let opaque = chunk
+        .uniform_voxel()
+        .is_some_and(|voxel| attenuation(voxel).is_none() && emission(voxel) == 0);
+    let sky_open = !world.chunk_exists(chunk_pos + IVec3::Y);
+
+    if !opaque {
+        let mut sky = VecDeque::new();
+        let mut block = VecDeque::new();
+        for x in 0..size {
+            for y in 0..size {
+                for z in 0..size {
+                    let pos = origin + IVec3::new(x, y, z);
+                    let Some(voxel) = world.get_voxel(pos) else {
+                        continue;
+                    };
+                    let own = emission(voxel);
+                    if own > 0 {
+                        world.set_light(pos, LightChannel::Block, own);
+                        block.push_back(pos);
+                    }
+                    if sky_open && y == size - 1 {
+                        let level = propagated(LightChannel::Sky, MAX_LIGHT, IVec3::NEG_Y, voxel);
+                        if level > 0 {
+                            world.set_light(pos, LightChannel::Sky, level);
+                            sky.push_back(pos);
+                        }
+                    }
+                    // Light shining in from the neighbouring chunks
+                    let on_border = [x, y, z].iter().any(|c| *c == 0 || *c == size - 1);
+                    if on_border {
+                        for dir in NEIGHBORS {
+                            let next = pos + dir;
+                            if VoxelWorld::world_to_chunk(next) != chunk_pos {
+                                sky.push_back(next);
+                                block.push_back(next);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        spread(world, LightChannel::Sky, sky, dirty);
+        spread(world, LightChannel::Block, block, dirty);
+    }
+
+    // The chunk below assumed open sky while this one was missing
+    let below_origin = origin - IVec3::Y;
+    let mut stale = VecDeque::new();
+    for x in 0..size {
+        for z in 0..size {
+            let below = below_origin + IVec3::new(x, 0, z);
+            let above = below + IVec3::Y;
+            if world.light(below, LightChannel::Sky) == Some(MAX_LIGHT)
+                && world.light(above, LightChannel::Sky) != Some(MAX_LIGHT)
+            {
+                world.set_light(below, LightChannel::Sky, 0);
+                dirty.add(below);
+                stale.push_back((below, MAX_LIGHT));
+            }
+        }
+    }
+    if !stale.is_empty() {
+        let refill = unspread(world, LightChannel::Sky, stale, dirty);
+        spread(world, LightChannel::Sky, refill, dirty);
+    }
+
+    if let Some(chunk) = world.get_chunk_mut(chunk_pos) {
+        chunk.mark_lit();
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct LightSettings {
+    /// Newly loaded chunks lit per frame; the rest wait, unmeshed
+    pub max_chunks_per_frame: usize,
+}
+
+impl Default for LightSettings {
+    fn default() -> Self {
+        Self {
+            max_chunks_per_frame: 16,
+        }
+    }
+}
+
+/// Sky and block light for the client's chunk meshes
+pub struct LightPlugin;
+
+impl Plugin for LightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LightSettings>().add_systems(
+            Update,
+            (update_light_for_edits, light_new_chunks_system).chain(),
+        );
+    }
+}
+
+fn update_light_for_edits(mut changes: MessageReader<VoxelChanged>, mut world: ResMut<VoxelWorld>) {
+    let mut dirty = DirtyChunks::default();
+    for change in changes.read() {
+        // Unlit chunks get everything when they are lit
+        let lit = world
+            .get_chunk(VoxelWorld::world_to_chunk(change.position))
+            .is_some_and(|chunk| chunk.is_lit());
+        if lit {
+            relight_voxel(&mut world, change.position, &mut dirty);
+        }
+    }
+    dirty.mark(&mut world);
+}
+
+/// Light chunks that loaded since the last frame, highest first so sky
+/// light comes from above before the chunks below need it
+pub fn light_new_chunks_system(settings: Res<LightSettings>, mut world: ResMut<VoxelWorld>) {
+    let mut unlit: Vec<IVec3> = world
+        .loaded_chunk_positions()
+        .filter(|pos| world.get_chunk(*pos).is_some_and(|chunk| !chunk.is_lit()))
+        .collect();
+    if unlit.is_empty() {
+        return;
+    }
+    unlit.sort_by_key(|pos| (-pos.y, pos.x, pos.z));
+
+    let mut dirty = DirtyChunks::default();
+    for chunk_pos in unlit.into_iter().take(settings.max_chunks_per_frame) {
+        light_chunk(&mut world, chunk_pos, &mut dirty);
+    }
+    dirty.mark(&mut world);
+}
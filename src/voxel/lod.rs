@@ -0,0 +1,625 @@
+//! Clipmap-style level of detail.
+//!
+//! The ground around the camera is split into a quadtree of `LodTile`s. A
+//! level-n tile covers 2^n x 2^n chunk columns (and 2^n chunks vertically)
+//! and is drawn from a single chunk of voxels downsampled 2^n times along
+//! each axis, so every level costs about the same to mesh while reaching
+//! twice as far as the one before. Level-0 tiles are the loaded chunks
+//! themselves and mesh as usual; loaded chunks under a coarser tile are
+//! culled. Seams between levels are closed with skirts (see `skirt.rs`),
+//! and blocky tiles draw their sides for the same reason.
+
+use crate::camera::controller::PlayerCamera;
+use crate::constants::{CHUNK_SIZE, CHUNK_SIZE_I32};
+use crate::rendering::materials::{VoxelMaterial, WaterMaterial};
+use crate::rendering::triplanar_material::TriplanarMaterialHandle;
+use crate::rendering::AmbientOcclusionConfig;
+use crate::voxel::chunk::{Chunk, LodLevel};
+use crate::voxel::mesh_jobs::MeshJobSettings;
+use crate::voxel::meshing::{generate_chunk_mesh_with_mode, ChunkMeshResult, MeshMode, MeshSettings};
+use crate::voxel::plugin::LodSettings;
+use crate::voxel::skirt::{NeighborLods, SkirtConfig};
+use crate::voxel::streaming::ChunkStreamingSettings;
+use crate::voxel::types::{Voxel, VoxelType};
+use crate::voxel::world::{VoxelChanged, VoxelWorld};
+use crate::voxel::worldgen::{GenerationStats, WorldGen};
+use bevy::prelude::*;
+use bevy::tasks::AsyncComputeTaskPool;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+
+/// Coarsest supported tile level; a level-5 tile is 512 voxels across
+pub const MAX_LOD_LEVEL: u8 = 5;
+
+/// Tiles up to this level are downsampled from the loaded chunks when all of
+/// them are in memory, so edits near the player show up in them
+const MAX_DOWNSAMPLED_LEVEL: u8 = 2;
+
+/// Source voxels sampled per axis for each downsampled voxel, at most
+const DOWNSAMPLE_SAMPLES: i32 = 4;
+
+/// A cube of 2^level chunks along each axis, meshed as one chunk
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LodTile {
+    pub level: u8,
+    /// Position in tiles of this level: the chunk position of its min corner
+    /// divided by `size_chunks`
+    pub position: IVec3,
+}
+
+impl LodTile {
+    /// The tile of `level` that contains a chunk
+    pub fn containing(chunk_pos: IVec3, level: u8) -> Self {
+        Self {
+            level,
+            position: chunk_pos.div_euclid(IVec3::splat(1 << level)),
+        }
+    }
+
+    pub fn lod_level(&self) -> LodLevel {
+        LodLevel::Level(self.level)
+    }
+
+    /// Chunks covered along each axis, which is also the world size of one
+    /// of its voxels
+    pub fn size_chunks(&self) -> i32 {
+        1 << self.level
+    }
+
+    pub fn min_chunk(&self) -> IVec3 {
+        self.position * self.size_chunks()
+    }
+
+    /// World position of the tile's min corner
+    pub fn origin(&self) -> IVec3 {
+        VoxelWorld::chunk_to_world(self.min_chunk())
+    }
+
+    pub fn contains_chunk(&self, chunk_pos: IVec3) -> bool {
+        Self::containing(chunk_pos, self.level) == *self
+    }
+
+    /// Every chunk the tile covers
+    pub fn chunk_positions(&self) -> impl Iterator<Item = IVec3> {
+        let min = self.min_chunk();
+        let size = self.size_chunks();
+        (0..size * size * size)
+            .map(move |i| min + IVec3::new(i % size, (i / size) % size, i / (size * size)))
+    }
+}
+
+/// One chunk at `tile.position` whose voxels each stand for a 2^level cube
+/// of the world read through `voxel_at`: the most common solid voxel when at
+/// least half the cube is solid, otherwise liquid or air, whichever fills
+/// more of the rest.
+pub fn downsample(tile: LodTile, voxel_at: impl Fn(IVec3) -> VoxelType) -> Chunk {
+    let scale = tile.size_chunks();
+    let step = (scale / DOWNSAMPLE_SAMPLES).max(1) as usize;
+    let origin = tile.origin();
+    let mut chunk = Chunk::new(tile.position);
+    let mut counts: Vec<(VoxelType, u32)> = Vec::new();
+
+    for x in 0..CHUNK_SIZE as u32 {
+        for y in 0..CHUNK_SIZE as u32 {
+            for z in 0..CHUNK_SIZE as u32 {
+                let local = UVec3::new(x, y, z);
+                let corner = origin + local.as_ivec3() * scale;
+                counts.clear();
+                let (mut total, mut solid, mut liquid) = (0, 0, 0);
+
+                for dx in (0..scale).step_by(step) {
+                    for dy in (0..scale).step_by(step) {
+                        for dz in (0..scale).step_by(step) {
+                            let voxel = voxel_at(corner + IVec3::new(dx, dy, dz));
+                            total += 1;
+                            if voxel.is_solid() {
+                                solid += 1;
+                            } else if voxel.is_liquid() {
+                                liquid += 1;
+                            } else {
+                                continue;
+                            }
+                            match counts.iter_mut().find(|(counted, _)| *counted == voxel) {
+                                Some((_, count)) => *count += 1,
+                                None => counts.push((voxel, 1)),
+                            }
+                        }
+                    }
+                }
+
+                let air = total - solid - liquid;
+                let voxel = if solid * 2 >= total {
+                    most_common(&counts, |voxel| voxel.is_solid())
+                } else if liquid > 0 && liquid >= air {
+                    most_common(&counts, |voxel| voxel.is_liquid())
+                } else {
+                    VoxelType::Air
+                };
+                if voxel != VoxelType::Air {
+                    chunk.set(local, voxel);
+                }
+            }
+        }
+    }
+    chunk
+}
+
+fn most_common(counts: &[(VoxelType, u32)], filter: impl Fn(&VoxelType) -> bool) -> VoxelType {
+    counts
+        .iter()
+        .filter(|(voxel, _)| filter(voxel))
+        .max_by_key(|(_, count)| *count)
+        .map_or(VoxelType::Air, |(voxel, _)| *voxel)
+}
+
+/// Which chunks and tiles are drawn for one camera position
+#[derive(Clone, Debug, Default)]
+pub struct LodSelection {
+    /// Chunk columns (x, z) drawn from their loaded chunks
+    pub full_detail: HashSet<IVec2>,
+    /// Downsampled tiles, level 1 and up
+    pub tiles: HashSet<LodTile>,
+}
+
+impl LodSelection {
+    /// Split the ground into tiles that get coarser with distance: a tile is
+    /// replaced by its four children while the camera is closer than
+    /// `LodSettings::split_distance` for its level. Tiles span the streamed
+    /// chunk layers vertically.
+    pub fn select(
+        camera_pos: Vec3,
+        settings: &LodSettings,
+        streaming: &ChunkStreamingSettings,
+    ) -> Self {
+        let mut selection = Self::default();
+        let root_level = settings.max_level.min(MAX_LOD_LEVEL);
+        let root_size = CHUNK_SIZE_I32 << root_level;
+        let camera = IVec2::new(camera_pos.x.floor() as i32, camera_pos.z.floor() as i32);
+        let center = camera.div_euclid(IVec2::splat(root_size));
+        let radius = (settings.cull_distance / root_size as f32).ceil() as i32 + 1;
+
+        for dx in -radius..=radius {
+            for dz in -radius..=radius {
+                selection.visit(
+                    root_level,
+                    center + IVec2::new(dx, dz),
+                    camera_pos,
+                    settings,
+                    streaming,
+                );
+            }
+        }
+        selection
+    }
+
+    fn visit(
+        &mut self,
+        level: u8,
+        column: IVec2,
+        camera_pos: Vec3,
+        settings: &LodSettings,
+        streaming: &ChunkStreamingSettings,
+    ) {
+        let size = (CHUNK_SIZE_I32 << level) as f32;
+        let min = column.as_vec2() * size;
+        let camera = Vec2::new(camera_pos.x, camera_pos.z);
+        let distance = camera.clamp(min, min + Vec2::splat(size)).distance(camera);
+        if distance > settings.cull_distance {
+            return;
+        }
+
+        if level == 0 {
+            self.full_detail.insert(column);
+        } else if distance < settings.split_distance(level) {
+            for child in [IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE] {
+                self.visit(level - 1, column * 2 + child, camera_pos, settings, streaming);
+            }
+        } else {
+            let tile_chunks = 1 << level;
+            let min_y = streaming.min_chunk_y.div_euclid(tile_chunks);
+            let max_y = (streaming.max_chunk_y - 1).div_euclid(tile_chunks);
+            for y in min_y..=max_y {
+                self.tiles.insert(LodTile {
+                    level,
+                    position: IVec3::new(column.x, y, column.y),
+                });
+            }
+        }
+    }
+
+    /// Level a chunk's area is drawn at
+    pub fn level_of_chunk(&self, chunk_pos: IVec3) -> LodLevel {
+        if self.full_detail.contains(&IVec2::new(chunk_pos.x, chunk_pos.z)) {
+            return LodLevel::FULL;
+        }
+        (1..=MAX_LOD_LEVEL)
+            .find(|level| self.tiles.contains(&LodTile::containing(chunk_pos, *level)))
+            .map_or(LodLevel::Culled, LodLevel::Level)
+    }
+
+    /// Selected tiles over a chunk that are downsampled from the loaded
+    /// chunks, so a chunk loading or changing there shows up in them
+    pub fn downsampled_tiles_over(&self, world: &VoxelWorld, chunk_pos: IVec3) -> Vec<LodTile> {
+        (1..=MAX_DOWNSAMPLED_LEVEL)
+            .map(|level| LodTile::containing(chunk_pos, level))
+            .filter(|tile| {
+                self.tiles.contains(tile)
+                    && tile.chunk_positions().all(|pos| world.chunk_exists(pos))
+            })
+            .collect()
+    }
+
+    /// Levels drawn next to each side of a chunk or tile. Along any side
+    /// there is either one coarser tile or any number of equal or finer
+    /// ones, so checking a single chunk per side is enough for skirts.
+    pub fn neighbor_lods(&self, tile: LodTile) -> NeighborLods {
+        let min = tile.min_chunk();
+        let size = tile.size_chunks();
+        NeighborLods {
+            neg_x: Some(self.level_of_chunk(min + IVec3::new(-1, 0, 0))),
+            pos_x: Some(self.level_of_chunk(min + IVec3::new(size, 0, 0))),
+            neg_z: Some(self.level_of_chunk(min + IVec3::new(0, 0, -1))),
+            pos_z: Some(self.level_of_chunk(min + IVec3::new(0, 0, size))),
+        }
+    }
+}
+
+/// Mesh entities of a drawn tile
+#[derive(Default)]
+struct TileEntities {
+    solid: Option<Entity>,
+    water: Option<Entity>,
+}
+
+struct LodJobResult {
+    tile: LodTile,
+    job_id: u64,
+    mesh: ChunkMeshResult,
+}
+
+/// Marks the mesh entities of a downsampled tile
+#[derive(Component)]
+pub struct LodTileMesh {
+    pub tile: LodTile,
+}
+
+/// The current `LodSelection` and the tiles being built or drawn for it
+#[derive(Resource)]
+pub struct LodTiles {
+    selection: LodSelection,
+    /// Camera chunk the selection was made for
+    selected_at: Option<IVec3>,
+    /// Selected tiles without a mesh or job yet, farthest first
+    pending: Vec<LodTile>,
+    in_flight: HashMap<LodTile, u64>,
+    entities: HashMap<LodTile, TileEntities>,
+    next_job_id: u64,
+    sender: Sender<LodJobResult>,
+    receiver: Arc<Mutex<Receiver<LodJobResult>>>,
+}
+
+impl Default for LodTiles {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            selection: LodSelection::default(),
+            selected_at: None,
+            pending: Vec::new(),
+            in_flight: HashMap::new(),
+            entities: HashMap::new(),
+            next_job_id: 0,
+            sender,
+            receiver: Arc::new(Mutex::new(receiver)),
+        }
+    }
+}
+
+impl LodTiles {
+    pub fn selection(&self) -> &LodSelection {
+        &self.selection
+    }
+
+    /// Tiles with a mesh on screen
+    pub fn drawn_count(&self) -> usize {
+        self.entities.len()
+    }
+}
+
+/// Reselect tiles when the camera crosses a chunk border, give every loaded
+/// chunk the level its area is drawn at, and rebuild tiles whose chunks
+/// loaded or changed
+pub fn select_lod_tiles_system(
+    mut commands: Commands,
+    mut tiles: ResMut<LodTiles>,
+    mut world: ResMut<VoxelWorld>,
+    mut changes: MessageReader<VoxelChanged>,
+    lod_settings: Res<LodSettings>,
+    streaming: Res<ChunkStreamingSettings>,
+    camera_query: Query<&Transform, With<PlayerCamera>>,
+) {
+    let Some(camera) = camera_query.iter().next() else {
+        return;
+    };
+    let camera_pos = camera.translation;
+    let camera_chunk = VoxelWorld::world_to_chunk(camera_pos.floor().as_ivec3());
+
+    if tiles.selected_at != Some(camera_chunk) || lod_settings.is_changed() {
+        let selection = LodSelection::select(camera_pos, &lod_settings, &streaming);
+        let tiles = &mut *tiles;
+
+        let dropped: Vec<LodTile> = tiles
+            .entities
+            .keys()
+            .chain(tiles.in_flight.keys())
+            .filter(|tile| !selection.tiles.contains(*tile))
+            .copied()
+            .collect();
+        for tile in dropped {
+            tiles.in_flight.remove(&tile);
+            if let Some(entities) = tiles.entities.remove(&tile) {
+                for entity in [entities.solid, entities.water].into_iter().flatten() {
+                    commands.entity(entity).despawn();
+                }
+            }
+        }
+
+        // New tiles, and tiles whose neighbours changed level (their skirts
+        // no longer fit), are (re)built
+        let previous = &tiles.selection;
+        let stale = |tile: &LodTile| {
+            !previous.tiles.contains(tile)
+                || previous.neighbor_lods(*tile) != selection.neighbor_lods(*tile)
+        };
+        let pending: Vec<LodTile> = selection
+            .tiles
+            .iter()
+            .filter(|tile| {
+                stale(tile)
+                    || !(tiles.entities.contains_key(*tile) || tiles.in_flight.contains_key(*tile))
+            })
+            .copied()
+            .collect();
+        for tile in &pending {
+            tiles.in_flight.remove(tile);
+        }
+        tiles.pending = pending;
+
+        let tile_distance = |tile: &LodTile| {
+            let half = (tile.size_chunks() * CHUNK_SIZE_I32) as f32 * 0.5;
+            let center = tile.origin().as_vec3() + Vec3::splat(half);
+            Vec2::new(center.x - camera_pos.x, center.z - camera_pos.z).length_squared()
+        };
+        tiles
+            .pending
+            .sort_by(|a, b| tile_distance(b).total_cmp(&tile_distance(a)));
+
+        tiles.selection = selection;
+        tiles.selected_at = Some(camera_chunk);
+    }
+
+    // Chunks stream in every frame, so levels are applied to all of them
+    let mut lod_changed: Vec<IVec3> = Vec::new();
+    for (chunk_pos, chunk) in world.chunk_entries_mut() {
+        if chunk.set_lod_level(tiles.selection.level_of_chunk(*chunk_pos)) {
+            lod_changed.push(*chunk_pos);
+        }
+    }
+
+    // Chunks that just loaded under a tile leave the full level they start
+    // at. Tiles built before they loaded came from the world generator.
+    let mut refreshed: HashSet<IVec3> = changes
+        .read()
+        .map(|change| VoxelWorld::world_to_chunk(change.position))
+        .collect();
+    refreshed.extend(lod_changed.iter().copied());
+    let stale: HashSet<LodTile> = refreshed
+        .into_iter()
+        .flat_map(|chunk_pos| tiles.selection.downsampled_tiles_over(&world, chunk_pos))
+        .collect();
+    for tile in stale {
+        // A job already running keeps its slot; the newer one replaces it
+        if !tiles.pending.contains(&tile) {
+            tiles.pending.push(tile);
+        }
+    }
+
+    for chunk_pos in lod_changed {
+        for offset in [
+            IVec3::new(-1, 0, 0),
+            IVec3::new(1, 0, 0),
+            IVec3::new(0, 0, -1),
+            IVec3::new(0, 0, 1),
+        ] {
+            if let Some(neighbor) = world.get_chunk_mut(chunk_pos + offset) {
+                neighbor.mark_dirty();
+            }
+        }
+    }
+}
+
+/// Build pending tiles on the `AsyncComputeTaskPool`: downsample them from
+/// loaded chunks when possible, otherwise sample the world generator
+#[allow(clippy::too_many_arguments)]
+pub fn build_lod_tiles_system(
+    mut tiles: ResMut<LodTiles>,
+    world: Res<VoxelWorld>,
+    world_gen: Res<WorldGen>,
+    job_settings: Res<MeshJobSettings>,
+    mesh_settings: Res<MeshSettings>,
+    lod_settings: Res<LodSettings>,
+    skirt_config: Res<SkirtConfig>,
+    ao_config: Res<AmbientOcclusionConfig>,
+) {
+    let mut started = 0;
+    while started < job_settings.max_jobs_per_frame
+        && tiles.in_flight.len() < job_settings.max_in_flight
+    {
+        let Some(tile) = tiles.pending.pop() else {
+            break;
+        };
+
+        // Copies of the covered chunks, when every one of them is loaded
+        let source: Option<Vec<Chunk>> = (tile.level <= MAX_DOWNSAMPLED_LEVEL)
+            .then(|| {
+                tile.chunk_positions()
+                    .map(|pos| world.get_chunk(pos).map(|chunk| chunk.snapshot()))
+                    .collect()
+            })
+            .flatten();
+
+        tiles.next_job_id += 1;
+        let job_id = tiles.next_job_id;
+        tiles.in_flight.insert(tile, job_id);
+
+        let sender = tiles.sender.clone();
+        let world_gen = world_gen.clone();
+        let settings = MeshSettings {
+            mode: lod_settings.low_detail_mode,
+            greedy: mesh_settings.greedy,
+        };
+        let neighbor_lods = tiles.selection.neighbor_lods(tile);
+        let skirt_config = skirt_config.clone();
+        let baked_ao = ao_config.baked.clone();
+
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                let chunk = match source {
+                    Some(chunks) => {
+                        let chunks: HashMap<IVec3, Chunk> =
+                            chunks.into_iter().map(|chunk| (chunk.position(), chunk)).collect();
+                        downsample(tile, |pos| {
+                            chunks
+                                .get(&VoxelWorld::world_to_chunk(pos))
+                                .map_or(VoxelType::Air, |chunk| {
+                                    chunk.get(VoxelWorld::world_to_local(pos))
+                                })
+                        })
+                    }
+                    None => world_gen.generate_lod_chunk(tile, &mut GenerationStats::default()),
+                };
+
+                // Surrounded by air, so blocky tiles close their sides
+                let mut tile_world = VoxelWorld::new(IVec3::ONE);
+                for dx in -1..=1 {
+                    for dy in 0..=1 {
+                        for dz in -1..=1 {
+                            let offset = IVec3::new(dx, dy, dz);
+                            if offset != IVec3::ZERO {
+                                tile_world.insert_chunk(Chunk::new(tile.position + offset));
+                            }
+                        }
+                    }
+                }
+                tile_world.insert_chunk(chunk);
+
+                let Some(chunk) = tile_world.get_chunk(tile.position) else {
+                    return;
+                };
+                let mesh = generate_chunk_mesh_with_mode(
+                    chunk,
+                    &tile_world,
+                    settings,
+                    tile.lod_level(),
+                    neighbor_lods,
+                    &skirt_config,
+                    &baked_ao,
+                );
+                // The resource only disappears when the app shuts down
+                let _ = sender.send(LodJobResult { tile, job_id, mesh });
+            })
+            .detach();
+
+        started += 1;
+    }
+}
+
+/// Spawn or update the mesh entities of finished tiles
+#[allow(clippy::too_many_arguments)]
+pub fn apply_lod_meshes_system(
+    mut commands: Commands,
+    mut tiles: ResMut<LodTiles>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    job_settings: Res<MeshJobSettings>,
+    lod_settings: Res<LodSettings>,
+    blocky_material: Option<Res<VoxelMaterial>>,
+    triplanar_material: Res<TriplanarMaterialHandle>,
+    water_material: Res<WaterMaterial>,
+) {
+    // Results stay queued until the blocky material has loaded
+    let Some(blocky_material) = blocky_material else {
+        return;
+    };
+
+    let finished: Vec<LodJobResult> = {
+        let Ok(receiver) = tiles.receiver.lock() else {
+            warn!("LOD tile result queue is poisoned");
+            return;
+        };
+        let mut finished = Vec::new();
+        while finished.len() < job_settings.max_results_per_frame {
+            match receiver.try_recv() {
+                Ok(result) => finished.push(result),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        finished
+    };
+
+    for result in finished {
+        let tile = result.tile;
+        if tiles.in_flight.get(&tile) != Some(&result.job_id) {
+            continue;
+        }
+        tiles.in_flight.remove(&tile);
+
+        let transform = Transform::from_translation(tile.origin().as_vec3())
+            .with_scale(Vec3::splat(tile.size_chunks() as f32));
+        let entities = tiles.entities.entry(tile).or_default();
+
+        let solid = (!result.mesh.solid.is_empty()).then(|| meshes.add(result.mesh.solid.into_mesh()));
+        match (solid, entities.solid) {
+            (Some(mesh), Some(entity)) => {
+                commands.entity(entity).insert(Mesh3d(mesh));
+            }
+            (Some(mesh), None) => {
+                let mut entity = commands.spawn((Mesh3d(mesh), transform, LodTileMesh { tile }));
+                match lod_settings.low_detail_mode {
+                    MeshMode::Blocky => entity.insert(MeshMaterial3d(blocky_material.handle.clone())),
+                    MeshMode::SurfaceNets => {
+                        entity.insert(MeshMaterial3d(triplanar_material.handle.clone()))
+                    }
+                };
+                entities.solid = Some(entity.id());
+            }
+            (None, Some(entity)) => {
+                commands.entity(entity).despawn();
+                entities.solid = None;
+            }
+            (None, None) => {}
+        }
+
+        let water = (!result.mesh.water.is_empty()).then(|| meshes.add(result.mesh.water.into_mesh()));
+        match (water, entities.water) {
+            (Some(mesh), Some(entity)) => {
+                commands.entity(entity).insert(Mesh3d(mesh));
+            }
+            (Some(mesh), None) => {
+                let entity = commands
+                    .spawn((
+                        Mesh3d(mesh),
+                        MeshMaterial3d(water_material.handle.clone()),
+                        transform,
+                        LodTileMesh { tile },
+                    ))
+                    .id();
+                entities.water = Some(entity);
+            }
+            (None, Some(entity)) => {
+                commands.entity(entity).despawn();
+                entities.water = None;
+            }
+            (None, None) => {}
+        }
+    }
+}
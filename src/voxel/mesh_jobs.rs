@@ -0,0 +1,156 @@
+use crate::voxel::meshing::ChunkMeshResult;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+
+/// Per-frame limits for background chunk meshing
+#[derive(Resource, Clone, Debug)]
+pub struct MeshJobSettings {
+    /// New meshing tasks spawned per frame
+    pub max_jobs_per_frame: usize,
+    /// Finished meshes uploaded and spawned per frame
+    pub max_results_per_frame: usize,
+    /// Upper bound on tasks running at once
+    pub max_in_flight: usize,
+}
+
+impl Default for MeshJobSettings {
+    fn default() -> Self {
+        Self {
+            max_jobs_per_frame: 16,
+            max_results_per_frame: 16,
+            max_in_flight: 64,
+        }
+    }
+}
+
+/// Mesh produced by a background task
+pub struct MeshJobResult {
+    pub chunk_pos: IVec3,
+    pub job_id: u64,
+    pub mesh: ChunkMeshResult,
+}
+
+struct InFlightJob {
+    job_id: u64,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Tracks meshing tasks running on the `AsyncComputeTaskPool` and the
+/// queue their results come back through.
+///
+/// Only the newest job per chunk is kept; when a chunk is re-dirtied its
+/// running job is cancelled and any result it still sends is dropped.
+#[derive(Resource)]
+pub struct MeshJobQueue {
+    next_job_id: u64,
+    in_flight: HashMap<IVec3, InFlightJob>,
+    sender: Sender<MeshJobResult>,
+    receiver: Arc<Mutex<Receiver<MeshJobResult>>>,
+}
+
+impl Default for MeshJobQueue {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            next_job_id: 0,
+            in_flight: HashMap::new(),
+            sender,
+            receiver: Arc::new(Mutex::new(receiver)),
+        }
+    }
+}
+
+/// Handle passed into a meshing task
+pub struct MeshJobTicket {
+    pub chunk_pos: IVec3,
+    pub job_id: u64,
+    cancelled: Arc<AtomicBool>,
+    sender: Sender<MeshJobResult>,
+}
+
+impl MeshJobTicket {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Send the finished mesh back to the main thread
+    pub fn finish(self, mesh: ChunkMeshResult) {
+        if self.is_cancelled() {
+            return;
+        }
+        // The queue only disappears when the app shuts down
+        let _ = self.sender.send(MeshJobResult {
+            chunk_pos: self.chunk_pos,
+            job_id: self.job_id,
+            mesh,
+        });
+    }
+}
+
+impl MeshJobQueue {
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    pub fn is_in_flight(&self, chunk_pos: IVec3) -> bool {
+        self.in_flight.contains_key(&chunk_pos)
+    }
+
+    /// Register a new job for `chunk_pos`, cancelling any older one
+    pub fn start(&mut self, chunk_pos: IVec3) -> MeshJobTicket {
+        self.cancel(chunk_pos);
+
+        self.next_job_id += 1;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.in_flight.insert(
+            chunk_pos,
+            InFlightJob {
+                job_id: self.next_job_id,
+                cancelled: cancelled.clone(),
+            },
+        );
+
+        MeshJobTicket {
+            chunk_pos,
+            job_id: self.next_job_id,
+            cancelled,
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// Cancel the running job for a chunk, if any
+    pub fn cancel(&mut self, chunk_pos: IVec3) {
+        if let Some(job) = self.in_flight.remove(&chunk_pos) {
+            job.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Take up to `max` finished results that are still current
+    pub fn drain_finished(&mut self, max: usize) -> Vec<MeshJobResult> {
+        let mut finished = Vec::new();
+        let Ok(receiver) = self.receiver.lock() else {
+            warn!("Mesh result queue is poisoned");
+            return finished;
+        };
+
+        while finished.len() < max {
+            let result = match receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+            };
+
+            let current = self
+                .in_flight
+                .get(&result.chunk_pos)
+                .is_some_and(|job| job.job_id == result.job_id);
+            if current {
+                self.in_flight.remove(&result.chunk_pos);
+                finished.push(result);
+            }
+        }
+        finished
+    }
+}
@@ -5,6 +5,8 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use crate::constants::{CHUNK_SIZE, VOXEL_SIZE};
 use crate::rendering::ao_config::BakedAoConfig;
 use crate::voxel::chunk::{Chunk, LodLevel};
+use crate::voxel::fluid::MAX_FLUID_LEVEL;
+use crate::voxel::light::{attenuation, LightChannel, MAX_LIGHT};
 use crate::voxel::baked_ao::compute_surface_nets_ao;
 use crate::voxel::skirt::{extract_boundary_edges, generate_skirts, NeighborLods, SkirtConfig};
 use crate::voxel::types::{VoxelType, Voxel};
@@ -39,6 +41,8 @@ pub struct MeshData {
     pub normals: Vec<[f32; 3]>,
     pub uvs: Vec<[f32; 2]>,
     pub colors: Vec<[f32; 4]>, // Vertex colors for AO (blocky) or material weights (surface nets)
+    /// Sky and block light per vertex (0..1), written as UV_1; empty for water
+    pub light: Vec<[f32; 2]>,
     pub indices: Vec<u32>,
 }
 
@@ -49,6 +53,7 @@ impl MeshData {
             normals: Vec::new(),
             uvs: Vec::new(),
             colors: Vec::new(),
+            light: Vec::new(),
             indices: Vec::new(),
         }
     }
@@ -63,6 +68,9 @@ impl MeshData {
         mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals);
         mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs);
         mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, self.colors);
+        if !self.light.is_empty() {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_UV_1, self.light);
+        }
         mesh.insert_indices(Indices::U32(self.indices));
         mesh
     }
@@ -147,6 +155,32 @@ fn check_water_face(
     }
 }
 
+fn face_offset(face: Face) -> IVec3 {
+    match face {
+        Face::Top => IVec3::Y,
+        Face::Bottom => IVec3::NEG_Y,
+        Face::North => IVec3::NEG_Z,
+        Face::South => IVec3::Z,
+        Face::East => IVec3::X,
+        Face::West => IVec3::NEG_X,
+    }
+}
+
+/// Sky and block light at a voxel scaled to 0..1. Voxels in chunks that are
+/// missing or not lit yet count as open sky, so unlit geometry isn't black.
+fn sample_light(world: &VoxelWorld, pos: IVec3) -> [f32; 2] {
+    match world.get_chunk(VoxelWorld::world_to_chunk(pos)) {
+        Some(chunk) if chunk.is_lit() => {
+            let local = VoxelWorld::world_to_local(pos);
+            [
+                chunk.light(local, LightChannel::Sky) as f32 / MAX_LIGHT as f32,
+                chunk.light(local, LightChannel::Block) as f32 / MAX_LIGHT as f32,
+            ]
+        }
+        _ => [1.0, 0.0],
+    }
+}
+
 fn is_face_visible(
     chunk: &Chunk,
     world: &VoxelWorld,
@@ -345,30 +379,23 @@ fn get_face_ao(
 
 /// Get the atlas index for a voxel face (supports face-specific textures)
 fn get_face_atlas_index(voxel: VoxelType, face: Face) -> u8 {
-    match voxel {
-        VoxelType::TopSoil => {
-            match face {
-                Face::Top => 0,    // Grass top texture
-                Face::Bottom => 1, // Dirt texture
-                _ => 7,            // Grass side texture (uses slot 7)
-            }
-        }
-        _ => voxel.atlas_index(),
-    }
+    let info = voxel.info();
+    let face_override = match face {
+        Face::Top => info.atlas_top,
+        Face::Bottom => info.atlas_bottom,
+        _ => info.atlas_side,
+    };
+    face_override.unwrap_or(info.atlas_index)
 }
 
 /// Map voxel/face to blocky texture array layer (grass, dirt, rock, sand).
 fn get_blocky_material_index(voxel: VoxelType, face: Face) -> u8 {
-    match voxel {
-        VoxelType::TopSoil => match face {
-            Face::Bottom => 1, // Dirt
-            _ => 0,            // Grass
-        },
-        VoxelType::SubSoil | VoxelType::Clay | VoxelType::Wood | VoxelType::Leaves => 1, // Dirt
-        VoxelType::Rock | VoxelType::Bedrock | VoxelType::DungeonWall | VoxelType::DungeonFloor => 2, // Rock
-        VoxelType::Sand => 3, // Sand
-        _ => 0, // Default to grass for unsupported/air/water
-    }
+    let info = voxel.info();
+    let material = match face {
+        Face::Bottom => info.bottom_material.unwrap_or(info.material),
+        _ => info.material,
+    };
+    material.blocky_layer()
 }
 
 fn add_face_with_ao(
@@ -433,6 +460,12 @@ fn add_face_with_ao(
     mesh_data.colors.push([ao[1], ao[1], ao[1], material_index]);
     mesh_data.colors.push([ao[2], ao[2], ao[2], material_index]);
     mesh_data.colors.push([ao[3], ao[3], ao[3], material_index]);
+
+    // Faces are lit by the voxel they face
+    let chunk_origin = VoxelWorld::chunk_to_world(chunk.position());
+    let facing = chunk_origin + local.as_ivec3() + face_offset(face);
+    let light = sample_light(world, facing);
+    mesh_data.light.extend([light; 4]);
     
     // Face-specific texture
     let atlas_idx = get_face_atlas_index(voxel, face);
@@ -587,6 +620,228 @@ fn add_face_no_ao(
     mesh_data.indices.push(start_idx + 2);
 }
 
+// =============================================================================
+// Greedy Blocky Meshing
+// =============================================================================
+
+/// Texture repeats every this many voxels on greedy quads
+const GREEDY_TEXTURE_PERIOD: i32 = 4;
+
+const ALL_FACES: [Face; 6] = [
+    Face::Top,
+    Face::Bottom,
+    Face::North,
+    Face::South,
+    Face::East,
+    Face::West,
+];
+
+/// Face normal, then the two in-plane axes quads grow along
+fn face_axes(face: Face) -> (IVec3, IVec3, IVec3) {
+    match face {
+        Face::Top => (IVec3::Y, IVec3::X, IVec3::Z),
+        Face::Bottom => (IVec3::NEG_Y, IVec3::X, IVec3::Z),
+        Face::North => (IVec3::NEG_Z, IVec3::X, IVec3::Y),
+        Face::South => (IVec3::Z, IVec3::X, IVec3::Y),
+        Face::East => (IVec3::X, IVec3::Z, IVec3::Y),
+        Face::West => (IVec3::NEG_X, IVec3::Z, IVec3::Y),
+    }
+}
+
+/// Everything a face is drawn with; neighbouring faces merge only when this
+/// matches exactly
+#[derive(Clone, Copy, PartialEq)]
+struct GreedyFace {
+    material: u8,
+    /// AO at the (-u,-v), (+u,-v), (+u,+v) and (-u,+v) corners
+    ao: [f32; 4],
+    light: [f32; 2],
+}
+
+impl GreedyFace {
+    /// Stretching along u keeps the AO gradient only if it doesn't vary along u
+    fn extends_along_u(&self) -> bool {
+        self.ao[0] == self.ao[1] && self.ao[3] == self.ao[2]
+    }
+
+    fn extends_along_v(&self) -> bool {
+        self.ao[0] == self.ao[3] && self.ao[1] == self.ao[2]
+    }
+}
+
+/// AO for the corners of a face in `GreedyFace::ao` order
+fn greedy_face_ao(
+    chunk: &Chunk,
+    world: &VoxelWorld,
+    local: UVec3,
+    face: Face,
+    ao_config: &BakedAoConfig,
+) -> [f32; 4] {
+    let (normal, u, v) = face_axes(face);
+    [(-1, -1), (1, -1), (1, 1), (-1, 1)].map(|(su, sv)| {
+        let side1 = is_solid_at_offset(chunk, world, local, normal + u * su);
+        let side2 = is_solid_at_offset(chunk, world, local, normal + v * sv);
+        let corner = is_solid_at_offset(chunk, world, local, normal + u * su + v * sv);
+        calculate_vertex_ao(side1, side2, corner, ao_config)
+    })
+}
+
+/// Blocky meshing that merges coplanar faces sharing a material, AO and
+/// light into larger quads. Produces the same surface as
+/// `generate_chunk_mesh` with far fewer vertices on flat terrain. Water
+/// faces are still emitted one per voxel.
+pub fn generate_chunk_mesh_greedy(
+    chunk: &Chunk,
+    world: &VoxelWorld,
+    ao_config: &BakedAoConfig,
+) -> ChunkMeshResult {
+    let mut solid_mesh = MeshData::new();
+    let mut water_mesh = MeshData::new();
+    let size = CHUNK_SIZE as i32;
+    let chunk_origin = VoxelWorld::chunk_to_world(chunk.position());
+
+    for face in ALL_FACES {
+        let (normal, u, v) = face_axes(face);
+        let depth_axis = normal.abs();
+        let mut mask: Vec<Option<GreedyFace>> = vec![None; CHUNK_SIZE * CHUNK_SIZE];
+
+        for d in 0..size {
+            for j in 0..size {
+                for i in 0..size {
+                    let local = (depth_axis * d + u * i + v * j).as_uvec3();
+                    let voxel = chunk.get(local);
+                    let visible = voxel.is_solid()
+                        && !voxel.is_liquid()
+                        && is_face_visible(chunk, world, local, face);
+                    mask[(j * size + i) as usize] = visible.then(|| GreedyFace {
+                        material: get_blocky_material_index(voxel, face),
+                        ao: greedy_face_ao(chunk, world, local, face, ao_config),
+                        light: sample_light(world, chunk_origin + local.as_ivec3() + normal),
+                    });
+                }
+            }
+
+            for j in 0..size {
+                for i in 0..size {
+                    let Some(quad) = mask[(j * size + i) as usize] else {
+                        continue;
+                    };
+
+                    let mut width = 1;
+                    if quad.extends_along_u() {
+                        while i + width < size
+                            && mask[(j * size + i + width) as usize] == Some(quad)
+                        {
+                            width += 1;
+                        }
+                    }
+                    let mut height = 1;
+                    if quad.extends_along_v() {
+                        while j + height < size
+                            && (0..width).all(|k| {
+                                mask[((j + height) * size + i + k) as usize] == Some(quad)
+                            })
+                        {
+                            height += 1;
+                        }
+                    }
+                    for row in j..j + height {
+                        for col in i..i + width {
+                            mask[(row * size + col) as usize] = None;
+                        }
+                    }
+
+                    add_greedy_quad(
+                        &mut solid_mesh,
+                        face,
+                        chunk_origin,
+                        depth_axis * d + u * i + v * j,
+                        IVec2::new(width, height),
+                        &quad,
+                        ao_config,
+                    );
+                }
+            }
+        }
+    }
+
+    for x in 0..16 {
+        for y in 0..16 {
+            for z in 0..16 {
+                let local = UVec3::new(x, y, z);
+                let voxel = chunk.get(local);
+                if voxel.is_liquid() {
+                    for face in ALL_FACES {
+                        check_water_face(chunk, world, local, face, &mut water_mesh, voxel);
+                    }
+                }
+            }
+        }
+    }
+
+    ChunkMeshResult {
+        solid: solid_mesh,
+        water: water_mesh,
+    }
+}
+
+/// Append a quad `size` voxels along u and v whose (-u,-v) voxel is at `start`
+fn add_greedy_quad(
+    mesh_data: &mut MeshData,
+    face: Face,
+    chunk_origin: IVec3,
+    start: IVec3,
+    size: IVec2,
+    quad: &GreedyFace,
+    ao_config: &BakedAoConfig,
+) {
+    let (normal, u, v) = face_axes(face);
+    // Faces pointing along +axis sit on the far side of their voxel
+    let plane = start + normal.max(IVec3::ZERO);
+    let corners = [
+        plane,
+        plane + u * size.x,
+        plane + u * size.x + v * size.y,
+        plane + v * size.y,
+    ];
+
+    let start_idx = mesh_data.positions.len() as u32;
+    let material_index = quad.material as f32 / 255.0;
+    // World-aligned texture coordinates so merged quads tile seamlessly
+    let texture_origin = chunk_origin.rem_euclid(IVec3::splat(GREEDY_TEXTURE_PERIOD));
+    let scale = 1.0 / GREEDY_TEXTURE_PERIOD as f32;
+    for (corner, ao) in corners.iter().zip(quad.ao) {
+        mesh_data
+            .positions
+            .push((corner.as_vec3() * VOXEL_SIZE).to_array());
+        mesh_data.normals.push(normal.as_vec3().to_array());
+        let texture_pos = texture_origin + *corner;
+        let tex_u = texture_pos.dot(u) as f32 * scale;
+        // Image rows run downwards, so flip v on vertical faces
+        let tex_v = texture_pos.dot(v) as f32 * scale;
+        let tex_v = if v == IVec3::Y { -tex_v } else { tex_v };
+        mesh_data.uvs.push([tex_u, tex_v]);
+        mesh_data.colors.push([ao, ao, ao, material_index]);
+        mesh_data.light.push(quad.light);
+    }
+
+    // Match the winding of `add_face_with_ao`, which faces away from u x v
+    // when that points along the normal
+    let flip_winding = u.cross(v) == normal;
+    // Split along the brighter diagonal so AO interpolates evenly
+    let ao = quad.ao;
+    let flip_diagonal = ao_config.fix_anisotropy && ao[0] + ao[2] <= ao[1] + ao[3];
+    let order: [u32; 6] = match (flip_diagonal, flip_winding) {
+        (false, false) => [0, 1, 2, 0, 2, 3],
+        (false, true) => [0, 2, 1, 0, 3, 2],
+        (true, false) => [1, 2, 3, 1, 3, 0],
+        (true, true) => [1, 3, 2, 1, 0, 3],
+    };
+    mesh_data
+        .indices
+        .extend(order.iter().map(|i| start_idx + i));
+}
+
 // =============================================================================
 // Surface Nets Smooth Meshing
 // =============================================================================
@@ -658,17 +913,58 @@ fn generate_sdf(chunk: &Chunk, world: &VoxelWorld) -> [f32; 5832] { // 18^3 = 58
     smoothed
 }
 
-/// Sample voxel from world or chunk, returns true if water
-fn sample_voxel_water(chunk: &Chunk, world: &VoxelWorld, chunk_origin: IVec3, px: u32, py: u32, pz: u32) -> bool {
-    let world_pos = chunk_origin + IVec3::new(px as i32 - 1, py as i32 - 1, pz as i32 - 1);
-
-    let voxel = if px >= 1 && px <= 16 && py >= 1 && py <= 16 && pz >= 1 && pz <= 16 {
-        chunk.get(UVec3::new(px - 1, py - 1, pz - 1))
+/// Sample voxel from world or chunk, returns how full of liquid it is
+/// (0 for anything else, 1 for sources and falling liquid) and whether it
+/// is solid
+fn sample_voxel_water(chunk: &Chunk, world: &VoxelWorld, world_pos: IVec3) -> (f32, bool) {
+    let local = world_pos - VoxelWorld::chunk_to_world(chunk.position());
+    let inside = local.cmpge(IVec3::ZERO).all() && local.cmplt(IVec3::splat(CHUNK_SIZE as i32)).all();
+    let (voxel, level) = if inside {
+        let local = local.as_uvec3();
+        (chunk.get(local), chunk.fluid_level(local))
     } else {
-        world.get_voxel(world_pos).unwrap_or(VoxelType::Air)
+        let voxel = world.get_voxel(world_pos).unwrap_or(VoxelType::Air);
+        (voxel, world.flow_level(world_pos))
     };
 
-    voxel.is_liquid()
+    if voxel.is_liquid() {
+        let level = level.unwrap_or(MAX_FLUID_LEVEL);
+        (level as f32 / MAX_FLUID_LEVEL as f32, false)
+    } else {
+        (0.0, voxel.is_solid())
+    }
+}
+
+/// Water SDF value of a solid voxel under shallow liquid. The liquid above
+/// interpolates against it, and the surface it makes with open neighbours
+/// stays inside the voxel where the terrain hides it.
+const WATER_GROUND_DENSITY: f32 = -0.5;
+
+/// Water SDF value for a voxel filled to `fill`, above a voxel whose value is
+/// `below`. Samples sit at voxel centres, so a full voxel (-1) puts the
+/// surface at its top face next to air (+1). Over half full pulls it down
+/// towards the centre; less than half puts it between the centre below and
+/// this one, so every level gets its own height.
+fn water_density(fill: f32, below: f32) -> f32 {
+    /// Keeps shallow water with nothing under it inside its voxel
+    const MIN_DEPTH: f32 = 0.1;
+
+    if fill <= 0.0 {
+        return 1.0;
+    }
+    // Height of the surface above the centre
+    let surface = fill - 0.5;
+    if surface > 0.0 {
+        // Interpolating towards the +1 of the air above crosses zero here
+        return -(surface / (1.0 - surface));
+    }
+    if below < 0.0 {
+        // Interpolating up from the voxel below crosses zero `height` above
+        // its centre
+        let height = 1.0 + surface;
+        return -below * (1.0 - height) / height;
+    }
+    -MIN_DEPTH
 }
 
 /// Generate an SDF array for water only
@@ -676,13 +972,47 @@ fn generate_water_sdf(chunk: &Chunk, world: &VoxelWorld) -> [f32; 5832] {
     let mut sdf = [1.0f32; PaddedChunkShape::USIZE];
     let chunk_pos = chunk.position();
     let chunk_origin = VoxelWorld::chunk_to_world(chunk_pos);
+    let world_pos = |px: u32, py: u32, pz: u32| {
+        chunk_origin + IVec3::new(px as i32 - 1, py as i32 - 1, pz as i32 - 1)
+    };
 
-    // First pass: set binary solid/air values
+    let mut fills = [0.0f32; PaddedChunkShape::USIZE];
+    let mut solid = [false; PaddedChunkShape::USIZE];
+    for i in 0..PaddedChunkShape::USIZE {
+        let [px, py, pz] = PaddedChunkShape::delinearize(i as u32);
+        (fills[i], solid[i]) = sample_voxel_water(chunk, world, world_pos(px, py, pz));
+    }
+
+    // First pass: water levels to SDF values, bottom up so each voxel can
+    // interpolate against the one below it
     for i in 0..PaddedChunkShape::USIZE {
         let [px, py, pz] = PaddedChunkShape::delinearize(i as u32);
-        let is_water = sample_voxel_water(chunk, world, chunk_origin, px, py, pz);
         // SDF: negative inside water, positive in air
-        sdf[i] = if is_water { -1.0 } else { 1.0 };
+        sdf[i] = if fills[i] > 0.0 {
+            let below = if py > 0 {
+                sdf[PaddedChunkShape::linearize([px, py - 1, pz]) as usize]
+            } else {
+                match sample_voxel_water(chunk, world, world_pos(px, py, pz) - IVec3::Y) {
+                    (fill, _) if fill > 0.0 => water_density(fill, 1.0),
+                    (_, true) if fills[i] <= 0.5 => WATER_GROUND_DENSITY,
+                    _ => 1.0,
+                }
+            };
+            water_density(fills[i], below)
+        } else if solid[i] {
+            let fill_above = if py < 17 {
+                fills[PaddedChunkShape::linearize([px, py + 1, pz]) as usize]
+            } else {
+                sample_voxel_water(chunk, world, world_pos(px, py, pz) + IVec3::Y).0
+            };
+            if fill_above > 0.0 && fill_above <= 0.5 {
+                WATER_GROUND_DENSITY
+            } else {
+                1.0
+            }
+        } else {
+            1.0
+        };
     }
 
     // Second pass: smooth SDF values
@@ -862,18 +1192,7 @@ pub fn generate_chunk_mesh_surface_nets(
                             };
 
                             if voxel != VoxelType::Air && voxel != VoxelType::Water {
-                                let mat_idx = match voxel {
-                                    VoxelType::TopSoil => 0, // Grass
-                                    
-                                    VoxelType::Rock | VoxelType::Bedrock | 
-                                    VoxelType::DungeonWall | VoxelType::DungeonFloor => 1, // Rock
-                                    
-                                    VoxelType::Sand => 2, // Sand
-                                    
-                                    // Everything else maps to Dirt
-                                    VoxelType::SubSoil | VoxelType::Clay | 
-                                    VoxelType::Wood | VoxelType::Leaves | _ => 3, 
-                                };
+                                let mat_idx = voxel.info().material.triplanar_slot();
                                 
                                 // Distance-based weighting (closer voxels have more influence)
                                 // This assumes local_pos is within the cell [base, base+1]
@@ -909,6 +1228,38 @@ pub fn generate_chunk_mesh_surface_nets(
             let weights1 = compute_vertex_weights(local1);
             let weights2 = compute_vertex_weights(local2);
 
+            // Average light of the open voxels around the vertex
+            let compute_vertex_light = |local_pos: Vec3| -> [f32; 2] {
+                let base = chunk_origin + local_pos.floor().as_ivec3();
+                let mut total = [0.0f32; 2];
+                let mut count = 0;
+                for dz in 0..2 {
+                    for dy in 0..2 {
+                        for dx in 0..2 {
+                            let pos = base + IVec3::new(dx, dy, dz);
+                            let open = world
+                                .get_voxel(pos)
+                                .is_none_or(|voxel| attenuation(voxel).is_some());
+                            if open {
+                                let light = sample_light(world, pos);
+                                total[0] += light[0];
+                                total[1] += light[1];
+                                count += 1;
+                            }
+                        }
+                    }
+                }
+                if count > 0 {
+                    [total[0] / count as f32, total[1] / count as f32]
+                } else {
+                    [1.0, 0.0]
+                }
+            };
+
+            let light0 = compute_vertex_light(local0);
+            let light1 = compute_vertex_light(local1);
+            let light2 = compute_vertex_light(local2);
+
             let compute_ao = |local: Vec3, normal: [f32; 3]| -> f32 {
                 if !ao_config.enabled {
                     return 1.0;
@@ -937,6 +1288,7 @@ pub fn generate_chunk_mesh_surface_nets(
             solid_mesh.normals.push(normal0);
             solid_mesh.uvs.push([ao0, 0.0]); // AO stored in uv.x for triplanar shader
             solid_mesh.colors.push(weights0);
+            solid_mesh.light.push(light0);
             local_positions.push(local0);
 
             // Vertex 1
@@ -944,6 +1296,7 @@ pub fn generate_chunk_mesh_surface_nets(
             solid_mesh.normals.push(normal1);
             solid_mesh.uvs.push([ao1, 0.0]);
             solid_mesh.colors.push(weights1);
+            solid_mesh.light.push(light1);
             local_positions.push(local1);
 
             // Vertex 2
@@ -951,6 +1304,7 @@ pub fn generate_chunk_mesh_surface_nets(
             solid_mesh.normals.push(normal2);
             solid_mesh.uvs.push([ao2, 0.0]);
             solid_mesh.colors.push(weights2);
+            solid_mesh.light.push(light2);
             local_positions.push(local2);
 
             // Add triangle indices (sequential since vertices are not shared)
@@ -967,6 +1321,7 @@ pub fn generate_chunk_mesh_surface_nets(
             &solid_mesh.normals,
             &solid_mesh.indices,
             &solid_mesh.colors,
+            &solid_mesh.light,
             CHUNK_SIZE as f32,
         );
 
@@ -975,6 +1330,7 @@ pub fn generate_chunk_mesh_surface_nets(
             &mut solid_mesh.normals,
             &mut solid_mesh.uvs,
             &mut solid_mesh.colors,
+            &mut solid_mesh.light,
             &mut solid_mesh.indices,
             &boundary_edges,
             skirt_config,
@@ -1110,12 +1466,15 @@ pub enum MeshMode {
 #[derive(Resource, Clone, Copy, Debug)]
 pub struct MeshSettings {
     pub mode: MeshMode,
+    /// Merge coplanar blocky faces, see `generate_chunk_mesh_greedy`
+    pub greedy: bool,
 }
 
 impl Default for MeshSettings {
     fn default() -> Self {
         Self {
             mode: MeshMode::Blocky,
+            greedy: true,
         }
     }
 }
@@ -1124,13 +1483,16 @@ impl Default for MeshSettings {
 pub fn generate_chunk_mesh_with_mode(
     chunk: &Chunk,
     world: &VoxelWorld,
-    mode: MeshMode,
+    settings: MeshSettings,
     my_lod: LodLevel,
     neighbor_lods: NeighborLods,
     skirt_config: &SkirtConfig,
     ao_config: &BakedAoConfig,
 ) -> ChunkMeshResult {
-    match mode {
+    match settings.mode {
+        MeshMode::Blocky if settings.greedy => {
+            generate_chunk_mesh_greedy(chunk, world, ao_config)
+        }
         MeshMode::Blocky => generate_chunk_mesh(chunk, world, ao_config),
         MeshMode::SurfaceNets => {
             generate_chunk_mesh_surface_nets(
@@ -1,9 +1,21 @@
 pub mod chunk;
+pub mod commands;
+pub mod storage;
 pub mod types;
+pub mod registry;
 pub mod world;
 pub mod meshing;
+pub mod mesh_jobs;
 pub mod plugin;
 pub mod persistence;
+pub mod region;
+pub mod streaming;
 pub mod gravity;
+pub mod fluid;
+pub mod light;
+pub mod lod;
+pub mod visibility;
 pub mod skirt;
 pub mod baked_ao;
+pub mod worldgen;
+pub mod inspect;
@@ -1,71 +1,486 @@
-use crate::voxel::chunk::ChunkData;
+use crate::voxel::chunk::{Chunk, ChunkData, FlatChunkData};
+use crate::voxel::region::{region_pos, RegionFile, REGION_FORMAT_VERSION};
 use crate::voxel::world::VoxelWorld;
 use bevy::prelude::*;
 use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-const WORLD_SAVE_PATH: &str = "world_data.bin";
+/// Single-file save written before region files; migrated into the default slot
+pub const LEGACY_WORLD_SAVE_PATH: &str = "world_data.bin";
+/// Directory holding one sub-directory per save slot
+pub const SAVES_DIR: &str = "saves";
+/// Slot used when no world has been created yet
+pub const DEFAULT_SLOT_ID: &str = "world";
+const WORLD_META_FILE: &str = "world.json";
+const THUMBNAIL_FILE: &str = "thumbnail.png";
+const CHAT_LOG_FILE: &str = "chat.log";
+const REGION_DIR: &str = "region";
+/// Bumped whenever the save layout (meta or region files) changes
+pub const WORLD_FORMAT_VERSION: u32 = REGION_FORMAT_VERSION;
+/// Region files kept open between chunk reads and writes
+const MAX_OPEN_REGIONS: usize = 64;
 
-/// Serializable world data
+/// Open region files by path. Shared by every `ChunkStore` so two stores for
+/// the same save never hold different offset tables for one file.
+static OPEN_REGIONS: LazyLock<Mutex<HashMap<PathBuf, RegionFile>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Close the cached region files under `directory`, before they are deleted
+fn close_regions(directory: &Path) {
+    OPEN_REGIONS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .retain(|path, _| !path.starts_with(directory));
+}
+
+/// Legacy serializable world data (`world_data.bin`)
 #[derive(Serialize, Deserialize)]
 pub struct WorldData {
     pub world_size_chunks: IVec3,
-    pub chunks: Vec<ChunkData>,
+    pub chunks: Vec<FlatChunkData>,
+}
+
+/// Per-slot metadata shown in the world browser (`world.json`)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WorldMetadata {
+    pub format_version: u32,
+    /// Display name; the slot directory keeps the id it was created with
+    pub name: String,
+    pub seed: u64,
+    /// Unix timestamps in seconds
+    pub created_at: u64,
+    pub last_played: u64,
+    pub play_time_secs: u64,
+    pub world_size_chunks: IVec3,
+    /// Screenshot file inside the slot directory, once one was taken
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+    /// Append chat in this world to `chat.log` in the slot directory
+    #[serde(default)]
+    pub chat_log: bool,
+    #[serde(default)]
+    pub game_rules: GameRules,
+    /// Respawn point set with `/spawnpoint`, `None` for the default one
+    #[serde(default)]
+    pub spawn_point: Option<Vec3>,
+}
+
+/// Per-world switches for what can hurt the player, set with `/gamerule`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct GameRules {
+    pub fall_damage: bool,
+    pub drowning: bool,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            fall_damage: true,
+            drowning: true,
+        }
+    }
+}
+
+impl GameRules {
+    pub const NAMES: [&'static str; 2] = ["fall_damage", "drowning"];
+
+    pub fn get(&self, name: &str) -> Option<bool> {
+        match name {
+            "fall_damage" => Some(self.fall_damage),
+            "drowning" => Some(self.drowning),
+            _ => None,
+        }
+    }
+
+    /// Returns false for an unknown rule
+    pub fn set(&mut self, name: &str, enabled: bool) -> bool {
+        match name {
+            "fall_damage" => self.fall_damage = enabled,
+            "drowning" => self.drowning = enabled,
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl WorldMetadata {
+    pub fn new(name: impl Into<String>, seed: u64, world_size_chunks: IVec3) -> Self {
+        let now = unix_now();
+        Self {
+            format_version: WORLD_FORMAT_VERSION,
+            name: name.into(),
+            seed,
+            created_at: now,
+            last_played: now,
+            play_time_secs: 0,
+            world_size_chunks,
+            thumbnail: None,
+            chat_log: false,
+            game_rules: GameRules::default(),
+            spawn_point: None,
+        }
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A named world in the saves directory
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SaveSlot {
+    pub root: PathBuf,
+    pub id: String,
+}
+
+impl SaveSlot {
+    /// Slot `id` inside the default saves directory
+    pub fn new(id: impl Into<String>) -> Self {
+        Self::in_dir(SAVES_DIR, id)
+    }
+
+    pub fn in_dir(root: impl Into<PathBuf>, id: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            id: id.into(),
+        }
+    }
+
+    pub fn directory(&self) -> PathBuf {
+        self.root.join(&self.id)
+    }
+
+    /// Chunk storage for this slot's region files
+    pub fn store(&self) -> ChunkStore {
+        ChunkStore {
+            directory: self.directory(),
+        }
+    }
+
+    pub fn thumbnail_path(&self) -> PathBuf {
+        self.directory().join(THUMBNAIL_FILE)
+    }
+
+    pub fn chat_log_path(&self) -> PathBuf {
+        self.directory().join(CHAT_LOG_FILE)
+    }
+
+    pub fn read_metadata(&self) -> Result<WorldMetadata, String> {
+        self.store().read_meta()
+    }
+}
+
+/// Turn a display name into a directory-safe slot id that isn't taken yet
+fn unique_slot_id(root: &Path, name: &str) -> String {
+    let mut base: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    base = base.trim_matches('_').to_string();
+    if base.is_empty() {
+        base = DEFAULT_SLOT_ID.to_string();
+    }
+
+    let mut id = base.clone();
+    let mut suffix = 2;
+    while root.join(&id).exists() {
+        id = format!("{}_{}", base, suffix);
+        suffix += 1;
+    }
+    id
+}
+
+/// `slot` when nothing was saved there yet, otherwise an unused sibling slot.
+/// Used to start a new world without touching a save that failed to load.
+pub fn unused_slot_like(slot: &SaveSlot) -> SaveSlot {
+    if !saved_world_exists(slot) {
+        return slot.clone();
+    }
+    SaveSlot::in_dir(&slot.root, unique_slot_id(&slot.root, &slot.id))
+}
+
+/// All slots with readable metadata, most recently played first
+pub fn list_save_slots(root: &Path) -> Vec<(SaveSlot, WorldMetadata)> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+
+    let mut slots: Vec<(SaveSlot, WorldMetadata)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let slot = SaveSlot::in_dir(root, entry.file_name().to_string_lossy().to_string());
+            match slot.read_metadata() {
+                Ok(meta) => Some((slot, meta)),
+                Err(e) => {
+                    warn!("Skipping save slot {:?}: {}", slot.directory(), e);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    slots.sort_by(|a, b| b.1.last_played.cmp(&a.1.last_played));
+    slots
+}
+
+/// Create an empty slot with fresh metadata
+pub fn create_save_slot(
+    root: &Path,
+    name: &str,
+    seed: u64,
+    world_size_chunks: IVec3,
+) -> Result<(SaveSlot, WorldMetadata), String> {
+    let slot = SaveSlot::in_dir(root, unique_slot_id(root, name));
+    let name = if name.trim().is_empty() { "New World" } else { name.trim() };
+    let meta = WorldMetadata::new(name, seed, world_size_chunks);
+    slot.store().write_meta(&meta)?;
+    info!("Created save slot {:?}", slot.directory());
+    Ok((slot, meta))
+}
+
+/// Copy a slot (region files, metadata and thumbnail) under a new name
+pub fn duplicate_save_slot(slot: &SaveSlot, new_name: &str) -> Result<SaveSlot, String> {
+    let mut meta = slot.read_metadata()?;
+    let copy = SaveSlot::in_dir(&slot.root, unique_slot_id(&slot.root, new_name));
+    copy_dir_recursive(&slot.directory(), &copy.directory())?;
+
+    meta.name = new_name.trim().to_string();
+    meta.created_at = unix_now();
+    copy.store().write_meta(&meta)?;
+    info!("Duplicated save slot {:?} to {:?}", slot.directory(), copy.directory());
+    Ok(copy)
+}
+
+/// Change a slot's display name (the directory keeps its id)
+pub fn rename_save_slot(slot: &SaveSlot, new_name: &str) -> Result<(), String> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err("World name cannot be empty".to_string());
+    }
+
+    let mut meta = slot.read_metadata()?;
+    meta.name = new_name.to_string();
+    slot.store().write_meta(&meta)
 }
 
-/// Save the world to disk using bincode for fast serialization
-pub fn save_world(world: &VoxelWorld) -> Result<(), String> {
-    let data = world.to_data();
+fn copy_dir_recursive(from: &Path, to: &Path) -> Result<(), String> {
+    fs::create_dir_all(to).map_err(|e| format!("Failed to create {:?}: {}", to, e))?;
+    let entries = fs::read_dir(from).map_err(|e| format!("Failed to read {:?}: {}", from, e))?;
 
-    let file = File::create(WORLD_SAVE_PATH)
-        .map_err(|e| format!("Failed to create save file: {}", e))?;
-    let writer = BufWriter::new(file);
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read {:?}: {}", from, e))?;
+        let target = to.join(entry.file_name());
+        if entry.path().is_dir() {
+            copy_dir_recursive(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)
+                .map_err(|e| format!("Failed to copy {:?}: {}", entry.path(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Save the world's edited chunks and update the slot metadata.
+///
+/// Only chunks modified since they were last written are saved; everything
+/// else is either already on disk or regenerated from the world seed.
+pub fn save_world(
+    world: &mut VoxelWorld,
+    store: &ChunkStore,
+    meta: &mut WorldMetadata,
+) -> Result<(), String> {
+    meta.format_version = WORLD_FORMAT_VERSION;
+    meta.world_size_chunks = world.world_size_chunks();
+    meta.last_played = unix_now();
+    store.write_meta(meta)?;
 
-    bincode::serialize_into(writer, &data)
-        .map_err(|e| format!("Failed to serialize world: {}", e))?;
+    let modified: Vec<IVec3> = world
+        .loaded_chunk_positions()
+        .filter(|pos| world.get_chunk(*pos).is_some_and(|c| c.is_modified()))
+        .collect();
 
-    info!("World saved to {} ({} chunks)", WORLD_SAVE_PATH, data.chunks.len());
+    let saved = store.save_chunks(modified.iter().filter_map(|pos| world.get_chunk(*pos)))?;
+
+    for pos in modified {
+        if let Some(chunk) = world.get_chunk_mut(pos) {
+            chunk.clear_modified();
+        }
+    }
+
+    info!("World saved to {:?} ({} chunks written)", store.directory, saved);
     Ok(())
 }
 
-/// Load the world from disk
-pub fn load_world() -> Result<VoxelWorld, String> {
-    let path = Path::new(WORLD_SAVE_PATH);
+/// Load a slot's metadata. Chunks are read lazily from the region files
+/// through `ChunkStore::load_chunk`, so the returned world starts empty.
+pub fn load_world(slot: &SaveSlot) -> Result<(VoxelWorld, WorldMetadata), String> {
+    if !saved_world_exists(slot) {
+        return Err(format!("No saved world in slot '{}'", slot.id));
+    }
+
+    let meta = slot.read_metadata()?;
+    info!(
+        "World '{}' loaded from {:?} (format version {})",
+        meta.name,
+        slot.directory(),
+        meta.format_version
+    );
+
+    Ok((VoxelWorld::new(meta.world_size_chunks), meta))
+}
 
-    if !path.exists() {
-        return Err("No saved world found".to_string());
+/// Move a pre-slot `world_data.bin` into the default slot if that slot
+/// doesn't exist yet. Returns the slot it was migrated into.
+pub fn migrate_legacy_save(root: &Path, legacy_path: &Path) -> Result<Option<SaveSlot>, String> {
+    let slot = SaveSlot::in_dir(root, DEFAULT_SLOT_ID);
+    if !legacy_path.exists() || saved_world_exists(&slot) {
+        return Ok(None);
     }
 
-    let file = File::open(path)
+    migrate_legacy_world(legacy_path, &slot.store())?;
+    Ok(Some(slot))
+}
+
+/// Convert a single-file `world_data.bin` save into region files.
+/// The old file is kept alongside as `<name>.bak`.
+pub fn migrate_legacy_world(legacy_path: &Path, store: &ChunkStore) -> Result<usize, String> {
+    info!("Migrating {:?} to region files...", legacy_path);
+
+    let file = File::open(legacy_path)
         .map_err(|e| format!("Failed to open save file: {}", e))?;
     let reader = BufReader::new(file);
 
     let data: WorldData = bincode::deserialize_from(reader)
         .map_err(|e| format!("Failed to deserialize world: {}", e))?;
 
-    info!("World loaded from {} ({} chunks)", WORLD_SAVE_PATH, data.chunks.len());
+    let world_size_chunks = data.world_size_chunks;
+    let chunks: Vec<Chunk> = data
+        .chunks
+        .into_iter()
+        .map(|flat| Chunk::from_data(ChunkData::from(flat)))
+        .collect();
+    let migrated = store.save_chunks(chunks.iter())?;
+
+    store.write_meta(&WorldMetadata::new("World", 0, world_size_chunks))?;
+
+    let mut backup = legacy_path.as_os_str().to_owned();
+    backup.push(".bak");
+    fs::rename(legacy_path, &backup)
+        .map_err(|e| format!("Failed to back up legacy save: {}", e))?;
 
-    Ok(VoxelWorld::from_data(data))
+    info!("Migrated {} chunks to {:?}", migrated, store.directory);
+    Ok(migrated)
 }
 
-/// Check if a saved world exists
-pub fn saved_world_exists() -> bool {
-    Path::new(WORLD_SAVE_PATH).exists()
+/// Check if a slot holds a saved world
+pub fn saved_world_exists(slot: &SaveSlot) -> bool {
+    slot.store().meta_path().exists()
 }
 
-/// Delete the saved world file
-pub fn delete_saved_world() -> Result<(), String> {
-    let path = Path::new(WORLD_SAVE_PATH);
-    if path.exists() {
-        fs::remove_file(path)
-            .map_err(|e| format!("Failed to delete save file: {}", e))?;
-        info!("Deleted saved world at {}", WORLD_SAVE_PATH);
+/// Delete a slot with all of its files
+pub fn delete_saved_world(slot: &SaveSlot) -> Result<(), String> {
+    let directory = slot.directory();
+    close_regions(&directory);
+    if directory.exists() {
+        fs::remove_dir_all(&directory)
+            .map_err(|e| format!("Failed to delete save slot: {}", e))?;
+        info!("Deleted saved world at {:?}", directory);
     }
     Ok(())
 }
 
+/// The slot being played, its metadata and unsaved play time
+#[derive(Resource, Clone, Debug)]
+pub struct ActiveWorld {
+    pub slot: SaveSlot,
+    pub metadata: WorldMetadata,
+    /// Play time since the metadata was last written
+    pub unsaved_play_time: f32,
+    /// Take a screenshot for the browser once the menu is closed
+    pub thumbnail_pending: bool,
+}
+
+impl ActiveWorld {
+    pub fn new(slot: SaveSlot, metadata: WorldMetadata) -> Self {
+        Self {
+            slot,
+            metadata,
+            unsaved_play_time: 0.0,
+            thumbnail_pending: false,
+        }
+    }
+
+    /// Most recently played slot, or a new default slot when there are none.
+    /// Migrates a legacy `world_data.bin` first.
+    pub fn most_recent_or_default(world_size_chunks: IVec3) -> Self {
+        let root = Path::new(SAVES_DIR);
+        if let Err(e) = migrate_legacy_save(root, Path::new(LEGACY_WORLD_SAVE_PATH)) {
+            warn!("Failed to migrate legacy save: {}", e);
+        }
+
+        match list_save_slots(root).into_iter().next() {
+            Some((slot, metadata)) => Self::new(slot, metadata),
+            // An unreadable default slot is skipped by the listing; keep it intact
+            None => Self::new(
+                unused_slot_like(&SaveSlot::in_dir(root, DEFAULT_SLOT_ID)),
+                WorldMetadata::new("World", 0, world_size_chunks),
+            ),
+        }
+    }
+
+    /// `slot` with its metadata, or fresh metadata named after the slot
+    /// when it has never been saved. A save that exists but can't be read
+    /// is left alone and a new sibling slot is used instead.
+    pub fn open_or_new(slot: SaveSlot, world_size_chunks: IVec3) -> Self {
+        match slot.read_metadata() {
+            Ok(metadata) => Self::new(slot, metadata),
+            Err(e) => {
+                let fresh = unused_slot_like(&slot);
+                if fresh != slot {
+                    warn!(
+                        "Failed to read save slot {:?}: {}. Starting a new world in {:?}",
+                        slot.directory(),
+                        e,
+                        fresh.directory()
+                    );
+                }
+                let metadata = WorldMetadata::new(fresh.id.clone(), 0, world_size_chunks);
+                Self::new(fresh, metadata)
+            }
+        }
+    }
+
+    /// Switch to a new, unsaved slot next to the current one, keeping the
+    /// name and seed. Used when the current save can't be loaded.
+    pub fn start_beside(&mut self) {
+        let slot = unused_slot_like(&self.slot);
+        let metadata = WorldMetadata::new(
+            self.metadata.name.clone(),
+            self.metadata.seed,
+            self.metadata.world_size_chunks,
+        );
+        *self = Self::new(slot, metadata);
+    }
+
+    /// Save the world into this slot, folding in play time since the last save
+    pub fn save(&mut self, world: &mut VoxelWorld) -> Result<(), String> {
+        self.metadata.play_time_secs += self.unsaved_play_time as u64;
+        self.unsaved_play_time = self.unsaved_play_time.fract();
+        save_world(world, &self.slot.store(), &mut self.metadata)
+    }
+}
+
 /// Resource to control world persistence behavior
 #[derive(Resource, Clone, Debug)]
 pub struct WorldPersistence {
@@ -83,3 +498,189 @@ impl Default for WorldPersistence {
         }
     }
 }
+
+/// Region-file backed chunk storage for a world save directory.
+///
+/// Used for saving, for lazy loading and by chunk streaming to evict chunks
+/// and load them back. Region files stay open between calls, so streaming
+/// doesn't re-read a region header for every chunk.
+#[derive(Resource, Clone, Debug)]
+pub struct ChunkStore {
+    pub directory: PathBuf,
+}
+
+impl Default for ChunkStore {
+    fn default() -> Self {
+        SaveSlot::new(DEFAULT_SLOT_ID).store()
+    }
+}
+
+impl ChunkStore {
+    fn meta_path(&self) -> PathBuf {
+        self.directory.join(WORLD_META_FILE)
+    }
+
+    fn region_path(&self, region: IVec3) -> PathBuf {
+        self.directory
+            .join(REGION_DIR)
+            .join(format!("r.{}.{}.{}.vxr", region.x, region.y, region.z))
+    }
+
+    pub fn write_meta(&self, meta: &WorldMetadata) -> Result<(), String> {
+        fs::create_dir_all(&self.directory)
+            .map_err(|e| format!("Failed to create save directory: {}", e))?;
+
+        let file = File::create(self.meta_path())
+            .map_err(|e| format!("Failed to create world meta file: {}", e))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), meta)
+            .map_err(|e| format!("Failed to serialize world meta: {}", e))
+    }
+
+    pub fn read_meta(&self) -> Result<WorldMetadata, String> {
+        let file = File::open(self.meta_path())
+            .map_err(|e| format!("Failed to open world meta file: {}", e))?;
+        let meta: WorldMetadata = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("Failed to deserialize world meta: {}", e))?;
+
+        if meta.format_version > WORLD_FORMAT_VERSION {
+            return Err(format!(
+                "Save uses format version {}, newest supported is {}",
+                meta.format_version, WORLD_FORMAT_VERSION
+            ));
+        }
+        Ok(meta)
+    }
+
+    /// Write a single chunk into its region file
+    pub fn save_chunk(&self, chunk: &Chunk) -> Result<(), String> {
+        self.save_chunks(std::iter::once(chunk)).map(|_| ())
+    }
+
+    /// Write several chunks, opening each region file once
+    pub fn save_chunks<'a>(&self, chunks: impl IntoIterator<Item = &'a Chunk>) -> Result<usize, String> {
+        let mut by_region: HashMap<IVec3, Vec<&Chunk>> = HashMap::new();
+        for chunk in chunks {
+            by_region.entry(region_pos(chunk.position())).or_default().push(chunk);
+        }
+
+        if by_region.is_empty() {
+            return Ok(0);
+        }
+
+        fs::create_dir_all(self.directory.join(REGION_DIR))
+            .map_err(|e| format!("Failed to create region directory: {}", e))?;
+
+        let mut saved = 0;
+        for (region, chunks) in by_region {
+            self.with_region(region, true, |file| {
+                for chunk in chunks {
+                    file.write_chunk(&chunk.to_data())?;
+                    saved += 1;
+                }
+                file.flush()
+            })?;
+        }
+        Ok(saved)
+    }
+
+    /// Read a chunk from its region file, or `None` if it was never stored
+    pub fn load_chunk(&self, chunk_pos: IVec3) -> Result<Option<Chunk>, String> {
+        let data = self.with_region(region_pos(chunk_pos), false, |file| file.read_chunk(chunk_pos))?;
+        Ok(data.flatten().map(Chunk::from_data))
+    }
+
+    /// Run `f` on a region file, opening it (or creating it when `create`
+    /// is set) unless it is already open. `None` when the file doesn't exist
+    /// and wasn't created.
+    fn with_region<T>(
+        &self,
+        region: IVec3,
+        create: bool,
+        f: impl FnOnce(&mut RegionFile) -> Result<T, String>,
+    ) -> Result<Option<T>, String> {
+        let path = self.region_path(region);
+        let mut open = OPEN_REGIONS.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // Deleted behind our back (e.g. the slot directory was removed)
+        if !path.exists() {
+            open.remove(&path);
+            if !create {
+                return Ok(None);
+            }
+        }
+
+        if !open.contains_key(&path) {
+            let file = if create {
+                RegionFile::open_or_create(&path)?
+            } else {
+                RegionFile::open(&path)?
+            };
+            if open.len() >= MAX_OPEN_REGIONS {
+                let evicted = open.keys().next().cloned();
+                if let Some(evicted) = evicted {
+                    open.remove(&evicted);
+                }
+            }
+            open.insert(path.clone(), file);
+        }
+
+        let file = open.get_mut(&path).expect("region was just opened");
+        f(file).map(Some)
+    }
+
+    /// Region files of this save with the region position parsed from
+    /// their `r.<x>.<y>.<z>.vxr` names
+    pub fn region_files(&self) -> Result<Vec<(IVec3, PathBuf)>, String> {
+        let region_dir = self.directory.join(REGION_DIR);
+        if !region_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = fs::read_dir(&region_dir)
+            .map_err(|e| format!("Failed to read region directory: {}", e))?;
+
+        let mut regions = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| format!("Failed to read region directory: {}", e))?
+                .path();
+            if path.extension().is_none_or(|ext| ext != "vxr") {
+                continue;
+            }
+
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+            let coords: Vec<i32> = name
+                .strip_prefix("r.")
+                .map(|rest| rest.split('.').filter_map(|c| c.parse().ok()).collect())
+                .unwrap_or_default();
+            match coords.as_slice() {
+                [x, y, z] => regions.push((IVec3::new(*x, *y, *z), path)),
+                _ => return Err(format!("Unexpected region file name {:?}", path)),
+            }
+        }
+
+        regions.sort_by_key(|(region, _)| (region.x, region.y, region.z));
+        Ok(regions)
+    }
+
+    /// Positions of every chunk stored in this save
+    pub fn stored_chunk_positions(&self) -> Result<Vec<IVec3>, String> {
+        let mut positions = Vec::new();
+        for (region, path) in self.region_files()? {
+            positions.extend(RegionFile::open_read_only(&path)?.stored_chunks(region));
+        }
+        Ok(positions)
+    }
+
+    /// Remove all stored chunks, keeping the metadata (used when the world
+    /// is regenerated)
+    pub fn clear(&self) -> Result<(), String> {
+        let region_dir = self.directory.join(REGION_DIR);
+        close_regions(&region_dir);
+        if region_dir.exists() {
+            fs::remove_dir_all(&region_dir)
+                .map_err(|e| format!("Failed to clear region directory: {}", e))?;
+        }
+        Ok(())
+    }
+}
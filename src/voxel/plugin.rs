@@ -1,18 +1,32 @@
 use crate::camera::controller::PlayerCamera;
-use crate::constants::{CHUNK_SIZE, CHUNK_SIZE_I32};
+use crate::chat::commands::ChatCommandAppExt;
 use crate::rendering::capabilities::GraphicsCapabilities;
 use crate::rendering::materials::VoxelMaterial;
 use crate::rendering::triplanar_material::TriplanarMaterialHandle;
 use crate::rendering::AmbientOcclusionConfig;
-use crate::voxel::chunk::{Chunk, LodLevel};
-// use crate::voxel::gravity::GravityPlugin;
+use crate::voxel::commands::{SaveCommand, SeedCommand};
+use crate::voxel::fluid::FluidPlugin;
+use crate::voxel::gravity::{attach_falling_block_visuals, GravityPlugin};
+use crate::voxel::light::{light_new_chunks_system, LightPlugin};
+use crate::voxel::lod::{
+    apply_lod_meshes_system, build_lod_tiles_system, select_lod_tiles_system, LodTile, LodTiles,
+    MAX_LOD_LEVEL,
+};
+use crate::voxel::mesh_jobs::{MeshJobQueue, MeshJobSettings};
 use crate::voxel::meshing::{generate_chunk_mesh_with_mode, MeshMode, MeshSettings};
-use crate::voxel::skirt::{NeighborLods, SkirtConfig};
-use crate::voxel::persistence::{self, WorldPersistence};
-use crate::voxel::types::VoxelType;
-use crate::voxel::world::VoxelWorld;
+use crate::voxel::skirt::SkirtConfig;
+use crate::voxel::visibility::{cull_chunk_meshes_system, ChunkCullingSettings, ChunkVisibility};
+use crate::voxel::streaming::{
+    populate_initial_chunks, stream_chunks_system, unload_far_chunks_system, ChunkStreamingSettings,
+    StreamingAnchors,
+};
+use crate::voxel::persistence::{self, ActiveWorld, ChunkStore, SaveSlot, WorldPersistence};
+use crate::voxel::registry::VoxelRegistry;
+use crate::voxel::world::{publish_voxel_changes, VoxelChanged, VoxelWorld};
+use crate::voxel::worldgen::{GenerationStats, GeneratorConfig, TerrainPipeline, WorldGen};
 use crate::physics::NeedsCollider;
 use bevy::prelude::*;
+use bevy::tasks::AsyncComputeTaskPool;
 
 pub struct VoxelPlugin;
 
@@ -25,559 +39,220 @@ pub struct WorldConfig {
 
 #[derive(Resource, Clone, Copy, Debug)]
 pub struct LodSettings {
-    /// Distance in world units for high detail meshing (Surface Nets by default).
+    /// Distance in world units drawn from full-resolution chunks (Surface Nets by default).
     pub high_detail_distance: f32,
-    /// Distance in world units at which chunks are culled entirely.
+    /// Distance in world units at which terrain is culled entirely.
     pub cull_distance: f32,
-    /// Mesh mode to use for far chunks that are still visible.
+    /// Mesh mode to use for downsampled LOD tiles.
     pub low_detail_mode: MeshMode,
+    /// Coarsest LOD level, up to `lod::MAX_LOD_LEVEL`; each level reaches twice as far.
+    pub max_level: u8,
 }
 
 impl Default for LodSettings {
     fn default() -> Self {
         Self {
             high_detail_distance: 96.0,
-            cull_distance: 192.0,
+            cull_distance: 3072.0,
             low_detail_mode: MeshMode::Blocky,
+            max_level: MAX_LOD_LEVEL,
         }
     }
 }
 
+impl LodSettings {
+    /// Closer than this a tile of `level` is split into four finer ones
+    pub fn split_distance(&self, level: u8) -> f32 {
+        self.high_detail_distance * (1u32 << level.saturating_sub(1)) as f32
+    }
+}
+
+/// World data, persistence, streaming and generation without any meshing;
+/// the dedicated server runs only this part
+#[derive(Default)]
+pub struct VoxelSimulationPlugin {
+    /// Save slot to play; the most recently played one when `None`
+    pub slot: Option<SaveSlot>,
+}
+
+impl Plugin for VoxelSimulationPlugin {
+    fn build(&self, app: &mut App) {
+        // Voxel properties come from voxel_types.yaml; install them globally so
+        // the `Voxel` trait and meshing see the same data as the ECS resource.
+        let registry = VoxelRegistry::load_or_builtin();
+        if !registry.install_global() {
+            warn!("Voxel registry was already installed; keeping the existing global registry");
+        }
+
+        // The requested slot, or the most recently played one (a legacy
+        // world_data.bin is migrated there)
+        let active_world = match &self.slot {
+            Some(slot) => ActiveWorld::open_or_new(slot.clone(), IVec3::new(32, 4, 32)),
+            None => ActiveWorld::most_recent_or_default(IVec3::new(32, 4, 32)),
+        };
+        info!(
+            "Active world: '{}' ({:?})",
+            active_world.metadata.name,
+            active_world.slot.directory()
+        );
+
+        let generator_config = GeneratorConfig::load_or_default();
+        let world_gen = WorldGen::new(TerrainPipeline::new(
+            active_world.metadata.seed,
+            &generator_config,
+        ));
+
+        app.insert_resource(registry)
+        .insert_resource(VoxelWorld::new(IVec3::new(32, 4, 32)))
+        // World persistence settings (set force_regenerate to true to regenerate)
+        .insert_resource(WorldPersistence {
+            force_regenerate: false,
+            ..default()
+        })
+        .insert_resource(ChunkStreamingSettings::default())
+        .init_resource::<StreamingAnchors>()
+        .insert_resource(active_world.slot.store())
+        .insert_resource(active_world)
+        .insert_resource(generator_config)
+        .insert_resource(world_gen)
+        .add_chat_command(SeedCommand)
+        .add_chat_command(SaveCommand)
+        .add_systems(Startup, setup_voxel_world)
+        .add_systems(
+            Update,
+            (stream_chunks_system, unload_far_chunks_system).chain(),
+        )
+        .add_systems(Update, track_play_time_system)
+        .add_message::<VoxelChanged>()
+        .add_systems(First, publish_voxel_changes)
+        .add_plugins((FluidPlugin, GravityPlugin));
+    }
+}
+
 impl Plugin for VoxelPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(WorldConfig {
+        let world_config = WorldConfig {
             size_chunks: IVec3::new(32, 4, 32),
             chunk_size: 16,
             greedy_meshing: true,
-        })
-        .insert_resource(VoxelWorld::new(IVec3::new(32, 4, 32)))
+        };
+
+        app.add_plugins(VoxelSimulationPlugin::default())
         // Use SurfaceNets for smooth terrain meshing (change to Blocky for Minecraft-style)
         .insert_resource(MeshSettings {
             mode: MeshMode::SurfaceNets,
+            greedy: world_config.greedy_meshing,
         })
+        .insert_resource(world_config)
         .insert_resource(LodSettings::default())
         .insert_resource(SkirtConfig::default())
-        // World persistence settings (set force_regenerate to true to regenerate)
-        .insert_resource(WorldPersistence {
-            force_regenerate: false,
-            ..default()
-        })
-        .add_systems(Startup, setup_voxel_world)
+        .init_resource::<MeshJobSettings>()
+        .init_resource::<MeshJobQueue>()
+        .init_resource::<LodTiles>()
+        .init_resource::<ChunkCullingSettings>()
+        .init_resource::<ChunkVisibility>()
+        .add_plugins(LightPlugin)
         .add_systems(
             Update,
             (
                 adjust_lod_for_integrated_gpu,
-                update_chunk_lod_system,
+                select_lod_tiles_system,
                 mesh_dirty_chunks_system,
+                apply_mesh_results_system,
+                build_lod_tiles_system,
+                apply_lod_meshes_system,
+                cull_chunk_meshes_system,
             )
-                .chain(),
-        );
-        // .add_plugins(GravityPlugin); // Deactivated due to performance impact
-    }
-}
-
-// Simple pseudo-random noise functions for terrain generation
-fn hash(x: i32, z: i32) -> f32 {
-    let n = x
-        .wrapping_mul(374761393)
-        .wrapping_add(z.wrapping_mul(668265263));
-    let n = (n ^ (n >> 13)).wrapping_mul(1274126177);
-    ((n ^ (n >> 16)) as u32 as f32) / u32::MAX as f32
-}
-
-fn smoothstep(t: f32) -> f32 {
-    t * t * (3.0 - 2.0 * t)
-}
-
-fn lerp(a: f32, b: f32, t: f32) -> f32 {
-    a + t * (b - a)
-}
-
-fn value_noise(x: f32, z: f32) -> f32 {
-    let xi = x.floor() as i32;
-    let zi = z.floor() as i32;
-    let xf = x - x.floor();
-    let zf = z - z.floor();
-
-    let v00 = hash(xi, zi);
-    let v10 = hash(xi + 1, zi);
-    let v01 = hash(xi, zi + 1);
-    let v11 = hash(xi + 1, zi + 1);
-
-    let u = smoothstep(xf);
-    let v = smoothstep(zf);
-
-    lerp(lerp(v00, v10, u), lerp(v01, v11, u), v)
-}
-
-fn fbm(x: f32, z: f32, octaves: u32) -> f32 {
-    let mut value = 0.0;
-    let mut amplitude = 1.0;
-    let mut frequency = 1.0;
-    let mut max_value = 0.0;
-
-    for _ in 0..octaves {
-        value += amplitude * value_noise(x * frequency, z * frequency);
-        max_value += amplitude;
-        amplitude *= 0.5;
-        frequency *= 2.0;
-    }
-
-    value / max_value
-}
-
-fn get_terrain_height(world_x: i32, world_z: i32) -> i32 {
-    let x = world_x as f32;
-    let z = world_z as f32;
-
-    // Base terrain with multiple noise layers
-    // Base ranges from 16-36, keeping most land above water level (18)
-    let base = fbm(x * 0.008, z * 0.008, 4) * 20.0 + 16.0;
-
-    // Hills - larger features
-    let hills = fbm(x * 0.02, z * 0.02, 3) * 10.0;
-
-    // Mountains - occasional tall peaks
-    let mountain_mask = fbm(x * 0.005, z * 0.005, 2);
-    let mountains = if mountain_mask > 0.65 {
-        (mountain_mask - 0.65) * 50.0
-    } else {
-        0.0
-    };
-
-    // River valleys - carve into terrain (wider rivers)
-    let river_noise = (fbm(x * 0.015, z * 0.015, 2) * 6.28).sin();
-    let river_factor = if river_noise.abs() < 0.2 {
-        -10.0 * (1.0 - river_noise.abs() / 0.2)
-    } else {
-        0.0
-    };
-
-    (base + hills + mountains + river_factor).max(1.0).min(58.0) as i32
-}
-
-fn get_biome(world_x: i32, world_z: i32) -> u8 {
-    // 0 = normal, 1 = sandy/beach, 2 = rocky, 3 = clay deposits
-    let x = world_x as f32;
-    let z = world_z as f32;
-
-    let biome_noise = fbm(x * 0.01, z * 0.01, 2);
-    let detail_noise = fbm(x * 0.05, z * 0.05, 2);
-
-    if biome_noise < 0.25 {
-        1 // Sandy areas
-    } else if biome_noise > 0.75 && detail_noise > 0.5 {
-        2 // Rocky outcrops
-    } else if biome_noise > 0.4 && biome_noise < 0.5 && detail_noise > 0.6 {
-        3 // Clay deposits
-    } else {
-        0 // Normal terrain
-    }
-}
-
-fn is_cave(world_x: i32, world_y: i32, world_z: i32) -> bool {
-    let x = world_x as f32;
-    let y = world_y as f32;
-    let z = world_z as f32;
-
-    // 3D noise for caves
-    let cave_noise = fbm(x * 0.05 + y * 0.03, z * 0.05 + y * 0.02, 3);
-    let cave_threshold = 0.65 + (y / 64.0) * 0.1; // Caves more common at lower depths
-
-    cave_noise > cave_threshold && world_y > 2 && world_y < 45
-}
-
-fn is_dungeon_wall(world_x: i32, world_y: i32, world_z: i32) -> Option<VoxelType> {
-    // Create dungeon structures at specific locations
-    let dungeon_spacing = 96; // Closer spacing for more dungeons
-    let dungeon_size = 20;
-    let dungeon_floor_y = 3; // Dungeon floor level
-    let dungeon_height = 12; // Dungeon interior height
-
-    let dx = ((world_x % dungeon_spacing) + dungeon_spacing) % dungeon_spacing;
-    let dz = ((world_z % dungeon_spacing) + dungeon_spacing) % dungeon_spacing;
-
-    // Dungeon entrance staircase - visible from surface
-    // Located at corner of each dungeon (position 2-4, 2-4 in dungeon local coords)
-    let entrance_x = 2;
-    let entrance_z = 2;
-    let entrance_size = 3;
-
-    if dx >= entrance_x
-        && dx < entrance_x + entrance_size
-        && dz >= entrance_z
-        && dz < entrance_z + entrance_size
-    {
-        // Staircase from surface down to dungeon
-        // Stairs go from Y=dungeon_floor_y+1 up to Y=50 (well above terrain)
-        if world_y > dungeon_floor_y && world_y <= 50 {
-            let stair_local_x = dx - entrance_x;
-            let stair_local_z = dz - entrance_z;
-
-            // Create spiral/straight staircase walls
-            let is_stair_wall = stair_local_x == 0
-                || stair_local_x == entrance_size - 1
-                || stair_local_z == 0
-                || stair_local_z == entrance_size - 1;
-
-            // Interior is air (the stairwell)
-            if is_stair_wall && stair_local_x != 1 && stair_local_z != 1 {
-                return Some(VoxelType::DungeonWall);
-            } else {
-                // Stairwell interior - just air for the shaft
-                return Some(VoxelType::Air);
-            }
-        }
-    }
-
-    // Check if we're in a dungeon area
-    if dx < dungeon_size
-        && dz < dungeon_size
-        && world_y >= dungeon_floor_y
-        && world_y <= dungeon_floor_y + dungeon_height + 3
-    {
-        let local_x = dx;
-        let local_z = dz;
-        let local_y = world_y - dungeon_floor_y;
-
-        // Only generate dungeon structure within height bounds
-        if local_y > dungeon_height {
-            return None; // Above dungeon ceiling
-        }
-
-        // Create room walls
-        let is_outer_wall = local_x == 0
-            || local_x == dungeon_size - 1
-            || local_z == 0
-            || local_z == dungeon_size - 1;
-
-        // Create inner walls forming corridors
-        let wall_at_x =
-            (local_x % 8 == 0 || local_x % 8 == 1) && local_x > 0 && local_x < dungeon_size - 1;
-        let wall_at_z =
-            (local_z % 8 == 0 || local_z % 8 == 1) && local_z > 0 && local_z < dungeon_size - 1;
-
-        // Doorways in inner walls
-        let doorway_x = local_z >= 3 && local_z <= 5
-            || local_z >= 11 && local_z <= 13
-            || local_z >= 17 && local_z <= 19;
-        let doorway_z = local_x >= 3 && local_x <= 5
-            || local_x >= 11 && local_x <= 13
-            || local_x >= 17 && local_x <= 19;
-
-        let is_inner_wall = (wall_at_x && !doorway_x) || (wall_at_z && !doorway_z);
-
-        // Floor and ceiling
-        let is_floor = local_y == 0;
-        let is_ceiling = local_y == dungeon_height;
-
-        // Pillars at intersections
-        let is_pillar = (local_x % 8 <= 1)
-            && (local_z % 8 <= 1)
-            && local_x > 0
-            && local_x < dungeon_size - 1
-            && local_z > 0
-            && local_z < dungeon_size - 1;
-
-        // Don't place ceiling over entrance
-        let over_entrance = dx >= entrance_x
-            && dx < entrance_x + entrance_size
-            && dz >= entrance_z
-            && dz < entrance_z + entrance_size;
-
-        if is_floor {
-            return Some(VoxelType::DungeonFloor);
-        } else if is_ceiling && !over_entrance {
-            return Some(VoxelType::DungeonFloor);
-        } else if is_outer_wall || is_inner_wall || is_pillar {
-            return Some(VoxelType::DungeonWall);
-        } else {
-            // Interior dungeon space - return Air so terrain doesn't fill it
-            return Some(VoxelType::Air);
-        }
-    }
-
-    None
-}
-
-/// Check if a tree should spawn at this location
-fn should_spawn_tree(world_x: i32, world_z: i32, terrain_height: i32) -> bool {
-    // Trees only spawn above water level on grass
-    if terrain_height <= WATER_LEVEL + 2 {
-        return false;
-    }
-
-    // Use hash to determine tree placement - sparse distribution
-    let tree_noise = hash(world_x.wrapping_mul(7), world_z.wrapping_mul(13));
-
-    // About 2% chance per block
-    tree_noise > 0.98
-}
-
-/// Get tree height at this location (for consistent tree generation)
-fn get_tree_height(world_x: i32, world_z: i32) -> i32 {
-    let h = hash(world_x.wrapping_add(1000), world_z.wrapping_add(2000));
-    3 + (h * 3.0) as i32 // Height between 3 and 5
-}
-
-/// Check if a position is part of a tree trunk
-fn is_tree_trunk(world_x: i32, world_y: i32, world_z: i32, terrain_height: i32) -> bool {
-    if !should_spawn_tree(world_x, world_z, terrain_height) {
-        return false;
+                .chain()
+                .after(unload_far_chunks_system)
+                .after(light_new_chunks_system),
+        )
+        .add_systems(Update, attach_falling_block_visuals);
     }
-
-    let trunk_height = get_tree_height(world_x, world_z);
-    let trunk_bottom = terrain_height + 1;
-    let trunk_top = trunk_bottom + trunk_height;
-
-    world_y >= trunk_bottom && world_y < trunk_top
-}
-
-/// Check if a position is part of tree leaves
-fn is_tree_leaves(world_x: i32, world_y: i32, world_z: i32) -> bool {
-    // Check nearby positions for tree trunks
-    let radius = 3;
-
-    for dx in -radius..=radius {
-        for dz in -radius..=radius {
-            let check_x = world_x + dx;
-            let check_z = world_z + dz;
-
-            let check_height = get_terrain_height(check_x, check_z);
-
-            if should_spawn_tree(check_x, check_z, check_height) {
-                let trunk_height = get_tree_height(check_x, check_z);
-                let trunk_top = check_height + 1 + trunk_height;
-                let leaf_center_y = trunk_top - 1;
-
-                // Spherical leaf shape
-                let dx_f = dx as f32;
-                let dz_f = dz as f32;
-                let dy_f = (world_y - leaf_center_y) as f32;
-
-                let dist_sq = dx_f * dx_f + dy_f * dy_f * 1.5 + dz_f * dz_f;
-                let leaf_radius = 2.5;
-
-                if dist_sq < leaf_radius * leaf_radius {
-                    // Don't place leaves where trunk is
-                    if !(dx == 0 && dz == 0 && world_y < trunk_top) {
-                        return true;
-                    }
-                }
-            }
-        }
-    }
-
-    false
 }
 
-// Water level constant - areas below this height will be filled with water
+// Default water level - areas below this height are filled with water
+// (worldgen.yaml can override it for generated terrain)
 pub const WATER_LEVEL: i32 = 18;
 
-// Debug flat world toggle (disabled by default)
-const DEBUG_FLAT_WORLD: bool = false;
-
-fn setup_voxel_world(mut world: ResMut<VoxelWorld>, persistence_settings: Res<WorldPersistence>) {
+fn setup_voxel_world(
+    mut world: ResMut<VoxelWorld>,
+    persistence_settings: Res<WorldPersistence>,
+    streaming: Res<ChunkStreamingSettings>,
+    mut chunk_store: ResMut<ChunkStore>,
+    mut active_world: ResMut<ActiveWorld>,
+    mut world_gen: ResMut<WorldGen>,
+) {
     // Try to load saved world unless force_regenerate is set
-    if !persistence_settings.force_regenerate && persistence::saved_world_exists() {
-        info!("Loading saved world from disk...");
-        match persistence::load_world() {
-            Ok(loaded_world) => {
+    if !persistence_settings.force_regenerate && persistence::saved_world_exists(&active_world.slot) {
+        info!("Loading saved world '{}' from disk...", active_world.metadata.name);
+        match persistence::load_world(&active_world.slot) {
+            Ok((loaded_world, metadata)) => {
                 *world = loaded_world;
-                info!("World loaded successfully!");
+                world_gen.reseed(metadata.seed);
+                active_world.metadata = metadata;
+                world.set_border_enabled(streaming.world_border);
+                populate_initial_chunks(
+                    &mut world,
+                    &chunk_store,
+                    &world_gen,
+                    &streaming,
+                    IVec3::ZERO,
+                    &mut GenerationStats::default(),
+                );
+                info!("World loaded successfully! ({} chunks)", world.loaded_chunk_count());
                 return;
             }
             Err(e) => {
-                warn!("Failed to load saved world: {}. Generating new world...", e);
+                // Never overwrite a save we couldn't read (corrupt or from a
+                // newer version); the new world goes into a separate slot
+                active_world.start_beside();
+                *chunk_store = active_world.slot.store();
+                warn!(
+                    "Failed to load saved world: {}. Generating new world in {:?}...",
+                    e,
+                    active_world.slot.directory()
+                );
             }
         }
     }
 
-    info!("Generating new world...");
+    // Chunks saved for a previous world would override the fresh terrain
+    if let Err(e) = chunk_store.clear() {
+        warn!("Failed to clear saved chunks: {}", e);
+    }
+    world.set_border_enabled(streaming.world_border);
+
+    world_gen.reseed(active_world.metadata.seed);
+    info!("Generating new world (seed {})...", active_world.metadata.seed);
     let start_time = std::time::Instant::now();
 
-    // Generate extensive procedural terrain
-    let chunk_positions: Vec<IVec3> = world.all_chunk_positions().collect();
-    let mut total_sand = 0u32;
-    let mut total_dungeon_wall = 0u32;
-    let mut total_dungeon_floor = 0u32;
+    // With streaming only the spawn area is generated up front; the rest
+    // streams in around the player.
+    let chunk_positions: Vec<IVec3> = if streaming.enabled {
+        streaming
+            .positions_around(IVec3::ZERO)
+            .into_iter()
+            .filter(|pos| world.chunk_in_bounds(*pos))
+            .collect()
+    } else {
+        world.all_chunk_positions().collect()
+    };
+    let mut totals = GenerationStats::default();
 
     for chunk_pos in chunk_positions {
-        let mut chunk = Chunk::new(chunk_pos);
-        let chunk_world_x = chunk_pos.x * CHUNK_SIZE_I32;
-        let chunk_world_z = chunk_pos.z * CHUNK_SIZE_I32;
-        let chunk_world_y = chunk_pos.y * CHUNK_SIZE_I32;
-        let mut _water_count = 0u32;
-        let mut sand_count = 0u32;
-        let mut dungeon_wall_count = 0u32;
-        let mut dungeon_floor_count = 0u32;
-
-        for x in 0..CHUNK_SIZE {
-            for z in 0..CHUNK_SIZE {
-                let world_x = chunk_world_x + x as i32;
-                let world_z = chunk_world_z + z as i32;
-
-                let terrain_height = get_terrain_height(world_x, world_z);
-                let biome = get_biome(world_x, world_z);
-
-                for y in 0..CHUNK_SIZE {
-                    let world_y = chunk_world_y + y as i32;
-
-                    // Check for dungeon structures first
-                    if let Some(dungeon_voxel) = is_dungeon_wall(world_x, world_y, world_z) {
-                        match dungeon_voxel {
-                            VoxelType::DungeonWall => dungeon_wall_count += 1,
-                            VoxelType::DungeonFloor => dungeon_floor_count += 1,
-                            _ => {}
-                        }
-                        chunk.set(UVec3::new(x as u32, y as u32, z as u32), dungeon_voxel);
-                        continue;
-                    }
-
-                    // Check for caves
-                    // Caves disabled for debugging blue holes
-                    if is_cave(world_x, world_y, world_z) && world_y < terrain_height - 3 {
-                        // Fill caves below water level with water
-                        let voxel = if world_y <= WATER_LEVEL {
-                            VoxelType::Water
-                        } else {
-                            VoxelType::Air
-                        };
-                        if voxel == VoxelType::Water {
-                            _water_count += 1;
-                        }
-                        chunk.set(UVec3::new(x as u32, y as u32, z as u32), voxel);
-                        continue;
-                    }
-
-                    // Check for tree trunks
-                    if is_tree_trunk(world_x, world_y, world_z, terrain_height) {
-                        chunk.set(UVec3::new(x as u32, y as u32, z as u32), VoxelType::Wood);
-                        continue;
-                    }
-
-                    // Check for tree leaves
-                    if world_y > terrain_height && is_tree_leaves(world_x, world_y, world_z) {
-                        chunk.set(UVec3::new(x as u32, y as u32, z as u32), VoxelType::Leaves);
-                        continue;
-                    }
-
-                    let voxel = if DEBUG_FLAT_WORLD {
-                        if world_y <= 12 {
-                            VoxelType::TopSoil
-                        } else {
-                            VoxelType::Air
-                        }
-                    } else if world_y > terrain_height {
-                        // Above terrain - check if below water level (lakes/rivers)
-                        if world_y <= WATER_LEVEL {
-                            VoxelType::Water
-                        } else {
-                            VoxelType::Air
-                        }
-                    } else if world_y == 0 {
-                        VoxelType::Bedrock
-                    } else if world_y <= 3 {
-                        // Deep bedrock layer with some rock
-                        if hash(world_x, world_z + world_y * 1000) > 0.3 {
-                            VoxelType::Bedrock
-                        } else {
-                            VoxelType::Rock
-                        }
-                    } else {
-                        // Determine block based on depth from surface and biome
-                        let depth = terrain_height - world_y;
-
-                        // Near water, use sand instead of topsoil (beaches and shorelines)
-                        // Beach area: terrain within 2 blocks above water level only
-                        let near_water = terrain_height <= WATER_LEVEL + 2;
-
-                        match biome {
-                            1 => {
-                                // Sandy biome
-                                if depth <= 4 {
-                                    VoxelType::Sand
-                                } else if depth <= 8 {
-                                    VoxelType::SubSoil
-                                } else {
-                                    VoxelType::Rock
-                                }
-                            }
-                            2 => {
-                                // Rocky biome
-                                if depth <= 1 {
-                                    VoxelType::Rock
-                                } else if depth <= 3 {
-                                    VoxelType::SubSoil
-                                } else {
-                                    VoxelType::Rock
-                                }
-                            }
-                            3 => {
-                                // Clay deposits
-                                if near_water {
-                                    if depth <= 2 {
-                                        VoxelType::Sand
-                                    } else if depth <= 6 {
-                                        VoxelType::Clay
-                                    } else {
-                                        VoxelType::Rock
-                                    }
-                                } else if depth <= 2 {
-                                    VoxelType::TopSoil
-                                } else if depth <= 6 {
-                                    VoxelType::Clay
-                                } else if depth <= 10 {
-                                    VoxelType::SubSoil
-                                } else {
-                                    VoxelType::Rock
-                                }
-                            }
-                            _ => {
-                                // Normal terrain - use sand near water (beaches)
-                                if near_water {
-                                    if depth <= 2 {
-                                        VoxelType::Sand
-                                    } else if depth <= 5 {
-                                        VoxelType::SubSoil
-                                    } else {
-                                        VoxelType::Rock
-                                    }
-                                } else if depth == 0 {
-                                    VoxelType::TopSoil
-                                } else if depth <= 4 {
-                                    VoxelType::SubSoil
-                                } else {
-                                    VoxelType::Rock
-                                }
-                            }
-                        }
-                    };
-
-                    match voxel {
-                        VoxelType::Water => _water_count += 1,
-                        VoxelType::Sand => sand_count += 1,
-                        _ => {}
-                    }
-                    chunk.set(UVec3::new(x as u32, y as u32, z as u32), voxel);
-                }
-            }
-        }
-
-        chunk.mark_dirty();
+        let mut stats = GenerationStats::default();
+        let chunk = world_gen.generate_chunk(chunk_pos, &mut stats);
         world.insert_chunk(chunk);
+        totals.add(&stats);
 
-        total_sand += sand_count;
-        total_dungeon_wall += dungeon_wall_count;
-        total_dungeon_floor += dungeon_floor_count;
-
-        if dungeon_wall_count > 0 || dungeon_floor_count > 0 {
+        if stats.dungeon_wall > 0 || stats.dungeon_floor > 0 {
             info!(
                 "Chunk {:?} (world pos {:?}) has {} dungeon walls, {} dungeon floors",
                 chunk_pos,
-                IVec3::new(chunk_world_x, chunk_world_y, chunk_world_z),
-                dungeon_wall_count,
-                dungeon_floor_count
+                VoxelWorld::chunk_to_world(chunk_pos),
+                stats.dungeon_wall,
+                stats.dungeon_floor
             );
         }
     }
@@ -585,57 +260,65 @@ fn setup_voxel_world(mut world: ResMut<VoxelWorld>, persistence_settings: Res<Wo
     let generation_time = start_time.elapsed();
     info!("=== WORLD GENERATION SUMMARY ===");
     info!("Generation time: {:.2}s", generation_time.as_secs_f32());
-    info!("Total sand blocks: {}", total_sand);
-    info!("Total dungeon wall blocks: {}", total_dungeon_wall);
-    info!("Total dungeon floor blocks: {}", total_dungeon_floor);
+    info!("Chunks generated: {}", world.loaded_chunk_count());
+    info!("Total sand blocks: {}", totals.sand);
+    info!("Total dungeon wall blocks: {}", totals.dungeon_wall);
+    info!("Total dungeon floor blocks: {}", totals.dungeon_floor);
     info!("Dungeons should be at positions like (0-19, 3-18, 0-19), (96-115, 3-18, 96-115), etc.");
     info!("Sand appears near water (terrain height <= 24) and in sandy biomes");
 
     // Save world to disk if auto_save is enabled
     if persistence_settings.auto_save {
         info!("Saving world to disk...");
-        match persistence::save_world(&world) {
+        match active_world.save(&mut world) {
             Ok(()) => info!("World saved successfully!"),
             Err(e) => warn!("Failed to save world: {}", e),
         }
     }
 }
 
+/// Accumulate play time for the active save slot
+fn track_play_time_system(time: Res<Time>, mut active_world: ResMut<ActiveWorld>) {
+    active_world.unsaved_play_time += time.delta_secs();
+}
+
+/// Snapshot dirty chunks and mesh them on the `AsyncComputeTaskPool`
 fn mesh_dirty_chunks_system(
     mut commands: Commands,
     mut world: ResMut<VoxelWorld>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    blocky_material: Option<Res<VoxelMaterial>>,
-    triplanar_material: Res<TriplanarMaterialHandle>,
-    water_material: Res<crate::rendering::materials::WaterMaterial>,
+    mut job_queue: ResMut<MeshJobQueue>,
+    job_settings: Res<MeshJobSettings>,
     mesh_settings: Res<MeshSettings>,
-    lod_settings: Res<LodSettings>,
+    lod_tiles: Res<LodTiles>,
     skirt_config: Res<SkirtConfig>,
     ao_config: Res<AmbientOcclusionConfig>,
+    camera_query: Query<&Transform, With<PlayerCamera>>,
 ) {
-    // Bail out until the blocky material is ready to avoid panicking when resources are still loading.
-    let blocky_material = match blocky_material {
-        Some(mat) => mat,
-        None => return,
-    };
+    let mut dirty_chunks: Vec<IVec3> = world.dirty_chunks().collect();
 
-    // Collect dirty chunks first to avoid borrowing issues
-    let dirty_chunks: Vec<IVec3> = world.dirty_chunks().collect();
+    // Mesh the chunks closest to the player first
+    if let Some(camera) = camera_query.iter().next() {
+        let camera_chunk = VoxelWorld::world_to_chunk(camera.translation.floor().as_ivec3());
+        dirty_chunks.sort_by_key(|pos| pos.distance_squared(camera_chunk));
+    }
 
+    let mut started = 0;
     for chunk_pos in dirty_chunks {
-        let (target_mode, lod_level) = if let Some(chunk) = world.get_chunk(chunk_pos) {
-            let target_mode = match chunk.lod_level() {
-                LodLevel::High => mesh_settings.mode,
-                LodLevel::Low => lod_settings.low_detail_mode,
-                LodLevel::Culled => lod_settings.low_detail_mode,
-            };
-
-            (target_mode, chunk.lod_level())
+        // Dirty again while its job runs: that result would be stale
+        job_queue.cancel(chunk_pos);
+
+        let lod_level = if let Some(chunk) = world.get_chunk(chunk_pos) {
+            // Stays dirty until its light is in
+            if !chunk.is_lit() {
+                continue;
+            }
+            chunk.lod_level()
         } else {
             continue;
         };
 
-        if lod_level == LodLevel::Culled {
+        // Culled, or drawn by the coarser LOD tile covering it
+        if !lod_level.draws_chunk_mesh() {
             if let Some(chunk) = world.get_chunk_mut(chunk_pos) {
                 if let Some(entity) = chunk.mesh_entity() {
                     commands.entity(entity).despawn();
@@ -650,41 +333,82 @@ fn mesh_dirty_chunks_system(
             continue;
         }
 
-        let neighbor_lods = NeighborLods {
-            neg_x: world
-                .get_chunk(chunk_pos + IVec3::new(-1, 0, 0))
-                .map(|c| c.lod_level()),
-            pos_x: world
-                .get_chunk(chunk_pos + IVec3::new(1, 0, 0))
-                .map(|c| c.lod_level()),
-            neg_z: world
-                .get_chunk(chunk_pos + IVec3::new(0, 0, -1))
-                .map(|c| c.lod_level()),
-            pos_z: world
-                .get_chunk(chunk_pos + IVec3::new(0, 0, 1))
-                .map(|c| c.lod_level()),
-        };
-
-        // Step 1: Generate mesh data using immutable borrow
-        let mesh_result = if let Some(chunk) = world.get_chunk(chunk_pos) {
-            generate_chunk_mesh_with_mode(
-                chunk,
-                &world,
-                target_mode,
-                lod_level,
-                neighbor_lods,
-                &skirt_config,
-                &ao_config.baked,
-            )
-        } else {
+        if started >= job_settings.max_jobs_per_frame
+            || job_queue.in_flight_count() >= job_settings.max_in_flight
+        {
             continue;
-        };
+        }
 
-        // Step 2: Update chunk state using mutable borrow
+        // Neighbouring areas may be drawn by coarser tiles rather than chunks
+        let neighbor_lods = lod_tiles
+            .selection()
+            .neighbor_lods(LodTile::containing(chunk_pos, 0));
+
+        // The job works on a copy, so the chunk counts as clean from here on;
+        // any edit while it runs dirties it again and cancels the job.
+        let snapshot = world.snapshot_around(chunk_pos);
         if let Some(chunk) = world.get_chunk_mut(chunk_pos) {
-            // Clear dirty flag
             chunk.clear_dirty();
+        }
+
+        let ticket = job_queue.start(chunk_pos);
+        let skirt_config = skirt_config.clone();
+        let baked_ao = ao_config.baked.clone();
+        // Far chunks are blocky too, so they get greedy quads as well
+        let settings = MeshSettings {
+            mode: mesh_settings.mode,
+            greedy: mesh_settings.greedy,
+        };
+
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                if ticket.is_cancelled() {
+                    return;
+                }
+                let Some(chunk) = snapshot.get_chunk(ticket.chunk_pos) else {
+                    return;
+                };
+                let mesh = generate_chunk_mesh_with_mode(
+                    chunk,
+                    &snapshot,
+                    settings,
+                    lod_level,
+                    neighbor_lods,
+                    &skirt_config,
+                    &baked_ao,
+                );
+                ticket.finish(mesh);
+            })
+            .detach();
+
+        started += 1;
+    }
+}
 
+/// Upload finished meshes and spawn or update the chunk mesh entities
+fn apply_mesh_results_system(
+    mut commands: Commands,
+    mut world: ResMut<VoxelWorld>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut job_queue: ResMut<MeshJobQueue>,
+    job_settings: Res<MeshJobSettings>,
+    blocky_material: Option<Res<VoxelMaterial>>,
+    triplanar_material: Res<TriplanarMaterialHandle>,
+    water_material: Res<crate::rendering::materials::WaterMaterial>,
+    mesh_settings: Res<MeshSettings>,
+) {
+    // Bail out until the blocky material is ready to avoid panicking when resources are still loading.
+    let blocky_material = match blocky_material {
+        Some(mat) => mat,
+        None => return,
+    };
+
+    for result in job_queue.drain_finished(job_settings.max_results_per_frame) {
+        let chunk_pos = result.chunk_pos;
+        let mesh_result = result.mesh;
+
+        // The chunk may have been unloaded while its mesh was being built
+        if let Some(chunk) = world.get_chunk_mut(chunk_pos) {
             let world_pos = VoxelWorld::chunk_to_world(chunk_pos);
 
             // Handle solid mesh
@@ -793,57 +517,12 @@ fn adjust_lod_for_integrated_gpu(
 
     if capabilities.integrated_gpu {
         lod_settings.high_detail_distance = 48.0;
-        lod_settings.cull_distance = 96.0;
+        lod_settings.cull_distance = 768.0;
         lod_settings.low_detail_mode = MeshMode::Blocky;
+        lod_settings.max_level = 4;
         mesh_settings.mode = MeshMode::Blocky;
         info!("Integrated GPU detected; using more aggressive chunk LOD distances.");
     }
 
     *applied = true;
 }
-
-fn update_chunk_lod_system(
-    mut world: ResMut<VoxelWorld>,
-    camera_query: Query<&Transform, With<PlayerCamera>>,
-    lod_settings: Res<LodSettings>,
-) {
-    let Some(camera_transform) = camera_query.iter().next() else {
-        return;
-    };
-
-    let camera_pos = camera_transform.translation;
-
-    let mut lod_changed: Vec<IVec3> = Vec::new();
-
-    for (chunk_pos, chunk) in world.chunk_entries_mut() {
-        let world_pos = VoxelWorld::chunk_to_world(*chunk_pos);
-        let chunk_center = world_pos.as_vec3() + Vec3::splat(CHUNK_SIZE as f32 * 0.5);
-        let distance = chunk_center.distance(camera_pos);
-
-        let target_lod = if distance <= lod_settings.high_detail_distance {
-            LodLevel::High
-        } else if distance <= lod_settings.cull_distance {
-            LodLevel::Low
-        } else {
-            LodLevel::Culled
-        };
-
-        if chunk.set_lod_level(target_lod) {
-            lod_changed.push(*chunk_pos);
-        }
-    }
-
-    for chunk_pos in lod_changed {
-        for offset in [
-            IVec3::new(-1, 0, 0),
-            IVec3::new(1, 0, 0),
-            IVec3::new(0, 0, -1),
-            IVec3::new(0, 0, 1),
-        ] {
-            let neighbor_pos = chunk_pos + offset;
-            if let Some(neighbor) = world.get_chunk_mut(neighbor_pos) {
-                neighbor.mark_dirty();
-            }
-        }
-    }
-}
@@ -0,0 +1,381 @@
+use crate::voxel::chunk::{ChunkData, FlatChunkData, PalettedChunkData};
+use bevy::prelude::*;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Chunk columns per region side
+pub const REGION_SIZE: i32 = 16;
+/// Chunk layers stacked in one region
+pub const REGION_HEIGHT: i32 = 8;
+/// Bumped whenever the on-disk layout changes.
+/// 1: flat voxel arrays, 2: palette-compressed chunk storage,
+/// 3: flowing liquid levels
+pub const REGION_FORMAT_VERSION: u32 = 3;
+
+const REGION_MAGIC: [u8; 4] = *b"VXRG";
+const REGION_CHUNKS: usize = (REGION_SIZE * REGION_SIZE * REGION_HEIGHT) as usize;
+const SECTOR_SIZE: u64 = 4096;
+/// Magic + version, followed by one (sector offset, sector count) pair per chunk
+const HEADER_BYTES: u64 = 8 + REGION_CHUNKS as u64 * 8;
+const HEADER_SECTORS: u32 = HEADER_BYTES.div_ceil(SECTOR_SIZE) as u32;
+/// Payload length (u32) + compression tag (u8)
+const PAYLOAD_HEADER_BYTES: u64 = 5;
+
+const COMPRESSION_NONE: u8 = 0;
+const COMPRESSION_ZLIB: u8 = 1;
+
+/// Region containing a chunk
+pub fn region_pos(chunk_pos: IVec3) -> IVec3 {
+    IVec3::new(
+        chunk_pos.x.div_euclid(REGION_SIZE),
+        chunk_pos.y.div_euclid(REGION_HEIGHT),
+        chunk_pos.z.div_euclid(REGION_SIZE),
+    )
+}
+
+fn local_index(chunk_pos: IVec3) -> usize {
+    let x = chunk_pos.x.rem_euclid(REGION_SIZE);
+    let y = chunk_pos.y.rem_euclid(REGION_HEIGHT);
+    let z = chunk_pos.z.rem_euclid(REGION_SIZE);
+    ((y * REGION_SIZE + z) * REGION_SIZE + x) as usize
+}
+
+#[derive(Clone, Copy, Default)]
+struct ChunkLocation {
+    sector_offset: u32,
+    sector_count: u32,
+}
+
+/// A region file: a header with an offset table followed by individually
+/// compressed chunk payloads aligned to 4 KiB sectors.
+///
+/// Rewritten chunks stay in place when they still fit their sectors and
+/// otherwise move to the first run of sectors no other chunk uses, growing
+/// the file only when there is none.
+pub struct RegionFile {
+    file: File,
+    version: u32,
+    table: Vec<ChunkLocation>,
+}
+
+impl RegionFile {
+    /// Open an existing region file, upgrading it if it uses an older format
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(|e| format!("Failed to open region file: {}", e))?;
+
+        let region = Self::read_header(file, path)?;
+        if region.version < REGION_FORMAT_VERSION {
+            return region.upgrade(path);
+        }
+        Ok(region)
+    }
+
+    /// Open a region file for reading only. Older formats are read as they
+    /// are instead of being upgraded, so inspecting a save never changes it.
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| format!("Failed to open region file: {}", e))?;
+        Self::read_header(file, path)
+    }
+
+    /// Rewrite every chunk of an older region file in the current format
+    fn upgrade(mut self, path: &Path) -> Result<Self, String> {
+        info!(
+            "Upgrading region file {:?} from format version {} to {}",
+            path, self.version, REGION_FORMAT_VERSION
+        );
+
+        let mut chunks = Vec::new();
+        for index in 0..REGION_CHUNKS {
+            if let Some(data) = self.read_slot(index)? {
+                chunks.push(data);
+            }
+        }
+
+        let tmp_path = path.with_extension("vxr.tmp");
+        if tmp_path.exists() {
+            std::fs::remove_file(&tmp_path)
+                .map_err(|e| format!("Failed to remove stale region file: {}", e))?;
+        }
+
+        let mut upgraded = Self::open_or_create(&tmp_path)?;
+        for data in &chunks {
+            upgraded.write_chunk(data)?;
+        }
+        upgraded.flush()?;
+        drop(upgraded);
+
+        std::fs::rename(&tmp_path, path)
+            .map_err(|e| format!("Failed to replace region file: {}", e))?;
+        Self::open(path)
+    }
+
+    /// Open a region file, creating an empty one if it doesn't exist
+    pub fn open_or_create<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        if path.exists() {
+            return Self::open(path);
+        }
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .map_err(|e| format!("Failed to create region file: {}", e))?;
+
+        let mut header = vec![0u8; (HEADER_SECTORS as u64 * SECTOR_SIZE) as usize];
+        header[0..4].copy_from_slice(&REGION_MAGIC);
+        header[4..8].copy_from_slice(&REGION_FORMAT_VERSION.to_le_bytes());
+        file.write_all(&header)
+            .map_err(|e| format!("Failed to write region header: {}", e))?;
+
+        Ok(Self {
+            file,
+            version: REGION_FORMAT_VERSION,
+            table: vec![ChunkLocation::default(); REGION_CHUNKS],
+        })
+    }
+
+    fn read_header(mut file: File, path: &Path) -> Result<Self, String> {
+        let mut header = vec![0u8; HEADER_BYTES as usize];
+        file.read_exact(&mut header)
+            .map_err(|e| format!("Failed to read region header of {:?}: {}", path, e))?;
+
+        if header[0..4] != REGION_MAGIC {
+            return Err(format!("{:?} is not a region file", path));
+        }
+
+        let version = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        if version == 0 || version > REGION_FORMAT_VERSION {
+            return Err(format!(
+                "Region file {:?} has format version {}, newest supported is {}",
+                path, version, REGION_FORMAT_VERSION
+            ));
+        }
+
+        let table = header[8..]
+            .chunks_exact(8)
+            .map(|entry| ChunkLocation {
+                sector_offset: u32::from_le_bytes(entry[0..4].try_into().unwrap()),
+                sector_count: u32::from_le_bytes(entry[4..8].try_into().unwrap()),
+            })
+            .collect();
+
+        Ok(Self {
+            file,
+            version,
+            table,
+        })
+    }
+
+    /// Whether a chunk has been written to this region
+    pub fn contains(&self, chunk_pos: IVec3) -> bool {
+        self.table[local_index(chunk_pos)].sector_count > 0
+    }
+
+    /// Number of chunks stored in this region
+    pub fn chunk_count(&self) -> usize {
+        self.table.iter().filter(|loc| loc.sector_count > 0).count()
+    }
+
+    /// Format version from the file header
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Positions of the chunks stored in this region (`region` as returned
+    /// by `region_pos`)
+    pub fn stored_chunks(&self, region: IVec3) -> Vec<IVec3> {
+        let origin = region * IVec3::new(REGION_SIZE, REGION_HEIGHT, REGION_SIZE);
+        self.table
+            .iter()
+            .enumerate()
+            .filter(|(_, loc)| loc.sector_count > 0)
+            .map(|(index, _)| {
+                let index = index as i32;
+                let x = index % REGION_SIZE;
+                let z = (index / REGION_SIZE) % REGION_SIZE;
+                let y = index / (REGION_SIZE * REGION_SIZE);
+                origin + IVec3::new(x, y, z)
+            })
+            .collect()
+    }
+
+    /// Read and decompress one chunk, or `None` if it was never written
+    pub fn read_chunk(&mut self, chunk_pos: IVec3) -> Result<Option<ChunkData>, String> {
+        let Some(data) = self.read_slot(local_index(chunk_pos))? else {
+            return Ok(None);
+        };
+
+        if data.position != chunk_pos {
+            return Err(format!(
+                "Region slot for chunk {:?} holds chunk {:?}",
+                chunk_pos, data.position
+            ));
+        }
+
+        Ok(Some(data))
+    }
+
+    fn read_slot(&mut self, index: usize) -> Result<Option<ChunkData>, String> {
+        let location = self.table[index];
+        if location.sector_count == 0 {
+            return Ok(None);
+        }
+
+        self.file
+            .seek(SeekFrom::Start(location.sector_offset as u64 * SECTOR_SIZE))
+            .map_err(|e| format!("Failed to seek to region slot {}: {}", index, e))?;
+
+        let mut payload_header = [0u8; PAYLOAD_HEADER_BYTES as usize];
+        self.file
+            .read_exact(&mut payload_header)
+            .map_err(|e| format!("Failed to read region slot {}: {}", index, e))?;
+
+        let length = u32::from_le_bytes(payload_header[0..4].try_into().unwrap()) as u64;
+        if length + PAYLOAD_HEADER_BYTES > location.sector_count as u64 * SECTOR_SIZE {
+            return Err(format!("Region slot {} payload overruns its sectors", index));
+        }
+
+        let mut payload = vec![0u8; length as usize];
+        self.file
+            .read_exact(&mut payload)
+            .map_err(|e| format!("Failed to read region slot {}: {}", index, e))?;
+
+        let bytes = match payload_header[4] {
+            COMPRESSION_NONE => payload,
+            COMPRESSION_ZLIB => {
+                let mut bytes = Vec::new();
+                ZlibDecoder::new(payload.as_slice())
+                    .read_to_end(&mut bytes)
+                    .map_err(|e| format!("Failed to decompress region slot {}: {}", index, e))?;
+                bytes
+            }
+            other => {
+                return Err(format!(
+                    "Region slot {} uses unknown compression {}",
+                    index, other
+                ));
+            }
+        };
+
+        let deserialize_error =
+            |e: bincode::Error| format!("Failed to deserialize region slot {}: {}", index, e);
+        let data = match self.version {
+            1 => {
+                let flat: FlatChunkData = bincode::deserialize(&bytes).map_err(deserialize_error)?;
+                ChunkData::from(flat)
+            }
+            2 => {
+                let paletted: PalettedChunkData =
+                    bincode::deserialize(&bytes).map_err(deserialize_error)?;
+                ChunkData::from(paletted)
+            }
+            _ => bincode::deserialize::<ChunkData>(&bytes).map_err(deserialize_error)?,
+        };
+        data.validate()
+            .map_err(|e| format!("Corrupt chunk {:?}: {}", data.position, e))?;
+
+        Ok(Some(data))
+    }
+
+    /// Compress and write one chunk, leaving the rest of the region untouched
+    pub fn write_chunk(&mut self, data: &ChunkData) -> Result<(), String> {
+        let bytes = bincode::serialize(data)
+            .map_err(|e| format!("Failed to serialize chunk {:?}: {}", data.position, e))?;
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&bytes)
+            .map_err(|e| format!("Failed to compress chunk {:?}: {}", data.position, e))?;
+        let compressed = encoder
+            .finish()
+            .map_err(|e| format!("Failed to compress chunk {:?}: {}", data.position, e))?;
+
+        let mut payload = Vec::with_capacity(compressed.len() + PAYLOAD_HEADER_BYTES as usize);
+        payload.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        payload.push(COMPRESSION_ZLIB);
+        payload.extend_from_slice(&compressed);
+
+        let sectors_needed = (payload.len() as u64).div_ceil(SECTOR_SIZE) as u32;
+        let index = local_index(data.position);
+        let current = self.table[index];
+
+        let sector_offset = if current.sector_count > 0 && sectors_needed <= current.sector_count {
+            current.sector_offset
+        } else {
+            // The old sectors stay in use until the table points elsewhere,
+            // and are free for the next write after that
+            let file_len = self
+                .file
+                .metadata()
+                .map_err(|e| format!("Failed to read region size: {}", e))?
+                .len();
+            self.free_sectors(sectors_needed, file_len.div_ceil(SECTOR_SIZE) as u32)
+        };
+
+        // Pad to whole sectors so the next append starts on a boundary
+        payload.resize((sectors_needed as u64 * SECTOR_SIZE) as usize, 0);
+
+        self.file
+            .seek(SeekFrom::Start(sector_offset as u64 * SECTOR_SIZE))
+            .and_then(|_| self.file.write_all(&payload))
+            .map_err(|e| format!("Failed to write chunk {:?}: {}", data.position, e))?;
+
+        let location = ChunkLocation {
+            sector_offset,
+            sector_count: sectors_needed,
+        };
+        let mut entry = [0u8; 8];
+        entry[0..4].copy_from_slice(&location.sector_offset.to_le_bytes());
+        entry[4..8].copy_from_slice(&location.sector_count.to_le_bytes());
+
+        self.file
+            .seek(SeekFrom::Start(8 + index as u64 * 8))
+            .and_then(|_| self.file.write_all(&entry))
+            .map_err(|e| format!("Failed to update region table: {}", e))?;
+
+        self.table[index] = location;
+        Ok(())
+    }
+
+    /// Start of the first run of `count` sectors that no stored chunk uses.
+    /// A free run at the end of the file may continue past it.
+    fn free_sectors(&self, count: u32, file_sectors: u32) -> u32 {
+        let mut used = vec![false; file_sectors as usize];
+        for location in &self.table {
+            let start = location.sector_offset as usize;
+            let end = (start + location.sector_count as usize).min(used.len());
+            if start < end {
+                used[start..end].fill(true);
+            }
+        }
+
+        let mut run_start = HEADER_SECTORS;
+        for sector in HEADER_SECTORS..file_sectors {
+            if used[sector as usize] {
+                run_start = sector + 1;
+            } else if sector + 1 - run_start == count {
+                return run_start;
+            }
+        }
+        run_start.max(HEADER_SECTORS)
+    }
+
+    /// Flush pending writes to disk
+    pub fn flush(&mut self) -> Result<(), String> {
+        self.file
+            .sync_data()
+            .map_err(|e| format!("Failed to flush region file: {}", e))
+    }
+}
@@ -0,0 +1,286 @@
+use crate::config::loader::{load_config, ConfigError};
+use crate::voxel::light::MAX_LIGHT;
+use crate::voxel::types::{Support, TerrainMaterial, ToolType, VoxelType, VoxelTypeInfo};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use thiserror::Error;
+
+pub const VOXEL_TYPES_CONFIG_PATH: &str = "assets/config/voxel_types.yaml";
+
+/// Registry ids the engine refers to by constant, in id order
+const BUILTIN_IDS: [&str; 12] = [
+    "air",
+    "topsoil",
+    "subsoil",
+    "rock",
+    "bedrock",
+    "sand",
+    "clay",
+    "water",
+    "wood",
+    "leaves",
+    "dungeon_wall",
+    "dungeon_floor",
+];
+
+/// Registry installed for the `Voxel` trait and other code without ECS access
+static GLOBAL_REGISTRY: OnceLock<VoxelRegistry> = OnceLock::new();
+
+#[derive(Error, Debug)]
+pub enum RegistryError {
+    #[error("failed to read voxel types: {0}")]
+    Config(#[from] ConfigError),
+    #[error("voxel type list is empty")]
+    Empty,
+    #[error("too many voxel types ({0}), at most 256 are supported")]
+    TooMany(usize),
+    #[error("duplicate voxel type id '{0}'")]
+    DuplicateId(String),
+    #[error("built-in voxel type '{expected}' must be entry {index}, found '{found}'")]
+    BuiltinMismatch {
+        index: usize,
+        expected: &'static str,
+        found: String,
+    },
+}
+
+/// One entry of `voxel_types.yaml`
+#[derive(Deserialize, Clone, Debug)]
+pub struct VoxelTypeDef {
+    pub id: String,
+    #[serde(default)]
+    pub solid: bool,
+    /// Defaults to `!solid`
+    #[serde(default)]
+    pub transparent: Option<bool>,
+    #[serde(default)]
+    pub liquid: bool,
+    #[serde(default)]
+    pub hardness: f32,
+    #[serde(default)]
+    pub tool_required: ToolType,
+    #[serde(default)]
+    pub atlas_index: u8,
+    #[serde(default)]
+    pub atlas_top: Option<u8>,
+    #[serde(default)]
+    pub atlas_bottom: Option<u8>,
+    #[serde(default)]
+    pub atlas_side: Option<u8>,
+    #[serde(default)]
+    pub material: TerrainMaterial,
+    #[serde(default)]
+    pub bottom_material: Option<TerrainMaterial>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub support: Support,
+    /// Clamped to `MAX_LIGHT`
+    #[serde(default)]
+    pub light_emission: u8,
+}
+
+#[derive(Deserialize)]
+struct VoxelTypesFile {
+    voxel_types: Vec<VoxelTypeDef>,
+}
+
+/// Voxel properties indexed by `VoxelType` id.
+///
+/// Entries are assigned ids in file order, so new types must be appended to
+/// keep existing saves valid.
+#[derive(Resource, Clone, Debug)]
+pub struct VoxelRegistry {
+    types: Vec<VoxelTypeInfo>,
+    by_name: HashMap<String, VoxelType>,
+}
+
+impl VoxelRegistry {
+    /// Load and validate the registry from a YAML file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RegistryError> {
+        let file: VoxelTypesFile = load_config(path)?;
+        Self::from_defs(file.voxel_types)
+    }
+
+    pub fn from_defs(defs: Vec<VoxelTypeDef>) -> Result<Self, RegistryError> {
+        if defs.is_empty() {
+            return Err(RegistryError::Empty);
+        }
+        if defs.len() > u8::MAX as usize + 1 {
+            return Err(RegistryError::TooMany(defs.len()));
+        }
+
+        for (index, &expected) in BUILTIN_IDS.iter().enumerate() {
+            match defs.get(index) {
+                Some(def) if def.id == expected => {}
+                other => {
+                    return Err(RegistryError::BuiltinMismatch {
+                        index,
+                        expected,
+                        found: other.map(|d| d.id.clone()).unwrap_or_default(),
+                    });
+                }
+            }
+        }
+
+        let mut types = Vec::with_capacity(defs.len());
+        let mut by_name = HashMap::with_capacity(defs.len());
+
+        for (index, def) in defs.into_iter().enumerate() {
+            let voxel = VoxelType::from_id(index as u8);
+            if by_name.insert(def.id.clone(), voxel).is_some() {
+                return Err(RegistryError::DuplicateId(def.id));
+            }
+
+            types.push(VoxelTypeInfo {
+                transparent: def.transparent.unwrap_or(!def.solid),
+                name: def.id,
+                solid: def.solid,
+                liquid: def.liquid,
+                hardness: def.hardness,
+                tool_required: def.tool_required,
+                atlas_index: def.atlas_index,
+                atlas_top: def.atlas_top,
+                atlas_bottom: def.atlas_bottom,
+                atlas_side: def.atlas_side,
+                material: def.material,
+                bottom_material: def.bottom_material,
+                tags: def.tags,
+                support: def.support,
+                light_emission: def.light_emission.min(MAX_LIGHT),
+            });
+        }
+
+        Ok(Self { types, by_name })
+    }
+
+    /// Built-in types matching the shipped `voxel_types.yaml`.
+    /// Used when the file is missing and by tools that run without assets.
+    pub fn builtin() -> Self {
+        let def = |id: &str, solid: bool, hardness: f32, tool: ToolType, atlas: u8, material: TerrainMaterial, tags: &[&str]| VoxelTypeDef {
+            id: id.to_string(),
+            solid,
+            transparent: None,
+            liquid: false,
+            hardness,
+            tool_required: tool,
+            atlas_index: atlas,
+            atlas_top: None,
+            atlas_bottom: None,
+            atlas_side: None,
+            material,
+            bottom_material: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            support: Support::Attached,
+            light_emission: 0,
+        };
+
+        let defs = vec![
+            def("air", false, 0.0, ToolType::None, 0, TerrainMaterial::Grass, &["hidden"]),
+            VoxelTypeDef {
+                atlas_top: Some(0),
+                atlas_bottom: Some(1),
+                atlas_side: Some(7),
+                bottom_material: Some(TerrainMaterial::Dirt),
+                ..def("topsoil", true, 1.0, ToolType::Shovel, 0, TerrainMaterial::Grass, &["material", "soil", "ground"])
+            },
+            def("subsoil", true, 1.5, ToolType::Shovel, 1, TerrainMaterial::Dirt, &["material", "soil"]),
+            def("rock", true, 4.0, ToolType::Pickaxe, 2, TerrainMaterial::Rock, &["material", "stone"]),
+            VoxelTypeDef {
+                support: Support::Anchored,
+                ..def("bedrock", true, -1.0, ToolType::None, 3, TerrainMaterial::Rock, &["hidden"])
+            },
+            VoxelTypeDef {
+                support: Support::Loose,
+                ..def("sand", true, 0.8, ToolType::Shovel, 4, TerrainMaterial::Sand, &["material", "sand"])
+            },
+            VoxelTypeDef {
+                support: Support::Loose,
+                ..def("clay", true, 2.0, ToolType::Shovel, 5, TerrainMaterial::Dirt, &["material", "clay"])
+            },
+            VoxelTypeDef {
+                transparent: Some(true),
+                liquid: true,
+                ..def("water", false, 0.0, ToolType::None, 6, TerrainMaterial::Grass, &["liquid", "water"])
+            },
+            def("wood", true, 2.0, ToolType::None, 8, TerrainMaterial::Dirt, &["material", "wood", "tree"]),
+            VoxelTypeDef {
+                transparent: Some(true),
+                ..def("leaves", true, 0.2, ToolType::None, 9, TerrainMaterial::Dirt, &["material", "foliage"])
+            },
+            def("dungeon_wall", true, 6.0, ToolType::Pickaxe, 10, TerrainMaterial::Rock, &["material", "dungeon"]),
+            def("dungeon_floor", true, 6.0, ToolType::Pickaxe, 11, TerrainMaterial::Rock, &["material", "dungeon"]),
+            VoxelTypeDef {
+                light_emission: MAX_LIGHT,
+                ..def("glowstone", true, 1.0, ToolType::Pickaxe, 12, TerrainMaterial::Rock, &["material", "light"])
+            },
+            def("workbench", true, 2.5, ToolType::None, 8, TerrainMaterial::Dirt, &["material", "wood", "crafting"]),
+        ];
+
+        Self::from_defs(defs).expect("built-in voxel types are valid")
+    }
+
+    /// Load from the default config path, falling back to the built-in types
+    pub fn load_or_builtin() -> Self {
+        match Self::load(VOXEL_TYPES_CONFIG_PATH) {
+            Ok(registry) => {
+                info!(
+                    "Loaded {} voxel types from {}",
+                    registry.len(),
+                    VOXEL_TYPES_CONFIG_PATH
+                );
+                registry
+            }
+            Err(e) => {
+                warn!("Failed to load voxel types: {}. Using built-in types.", e);
+                Self::builtin()
+            }
+        }
+    }
+
+    /// Registry used by the `Voxel` trait. Falls back to the built-in types
+    /// if nothing was installed.
+    pub fn global() -> &'static VoxelRegistry {
+        GLOBAL_REGISTRY.get_or_init(Self::builtin)
+    }
+
+    /// Install this registry for code that cannot reach the ECS resource.
+    /// Only the first install wins; returns false if one was already set.
+    pub fn install_global(&self) -> bool {
+        GLOBAL_REGISTRY.set(self.clone()).is_ok()
+    }
+
+    pub fn info(&self, voxel: VoxelType) -> &VoxelTypeInfo {
+        self.types
+            .get(voxel.id() as usize)
+            .unwrap_or(&self.types[VoxelType::Air.id() as usize])
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<VoxelType> {
+        self.by_name.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// All registered voxel types in id order
+    pub fn iter(&self) -> impl Iterator<Item = (VoxelType, &VoxelTypeInfo)> {
+        self.types
+            .iter()
+            .enumerate()
+            .map(|(index, info)| (VoxelType::from_id(index as u8), info))
+    }
+
+    /// Types the player can pick from the placement palette
+    pub fn placeable(&self) -> impl Iterator<Item = (VoxelType, &VoxelTypeInfo)> {
+        self.iter().filter(|(_, info)| !info.is_hidden())
+    }
+}
@@ -1,6 +1,6 @@
 use crate::constants::VOXEL_SIZE;
 use crate::voxel::chunk::LodLevel;
-use bevy::prelude::{Resource, Vec3};
+use bevy::prelude::{IVec3, Resource, Vec3};
 use std::collections::HashSet;
 
 /// Flags indicating which chunk faces a vertex touches.
@@ -31,7 +31,7 @@ impl BoundaryFlags {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ChunkFace {
     NegX,
     PosX,
@@ -41,6 +41,44 @@ pub enum ChunkFace {
     PosZ,
 }
 
+impl ChunkFace {
+    pub const ALL: [ChunkFace; 6] = [
+        ChunkFace::NegX,
+        ChunkFace::PosX,
+        ChunkFace::NegY,
+        ChunkFace::PosY,
+        ChunkFace::NegZ,
+        ChunkFace::PosZ,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Offset to the neighbouring chunk across this face
+    pub fn offset(self) -> IVec3 {
+        match self {
+            ChunkFace::NegX => IVec3::NEG_X,
+            ChunkFace::PosX => IVec3::X,
+            ChunkFace::NegY => IVec3::NEG_Y,
+            ChunkFace::PosY => IVec3::Y,
+            ChunkFace::NegZ => IVec3::NEG_Z,
+            ChunkFace::PosZ => IVec3::Z,
+        }
+    }
+
+    pub fn opposite(self) -> ChunkFace {
+        match self {
+            ChunkFace::NegX => ChunkFace::PosX,
+            ChunkFace::PosX => ChunkFace::NegX,
+            ChunkFace::NegY => ChunkFace::PosY,
+            ChunkFace::PosY => ChunkFace::NegY,
+            ChunkFace::NegZ => ChunkFace::PosZ,
+            ChunkFace::PosZ => ChunkFace::NegZ,
+        }
+    }
+}
+
 /// Determine boundary flags for a vertex position in chunk-local voxel units.
 pub fn compute_boundary_flags(local_pos: Vec3, chunk_size: f32) -> BoundaryFlags {
     const EPSILON: f32 = 0.01;
@@ -64,6 +102,9 @@ pub struct BoundaryEdge {
     pub v1_normal: Vec3,
     pub v0_weights: [f32; 4],
     pub v1_weights: [f32; 4],
+    /// Sky and block light, see `MeshData::light`
+    pub v0_light: [f32; 2],
+    pub v1_light: [f32; 2],
     pub face: ChunkFace,
 }
 
@@ -106,6 +147,7 @@ pub fn extract_boundary_edges(
     normals: &[[f32; 3]],
     indices: &[u32],
     material_weights: &[[f32; 4]],
+    light: &[[f32; 2]],
     chunk_size: f32,
 ) -> Vec<BoundaryEdge> {
     let mut boundary_edges: Vec<BoundaryEdge> = Vec::new();
@@ -157,6 +199,8 @@ pub fn extract_boundary_edges(
                 let v1_normal = Vec3::from_array(normals.get(i1).copied().unwrap_or([0.0, 1.0, 0.0]));
                 let v0_weights = *material_weights.get(i0).unwrap_or(&[0.0, 0.0, 0.0, 1.0]);
                 let v1_weights = *material_weights.get(i1).unwrap_or(&[0.0, 0.0, 0.0, 1.0]);
+                let v0_light = *light.get(i0).unwrap_or(&[1.0, 0.0]);
+                let v1_light = *light.get(i1).unwrap_or(&[1.0, 0.0]);
 
                 boundary_edges.push(BoundaryEdge {
                     v0_pos,
@@ -165,6 +209,8 @@ pub fn extract_boundary_edges(
                     v1_normal,
                     v0_weights,
                     v1_weights,
+                    v0_light,
+                    v1_light,
                     face,
                 });
             }
@@ -193,6 +239,7 @@ impl Default for SkirtConfig {
 }
 
 /// Neighbor LOD information for adaptive skirts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct NeighborLods {
     pub neg_x: Option<LodLevel>,
     pub pos_x: Option<LodLevel>,
@@ -201,18 +248,29 @@ pub struct NeighborLods {
 }
 
 impl NeighborLods {
-    pub fn needs_skirt(&self, face: ChunkFace, my_lod: LodLevel) -> bool {
-        let neighbor_lod = match face {
+    fn neighbor(&self, face: ChunkFace) -> Option<LodLevel> {
+        match face {
             ChunkFace::NegX => self.neg_x,
             ChunkFace::PosX => self.pos_x,
             ChunkFace::NegZ => self.neg_z,
             ChunkFace::PosZ => self.pos_z,
             ChunkFace::NegY | ChunkFace::PosY => None,
-        };
+        }
+    }
+
+    pub fn needs_skirt(&self, face: ChunkFace, my_lod: LodLevel) -> bool {
+        self.skirt_scale(face, my_lod).is_some()
+    }
 
-        match neighbor_lod {
-            Some(n_lod) => n_lod.is_lower_detail_than(my_lod),
-            None => true,
+    /// Multiple of `SkirtConfig::depth` the skirt on `face` must reach down,
+    /// or `None` when the neighbor is at least as detailed and covers the
+    /// seam itself. A neighbor n levels coarser has voxels 2^n times larger,
+    /// so its surface can be that much further off.
+    pub fn skirt_scale(&self, face: ChunkFace, my_lod: LodLevel) -> Option<f32> {
+        match self.neighbor(face) {
+            Some(n_lod) if !n_lod.is_lower_detail_than(my_lod) => None,
+            Some(n_lod) => Some((1u32 << n_lod.levels_coarser_than(my_lod).min(16)) as f32),
+            None => Some(1.0),
         }
     }
 }
@@ -223,6 +281,7 @@ pub fn generate_skirts(
     normals: &mut Vec<[f32; 3]>,
     uvs: &mut Vec<[f32; 2]>,
     material_weights: &mut Vec<[f32; 4]>,
+    light: &mut Vec<[f32; 2]>,
     indices: &mut Vec<u32>,
     boundary_edges: &[BoundaryEdge],
     config: &SkirtConfig,
@@ -234,9 +293,11 @@ pub fn generate_skirts(
     }
 
     for edge in boundary_edges {
-        if config.adaptive && !neighbor_lods.needs_skirt(edge.face, my_lod) {
-            continue;
-        }
+        let scale = match neighbor_lods.skirt_scale(edge.face, my_lod) {
+            Some(scale) => scale,
+            None if config.adaptive => continue,
+            None => 1.0,
+        };
 
         let skirt_normal = match edge.face {
             ChunkFace::NegX => Vec3::NEG_X,
@@ -247,7 +308,7 @@ pub fn generate_skirts(
         };
 
         let base_idx = positions.len() as u32;
-        let drop = Vec3::new(0.0, -config.depth, 0.0);
+        let drop = Vec3::new(0.0, -config.depth * scale, 0.0);
 
         let top0 = edge.v0_pos;
         let top1 = edge.v1_pos;
@@ -262,21 +323,25 @@ pub fn generate_skirts(
         normals.push(blended_normal0.to_array());
         uvs.push([1.0, 0.0]);
         material_weights.push(edge.v0_weights);
+        light.push(edge.v0_light);
 
         positions.push(top1.to_array());
         normals.push(blended_normal1.to_array());
         uvs.push([1.0, 0.0]);
         material_weights.push(edge.v1_weights);
+        light.push(edge.v1_light);
 
         positions.push(bot0.to_array());
         normals.push(blended_normal0.to_array());
         uvs.push([1.0, 0.0]);
         material_weights.push(edge.v0_weights);
+        light.push(edge.v0_light);
 
         positions.push(bot1.to_array());
         normals.push(blended_normal1.to_array());
         uvs.push([1.0, 0.0]);
         material_weights.push(edge.v1_weights);
+        light.push(edge.v1_light);
 
         match edge.face {
             ChunkFace::NegX | ChunkFace::PosZ => {
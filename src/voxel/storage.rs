@@ -0,0 +1,216 @@
+use crate::constants::CHUNK_VOLUME;
+use crate::voxel::types::VoxelType;
+use serde::{Deserialize, Serialize};
+
+/// Voxel storage for a single chunk.
+///
+/// Chunks made of one type (all air, all rock) keep just that type. Mixed
+/// chunks keep a palette of the types they contain plus bit-packed palette
+/// indices, so memory scales with how varied a chunk is rather than its volume.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ChunkStorage {
+    Uniform(VoxelType),
+    Paletted(PalettedStorage),
+}
+
+/// Palette plus packed indices. Indices never straddle a word, so `bits` is
+/// always 1, 2, 4 or 8.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PalettedStorage {
+    palette: Vec<VoxelType>,
+    bits: u32,
+    words: Vec<u64>,
+}
+
+/// Smallest supported index width that can address `palette_len` entries
+fn bits_for(palette_len: usize) -> u32 {
+    match palette_len {
+        0..=2 => 1,
+        3..=4 => 2,
+        5..=16 => 4,
+        _ => 8,
+    }
+}
+
+impl PalettedStorage {
+    fn new(palette: Vec<VoxelType>, bits: u32) -> Self {
+        let per_word = (64 / bits) as usize;
+        Self {
+            palette,
+            bits,
+            words: vec![0; CHUNK_VOLUME.div_ceil(per_word)],
+        }
+    }
+
+    fn index_at(&self, index: usize) -> usize {
+        let per_word = (64 / self.bits) as usize;
+        let shift = (index % per_word) as u32 * self.bits;
+        let mask = (1u64 << self.bits) - 1;
+        ((self.words[index / per_word] >> shift) & mask) as usize
+    }
+
+    fn set_index_at(&mut self, index: usize, palette_index: usize) {
+        let per_word = (64 / self.bits) as usize;
+        let shift = (index % per_word) as u32 * self.bits;
+        let mask = (1u64 << self.bits) - 1;
+        let word = &mut self.words[index / per_word];
+        *word = (*word & !(mask << shift)) | ((palette_index as u64 & mask) << shift);
+    }
+
+    fn get(&self, index: usize) -> VoxelType {
+        self.palette[self.index_at(index)]
+    }
+
+    /// Palette index for `voxel`, adding it (and widening indices) if needed
+    fn palette_index(&mut self, voxel: VoxelType) -> usize {
+        if let Some(i) = self.palette.iter().position(|v| *v == voxel) {
+            return i;
+        }
+
+        if self.palette.len() == 1 << self.bits {
+            self.repack(bits_for(self.palette.len() + 1));
+        }
+        self.palette.push(voxel);
+        self.palette.len() - 1
+    }
+
+    fn repack(&mut self, bits: u32) {
+        let mut packed = PalettedStorage::new(std::mem::take(&mut self.palette), bits);
+        for i in 0..CHUNK_VOLUME {
+            packed.set_index_at(i, self.index_at(i));
+        }
+        *self = packed;
+    }
+}
+
+impl ChunkStorage {
+    /// Build compact storage from a flat voxel array (missing entries are air)
+    pub fn from_voxels(voxels: &[VoxelType]) -> Self {
+        let mut storage = ChunkStorage::Uniform(voxels.first().copied().unwrap_or(VoxelType::Air));
+        for i in 0..CHUNK_VOLUME {
+            storage.set(i, voxels.get(i).copied().unwrap_or(VoxelType::Air));
+        }
+        storage.compact();
+        storage
+    }
+
+    pub fn get(&self, index: usize) -> VoxelType {
+        match self {
+            ChunkStorage::Uniform(voxel) => *voxel,
+            ChunkStorage::Paletted(paletted) => paletted.get(index),
+        }
+    }
+
+    /// Returns true if the voxel changed
+    pub fn set(&mut self, index: usize, voxel: VoxelType) -> bool {
+        match self {
+            ChunkStorage::Uniform(current) => {
+                if *current == voxel {
+                    return false;
+                }
+                let mut paletted = PalettedStorage::new(vec![*current, voxel], 1);
+                paletted.set_index_at(index, 1);
+                *self = ChunkStorage::Paletted(paletted);
+                true
+            }
+            ChunkStorage::Paletted(paletted) => {
+                if paletted.get(index) == voxel {
+                    return false;
+                }
+                let palette_index = paletted.palette_index(voxel);
+                paletted.set_index_at(index, palette_index);
+                true
+            }
+        }
+    }
+
+    /// The single voxel type if the whole chunk is one type
+    pub fn uniform(&self) -> Option<VoxelType> {
+        match self {
+            ChunkStorage::Uniform(voxel) => Some(*voxel),
+            ChunkStorage::Paletted(_) => None,
+        }
+    }
+
+    /// Palette entries, including ones left unused until `compact` runs
+    pub fn palette_len(&self) -> usize {
+        match self {
+            ChunkStorage::Uniform(_) => 1,
+            ChunkStorage::Paletted(paletted) => paletted.palette.len(),
+        }
+    }
+
+    /// Approximate heap size in bytes
+    pub fn memory_bytes(&self) -> usize {
+        match self {
+            ChunkStorage::Uniform(_) => 0,
+            ChunkStorage::Paletted(paletted) => {
+                paletted.palette.len() * std::mem::size_of::<VoxelType>()
+                    + paletted.words.len() * std::mem::size_of::<u64>()
+            }
+        }
+    }
+
+    /// Drop palette entries that are no longer used, shrink the index width
+    /// and collapse to `Uniform` when only one type is left
+    pub fn compact(&mut self) {
+        let ChunkStorage::Paletted(paletted) = self else {
+            return;
+        };
+
+        let mut used = vec![false; paletted.palette.len()];
+        for i in 0..CHUNK_VOLUME {
+            used[paletted.index_at(i)] = true;
+        }
+
+        let used_count = used.iter().filter(|u| **u).count();
+        if used_count == 1 {
+            let voxel = paletted.get(0);
+            *self = ChunkStorage::Uniform(voxel);
+            return;
+        }
+        if used_count == paletted.palette.len() && bits_for(used_count) == paletted.bits {
+            return;
+        }
+
+        let mut remap = vec![0; paletted.palette.len()];
+        let mut palette = Vec::with_capacity(used_count);
+        for (old, voxel) in paletted.palette.iter().enumerate() {
+            if used[old] {
+                remap[old] = palette.len();
+                palette.push(*voxel);
+            }
+        }
+
+        let mut packed = PalettedStorage::new(palette, bits_for(used_count));
+        for i in 0..CHUNK_VOLUME {
+            packed.set_index_at(i, remap[paletted.index_at(i)]);
+        }
+        *paletted = packed;
+    }
+
+    /// Check deserialized storage before indexing into it
+    pub fn validate(&self) -> Result<(), String> {
+        let ChunkStorage::Paletted(paletted) = self else {
+            return Ok(());
+        };
+
+        if ![1, 2, 4, 8].contains(&paletted.bits) {
+            return Err(format!("invalid palette index width {}", paletted.bits));
+        }
+        if paletted.palette.is_empty() || paletted.palette.len() > 1 << paletted.bits {
+            return Err(format!(
+                "palette of {} entries does not fit {} bit indices",
+                paletted.palette.len(),
+                paletted.bits
+            ));
+        }
+        if paletted.words.len() != CHUNK_VOLUME.div_ceil((64 / paletted.bits) as usize) {
+            return Err(format!("expected packed data for {} voxels", CHUNK_VOLUME));
+        }
+        if (0..CHUNK_VOLUME).any(|i| paletted.index_at(i) >= paletted.palette.len()) {
+            return Err("palette index out of range".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,317 @@
+use crate::camera::controller::PlayerCamera;
+use crate::constants::CHUNK_SIZE_I32;
+use crate::voxel::chunk::Chunk;
+use crate::voxel::persistence::{self, ActiveWorld, ChunkStore, SaveSlot};
+use crate::voxel::world::VoxelWorld;
+use crate::voxel::worldgen::{GenerationStats, WorldGen};
+use bevy::prelude::*;
+
+/// Controls which chunks are kept in memory around the player
+#[derive(Resource, Clone, Debug)]
+pub struct ChunkStreamingSettings {
+    pub enabled: bool,
+    /// Horizontal radius in chunks that is generated or loaded around the player
+    pub load_radius: i32,
+    /// Chunks further than this (in chunks) are evicted; keep it above `load_radius`
+    pub unload_radius: i32,
+    /// Vertical chunk range that gets streamed (min inclusive, max exclusive)
+    pub min_chunk_y: i32,
+    pub max_chunk_y: i32,
+    /// Per-frame budgets so crossing a chunk border doesn't stall a frame
+    pub max_loads_per_frame: usize,
+    pub max_unloads_per_frame: usize,
+    /// Keep the world inside `VoxelWorld::world_size_chunks`
+    pub world_border: bool,
+}
+
+impl Default for ChunkStreamingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            load_radius: 12,
+            unload_radius: 16,
+            min_chunk_y: 0,
+            max_chunk_y: 4,
+            max_loads_per_frame: 8,
+            max_unloads_per_frame: 16,
+            world_border: false,
+        }
+    }
+}
+
+impl ChunkStreamingSettings {
+    /// Chunk positions within `load_radius` of `center`, nearest first
+    pub fn positions_around(&self, center: IVec3) -> Vec<IVec3> {
+        let radius = self.load_radius.max(0);
+        let radius_sq = radius * radius;
+        let mut positions = Vec::new();
+
+        for dx in -radius..=radius {
+            for dz in -radius..=radius {
+                if dx * dx + dz * dz > radius_sq {
+                    continue;
+                }
+                for y in self.min_chunk_y..self.max_chunk_y {
+                    positions.push(IVec3::new(center.x + dx, y, center.z + dz));
+                }
+            }
+        }
+
+        positions.sort_by_key(|pos| {
+            let dx = pos.x - center.x;
+            let dz = pos.z - center.z;
+            (dx * dx + dz * dz, pos.y)
+        });
+        positions
+    }
+
+    /// Whether a loaded chunk is far enough from `center` to be evicted
+    pub fn should_unload(&self, chunk_pos: IVec3, center: IVec3) -> bool {
+        let dx = chunk_pos.x - center.x;
+        let dz = chunk_pos.z - center.z;
+        dx * dx + dz * dz > self.unload_radius * self.unload_radius
+    }
+}
+
+/// Chunks still waiting to be loaded for the current streaming centres
+#[derive(Default)]
+pub struct StreamingQueue {
+    centers: Vec<IVec3>,
+    /// Farthest first, so the nearest chunk is popped from the end
+    pending: Vec<IVec3>,
+}
+
+/// Positions that keep chunks loaded besides the local player, such as the
+/// players connected to a server
+#[derive(Resource, Default, Clone, Debug)]
+pub struct StreamingAnchors {
+    pub positions: Vec<Vec3>,
+}
+
+/// Chunk column (y = 0) containing a world position
+pub fn chunk_column(pos: Vec3) -> IVec3 {
+    IVec3::new(
+        (pos.x / CHUNK_SIZE_I32 as f32).floor() as i32,
+        0,
+        (pos.z / CHUNK_SIZE_I32 as f32).floor() as i32,
+    )
+}
+
+/// Chunk columns of the local player and every anchor, without duplicates
+fn streaming_centers(
+    camera_query: &Query<&Transform, With<PlayerCamera>>,
+    anchors: &StreamingAnchors,
+) -> Vec<IVec3> {
+    let mut centers: Vec<IVec3> = camera_query
+        .iter()
+        .next()
+        .map(|transform| transform.translation)
+        .into_iter()
+        .chain(anchors.positions.iter().copied())
+        .map(chunk_column)
+        .collect();
+    centers.sort_by_key(|c| (c.x, c.z));
+    centers.dedup();
+    centers
+}
+
+/// Mark the six face neighbours dirty so their boundary faces get rebuilt
+pub fn mark_neighbor_chunks_dirty(world: &mut VoxelWorld, chunk_pos: IVec3) {
+    for offset in [
+        IVec3::X,
+        IVec3::NEG_X,
+        IVec3::Y,
+        IVec3::NEG_Y,
+        IVec3::Z,
+        IVec3::NEG_Z,
+    ] {
+        if let Some(neighbor) = world.get_chunk_mut(chunk_pos + offset) {
+            neighbor.mark_dirty();
+        }
+    }
+}
+
+/// Read a chunk from the save, generating it if it was never stored
+pub fn load_or_generate_chunk(
+    store: &ChunkStore,
+    generator: &WorldGen,
+    chunk_pos: IVec3,
+    stats: &mut GenerationStats,
+) -> Chunk {
+    match store.load_chunk(chunk_pos) {
+        Ok(Some(chunk)) => chunk,
+        Ok(None) => generator.generate_chunk(chunk_pos, stats),
+        Err(e) => {
+            warn!("{}. Regenerating chunk {:?}", e, chunk_pos);
+            generator.generate_chunk(chunk_pos, stats)
+        }
+    }
+}
+
+/// Fill in the chunks needed before the first frame: the area around
+/// `center` when streaming, otherwise the whole bordered world.
+pub fn populate_initial_chunks(
+    world: &mut VoxelWorld,
+    store: &ChunkStore,
+    generator: &WorldGen,
+    settings: &ChunkStreamingSettings,
+    center: IVec3,
+    totals: &mut GenerationStats,
+) {
+    let chunk_positions: Vec<IVec3> = if settings.enabled {
+        settings
+            .positions_around(center)
+            .into_iter()
+            .filter(|pos| world.chunk_in_bounds(*pos))
+            .collect()
+    } else {
+        world.all_chunk_positions().collect()
+    };
+
+    for chunk_pos in chunk_positions {
+        if world.chunk_exists(chunk_pos) {
+            continue;
+        }
+        let chunk = load_or_generate_chunk(store, generator, chunk_pos, totals);
+        world.insert_chunk(chunk);
+    }
+}
+
+/// Swap the running world for the one stored in `slot`
+pub fn enter_world(
+    slot: SaveSlot,
+    world: &mut VoxelWorld,
+    active_world: &mut ActiveWorld,
+    chunk_store: &mut ChunkStore,
+    world_gen: &mut WorldGen,
+    streaming: &ChunkStreamingSettings,
+    center: IVec3,
+) -> Result<(), String> {
+    let (loaded_world, metadata) = persistence::load_world(&slot)?;
+    world_gen.reseed(metadata.seed);
+
+    *world = loaded_world;
+    world.set_border_enabled(streaming.world_border);
+    *chunk_store = slot.store();
+    *active_world = ActiveWorld::new(slot, metadata);
+
+    populate_initial_chunks(
+        world,
+        chunk_store,
+        world_gen,
+        streaming,
+        center,
+        &mut GenerationStats::default(),
+    );
+    Ok(())
+}
+
+/// Load stored chunks or generate new ones around the player and anchors
+pub fn stream_chunks_system(
+    mut world: ResMut<VoxelWorld>,
+    settings: Res<ChunkStreamingSettings>,
+    store: Res<ChunkStore>,
+    generator: Res<WorldGen>,
+    camera_query: Query<&Transform, With<PlayerCamera>>,
+    anchors: Res<StreamingAnchors>,
+    mut queue: Local<StreamingQueue>,
+) {
+    if world.border_enabled() != settings.world_border {
+        world.set_border_enabled(settings.world_border);
+    }
+
+    if !settings.enabled {
+        return;
+    }
+
+    let centers = streaming_centers(&camera_query, &anchors);
+    if centers.is_empty() {
+        return;
+    }
+
+    if queue.centers != centers || settings.is_changed() {
+        let mut pending: Vec<IVec3> = centers
+            .iter()
+            .flat_map(|center| settings.positions_around(*center))
+            .collect();
+        // Nearest to any centre first
+        pending.sort_by_key(|pos| {
+            let distance = centers
+                .iter()
+                .map(|c| (pos.x - c.x).pow(2) + (pos.z - c.z).pow(2))
+                .min()
+                .unwrap_or(0);
+            (distance, pos.x, pos.y, pos.z)
+        });
+        pending.dedup();
+        pending.reverse();
+        queue.pending = pending;
+        queue.centers = centers;
+    }
+
+    let mut loaded = 0;
+    while loaded < settings.max_loads_per_frame {
+        let Some(chunk_pos) = queue.pending.pop() else {
+            break;
+        };
+
+        if world.chunk_exists(chunk_pos) || !world.chunk_in_bounds(chunk_pos) {
+            continue;
+        }
+
+        let chunk = load_or_generate_chunk(&store, &generator, chunk_pos, &mut GenerationStats::default());
+        world.insert_chunk(chunk);
+        mark_neighbor_chunks_dirty(&mut world, chunk_pos);
+        loaded += 1;
+    }
+}
+
+/// Evict chunks far from the player and every anchor, writing edited ones
+/// back to disk
+pub fn unload_far_chunks_system(
+    mut commands: Commands,
+    mut world: ResMut<VoxelWorld>,
+    settings: Res<ChunkStreamingSettings>,
+    store: Res<ChunkStore>,
+    camera_query: Query<&Transform, With<PlayerCamera>>,
+    anchors: Res<StreamingAnchors>,
+) {
+    if !settings.enabled {
+        return;
+    }
+
+    let centers = streaming_centers(&camera_query, &anchors);
+    if centers.is_empty() {
+        return;
+    }
+
+    let far_chunks: Vec<IVec3> = world
+        .loaded_chunk_positions()
+        .filter(|pos| centers.iter().all(|center| settings.should_unload(*pos, *center)))
+        .take(settings.max_unloads_per_frame)
+        .collect();
+
+    for chunk_pos in far_chunks {
+        // Keep edited chunks in memory if they can't be written out
+        if let Some(chunk) = world.get_chunk(chunk_pos) {
+            if chunk.is_modified() {
+                if let Err(e) = store.save_chunk(chunk) {
+                    warn!("Failed to evict chunk {:?}: {}", chunk_pos, e);
+                    continue;
+                }
+            }
+        }
+
+        let Some(chunk) = world.remove_chunk(chunk_pos) else {
+            continue;
+        };
+
+        // Colliders live on the solid mesh entity, so despawning it removes both
+        if let Some(entity) = chunk.mesh_entity() {
+            commands.entity(entity).despawn();
+        }
+        if let Some(entity) = chunk.water_mesh_entity() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -1,39 +1,233 @@
+use std::fmt;
 use std::hash::Hash;
 use serde::{Serialize, Deserialize};
+use crate::voxel::registry::VoxelRegistry;
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Default, Debug, Serialize, Deserialize)]
-#[repr(u8)]
-pub enum VoxelType {
-    #[default]
-    Air = 0,
-    TopSoil = 1,
-    SubSoil = 2,
-    Rock = 3,
-    Bedrock = 4,
-    Sand = 5,
-    Clay = 6,
-    Water = 7,
-    Wood = 8,
-    Leaves = 9,
-    DungeonWall = 10,
-    DungeonFloor = 11,
+/// Numeric voxel id. The built-in ids below are referenced by world generation
+/// and gameplay code; any further ids come from `assets/config/voxel_types.yaml`.
+///
+/// Serialized as a u32 so saves written while this was a `#[repr(u8)]` enum
+/// (bincode encodes enum tags as u32) keep loading.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default, Serialize, Deserialize)]
+#[serde(try_from = "u32", into = "u32")]
+pub struct VoxelType(u8);
+
+#[allow(non_upper_case_globals)]
+impl VoxelType {
+    pub const Air: VoxelType = VoxelType(0);
+    pub const TopSoil: VoxelType = VoxelType(1);
+    pub const SubSoil: VoxelType = VoxelType(2);
+    pub const Rock: VoxelType = VoxelType(3);
+    pub const Bedrock: VoxelType = VoxelType(4);
+    pub const Sand: VoxelType = VoxelType(5);
+    pub const Clay: VoxelType = VoxelType(6);
+    pub const Water: VoxelType = VoxelType(7);
+    pub const Wood: VoxelType = VoxelType(8);
+    pub const Leaves: VoxelType = VoxelType(9);
+    pub const DungeonWall: VoxelType = VoxelType(10);
+    pub const DungeonFloor: VoxelType = VoxelType(11);
+}
+
+/// Debug names of the built-in ids, indexed by id. Kept in the old enum
+/// spelling because prop `spawn_on` lists and palette labels match on it.
+pub(crate) const BUILTIN_DEBUG_NAMES: [&str; 12] = [
+    "Air",
+    "TopSoil",
+    "SubSoil",
+    "Rock",
+    "Bedrock",
+    "Sand",
+    "Clay",
+    "Water",
+    "Wood",
+    "Leaves",
+    "DungeonWall",
+    "DungeonFloor",
+];
+
+impl VoxelType {
+    pub const fn from_id(id: u8) -> Self {
+        Self(id)
+    }
+
+    pub const fn id(self) -> u8 {
+        self.0
+    }
+
+    /// Registry properties for this voxel type
+    pub fn info(self) -> &'static VoxelTypeInfo {
+        VoxelRegistry::global().info(self)
+    }
+
+    /// Registry id string (e.g. "topsoil")
+    pub fn name(self) -> &'static str {
+        &self.info().name
+    }
+}
+
+/// Only ids of types in the global registry convert; anything else (a
+/// corrupt save, or a type removed from `voxel_types.yaml`) is an error
+/// rather than being truncated into some other type.
+impl TryFrom<u32> for VoxelType {
+    type Error = String;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        let registered = VoxelRegistry::global().len();
+        u8::try_from(value)
+            .ok()
+            .filter(|id| (*id as usize) < registered)
+            .map(Self)
+            .ok_or_else(|| format!("unknown voxel id {} ({} types registered)", value, registered))
+    }
+}
+
+impl From<VoxelType> for u32 {
+    fn from(value: VoxelType) -> Self {
+        value.0 as u32
+    }
+}
+
+impl fmt::Debug for VoxelType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match BUILTIN_DEBUG_NAMES.get(self.0 as usize) {
+            Some(name) => f.write_str(name),
+            None => f.write_str(self.name()),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct VoxelTypeInfo {
+    pub name: String,
     pub solid: bool,
+    pub transparent: bool,
+    pub liquid: bool,
     pub hardness: f32,
     pub tool_required: ToolType,
     pub atlas_index: u8,
+    /// Per-face atlas overrides (grass top / dirt bottom / grass side)
+    pub atlas_top: Option<u8>,
+    pub atlas_bottom: Option<u8>,
+    pub atlas_side: Option<u8>,
+    /// Terrain texture layer used by both mesh paths
+    pub material: TerrainMaterial,
+    /// Texture layer for downward faces when it differs from `material`
+    pub bottom_material: Option<TerrainMaterial>,
+    /// Palette search tags; "hidden" keeps the type out of the palette
+    pub tags: Vec<String>,
+    /// What holds the voxel up when its surroundings are dug away
+    pub support: Support,
+    /// Block light given off, 0 (none) to `MAX_LIGHT`
+    pub light_emission: u8,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+impl VoxelTypeInfo {
+    /// Negative hardness marks a voxel as unbreakable (bedrock)
+    pub fn is_breakable(&self) -> bool {
+        self.hardness >= 0.0
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.tags.iter().any(|tag| tag == "hidden")
+    }
+}
+
+/// How a voxel stays up, see `crate::voxel::gravity`
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Support {
+    /// Stays up while connected to anchored ground through other solid voxels
+    #[default]
+    Attached,
+    /// Falls unless a solid voxel is directly below it (sand, clay)
+    Loose,
+    /// Never falls and holds up everything attached to it (bedrock)
+    Anchored,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ToolType {
+    #[default]
     None,
     Shovel,
     Pickaxe,
 }
 
+impl ToolType {
+    pub fn name(self) -> &'static str {
+        match self {
+            ToolType::None => "none",
+            ToolType::Shovel => "shovel",
+            ToolType::Pickaxe => "pickaxe",
+        }
+    }
+}
+
+/// Material grade of a tool; better tools dig their blocks faster
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolTier {
+    Wood,
+    #[default]
+    Stone,
+    Iron,
+}
+
+impl ToolTier {
+    pub const ALL: [ToolTier; 3] = [ToolTier::Wood, ToolTier::Stone, ToolTier::Iron];
+
+    /// Break speed multiplier on blocks the tool is made for
+    pub fn speed(self) -> f32 {
+        match self {
+            ToolTier::Wood => 2.0,
+            ToolTier::Stone => 4.0,
+            ToolTier::Iron => 6.0,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ToolTier::Wood => "wood",
+            ToolTier::Stone => "stone",
+            ToolTier::Iron => "iron",
+        }
+    }
+}
+
+/// Terrain texture layers shared by the blocky texture array and the triplanar shader
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TerrainMaterial {
+    #[default]
+    Grass,
+    Dirt,
+    Rock,
+    Sand,
+}
+
+impl TerrainMaterial {
+    /// Layer in the blocky texture array (grass, dirt, rock, sand)
+    pub fn blocky_layer(self) -> u8 {
+        match self {
+            TerrainMaterial::Grass => 0,
+            TerrainMaterial::Dirt => 1,
+            TerrainMaterial::Rock => 2,
+            TerrainMaterial::Sand => 3,
+        }
+    }
+
+    /// Weight slot for the triplanar shader (grass, rock, sand, dirt)
+    pub fn triplanar_slot(self) -> usize {
+        match self {
+            TerrainMaterial::Grass => 0,
+            TerrainMaterial::Rock => 1,
+            TerrainMaterial::Sand => 2,
+            TerrainMaterial::Dirt => 3,
+        }
+    }
+}
+
 // Trait for voxel queries (meshing needs this)
 pub trait Voxel {
     fn is_solid(&self) -> bool;
@@ -44,35 +238,18 @@ pub trait Voxel {
 
 impl Voxel for VoxelType {
     fn is_solid(&self) -> bool {
-        match self {
-            VoxelType::Air | VoxelType::Water => false,
-            _ => true,
-        }
+        self.info().solid
     }
 
     fn is_transparent(&self) -> bool {
-        matches!(self, VoxelType::Air | VoxelType::Water | VoxelType::Leaves)
+        self.info().transparent
     }
 
     fn is_liquid(&self) -> bool {
-        matches!(self, VoxelType::Water)
+        self.info().liquid
     }
 
     fn atlas_index(&self) -> u8 {
-        match self {
-            VoxelType::Air => 0,
-            VoxelType::TopSoil => 0,
-            VoxelType::SubSoil => 1,
-            VoxelType::Rock => 2,
-            VoxelType::Bedrock => 3,
-            VoxelType::Sand => 4,
-            VoxelType::Clay => 5,
-            VoxelType::Water => 6,
-            VoxelType::Wood => 8,
-            VoxelType::Leaves => 9,
-            VoxelType::DungeonWall => 10,
-            VoxelType::DungeonFloor => 11,
-        }
+        self.info().atlas_index
     }
 }
-
@@ -0,0 +1,329 @@
+//! CPU visibility for chunk meshes.
+//!
+//! Each frame the loaded chunks are walked breadth-first outward from the
+//! camera's chunk. A chunk is visible if it lies in the camera frustum and
+//! the walk can reach it: leaving a chunk through a face is only allowed if
+//! that face sees the face the walk came in by through non-opaque voxels
+//! (`FaceConnectivity`), and the walk never turns back towards the camera.
+//! Caves sealed off by rock are therefore never reached while the player is
+//! on the surface, and the surface is hidden while the player is deep in a
+//! sealed cave. Everything runs on the voxel data; there are no GPU queries.
+
+use crate::camera::controller::PlayerCamera;
+use crate::constants::{CHUNK_SIZE, CHUNK_SIZE_I32, CHUNK_VOLUME};
+use crate::voxel::chunk::Chunk;
+use crate::voxel::meshing::ChunkMesh;
+use crate::voxel::skirt::ChunkFace;
+use crate::voxel::types::{Voxel, VoxelType};
+use crate::voxel::world::VoxelWorld;
+use bevy::prelude::*;
+use std::collections::{HashSet, VecDeque};
+
+/// Chunks whose connectivity is recomputed per frame; the rest count as
+/// fully open until their turn comes
+const CONNECTIVITY_UPDATES_PER_FRAME: usize = 64;
+
+/// Which pairs of a chunk's faces are joined by a path of non-opaque voxels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FaceConnectivity(u64);
+
+impl FaceConnectivity {
+    pub const NONE: Self = Self(0);
+    pub const ALL: Self = Self((1 << 36) - 1);
+
+    fn bit(a: ChunkFace, b: ChunkFace) -> u64 {
+        1 << (a.index() * 6 + b.index())
+    }
+
+    pub fn connects(self, a: ChunkFace, b: ChunkFace) -> bool {
+        self.0 & Self::bit(a, b) != 0
+    }
+
+    fn connect_all(&mut self, faces: u8) {
+        for a in ChunkFace::ALL {
+            if faces & (1 << a.index()) == 0 {
+                continue;
+            }
+            for b in ChunkFace::ALL {
+                if faces & (1 << b.index()) != 0 {
+                    self.0 |= Self::bit(a, b);
+                }
+            }
+        }
+    }
+
+    /// Flood fill the chunk's non-opaque voxels, joining every pair of
+    /// faces touched by the same region.
+    pub fn compute(chunk: &Chunk) -> Self {
+        if let Some(voxel) = chunk.uniform_voxel() {
+            return if is_opaque(voxel) { Self::NONE } else { Self::ALL };
+        }
+
+        let mut visited = vec![false; CHUNK_VOLUME];
+        let mut stack = Vec::new();
+        let mut connectivity = Self::NONE;
+
+        for start in 0..CHUNK_VOLUME {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let start = IVec3::new(
+                (start % CHUNK_SIZE) as i32,
+                ((start / CHUNK_SIZE) % CHUNK_SIZE) as i32,
+                (start / (CHUNK_SIZE * CHUNK_SIZE)) as i32,
+            );
+            if is_opaque(chunk.get(start.as_uvec3())) {
+                continue;
+            }
+
+            let mut faces = 0u8;
+            stack.push(start);
+            while let Some(pos) = stack.pop() {
+                faces |= boundary_faces(pos);
+                for face in ChunkFace::ALL {
+                    let next = pos + face.offset();
+                    if next.min_element() < 0 || next.max_element() >= CHUNK_SIZE_I32 {
+                        continue;
+                    }
+                    let next_index = voxel_index(next);
+                    if visited[next_index] {
+                        continue;
+                    }
+                    visited[next_index] = true;
+                    if !is_opaque(chunk.get(next.as_uvec3())) {
+                        stack.push(next);
+                    }
+                }
+            }
+            connectivity.connect_all(faces);
+        }
+
+        connectivity
+    }
+}
+
+fn is_opaque(voxel: VoxelType) -> bool {
+    voxel.is_solid() && !voxel.is_transparent()
+}
+
+fn voxel_index(pos: IVec3) -> usize {
+    (pos.x + pos.y * CHUNK_SIZE_I32 + pos.z * CHUNK_SIZE_I32 * CHUNK_SIZE_I32) as usize
+}
+
+/// Bit set of the chunk faces a local voxel position lies on
+fn boundary_faces(pos: IVec3) -> u8 {
+    let last = CHUNK_SIZE_I32 - 1;
+    let mut faces = 0;
+    for (on_face, face) in [
+        (pos.x == 0, ChunkFace::NegX),
+        (pos.x == last, ChunkFace::PosX),
+        (pos.y == 0, ChunkFace::NegY),
+        (pos.y == last, ChunkFace::PosY),
+        (pos.z == 0, ChunkFace::NegZ),
+        (pos.z == last, ChunkFace::PosZ),
+    ] {
+        if on_face {
+            faces |= 1 << face.index();
+        }
+    }
+    faces
+}
+
+/// Side and near planes of a camera frustum, normals pointing inwards.
+#[derive(Clone, Copy, Debug)]
+pub struct ChunkFrustum {
+    planes: [Vec4; 5],
+}
+
+impl ChunkFrustum {
+    /// Planes from the camera's clip-from-world matrix. The far plane is
+    /// left out: Bevy projects it to infinity and streaming bounds the
+    /// distance anyway.
+    pub fn from_clip_from_world(clip_from_world: Mat4) -> Self {
+        let [x, y, z, w] = [0, 1, 2, 3].map(|row| clip_from_world.row(row));
+        Self {
+            planes: [w + x, w - x, w + y, w - y, w - z],
+        }
+    }
+
+    pub fn intersects_aabb(&self, min: Vec3, max: Vec3) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            // Corner furthest along the normal
+            let corner = Vec3::select(normal.cmpge(Vec3::ZERO), max, min);
+            normal.dot(corner) + plane.w >= 0.0
+        })
+    }
+
+    pub fn intersects_chunk(&self, chunk_pos: IVec3) -> bool {
+        let min = VoxelWorld::chunk_to_world(chunk_pos).as_vec3();
+        self.intersects_aabb(min, min + Vec3::splat(CHUNK_SIZE as f32))
+    }
+}
+
+/// Chunks visible from `camera_pos`. With `occlusion` off every loaded
+/// chunk in the frustum counts as visible.
+pub fn visible_chunks(
+    world: &VoxelWorld,
+    camera_pos: Vec3,
+    frustum: Option<&ChunkFrustum>,
+    occlusion: bool,
+) -> HashSet<IVec3> {
+    let in_frustum = |pos: IVec3| frustum.is_none_or(|frustum| frustum.intersects_chunk(pos));
+    if !occlusion {
+        return world.loaded_chunk_positions().filter(|pos| in_frustum(*pos)).collect();
+    }
+
+    struct Step {
+        chunk: IVec3,
+        entered: Option<ChunkFace>,
+        /// Directions travelled since leaving the camera's chunk
+        directions: u8,
+    }
+
+    let start = VoxelWorld::world_to_chunk(camera_pos.floor().as_ivec3());
+    let mut visible = HashSet::new();
+    let mut queue = VecDeque::new();
+    if world.chunk_exists(start) {
+        visible.insert(start);
+        queue.push_back(Step {
+            chunk: start,
+            entered: None,
+            directions: 0,
+        });
+    } else {
+        // Outside the loaded chunks (e.g. flying above the world) the space
+        // around the camera counts as open air: the walk starts from every
+        // loaded chunk it borders on the camera's side
+        for chunk in world.loaded_chunk_positions().filter(|pos| in_frustum(*pos)) {
+            let towards_camera = start - chunk;
+            let open_face = ChunkFace::ALL.into_iter().find(|face| {
+                face.offset().dot(towards_camera) > 0 && !world.chunk_exists(chunk + face.offset())
+            });
+            if let Some(face) = open_face {
+                visible.insert(chunk);
+                queue.push_back(Step {
+                    chunk,
+                    entered: Some(face),
+                    directions: 1 << face.opposite().index(),
+                });
+            }
+        }
+    }
+
+    while let Some(step) = queue.pop_front() {
+        let connectivity = world
+            .get_chunk(step.chunk)
+            .and_then(Chunk::connectivity)
+            .unwrap_or(FaceConnectivity::ALL);
+
+        for face in ChunkFace::ALL {
+            if step.directions & (1 << face.opposite().index()) != 0 {
+                continue;
+            }
+            if step.entered.is_some_and(|entered| !connectivity.connects(entered, face)) {
+                continue;
+            }
+            let next = step.chunk + face.offset();
+            if visible.contains(&next) || !world.chunk_exists(next) || !in_frustum(next) {
+                continue;
+            }
+            visible.insert(next);
+            queue.push_back(Step {
+                chunk: next,
+                entered: Some(face.opposite()),
+                directions: step.directions | (1 << face.index()),
+            });
+        }
+    }
+
+    visible
+}
+
+/// Toggles for chunk culling
+#[derive(Resource, Clone, Debug)]
+pub struct ChunkCullingSettings {
+    pub frustum: bool,
+    pub occlusion: bool,
+}
+
+impl Default for ChunkCullingSettings {
+    fn default() -> Self {
+        Self {
+            frustum: true,
+            occlusion: true,
+        }
+    }
+}
+
+/// Chunks shown after the last culling pass
+#[derive(Resource, Default)]
+pub struct ChunkVisibility {
+    visible: HashSet<IVec3>,
+}
+
+impl ChunkVisibility {
+    pub fn is_visible(&self, chunk_pos: IVec3) -> bool {
+        self.visible.contains(&chunk_pos)
+    }
+
+    pub fn visible_count(&self) -> usize {
+        self.visible.len()
+    }
+}
+
+/// Show the meshes of visible chunks and hide the rest
+pub fn cull_chunk_meshes_system(
+    mut world: ResMut<VoxelWorld>,
+    settings: Res<ChunkCullingSettings>,
+    mut chunk_visibility: ResMut<ChunkVisibility>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    mut mesh_query: Query<&mut Visibility, With<ChunkMesh>>,
+) {
+    let Some((camera, camera_transform)) = camera_query.iter().next() else {
+        return;
+    };
+
+    // Connectivity is a cache of the voxels; filling it in is not a change
+    // other systems need to see
+    if settings.occlusion {
+        let mut updated = 0;
+        for (_, chunk) in world.bypass_change_detection().chunk_entries_mut() {
+            if updated == CONNECTIVITY_UPDATES_PER_FRAME {
+                break;
+            }
+            if chunk.connectivity().is_none() {
+                chunk.update_connectivity();
+                updated += 1;
+            }
+        }
+    }
+
+    let frustum = settings.frustum.then(|| {
+        let view_from_world = Mat4::from(camera_transform.affine().inverse());
+        ChunkFrustum::from_clip_from_world(camera.clip_from_view() * view_from_world)
+    });
+    chunk_visibility.visible = visible_chunks(
+        &world,
+        camera_transform.translation(),
+        frustum.as_ref(),
+        settings.occlusion,
+    );
+
+    for pos in world.loaded_chunk_positions() {
+        let Some(chunk) = world.get_chunk(pos) else {
+            continue;
+        };
+        let visibility = if chunk_visibility.is_visible(pos) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        for entity in [chunk.mesh_entity(), chunk.water_mesh_entity()].into_iter().flatten() {
+            if let Ok(mut current) = mesh_query.get_mut(entity) {
+                current.set_if_neq(visibility);
+            }
+        }
+    }
+}
@@ -1,14 +1,31 @@
 use crate::constants::CHUNK_SIZE_I32;
 use crate::voxel::chunk::Chunk;
-use crate::voxel::persistence::WorldData;
-use crate::voxel::types::VoxelType;
+use crate::voxel::fluid::MAX_FLUID_LEVEL;
+use crate::voxel::light::LightChannel;
+use crate::voxel::types::{Voxel, VoxelType};
 use bevy::prelude::*;
 use std::collections::HashMap;
 
+/// A voxel changed through `set_voxel`, for systems that react to edits
+/// (flowing liquids, falling blocks)
+#[derive(Message, Clone, Copy, Debug)]
+pub struct VoxelChanged {
+    pub position: IVec3,
+    pub previous: VoxelType,
+    pub voxel: VoxelType,
+}
+
 #[derive(Resource)]
 pub struct VoxelWorld {
     chunks: HashMap<IVec3, Chunk>,
+    /// Size of the initial generation area; also the world border when enabled
     world_size_chunks: IVec3,
+    /// When disabled, chunks may exist (and stream in) outside `world_size_chunks`
+    border_enabled: bool,
+    /// Voxel changes since the last `take_changes`, while recording
+    changes: Option<Vec<(IVec3, VoxelType)>>,
+    /// Edits since the last `take_edits`, while watching
+    edits: Option<Vec<VoxelChanged>>,
     #[allow(dead_code)]
     chunk_size: i32,
 }
@@ -18,6 +35,9 @@ impl VoxelWorld {
         Self {
             chunks: HashMap::new(),
             world_size_chunks: size_chunks,
+            border_enabled: true,
+            changes: None,
+            edits: None,
             chunk_size: CHUNK_SIZE_I32,
         }
     }
@@ -39,6 +59,33 @@ impl VoxelWorld {
         self.chunks.insert(chunk.position(), chunk);
     }
 
+    /// Remove a chunk from memory (streaming eviction)
+    pub fn remove_chunk(&mut self, chunk_pos: IVec3) -> Option<Chunk> {
+        self.chunks.remove(&chunk_pos)
+    }
+
+    /// Immutable copy of a chunk and its 26 neighbours: everything meshing
+    /// reads, so it can run off the main thread
+    pub fn snapshot_around(&self, chunk_pos: IVec3) -> VoxelWorld {
+        let mut snapshot = VoxelWorld::new(self.world_size_chunks);
+        snapshot.border_enabled = self.border_enabled;
+
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    if let Some(chunk) = self.get_chunk(chunk_pos + IVec3::new(dx, dy, dz)) {
+                        snapshot.insert_chunk(chunk.snapshot());
+                    }
+                }
+            }
+        }
+        snapshot
+    }
+
+    pub fn loaded_chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
     // Voxel access (world coordinates)
     pub fn get_voxel(&self, world_pos: IVec3) -> Option<VoxelType> {
         let chunk_pos = Self::world_to_chunk(world_pos);
@@ -55,7 +102,20 @@ impl VoxelWorld {
         let chunk_pos = Self::world_to_chunk(world_pos);
         let local_pos = Self::world_to_local(world_pos);
 
-        if let Some(chunk) = self.get_chunk_mut(chunk_pos) {
+        if let Some(chunk) = self.chunks.get_mut(&chunk_pos) {
+            let previous = chunk.get(local_pos);
+            if previous != voxel {
+                if let Some(changes) = &mut self.changes {
+                    changes.push((world_pos, voxel));
+                }
+                if let Some(edits) = &mut self.edits {
+                    edits.push(VoxelChanged {
+                        position: world_pos,
+                        previous,
+                        voxel,
+                    });
+                }
+            }
             chunk.set(local_pos, voxel);
             true
         } else {
@@ -63,6 +123,107 @@ impl VoxelWorld {
         }
     }
 
+    /// Liquid level at a voxel: [`MAX_FLUID_LEVEL`] for sources and falling
+    /// liquid, lower while it spreads, 0 for anything that isn't liquid
+    pub fn fluid_level(&self, world_pos: IVec3) -> u8 {
+        let Some(chunk) = self.get_chunk(Self::world_to_chunk(world_pos)) else {
+            return 0;
+        };
+        let local_pos = Self::world_to_local(world_pos);
+        if !chunk.get(local_pos).is_liquid() {
+            return 0;
+        }
+        chunk.fluid_level(local_pos).unwrap_or(MAX_FLUID_LEVEL)
+    }
+
+    /// Level of flowing liquid at a voxel, `None` for sources, anything that
+    /// isn't liquid and unloaded chunks
+    pub fn flow_level(&self, world_pos: IVec3) -> Option<u8> {
+        self.get_chunk(Self::world_to_chunk(world_pos))?
+            .fluid_level(Self::world_to_local(world_pos))
+    }
+
+    /// Liquid that never drains: placed by players or world generation
+    pub fn is_fluid_source(&self, world_pos: IVec3) -> bool {
+        let Some(chunk) = self.get_chunk(Self::world_to_chunk(world_pos)) else {
+            return false;
+        };
+        let local_pos = Self::world_to_local(world_pos);
+        chunk.get(local_pos).is_liquid() && chunk.fluid_level(local_pos).is_none()
+    }
+
+    /// Make a liquid voxel flow at `level`, or a source with `None`. Level
+    /// changes are recorded like voxel changes so guests see the same flow.
+    pub fn set_fluid_level(&mut self, world_pos: IVec3, level: Option<u8>) -> bool {
+        let chunk_pos = Self::world_to_chunk(world_pos);
+        let local_pos = Self::world_to_local(world_pos);
+
+        match self.chunks.get_mut(&chunk_pos) {
+            Some(chunk) if chunk.get(local_pos).is_liquid() => {
+                let level = level.map(|l| l.clamp(1, MAX_FLUID_LEVEL));
+                if chunk.set_fluid_level(local_pos, level) {
+                    if let Some(changes) = &mut self.changes {
+                        changes.push((world_pos, chunk.get(local_pos)));
+                    }
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Light level at a voxel, `None` where no chunk is loaded
+    pub fn light(&self, world_pos: IVec3, channel: LightChannel) -> Option<u8> {
+        self.get_chunk(Self::world_to_chunk(world_pos))
+            .map(|chunk| chunk.light(Self::world_to_local(world_pos), channel))
+    }
+
+    /// Returns false if the chunk isn't loaded. See `crate::voxel::light`.
+    pub fn set_light(&mut self, world_pos: IVec3, channel: LightChannel, level: u8) -> bool {
+        let local_pos = Self::world_to_local(world_pos);
+        match self.chunks.get_mut(&Self::world_to_chunk(world_pos)) {
+            Some(chunk) => {
+                chunk.set_light(local_pos, channel, level);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Start or stop collecting `set_voxel` edits as [`VoxelChanged`].
+    /// Independent of `record_changes`.
+    pub fn watch_edits(&mut self, enabled: bool) {
+        match (enabled, self.edits.is_some()) {
+            (true, false) => self.edits = Some(Vec::new()),
+            (false, true) => self.edits = None,
+            _ => {}
+        }
+    }
+
+    /// Edits since the last call, oldest first
+    pub fn take_edits(&mut self) -> Vec<VoxelChanged> {
+        self.edits.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Start or stop recording `set_voxel` changes, e.g. to send them over
+    /// the network. Stopping drops anything not yet taken.
+    pub fn record_changes(&mut self, enabled: bool) {
+        match (enabled, self.changes.is_some()) {
+            (true, false) => self.changes = Some(Vec::new()),
+            (false, true) => self.changes = None,
+            _ => {}
+        }
+    }
+
+    pub fn is_recording_changes(&self) -> bool {
+        self.changes.is_some()
+    }
+
+    /// Changes recorded since the last call, oldest first
+    pub fn take_changes(&mut self) -> Vec<(IVec3, VoxelType)> {
+        self.changes.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
     // Coordinate conversion
     pub fn world_to_chunk(world_pos: IVec3) -> IVec3 {
         IVec3::new(
@@ -92,14 +253,18 @@ impl VoxelWorld {
             .map(|(pos, _)| *pos)
     }
 
+    /// Positions of all chunks currently in memory
+    pub fn loaded_chunk_positions(&self) -> impl Iterator<Item = IVec3> + '_ {
+        self.chunks.keys().copied()
+    }
+
     pub fn chunk_entries_mut(&mut self) -> impl Iterator<Item = (&IVec3, &mut Chunk)> {
         self.chunks.iter_mut()
     }
 
     pub fn all_chunk_positions(&self) -> impl Iterator<Item = IVec3> + '_ {
-        // Generate all positions within world bounds
-        // This is a naive implementation, might want to just iterate loaded chunks
-        // But for Phase 1 we want to generate the whole world
+        // All positions within the initial generation area. With streaming the
+        // world extends past this; use `loaded_chunk_positions` for what is in memory.
         let start = IVec3::ZERO;
         let end = self.world_size_chunks;
 
@@ -115,6 +280,10 @@ impl VoxelWorld {
     }
 
     pub fn chunk_in_bounds(&self, chunk_pos: IVec3) -> bool {
+        if !self.border_enabled {
+            return true;
+        }
+
         chunk_pos.x >= 0
             && chunk_pos.x < self.world_size_chunks.x
             && chunk_pos.y >= 0
@@ -127,21 +296,21 @@ impl VoxelWorld {
         self.world_size_chunks
     }
 
-    /// Convert world to serializable data
-    pub fn to_data(&self) -> WorldData {
-        WorldData {
-            world_size_chunks: self.world_size_chunks,
-            chunks: self.chunks.values().map(|c| c.to_data()).collect(),
-        }
+    pub fn border_enabled(&self) -> bool {
+        self.border_enabled
     }
 
-    /// Create world from serializable data
-    pub fn from_data(data: WorldData) -> Self {
-        let mut world = Self::new(data.world_size_chunks);
-        for chunk_data in data.chunks {
-            let chunk = Chunk::from_data(chunk_data);
-            world.insert_chunk(chunk);
-        }
-        world
+    /// Enable or disable the world border at `world_size_chunks`
+    pub fn set_border_enabled(&mut self, enabled: bool) {
+        self.border_enabled = enabled;
     }
 }
+
+/// Publish the voxels changed since the last frame as [`VoxelChanged`]
+pub fn publish_voxel_changes(
+    mut world: ResMut<VoxelWorld>,
+    mut changed: MessageWriter<VoxelChanged>,
+) {
+    world.watch_edits(true);
+    changed.write_batch(world.take_edits());
+}
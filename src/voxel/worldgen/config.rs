@@ -0,0 +1,293 @@
+use crate::config::loader::{load_config, ConfigError};
+use crate::voxel::plugin::WATER_LEVEL;
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::path::Path;
+use thiserror::Error;
+
+pub const WORLDGEN_CONFIG_PATH: &str = "assets/config/worldgen.yaml";
+
+/// Columns around a chunk whose height is known to the stages, so features
+/// like tree crowns can reach across chunk borders
+pub const COLUMN_PADDING: i32 = 4;
+
+#[derive(Error, Debug)]
+pub enum GeneratorConfigError {
+    #[error("failed to read generator config: {0}")]
+    Config(#[from] ConfigError),
+    #[error("invalid generator config: {0}")]
+    Invalid(String),
+}
+
+/// Parameters of the built-in terrain stages (`worldgen.yaml`)
+#[derive(Resource, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct GeneratorConfig {
+    /// Air at or below this height is filled with water
+    pub water_level: i32,
+    pub height: HeightConfig,
+    pub biomes: BiomeConfig,
+    pub caves: CaveConfig,
+    pub dungeons: DungeonConfig,
+    pub trees: TreeConfig,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            water_level: WATER_LEVEL,
+            height: HeightConfig::default(),
+            biomes: BiomeConfig::default(),
+            caves: CaveConfig::default(),
+            dungeons: DungeonConfig::default(),
+            trees: TreeConfig::default(),
+        }
+    }
+}
+
+/// One fbm layer: `fbm(pos * frequency, octaves) * amplitude + offset`
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct NoiseLayer {
+    pub frequency: f32,
+    pub octaves: u32,
+    pub amplitude: f32,
+    #[serde(default)]
+    pub offset: f32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct HeightConfig {
+    pub base: NoiseLayer,
+    pub hills: NoiseLayer,
+    /// Mountains rise where this layer's raw noise exceeds `mountain_threshold`
+    pub mountains: NoiseLayer,
+    pub mountain_threshold: f32,
+    /// Rivers follow the zero crossings of `sin(fbm * 2pi)`
+    pub rivers: NoiseLayer,
+    pub river_width: f32,
+    pub min_height: i32,
+    pub max_height: i32,
+    /// Bottom layers mixing bedrock and rock
+    pub bedrock_depth: i32,
+}
+
+impl Default for HeightConfig {
+    fn default() -> Self {
+        Self {
+            base: NoiseLayer {
+                frequency: 0.008,
+                octaves: 4,
+                amplitude: 20.0,
+                offset: 16.0,
+            },
+            hills: NoiseLayer {
+                frequency: 0.02,
+                octaves: 3,
+                amplitude: 10.0,
+                offset: 0.0,
+            },
+            mountains: NoiseLayer {
+                frequency: 0.005,
+                octaves: 2,
+                amplitude: 50.0,
+                offset: 0.0,
+            },
+            mountain_threshold: 0.65,
+            rivers: NoiseLayer {
+                frequency: 0.015,
+                octaves: 2,
+                amplitude: 10.0,
+                offset: 0.0,
+            },
+            river_width: 0.2,
+            min_height: 1,
+            max_height: 58,
+            bedrock_depth: 3,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct BiomeConfig {
+    pub frequency: f32,
+    pub detail_frequency: f32,
+    pub sandy_below: f32,
+    pub rocky_above: f32,
+    pub rocky_detail_above: f32,
+    pub clay_range: [f32; 2],
+    pub clay_detail_above: f32,
+    /// Columns at most this far above the water level get beaches
+    pub beach_height: i32,
+}
+
+impl Default for BiomeConfig {
+    fn default() -> Self {
+        Self {
+            frequency: 0.01,
+            detail_frequency: 0.05,
+            sandy_below: 0.25,
+            rocky_above: 0.75,
+            rocky_detail_above: 0.5,
+            clay_range: [0.4, 0.5],
+            clay_detail_above: 0.6,
+            beach_height: 2,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CaveConfig {
+    pub enabled: bool,
+    pub frequency: f32,
+    /// How much height shifts the noise lookup along x and z
+    pub vertical_skew: [f32; 2],
+    pub octaves: u32,
+    pub threshold: f32,
+    /// Threshold increase per 64 blocks of height
+    pub depth_bias: f32,
+    pub min_y: i32,
+    pub max_y: i32,
+    /// Solid blocks kept between caves and the surface
+    pub surface_margin: i32,
+}
+
+impl Default for CaveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            frequency: 0.05,
+            vertical_skew: [0.03, 0.02],
+            octaves: 3,
+            threshold: 0.65,
+            depth_bias: 0.1,
+            min_y: 2,
+            max_y: 45,
+            surface_margin: 3,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DungeonConfig {
+    pub enabled: bool,
+    /// Dungeons repeat on a grid with this spacing
+    pub spacing: i32,
+    pub size: i32,
+    pub floor_y: i32,
+    pub height: i32,
+    /// Top of the entrance shaft
+    pub stair_top_y: i32,
+}
+
+impl Default for DungeonConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            spacing: 96,
+            size: 20,
+            floor_y: 3,
+            height: 12,
+            stair_top_y: 50,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct TreeConfig {
+    pub enabled: bool,
+    /// Chance per grass column to grow a tree
+    pub density: f32,
+    /// Trees only grow on columns higher than the water level plus this
+    pub min_height_above_water: i32,
+    pub min_trunk: i32,
+    pub max_trunk: i32,
+    pub leaf_radius: f32,
+}
+
+impl Default for TreeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            density: 0.02,
+            min_height_above_water: 2,
+            min_trunk: 3,
+            max_trunk: 5,
+            leaf_radius: 2.5,
+        }
+    }
+}
+
+impl GeneratorConfig {
+    /// Load and validate a generator config from a YAML file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GeneratorConfigError> {
+        let config: GeneratorConfig = load_config(path)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load from the default config path, falling back to the built-in values
+    pub fn load_or_default() -> Self {
+        match Self::load(WORLDGEN_CONFIG_PATH) {
+            Ok(config) => {
+                info!("Loaded world generator config from {}", WORLDGEN_CONFIG_PATH);
+                config
+            }
+            Err(e) => {
+                warn!("{}. Using built-in generator settings.", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), GeneratorConfigError> {
+        let invalid = |message: String| Err(GeneratorConfigError::Invalid(message));
+
+        let layers = [
+            ("height.base", &self.height.base),
+            ("height.hills", &self.height.hills),
+            ("height.mountains", &self.height.mountains),
+            ("height.rivers", &self.height.rivers),
+        ];
+        for (name, layer) in layers {
+            if layer.octaves == 0 {
+                return invalid(format!("{} needs at least one octave", name));
+            }
+        }
+        if self.caves.octaves == 0 {
+            return invalid("caves need at least one octave".to_string());
+        }
+        if self.height.min_height > self.height.max_height {
+            return invalid(format!(
+                "height.min_height {} is above max_height {}",
+                self.height.min_height, self.height.max_height
+            ));
+        }
+        if self.dungeons.spacing <= self.dungeons.size {
+            return invalid(format!(
+                "dungeon spacing {} must be larger than dungeon size {}",
+                self.dungeons.spacing, self.dungeons.size
+            ));
+        }
+        if self.trees.min_trunk < 1 || self.trees.min_trunk > self.trees.max_trunk {
+            return invalid(format!(
+                "tree trunk range {}..={} is empty",
+                self.trees.min_trunk, self.trees.max_trunk
+            ));
+        }
+        if self.trees.leaf_radius.ceil() as i32 > COLUMN_PADDING {
+            return invalid(format!(
+                "tree leaf_radius {} reaches past the {} padding columns",
+                self.trees.leaf_radius, COLUMN_PADDING
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.trees.density) {
+            return invalid(format!("tree density {} is not in 0..=1", self.trees.density));
+        }
+        Ok(())
+    }
+}
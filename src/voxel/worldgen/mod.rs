@@ -0,0 +1,473 @@
+//! Seeded terrain generation.
+//!
+//! A `WorldGenerator` turns a chunk position into voxels. The built-in
+//! `TerrainPipeline` runs a list of `GenerationStage`s (height, biome, caves,
+//! structures, decoration) over a shared `ChunkContext`; custom stages can be
+//! inserted anywhere in that list. Far LOD tiles are sampled through the
+//! same stages with a coarser `LodContext`.
+
+mod config;
+mod noise;
+mod stages;
+
+pub use config::{
+    BiomeConfig, CaveConfig, DungeonConfig, GeneratorConfig, GeneratorConfigError, HeightConfig,
+    NoiseLayer, TreeConfig, COLUMN_PADDING, WORLDGEN_CONFIG_PATH,
+};
+pub use noise::Noise;
+pub use stages::{BiomeStage, CaveStage, DungeonStage, HeightStage, TreeStage};
+
+use crate::constants::{CHUNK_SIZE, CHUNK_SIZE_I32, CHUNK_VOLUME};
+use crate::voxel::chunk::{Chunk, ChunkData, FlatChunkData};
+use crate::voxel::lod::{self, LodTile};
+use crate::voxel::types::VoxelType;
+use crate::voxel::world::VoxelWorld;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Voxel counts gathered while generating terrain, for the generation summary
+#[derive(Default, Clone, Copy, Debug)]
+pub struct GenerationStats {
+    pub water: u32,
+    pub sand: u32,
+    pub dungeon_wall: u32,
+    pub dungeon_floor: u32,
+}
+
+impl GenerationStats {
+    fn count(&mut self, voxel: VoxelType) {
+        match voxel {
+            VoxelType::Water => self.water += 1,
+            VoxelType::Sand => self.sand += 1,
+            VoxelType::DungeonWall => self.dungeon_wall += 1,
+            VoxelType::DungeonFloor => self.dungeon_floor += 1,
+            _ => {}
+        }
+    }
+
+    pub fn add(&mut self, other: &GenerationStats) {
+        self.water += other.water;
+        self.sand += other.sand;
+        self.dungeon_wall += other.dungeon_wall;
+        self.dungeon_floor += other.dungeon_floor;
+    }
+}
+
+/// Produces the initial voxels of a chunk. Output must depend only on the
+/// seed and chunk position so the same seed always yields the same world.
+pub trait WorldGenerator: Send + Sync {
+    fn seed(&self) -> u64;
+
+    /// The same generator set up for another world seed
+    fn with_seed(&self, seed: u64) -> Arc<dyn WorldGenerator>;
+
+    fn generate_chunk(&self, chunk_pos: IVec3, stats: &mut GenerationStats) -> Chunk;
+
+    /// Voxels of a far LOD tile as one chunk at `tile.position`, each voxel
+    /// standing for a 2^level cube of the world. The default generates every
+    /// chunk the tile covers and downsamples them, which is exact but slow
+    /// past level 2; generators that can sample terrain directly override it.
+    fn generate_lod_chunk(&self, tile: LodTile, stats: &mut GenerationStats) -> Chunk {
+        let chunks: HashMap<IVec3, Chunk> = tile
+            .chunk_positions()
+            .map(|chunk_pos| (chunk_pos, self.generate_chunk(chunk_pos, stats)))
+            .collect();
+        lod::downsample(tile, |pos| {
+            chunks
+                .get(&VoxelWorld::world_to_chunk(pos))
+                .map_or(VoxelType::Air, |chunk| chunk.get(VoxelWorld::world_to_local(pos)))
+        })
+    }
+}
+
+/// One step of the `TerrainPipeline`
+pub trait GenerationStage: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn apply(&self, ctx: &mut ChunkContext);
+
+    /// Coarse version of `apply` for far LOD tiles. Stages that only add
+    /// detail too small to see from there keep the default and are skipped.
+    fn apply_lod(&self, _ctx: &mut LodContext) {}
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Biome {
+    #[default]
+    Plains,
+    Sandy,
+    Rocky,
+    Clay,
+}
+
+/// Per-column values for a chunk plus `COLUMN_PADDING` columns around it
+#[derive(Clone, Debug)]
+pub struct ColumnMap<T> {
+    min_x: i32,
+    min_z: i32,
+    width: i32,
+    values: Vec<T>,
+}
+
+impl<T: Copy + Default> ColumnMap<T> {
+    fn around_chunk(chunk_pos: IVec3) -> Self {
+        let width = CHUNK_SIZE_I32 + 2 * COLUMN_PADDING;
+        Self {
+            min_x: chunk_pos.x * CHUNK_SIZE_I32 - COLUMN_PADDING,
+            min_z: chunk_pos.z * CHUNK_SIZE_I32 - COLUMN_PADDING,
+            width,
+            values: vec![T::default(); (width * width) as usize],
+        }
+    }
+
+    fn index(&self, world_x: i32, world_z: i32) -> Option<usize> {
+        let x = world_x - self.min_x;
+        let z = world_z - self.min_z;
+        if x < 0 || z < 0 || x >= self.width || z >= self.width {
+            return None;
+        }
+        Some((z * self.width + x) as usize)
+    }
+
+    /// Value of a column, `None` outside the padded area
+    pub fn get(&self, world_x: i32, world_z: i32) -> Option<T> {
+        self.index(world_x, world_z).map(|i| self.values[i])
+    }
+
+    pub fn set(&mut self, world_x: i32, world_z: i32, value: T) {
+        if let Some(i) = self.index(world_x, world_z) {
+            self.values[i] = value;
+        }
+    }
+
+    /// World (x, z) of every column in the padded area
+    pub fn columns(&self) -> impl Iterator<Item = (i32, i32)> {
+        let (min_x, min_z, width) = (self.min_x, self.min_z, self.width);
+        (0..width * width).map(move |i| (min_x + i % width, min_z + i / width))
+    }
+}
+
+/// Working state of one chunk as it passes through the pipeline
+pub struct ChunkContext {
+    pub seed: u64,
+    pub noise: Noise,
+    pub chunk_pos: IVec3,
+    pub water_level: i32,
+    /// Surface height per column, filled by the height stage
+    pub heights: ColumnMap<i32>,
+    pub biomes: ColumnMap<Biome>,
+    voxels: Vec<VoxelType>,
+    /// Voxels placed by structures; later stages leave them alone
+    reserved: Vec<bool>,
+}
+
+impl ChunkContext {
+    pub fn new(seed: u64, chunk_pos: IVec3, water_level: i32) -> Self {
+        Self {
+            seed,
+            noise: Noise::new(seed),
+            chunk_pos,
+            water_level,
+            heights: ColumnMap::around_chunk(chunk_pos),
+            biomes: ColumnMap::around_chunk(chunk_pos),
+            voxels: vec![VoxelType::Air; CHUNK_VOLUME],
+            reserved: vec![false; CHUNK_VOLUME],
+        }
+    }
+
+    /// World position of the chunk's (0, 0, 0) voxel
+    pub fn origin(&self) -> IVec3 {
+        self.chunk_pos * CHUNK_SIZE_I32
+    }
+
+    fn index(local: UVec3) -> usize {
+        local.x as usize + local.y as usize * CHUNK_SIZE + local.z as usize * CHUNK_SIZE * CHUNK_SIZE
+    }
+
+    pub fn get(&self, local: UVec3) -> VoxelType {
+        self.voxels[Self::index(local)]
+    }
+
+    /// Set a voxel unless a structure already claimed it
+    pub fn set(&mut self, local: UVec3, voxel: VoxelType) {
+        let index = Self::index(local);
+        if !self.reserved[index] {
+            self.voxels[index] = voxel;
+        }
+    }
+
+    /// Set a voxel and protect it from later stages
+    pub fn reserve(&mut self, local: UVec3, voxel: VoxelType) {
+        let index = Self::index(local);
+        self.voxels[index] = voxel;
+        self.reserved[index] = true;
+    }
+
+    pub fn is_reserved(&self, local: UVec3) -> bool {
+        self.reserved[Self::index(local)]
+    }
+
+    /// Local positions of every voxel in the chunk
+    pub fn positions() -> impl Iterator<Item = UVec3> {
+        let size = CHUNK_SIZE as u32;
+        (0..size).flat_map(move |z| {
+            (0..size).flat_map(move |y| (0..size).map(move |x| UVec3::new(x, y, z)))
+        })
+    }
+
+    fn into_chunk(self, stats: &mut GenerationStats) -> Chunk {
+        for voxel in &self.voxels {
+            stats.count(*voxel);
+        }
+
+        let mut chunk = Chunk::from_data(ChunkData::from(FlatChunkData {
+            voxels: self.voxels,
+            position: self.chunk_pos,
+        }));
+        chunk.mark_dirty();
+        // Generated terrain can be recreated, so it only needs saving once edited
+        chunk.clear_modified();
+        chunk
+    }
+}
+
+/// Working state of a far LOD tile. Like `ChunkContext`, but each of its
+/// voxels stands for a `scale()`-sized cube of the world and columns are
+/// sampled once, at their centre.
+pub struct LodContext {
+    pub seed: u64,
+    pub noise: Noise,
+    pub tile: LodTile,
+    pub water_level: i32,
+    /// Surface height per column, filled by the height stage
+    heights: Vec<i32>,
+    biomes: Vec<Biome>,
+    voxels: Vec<VoxelType>,
+}
+
+impl LodContext {
+    pub fn new(seed: u64, tile: LodTile, water_level: i32) -> Self {
+        Self {
+            seed,
+            noise: Noise::new(seed),
+            tile,
+            water_level,
+            heights: vec![0; CHUNK_SIZE * CHUNK_SIZE],
+            biomes: vec![Biome::default(); CHUNK_SIZE * CHUNK_SIZE],
+            voxels: vec![VoxelType::Air; CHUNK_VOLUME],
+        }
+    }
+
+    /// World voxels along each axis covered by one voxel of the tile
+    pub fn scale(&self) -> i32 {
+        self.tile.size_chunks()
+    }
+
+    /// World position of the min corner of a voxel's cube
+    pub fn world_pos(&self, local: UVec3) -> IVec3 {
+        self.tile.origin() + local.as_ivec3() * self.scale()
+    }
+
+    /// World position sampled for a voxel: the centre of its cube
+    pub fn sample_pos(&self, local: UVec3) -> IVec3 {
+        self.world_pos(local) + IVec3::splat(self.scale() / 2)
+    }
+
+    /// Local (x, z) of every column of the tile
+    pub fn columns() -> impl Iterator<Item = (u32, u32)> {
+        let size = CHUNK_SIZE as u32;
+        (0..size * size).map(move |i| (i % size, i / size))
+    }
+
+    fn column_index(x: u32, z: u32) -> usize {
+        x as usize + z as usize * CHUNK_SIZE
+    }
+
+    pub fn height(&self, x: u32, z: u32) -> i32 {
+        self.heights[Self::column_index(x, z)]
+    }
+
+    pub fn set_height(&mut self, x: u32, z: u32, height: i32) {
+        self.heights[Self::column_index(x, z)] = height;
+    }
+
+    pub fn biome(&self, x: u32, z: u32) -> Biome {
+        self.biomes[Self::column_index(x, z)]
+    }
+
+    pub fn set_biome(&mut self, x: u32, z: u32, biome: Biome) {
+        self.biomes[Self::column_index(x, z)] = biome;
+    }
+
+    pub fn get(&self, local: UVec3) -> VoxelType {
+        self.voxels[ChunkContext::index(local)]
+    }
+
+    pub fn set(&mut self, local: UVec3, voxel: VoxelType) {
+        self.voxels[ChunkContext::index(local)] = voxel;
+    }
+
+    fn into_chunk(self, stats: &mut GenerationStats) -> Chunk {
+        for voxel in &self.voxels {
+            stats.count(*voxel);
+        }
+
+        let mut chunk = Chunk::from_data(ChunkData::from(FlatChunkData {
+            voxels: self.voxels,
+            position: self.tile.position,
+        }));
+        chunk.clear_modified();
+        chunk
+    }
+}
+
+/// Built-in generator: runs its stages in order over each chunk
+#[derive(Clone)]
+pub struct TerrainPipeline {
+    seed: u64,
+    water_level: i32,
+    stages: Vec<Arc<dyn GenerationStage>>,
+}
+
+impl TerrainPipeline {
+    /// The standard height, biome, cave, structure and decoration stages
+    pub fn new(seed: u64, config: &GeneratorConfig) -> Self {
+        let mut pipeline = Self::empty(seed, config.water_level);
+        pipeline.push_stage(HeightStage::new(config.height.clone()));
+        pipeline.push_stage(
+            BiomeStage::new(config.biomes.clone()).with_bedrock_depth(config.height.bedrock_depth),
+        );
+        if config.caves.enabled {
+            pipeline.push_stage(CaveStage::new(config.caves.clone()));
+        }
+        if config.dungeons.enabled {
+            pipeline.push_stage(DungeonStage::new(config.dungeons.clone()));
+        }
+        if config.trees.enabled {
+            pipeline.push_stage(TreeStage::new(config.trees.clone()));
+        }
+        pipeline
+    }
+
+    /// A pipeline without stages, producing empty (air) chunks
+    pub fn empty(seed: u64, water_level: i32) -> Self {
+        Self {
+            seed,
+            water_level,
+            stages: Vec::new(),
+        }
+    }
+
+    pub fn push_stage(&mut self, stage: impl GenerationStage + 'static) {
+        self.stages.push(Arc::new(stage));
+    }
+
+    /// Insert a stage so it runs right after the stage called `after`.
+    /// Returns false (and appends it) if there is no such stage.
+    pub fn insert_stage_after(&mut self, after: &str, stage: impl GenerationStage + 'static) -> bool {
+        match self.stages.iter().position(|s| s.name() == after) {
+            Some(index) => {
+                self.stages.insert(index + 1, Arc::new(stage));
+                true
+            }
+            None => {
+                self.stages.push(Arc::new(stage));
+                false
+            }
+        }
+    }
+
+    pub fn remove_stage(&mut self, name: &str) -> bool {
+        let before = self.stages.len();
+        self.stages.retain(|s| s.name() != name);
+        self.stages.len() != before
+    }
+
+    pub fn stage_names(&self) -> impl Iterator<Item = &str> {
+        self.stages.iter().map(|s| s.name())
+    }
+}
+
+impl WorldGenerator for TerrainPipeline {
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn with_seed(&self, seed: u64) -> Arc<dyn WorldGenerator> {
+        Arc::new(Self {
+            seed,
+            ..self.clone()
+        })
+    }
+
+    fn generate_chunk(&self, chunk_pos: IVec3, stats: &mut GenerationStats) -> Chunk {
+        let mut ctx = ChunkContext::new(self.seed, chunk_pos, self.water_level);
+        for stage in &self.stages {
+            stage.apply(&mut ctx);
+        }
+        ctx.into_chunk(stats)
+    }
+
+    fn generate_lod_chunk(&self, tile: LodTile, stats: &mut GenerationStats) -> Chunk {
+        let mut ctx = LodContext::new(self.seed, tile, self.water_level);
+        for stage in &self.stages {
+            stage.apply_lod(&mut ctx);
+        }
+        ctx.into_chunk(stats)
+    }
+}
+
+/// Flat terrain for testing: soil up to `height`, air above
+#[derive(Clone, Copy, Debug)]
+pub struct FlatGenerator {
+    pub seed: u64,
+    pub height: i32,
+}
+
+impl WorldGenerator for FlatGenerator {
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn with_seed(&self, seed: u64) -> Arc<dyn WorldGenerator> {
+        Arc::new(Self { seed, ..*self })
+    }
+
+    fn generate_chunk(&self, chunk_pos: IVec3, stats: &mut GenerationStats) -> Chunk {
+        let mut ctx = ChunkContext::new(self.seed, chunk_pos, i32::MIN);
+        let origin = ctx.origin();
+        for local in ChunkContext::positions() {
+            let world_y = origin.y + local.y as i32;
+            if world_y <= self.height {
+                ctx.set(local, VoxelType::TopSoil);
+            }
+        }
+        ctx.into_chunk(stats)
+    }
+}
+
+/// Generator used for new chunks of the active world
+#[derive(Resource, Clone)]
+pub struct WorldGen(pub Arc<dyn WorldGenerator>);
+
+impl WorldGen {
+    pub fn new(generator: impl WorldGenerator + 'static) -> Self {
+        Self(Arc::new(generator))
+    }
+
+    /// Switch to another world's seed, keeping the stages
+    pub fn reseed(&mut self, seed: u64) {
+        if self.0.seed() != seed {
+            self.0 = self.0.with_seed(seed);
+        }
+    }
+
+    pub fn generate_chunk(&self, chunk_pos: IVec3, stats: &mut GenerationStats) -> Chunk {
+        self.0.generate_chunk(chunk_pos, stats)
+    }
+
+    pub fn generate_lod_chunk(&self, tile: LodTile, stats: &mut GenerationStats) -> Chunk {
+        self.0.generate_lod_chunk(tile, stats)
+    }
+}
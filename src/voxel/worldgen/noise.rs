@@ -0,0 +1,91 @@
+/// Seeded value noise used by the terrain stages.
+///
+/// The whole 64-bit seed picks a permutation of the lattice (coordinates are
+/// xor-ed with per-seed keys before hashing) plus an offset, so different
+/// seeds give unrelated noise fields rather than shifted copies of one.
+/// Seed 0 reproduces the terrain generated before worlds had seeds, so
+/// migrated saves keep matching terrain at the edges of what was stored.
+#[derive(Clone, Copy, Debug)]
+pub struct Noise {
+    x_key: i32,
+    z_key: i32,
+    offset: i32,
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        if seed == 0 {
+            return Self {
+                x_key: 0,
+                z_key: 0,
+                offset: 0,
+            };
+        }
+        // splitmix64 is a bijection, so distinct seeds get distinct keys
+        let keys = splitmix64(seed);
+        Self {
+            x_key: keys as u32 as i32,
+            z_key: (keys >> 32) as u32 as i32,
+            offset: splitmix64(keys) as u32 as i32,
+        }
+    }
+
+    /// Hash of an integer lattice point in [0, 1]
+    pub fn hash(&self, x: i32, z: i32) -> f32 {
+        let n = (x ^ self.x_key)
+            .wrapping_mul(374761393)
+            .wrapping_add((z ^ self.z_key).wrapping_mul(668265263))
+            .wrapping_add(self.offset);
+        let n = (n ^ (n >> 13)).wrapping_mul(1274126177);
+        ((n ^ (n >> 16)) as u32 as f32) / u32::MAX as f32
+    }
+
+    pub fn value(&self, x: f32, z: f32) -> f32 {
+        let xi = x.floor() as i32;
+        let zi = z.floor() as i32;
+        let xf = x - x.floor();
+        let zf = z - z.floor();
+
+        let v00 = self.hash(xi, zi);
+        let v10 = self.hash(xi + 1, zi);
+        let v01 = self.hash(xi, zi + 1);
+        let v11 = self.hash(xi + 1, zi + 1);
+
+        let u = smoothstep(xf);
+        let v = smoothstep(zf);
+
+        lerp(lerp(v00, v10, u), lerp(v01, v11, u), v)
+    }
+
+    /// Fractal sum of `octaves` value noise layers, normalized to [0, 1]
+    pub fn fbm(&self, x: f32, z: f32, octaves: u32) -> f32 {
+        let mut value = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut max_value = 0.0;
+
+        for _ in 0..octaves {
+            value += amplitude * self.value(x * frequency, z * frequency);
+            max_value += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+
+        value / max_value
+    }
+}
+
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + t * (b - a)
+}
@@ -0,0 +1,506 @@
+use super::config::{BiomeConfig, CaveConfig, DungeonConfig, HeightConfig, NoiseLayer, TreeConfig};
+use super::{Biome, ChunkContext, GenerationStage, LodContext, Noise};
+use crate::constants::CHUNK_SIZE_I32;
+use crate::voxel::types::VoxelType;
+use bevy::prelude::*;
+
+impl NoiseLayer {
+    fn sample(&self, noise: &Noise, x: f32, z: f32) -> f32 {
+        noise.fbm(x * self.frequency, z * self.frequency, self.octaves) * self.amplitude + self.offset
+    }
+}
+
+/// Local position inside the chunk for a world position, if it is inside
+fn local_in_chunk(ctx: &ChunkContext, world: IVec3) -> Option<UVec3> {
+    let local = world - ctx.origin();
+    let inside = local.cmpge(IVec3::ZERO).all() && local.cmplt(IVec3::splat(CHUNK_SIZE_I32)).all();
+    inside.then(|| local.as_uvec3())
+}
+
+/// Surface heights and the base fill: rock below the surface, water up to
+/// the water level, bedrock at the bottom
+pub struct HeightStage {
+    config: HeightConfig,
+}
+
+impl HeightStage {
+    pub fn new(config: HeightConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn height_at(&self, noise: &Noise, world_x: i32, world_z: i32) -> i32 {
+        let config = &self.config;
+        let x = world_x as f32;
+        let z = world_z as f32;
+
+        let base = config.base.sample(noise, x, z);
+        let hills = config.hills.sample(noise, x, z);
+
+        // Mountains - occasional tall peaks
+        let mountain_mask = noise.fbm(
+            x * config.mountains.frequency,
+            z * config.mountains.frequency,
+            config.mountains.octaves,
+        );
+        let mountains = if mountain_mask > config.mountain_threshold {
+            (mountain_mask - config.mountain_threshold) * config.mountains.amplitude
+        } else {
+            0.0
+        };
+
+        // River valleys - carve into terrain
+        let river_noise = (noise.fbm(
+            x * config.rivers.frequency,
+            z * config.rivers.frequency,
+            config.rivers.octaves,
+        ) * 6.28)
+            .sin();
+        let river = if river_noise.abs() < config.river_width {
+            -config.rivers.amplitude * (1.0 - river_noise.abs() / config.river_width)
+        } else {
+            0.0
+        };
+
+        (base + hills + mountains + river)
+            .max(config.min_height as f32)
+            .min(config.max_height as f32) as i32
+    }
+}
+
+impl GenerationStage for HeightStage {
+    fn name(&self) -> &str {
+        "height"
+    }
+
+    fn apply(&self, ctx: &mut ChunkContext) {
+        for (x, z) in ctx.heights.columns().collect::<Vec<_>>() {
+            let height = self.height_at(&ctx.noise, x, z);
+            ctx.heights.set(x, z, height);
+        }
+
+        let origin = ctx.origin();
+        for local in ChunkContext::positions() {
+            let world = origin + local.as_ivec3();
+            let height = ctx.heights.get(world.x, world.z).unwrap_or_default();
+
+            let voxel = if world.y > height {
+                if world.y <= ctx.water_level {
+                    VoxelType::Water
+                } else {
+                    VoxelType::Air
+                }
+            } else if world.y <= 0 {
+                VoxelType::Bedrock
+            } else if world.y <= self.config.bedrock_depth {
+                // Deep bedrock layer with some rock
+                if ctx.noise.hash(world.x, world.z + world.y * 1000) > 0.3 {
+                    VoxelType::Bedrock
+                } else {
+                    VoxelType::Rock
+                }
+            } else {
+                VoxelType::Rock
+            };
+            ctx.set(local, voxel);
+        }
+    }
+
+    fn apply_lod(&self, ctx: &mut LodContext) {
+        for (x, z) in LodContext::columns() {
+            let sample = ctx.sample_pos(UVec3::new(x, 0, z));
+            let height = self.height_at(&ctx.noise, sample.x, sample.z);
+            ctx.set_height(x, z, height);
+        }
+
+        for local in ChunkContext::positions() {
+            // A voxel is solid when the surface reaches past its centre
+            let y = ctx.sample_pos(local).y;
+            let height = ctx.height(local.x, local.z);
+            let voxel = if y > height {
+                if y <= ctx.water_level {
+                    VoxelType::Water
+                } else {
+                    VoxelType::Air
+                }
+            } else if y <= 0 {
+                VoxelType::Bedrock
+            } else {
+                VoxelType::Rock
+            };
+            ctx.set(local, voxel);
+        }
+    }
+}
+
+/// Picks a biome per column and lays the surface layers (soil, sand, clay)
+/// over the rock left by the height stage
+pub struct BiomeStage {
+    config: BiomeConfig,
+    bedrock_depth: i32,
+}
+
+impl BiomeStage {
+    pub fn new(config: BiomeConfig) -> Self {
+        Self {
+            config,
+            bedrock_depth: HeightConfig::default().bedrock_depth,
+        }
+    }
+
+    /// Leave the bottom `depth` layers alone (match `HeightConfig::bedrock_depth`)
+    pub fn with_bedrock_depth(mut self, depth: i32) -> Self {
+        self.bedrock_depth = depth;
+        self
+    }
+
+    pub fn biome_at(&self, noise: &Noise, world_x: i32, world_z: i32) -> Biome {
+        let config = &self.config;
+        let x = world_x as f32;
+        let z = world_z as f32;
+
+        let biome_noise = noise.fbm(x * config.frequency, z * config.frequency, 2);
+        let detail_noise = noise.fbm(x * config.detail_frequency, z * config.detail_frequency, 2);
+
+        if biome_noise < config.sandy_below {
+            Biome::Sandy
+        } else if biome_noise > config.rocky_above && detail_noise > config.rocky_detail_above {
+            Biome::Rocky
+        } else if biome_noise > config.clay_range[0]
+            && biome_noise < config.clay_range[1]
+            && detail_noise > config.clay_detail_above
+        {
+            Biome::Clay
+        } else {
+            Biome::Plains
+        }
+    }
+
+    fn surface_voxel(&self, biome: Biome, depth: i32, near_water: bool) -> VoxelType {
+        match biome {
+            Biome::Sandy => {
+                if depth <= 4 {
+                    VoxelType::Sand
+                } else if depth <= 8 {
+                    VoxelType::SubSoil
+                } else {
+                    VoxelType::Rock
+                }
+            }
+            Biome::Rocky => {
+                if depth <= 1 {
+                    VoxelType::Rock
+                } else if depth <= 3 {
+                    VoxelType::SubSoil
+                } else {
+                    VoxelType::Rock
+                }
+            }
+            Biome::Clay => {
+                if near_water {
+                    if depth <= 2 {
+                        VoxelType::Sand
+                    } else if depth <= 6 {
+                        VoxelType::Clay
+                    } else {
+                        VoxelType::Rock
+                    }
+                } else if depth <= 2 {
+                    VoxelType::TopSoil
+                } else if depth <= 6 {
+                    VoxelType::Clay
+                } else if depth <= 10 {
+                    VoxelType::SubSoil
+                } else {
+                    VoxelType::Rock
+                }
+            }
+            Biome::Plains => {
+                // Sand near water (beaches and shorelines)
+                if near_water {
+                    if depth <= 2 {
+                        VoxelType::Sand
+                    } else if depth <= 5 {
+                        VoxelType::SubSoil
+                    } else {
+                        VoxelType::Rock
+                    }
+                } else if depth == 0 {
+                    VoxelType::TopSoil
+                } else if depth <= 4 {
+                    VoxelType::SubSoil
+                } else {
+                    VoxelType::Rock
+                }
+            }
+        }
+    }
+}
+
+impl GenerationStage for BiomeStage {
+    fn name(&self) -> &str {
+        "biome"
+    }
+
+    fn apply(&self, ctx: &mut ChunkContext) {
+        for (x, z) in ctx.biomes.columns().collect::<Vec<_>>() {
+            let biome = self.biome_at(&ctx.noise, x, z);
+            ctx.biomes.set(x, z, biome);
+        }
+
+        let origin = ctx.origin();
+        for local in ChunkContext::positions() {
+            let world = origin + local.as_ivec3();
+            let height = ctx.heights.get(world.x, world.z).unwrap_or_default();
+            if world.y > height || world.y <= self.bedrock_depth {
+                continue;
+            }
+
+            let biome = ctx.biomes.get(world.x, world.z).unwrap_or_default();
+            let near_water = height <= ctx.water_level + self.config.beach_height;
+            let voxel = self.surface_voxel(biome, height - world.y, near_water);
+            ctx.set(local, voxel);
+        }
+    }
+
+    fn apply_lod(&self, ctx: &mut LodContext) {
+        for (x, z) in LodContext::columns() {
+            let sample = ctx.sample_pos(UVec3::new(x, 0, z));
+            let biome = self.biome_at(&ctx.noise, sample.x, sample.z);
+            ctx.set_biome(x, z, biome);
+        }
+
+        let scale = ctx.scale();
+        for local in ChunkContext::positions() {
+            let y = ctx.sample_pos(local).y;
+            let height = ctx.height(local.x, local.z);
+            if y > height || y <= self.bedrock_depth {
+                continue;
+            }
+
+            // The topmost solid voxel holds the surface layer, the ones below
+            // it are a whole voxel (`scale` world voxels) deeper each
+            let depth = (height - y).div_euclid(scale) * scale;
+            let biome = ctx.biome(local.x, local.z);
+            let near_water = height <= ctx.water_level + self.config.beach_height;
+            ctx.set(local, self.surface_voxel(biome, depth, near_water));
+        }
+    }
+}
+
+/// Carves caves below the surface; flooded below the water level
+pub struct CaveStage {
+    config: CaveConfig,
+}
+
+impl CaveStage {
+    pub fn new(config: CaveConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn is_cave(&self, noise: &Noise, world: IVec3) -> bool {
+        let config = &self.config;
+        if world.y <= config.min_y || world.y >= config.max_y {
+            return false;
+        }
+
+        let x = world.x as f32;
+        let y = world.y as f32;
+        let z = world.z as f32;
+
+        let cave_noise = noise.fbm(
+            x * config.frequency + y * config.vertical_skew[0],
+            z * config.frequency + y * config.vertical_skew[1],
+            config.octaves,
+        );
+        let threshold = config.threshold + (y / 64.0) * config.depth_bias;
+        cave_noise > threshold
+    }
+}
+
+impl GenerationStage for CaveStage {
+    fn name(&self) -> &str {
+        "caves"
+    }
+
+    fn apply(&self, ctx: &mut ChunkContext) {
+        let origin = ctx.origin();
+        for local in ChunkContext::positions() {
+            let world = origin + local.as_ivec3();
+            let height = ctx.heights.get(world.x, world.z).unwrap_or_default();
+            if world.y >= height - self.config.surface_margin || !self.is_cave(&ctx.noise, world) {
+                continue;
+            }
+
+            let voxel = if world.y <= ctx.water_level {
+                VoxelType::Water
+            } else {
+                VoxelType::Air
+            };
+            ctx.set(local, voxel);
+        }
+    }
+}
+
+/// Dungeons on a fixed grid: rooms with corridors, pillars and an entrance
+/// shaft up to the surface. Their voxels are reserved so decoration keeps out.
+pub struct DungeonStage {
+    config: DungeonConfig,
+}
+
+impl DungeonStage {
+    pub fn new(config: DungeonConfig) -> Self {
+        Self { config }
+    }
+
+    /// Dungeon voxel at a world position, or `None` outside any dungeon
+    pub fn voxel_at(&self, world: IVec3) -> Option<VoxelType> {
+        let config = &self.config;
+        let size = config.size;
+        let floor_y = config.floor_y;
+        let height = config.height;
+
+        let dx = world.x.rem_euclid(config.spacing);
+        let dz = world.z.rem_euclid(config.spacing);
+
+        // Entrance staircase in the corner of each dungeon, visible from the surface
+        let entrance = 2;
+        let entrance_size = 3;
+        let over_entrance = dx >= entrance
+            && dx < entrance + entrance_size
+            && dz >= entrance
+            && dz < entrance + entrance_size;
+
+        if over_entrance && world.y > floor_y && world.y <= config.stair_top_y {
+            let stair_x = dx - entrance;
+            let stair_z = dz - entrance;
+            let is_stair_wall = stair_x == 0
+                || stair_x == entrance_size - 1
+                || stair_z == 0
+                || stair_z == entrance_size - 1;
+
+            // Interior is air (the stairwell)
+            if is_stair_wall && stair_x != 1 && stair_z != 1 {
+                return Some(VoxelType::DungeonWall);
+            }
+            return Some(VoxelType::Air);
+        }
+
+        let local_y = world.y - floor_y;
+        if dx >= size || dz >= size || local_y < 0 || local_y > height {
+            return None;
+        }
+
+        let is_outer_wall = dx == 0 || dx == size - 1 || dz == 0 || dz == size - 1;
+
+        // Inner walls forming corridors, with doorways
+        let wall_at_x = dx % 8 <= 1 && dx > 0 && dx < size - 1;
+        let wall_at_z = dz % 8 <= 1 && dz > 0 && dz < size - 1;
+        let doorway_x = (3..=5).contains(&dz) || (11..=13).contains(&dz) || (17..=19).contains(&dz);
+        let doorway_z = (3..=5).contains(&dx) || (11..=13).contains(&dx) || (17..=19).contains(&dx);
+        let is_inner_wall = (wall_at_x && !doorway_x) || (wall_at_z && !doorway_z);
+
+        // Pillars at intersections
+        let is_pillar = wall_at_x && wall_at_z;
+
+        if local_y == 0 || (local_y == height && !over_entrance) {
+            Some(VoxelType::DungeonFloor)
+        } else if is_outer_wall || is_inner_wall || is_pillar {
+            Some(VoxelType::DungeonWall)
+        } else {
+            // Interior space - air so terrain doesn't fill it
+            Some(VoxelType::Air)
+        }
+    }
+}
+
+impl GenerationStage for DungeonStage {
+    fn name(&self) -> &str {
+        "dungeons"
+    }
+
+    fn apply(&self, ctx: &mut ChunkContext) {
+        let origin = ctx.origin();
+        for local in ChunkContext::positions() {
+            if let Some(voxel) = self.voxel_at(origin + local.as_ivec3()) {
+                ctx.reserve(local, voxel);
+            }
+        }
+    }
+}
+
+/// Trees on grass columns above the water line
+pub struct TreeStage {
+    config: TreeConfig,
+}
+
+impl TreeStage {
+    pub fn new(config: TreeConfig) -> Self {
+        Self { config }
+    }
+
+    /// Trunk height if a tree grows on this column
+    pub fn tree_at(&self, noise: &Noise, world_x: i32, world_z: i32, height: i32, water_level: i32) -> Option<i32> {
+        let config = &self.config;
+        if height <= water_level + config.min_height_above_water {
+            return None;
+        }
+        if noise.hash(world_x.wrapping_mul(7), world_z.wrapping_mul(13)) <= 1.0 - config.density {
+            return None;
+        }
+
+        let h = noise.hash(world_x.wrapping_add(1000), world_z.wrapping_add(2000));
+        let range = config.max_trunk - config.min_trunk + 1;
+        Some((config.min_trunk + (h * range as f32) as i32).min(config.max_trunk))
+    }
+}
+
+impl GenerationStage for TreeStage {
+    fn name(&self) -> &str {
+        "trees"
+    }
+
+    fn apply(&self, ctx: &mut ChunkContext) {
+        let radius = self.config.leaf_radius.ceil() as i32;
+        let radius_sq = self.config.leaf_radius * self.config.leaf_radius;
+
+        let mut trunks = Vec::new();
+        for (x, z) in ctx.heights.columns().collect::<Vec<_>>() {
+            let height = ctx.heights.get(x, z).unwrap_or_default();
+            if let Some(trunk) = self.tree_at(&ctx.noise, x, z, height, ctx.water_level) {
+                trunks.push((IVec3::new(x, height + 1, z), trunk));
+            }
+        }
+
+        // Leaves first so trunks always win where they overlap
+        for &(base, trunk) in &trunks {
+            let center = base + IVec3::Y * (trunk - 1);
+            for dx in -radius..=radius {
+                for dz in -radius..=radius {
+                    for dy in -radius..=radius {
+                        let dist_sq = (dx * dx + dz * dz) as f32 + (dy * dy) as f32 * 1.5;
+                        if dist_sq >= radius_sq {
+                            continue;
+                        }
+
+                        let world = center + IVec3::new(dx, dy, dz);
+                        let Some(local) = local_in_chunk(ctx, world) else {
+                            continue;
+                        };
+                        let surface = ctx.heights.get(world.x, world.z).unwrap_or_default();
+                        if world.y > surface {
+                            ctx.set(local, VoxelType::Leaves);
+                        }
+                    }
+                }
+            }
+        }
+
+        for &(base, trunk) in &trunks {
+            for dy in 0..trunk {
+                if let Some(local) = local_in_chunk(ctx, base + IVec3::Y * dy) {
+                    ctx.set(local, VoxelType::Wood);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use bevy::ecs::world::World;
+use bevy::math::IVec3;
+use std::fs;
+use voxel_builder::chat::commands::{self, ChatCommand, ChatCommands, HelpCommand};
+use voxel_builder::chat::log::{self as chat_log, ChatLogCommand};
+use voxel_builder::chat::{ChatMessage, ChatState};
+use voxel_builder::environment::{AtmosphereSettings, TimeCommand};
+use voxel_builder::network::protocol::{clean_chat, MAX_CHAT_LENGTH};
+use voxel_builder::voxel::persistence::{self, ActiveWorld};
+
+/// Replies with its arguments, or fails when given none
+struct EchoCommand;
+
+impl ChatCommand for EchoCommand {
+    fn name(&self) -> &str {
+        "echo"
+    }
+
+    fn usage(&self) -> &str {
+        "<words>"
+    }
+
+    fn description(&self) -> &str {
+        "Repeat the words"
+    }
+
+    fn run(&self, args: &[&str], _world: &mut World) -> Result<String, String> {
+        if args.is_empty() {
+            return Err("Nothing to echo".to_string());
+        }
+        Ok(args.join(" "))
+    }
+}
+
+fn command_world() -> World {
+    let mut world = World::new();
+    let mut registry = ChatCommands::default();
+    registry.register(HelpCommand);
+    registry.register(EchoCommand);
+    world.insert_resource(registry);
+    world.insert_resource(ChatState::default());
+    world
+}
+
+fn contents(messages: &[ChatMessage]) -> Vec<&str> {
+    messages.iter().map(|msg| msg.content.as_str()).collect()
+}
+
+#[test]
+fn up_and_down_recall_sent_lines_and_restore_the_draft() {
+    let mut chat = ChatState::default();
+    chat.remember_sent("first");
+    chat.remember_sent("second");
+    chat.remember_sent("second");
+    chat.buffer = "draft".to_string();
+
+    chat.recall_previous();
+    assert_eq!(chat.buffer, "second");
+    chat.recall_previous();
+    assert_eq!(chat.buffer, "first");
+    chat.recall_previous();
+    assert_eq!(chat.buffer, "first", "stops at the oldest line");
+
+    chat.recall_next();
+    assert_eq!(chat.buffer, "second");
+    chat.recall_next();
+    assert_eq!(chat.buffer, "draft");
+}
+
+#[test]
+fn scrollback_keeps_older_messages_in_view() {
+    let mut chat = ChatState::default();
+    for i in 0..30 {
+        chat.push_system(format!("m{}", i));
+    }
+    assert_eq!(chat.visible_messages().len(), 10);
+    assert_eq!(chat.visible_messages()[9].content, "m29");
+
+    chat.scroll_by(15);
+    assert_eq!(contents(chat.visible_messages())[0], "m5");
+
+    // A new message doesn't move what the reader is looking at
+    chat.push_system("m30");
+    assert_eq!(contents(chat.visible_messages())[0], "m5");
+
+    chat.scroll_by(100);
+    assert_eq!(chat.scroll, chat.max_scroll());
+    assert_eq!(contents(chat.visible_messages())[0], "m0");
+    chat.scroll_by(-100);
+    assert_eq!(chat.scroll, 0);
+}
+
+#[test]
+fn commands_run_by_name_and_report_errors() {
+    let mut world = command_world();
+
+    assert_eq!(
+        commands::execute_command("/echo hello  there", &mut world),
+        Ok("hello there".to_string())
+    );
+    assert_eq!(
+        commands::execute_command("/ECHO hi", &mut world),
+        Ok("hi".to_string())
+    );
+    assert_eq!(
+        commands::execute_command("/echo", &mut world),
+        Err("Nothing to echo".to_string())
+    );
+    let unknown = commands::execute_command("/fly", &mut world).unwrap_err();
+    assert!(unknown.contains("/fly"), "{}", unknown);
+
+    let help = commands::execute_command("/help", &mut world).unwrap();
+    assert!(
+        help.contains("/echo <words> - Repeat the words"),
+        "{}",
+        help
+    );
+    assert!(help.contains("/help [command]"), "{}", help);
+}
+
+#[test]
+fn queued_command_replies_appear_in_chat() {
+    let mut world = command_world();
+    assert!(commands::is_command("/echo one"));
+    assert!(!commands::is_command("hello /echo"));
+
+    world.resource_mut::<ChatCommands>().queue("/echo one");
+    world.resource_mut::<ChatCommands>().queue("/echo");
+    commands::run_queued_commands(&mut world);
+
+    let chat = world.resource::<ChatState>();
+    assert_eq!(contents(&chat.messages), vec!["one", "Nothing to echo"]);
+}
+
+#[test]
+fn time_command_sets_the_clock() {
+    let mut world = World::new();
+    world.insert_resource(AtmosphereSettings::default());
+
+    TimeCommand.run(&["18:30"], &mut world).unwrap();
+    assert!((world.resource::<AtmosphereSettings>().hour() - 18.5).abs() < 1e-3);
+    TimeCommand.run(&["midnight"], &mut world).unwrap();
+    let reply = TimeCommand.run(&[], &mut world).unwrap();
+    assert!(reply.contains("00:00"), "{}", reply);
+
+    assert!(TimeCommand.run(&["25"], &mut world).is_err());
+    TimeCommand.run(&["stop"], &mut world).unwrap();
+    assert!(!world.resource::<AtmosphereSettings>().cycle_enabled);
+}
+
+#[test]
+fn chat_log_is_per_world_and_persists_its_setting() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (slot, metadata) =
+        persistence::create_save_slot(tmp.path(), "Logged", 1, IVec3::new(2, 1, 2)).unwrap();
+    let mut world = World::new();
+    world.insert_resource(ActiveWorld::new(slot.clone(), metadata));
+
+    ChatLogCommand.run(&["on"], &mut world).unwrap();
+    assert!(slot.read_metadata().unwrap().chat_log);
+
+    let messages = vec![
+        ChatMessage {
+            user: "alice".to_string(),
+            content: "hi".to_string(),
+        },
+        ChatMessage::system("bob joined the game"),
+    ];
+    chat_log::append_chat_log(&slot.chat_log_path(), &messages, 100).unwrap();
+    chat_log::append_chat_log(&slot.chat_log_path(), &messages[..1], 200).unwrap();
+    assert_eq!(
+        fs::read_to_string(slot.chat_log_path()).unwrap(),
+        "[100] alice: hi\n[100] System: bob joined the game\n[200] alice: hi\n"
+    );
+
+    ChatLogCommand.run(&["off"], &mut world).unwrap();
+    assert!(!slot.read_metadata().unwrap().chat_log);
+    assert!(ChatLogCommand.run(&["maybe"], &mut world).is_err());
+}
+
+#[test]
+fn chat_text_is_trimmed_and_limited() {
+    assert_eq!(
+        clean_chat("  hi\u{7} there \n"),
+        Some("hi there".to_string())
+    );
+    assert_eq!(clean_chat(" \t "), None);
+    let long = "x".repeat(MAX_CHAT_LENGTH + 10);
+    assert_eq!(clean_chat(&long).unwrap().len(), MAX_CHAT_LENGTH);
+}
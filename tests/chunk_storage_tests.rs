@@ -0,0 +1,104 @@
+use bevy::math::{IVec3, UVec3};
+use voxel_builder::constants::{CHUNK_SIZE, CHUNK_VOLUME};
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::storage::ChunkStorage;
+use voxel_builder::voxel::types::VoxelType;
+
+#[test]
+fn new_chunks_are_uniform_air() {
+    let chunk = Chunk::new(IVec3::ZERO);
+    assert_eq!(chunk.uniform_voxel(), Some(VoxelType::Air));
+    assert_eq!(chunk.voxel_memory_bytes(), 0);
+    assert_eq!(chunk.get(UVec3::new(15, 15, 15)), VoxelType::Air);
+}
+
+#[test]
+fn set_reports_changes_and_keeps_dirty_semantics() {
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    chunk.clear_dirty();
+    chunk.clear_modified();
+
+    chunk.set(UVec3::new(1, 2, 3), VoxelType::Air);
+    assert!(!chunk.is_dirty());
+    assert!(!chunk.is_modified());
+
+    chunk.set(UVec3::new(1, 2, 3), VoxelType::Rock);
+    assert!(chunk.is_dirty());
+    assert!(chunk.is_modified());
+    assert_eq!(chunk.get(UVec3::new(1, 2, 3)), VoxelType::Rock);
+    assert_eq!(chunk.get(UVec3::new(3, 2, 1)), VoxelType::Air);
+}
+
+#[test]
+fn palette_grows_and_keeps_every_voxel() {
+    let mut storage = ChunkStorage::Uniform(VoxelType::Air);
+    let expected = |i: usize| VoxelType::from_id((i % 37) as u8);
+
+    for i in 0..CHUNK_VOLUME {
+        storage.set(i, expected(i));
+    }
+
+    assert_eq!(storage.palette_len(), 37);
+    for i in 0..CHUNK_VOLUME {
+        assert_eq!(storage.get(i), expected(i));
+    }
+    assert!(storage.validate().is_ok());
+}
+
+#[test]
+fn compact_collapses_to_uniform() {
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    for x in 0..CHUNK_SIZE as u32 {
+        for y in 0..CHUNK_SIZE as u32 {
+            for z in 0..CHUNK_SIZE as u32 {
+                chunk.set(UVec3::new(x, y, z), VoxelType::Rock);
+            }
+        }
+    }
+
+    assert_eq!(chunk.uniform_voxel(), None);
+    chunk.compact();
+    assert_eq!(chunk.uniform_voxel(), Some(VoxelType::Rock));
+    assert_eq!(chunk.get(UVec3::new(7, 8, 9)), VoxelType::Rock);
+}
+
+#[test]
+fn compact_drops_unused_palette_entries() {
+    let mut storage = ChunkStorage::Uniform(VoxelType::Air);
+    for (i, voxel) in [VoxelType::Rock, VoxelType::Sand, VoxelType::Clay, VoxelType::Water, VoxelType::Wood]
+        .into_iter()
+        .enumerate()
+    {
+        storage.set(i, voxel);
+    }
+    storage.set(1, VoxelType::Air);
+    storage.set(2, VoxelType::Air);
+    assert_eq!(storage.palette_len(), 6);
+
+    let before_bytes = storage.memory_bytes();
+    storage.compact();
+
+    assert_eq!(storage.palette_len(), 4);
+    assert!(storage.memory_bytes() < before_bytes);
+    assert_eq!(storage.get(0), VoxelType::Rock);
+    assert_eq!(storage.get(3), VoxelType::Water);
+    assert_eq!(storage.get(4), VoxelType::Wood);
+    assert_eq!(storage.get(5), VoxelType::Air);
+}
+
+#[test]
+fn data_round_trip_preserves_voxels() {
+    let mut chunk = Chunk::new(IVec3::new(4, 1, -2));
+    chunk.set(UVec3::new(0, 0, 0), VoxelType::Bedrock);
+    chunk.set(UVec3::new(15, 15, 15), VoxelType::Leaves);
+
+    let bytes = bincode::serialize(&chunk.to_data()).expect("serialize");
+    let loaded = Chunk::from_data(bincode::deserialize(&bytes).expect("deserialize"));
+
+    assert_eq!(loaded.position(), IVec3::new(4, 1, -2));
+    assert_eq!(loaded.get(UVec3::new(0, 0, 0)), VoxelType::Bedrock);
+    assert_eq!(loaded.get(UVec3::new(15, 15, 15)), VoxelType::Leaves);
+    assert_eq!(loaded.get(UVec3::new(8, 8, 8)), VoxelType::Air);
+    assert!(loaded.is_dirty());
+    assert!(!loaded.is_modified());
+}
@@ -0,0 +1,86 @@
+use bevy::math::{IVec3, UVec3};
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::persistence::ChunkStore;
+use voxel_builder::voxel::streaming::ChunkStreamingSettings;
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::VoxelWorld;
+
+fn settings(load_radius: i32, unload_radius: i32) -> ChunkStreamingSettings {
+    ChunkStreamingSettings {
+        load_radius,
+        unload_radius,
+        min_chunk_y: 0,
+        max_chunk_y: 2,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn positions_around_are_nearest_first_within_radius() {
+    let center = IVec3::new(40, 0, -7);
+    let positions = settings(3, 5).positions_around(center);
+
+    assert_eq!(positions[0], IVec3::new(40, 0, -7));
+    assert_eq!(positions[1], IVec3::new(40, 1, -7));
+
+    let mut last_dist = 0;
+    for pos in &positions {
+        let dist = (pos.x - center.x).pow(2) + (pos.z - center.z).pow(2);
+        assert!(dist <= 9, "{:?} outside load radius", pos);
+        assert!(dist >= last_dist, "positions not sorted by distance");
+        assert!((0..2).contains(&pos.y));
+        last_dist = dist;
+    }
+}
+
+#[test]
+fn unload_radius_adds_hysteresis() {
+    let settings = settings(3, 5);
+    let center = IVec3::ZERO;
+
+    assert!(!settings.should_unload(IVec3::new(4, 0, 0), center));
+    assert!(!settings.should_unload(IVec3::new(3, 0, 4), center));
+    assert!(settings.should_unload(IVec3::new(6, 0, 0), center));
+}
+
+#[test]
+fn world_border_is_optional() {
+    let mut world = VoxelWorld::new(IVec3::new(2, 1, 2));
+    let outside = IVec3::new(-5, 0, 9);
+
+    assert!(!world.chunk_in_bounds(outside));
+
+    world.set_border_enabled(false);
+    assert!(world.chunk_in_bounds(outside));
+
+    world.insert_chunk(Chunk::new(outside));
+    assert!(world.set_voxel(VoxelWorld::chunk_to_world(outside), VoxelType::Rock));
+    assert_eq!(world.remove_chunk(outside).map(|c| c.position()), Some(outside));
+    assert_eq!(world.loaded_chunk_count(), 0);
+}
+
+#[test]
+fn chunk_store_round_trips_evicted_chunks() {
+    let tmp_dir = tempfile::tempdir().expect("create temp dir");
+    let store = ChunkStore {
+        directory: tmp_dir.path().join("chunks"),
+    };
+    let chunk_pos = IVec3::new(-3, 1, 12);
+
+    assert!(store.load_chunk(chunk_pos).expect("missing chunk is not an error").is_none());
+
+    let mut chunk = Chunk::new(chunk_pos);
+    chunk.set(UVec3::new(1, 2, 3), VoxelType::Clay);
+    store.save_chunk(&chunk).expect("save chunk");
+
+    let loaded = store
+        .load_chunk(chunk_pos)
+        .expect("load chunk")
+        .expect("chunk stored");
+    assert_eq!(loaded.position(), chunk_pos);
+    assert_eq!(loaded.get(UVec3::new(1, 2, 3)), VoxelType::Clay);
+    assert!(!loaded.is_modified());
+
+    store.clear().expect("clear store");
+    assert!(store.load_chunk(chunk_pos).expect("load after clear").is_none());
+}
@@ -0,0 +1,132 @@
+use bevy::math::{IVec3, Mat4, UVec3, Vec3};
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::skirt::ChunkFace;
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::visibility::{visible_chunks, ChunkFrustum, FaceConnectivity};
+use voxel_builder::voxel::world::VoxelWorld;
+
+fn filled(position: IVec3, voxel: VoxelType) -> Chunk {
+    let mut chunk = Chunk::new(position);
+    for x in 0..16 {
+        for y in 0..16 {
+            for z in 0..16 {
+                chunk.set(UVec3::new(x, y, z), voxel);
+            }
+        }
+    }
+    chunk
+}
+
+/// 3x3x3 chunks: rock in the bottom two layers with a sealed cave chunk in
+/// the middle of the bottom one, open air on top
+fn cave_world() -> VoxelWorld {
+    let mut world = VoxelWorld::new(IVec3::splat(3));
+    for x in 0..3 {
+        for y in 0..3 {
+            for z in 0..3 {
+                let position = IVec3::new(x, y, z);
+                let voxel = if y == 2 || position == IVec3::new(1, 0, 1) {
+                    VoxelType::Air
+                } else {
+                    VoxelType::Rock
+                };
+                world.insert_chunk(filled(position, voxel));
+            }
+        }
+    }
+    world
+}
+
+fn update_connectivity(world: &mut VoxelWorld) {
+    for (_, chunk) in world.chunk_entries_mut() {
+        chunk.update_connectivity();
+    }
+}
+
+#[test]
+fn uniform_chunks_are_open_or_closed() {
+    let air = FaceConnectivity::compute(&Chunk::new(IVec3::ZERO));
+    let rock = FaceConnectivity::compute(&filled(IVec3::ZERO, VoxelType::Rock));
+
+    assert_eq!(air, FaceConnectivity::ALL);
+    assert_eq!(rock, FaceConnectivity::NONE);
+}
+
+#[test]
+fn tunnels_connect_only_the_faces_they_reach() {
+    let mut chunk = filled(IVec3::ZERO, VoxelType::Rock);
+    // Along x from the -X face to the middle, then up to the +Y face
+    for x in 0..=8 {
+        chunk.set(UVec3::new(x, 8, 8), VoxelType::Air);
+    }
+    for y in 8..16 {
+        chunk.set(UVec3::new(8, y, 8), VoxelType::Air);
+    }
+
+    let connectivity = FaceConnectivity::compute(&chunk);
+    assert!(connectivity.connects(ChunkFace::NegX, ChunkFace::PosY));
+    assert!(connectivity.connects(ChunkFace::PosY, ChunkFace::NegX));
+    assert!(!connectivity.connects(ChunkFace::NegX, ChunkFace::PosX));
+    assert!(!connectivity.connects(ChunkFace::PosY, ChunkFace::NegY));
+}
+
+#[test]
+fn edits_invalidate_connectivity() {
+    let mut chunk = filled(IVec3::ZERO, VoxelType::Rock);
+    assert_eq!(chunk.update_connectivity(), FaceConnectivity::NONE);
+
+    chunk.set(UVec3::new(0, 0, 0), VoxelType::Air);
+    assert_eq!(chunk.connectivity(), None);
+    assert!(chunk.update_connectivity().connects(ChunkFace::NegX, ChunkFace::NegY));
+}
+
+#[test]
+fn frustum_keeps_chunks_in_front_of_the_camera() {
+    // Camera at the origin looking down -Z
+    let clip_from_view = Mat4::perspective_infinite_reverse_rh(90f32.to_radians(), 1.0, 0.1);
+    let frustum = ChunkFrustum::from_clip_from_world(clip_from_view);
+
+    assert!(frustum.intersects_chunk(IVec3::new(0, 0, -3)));
+    assert!(frustum.intersects_chunk(IVec3::new(-1, -1, -3)));
+    assert!(!frustum.intersects_chunk(IVec3::new(0, 0, 2)));
+    assert!(!frustum.intersects_chunk(IVec3::new(20, 0, -1)));
+}
+
+#[test]
+fn sealed_caves_are_hidden_from_the_surface() {
+    let mut world = cave_world();
+    update_connectivity(&mut world);
+    let surface = Vec3::new(24.0, 40.0, 24.0);
+
+    let visible = visible_chunks(&world, surface, None, true);
+    assert!(visible.contains(&IVec3::new(0, 2, 2)));
+    // The ground right under the surface still draws its top
+    assert!(visible.contains(&IVec3::new(1, 1, 1)));
+    assert!(!visible.contains(&IVec3::new(1, 0, 1)));
+
+    // Without occlusion only the frustum counts
+    assert_eq!(visible_chunks(&world, surface, None, false).len(), 27);
+}
+
+#[test]
+fn shafts_open_caves_to_the_surface() {
+    let mut world = cave_world();
+    for y in 16..32 {
+        world.set_voxel(IVec3::new(24, y, 24), VoxelType::Air);
+    }
+    update_connectivity(&mut world);
+
+    let visible = visible_chunks(&world, Vec3::new(24.0, 40.0, 24.0), None, true);
+    assert!(visible.contains(&IVec3::new(1, 0, 1)));
+}
+
+#[test]
+fn caves_are_walked_from_above_the_loaded_world() {
+    let mut world = cave_world();
+    update_connectivity(&mut world);
+
+    let visible = visible_chunks(&world, Vec3::new(24.0, 200.0, 24.0), None, true);
+    assert!(visible.contains(&IVec3::new(2, 2, 2)));
+    assert!(visible.contains(&IVec3::new(1, 1, 1)));
+    assert!(!visible.contains(&IVec3::new(1, 0, 1)));
+}
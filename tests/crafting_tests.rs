@@ -0,0 +1,200 @@
+use bevy::math::IVec3;
+use std::collections::HashMap;
+use voxel_builder::crafting::recipes::{
+    CraftingGrid, RecipeBook, RecipeDef, RecipeError, RECIPES_CONFIG_PATH,
+};
+use voxel_builder::crafting::station_nearby;
+use voxel_builder::entity::inventory::INVENTORY_SLOTS;
+use voxel_builder::entity::{Inventory, ItemType};
+use voxel_builder::interaction::mining::HeldTool;
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::registry::VoxelRegistry;
+use voxel_builder::voxel::types::{ToolTier, ToolType, VoxelType};
+use voxel_builder::voxel::world::VoxelWorld;
+
+const WOOD: ItemType = ItemType::Block(VoxelType::Wood);
+const ROCK: ItemType = ItemType::Block(VoxelType::Rock);
+
+fn shipped_book() -> RecipeBook {
+    RecipeBook::load(RECIPES_CONFIG_PATH, &VoxelRegistry::builtin()).expect("recipes.yaml loads")
+}
+
+fn grid(rows: [[Option<ItemType>; 3]; 3]) -> CraftingGrid {
+    let mut grid = CraftingGrid::default();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, item) in row.iter().enumerate() {
+            grid.set(x, y, *item);
+        }
+    }
+    grid
+}
+
+fn shaped(id: &str, pattern: &[&str], key: &[(&str, &str)], result: &str) -> RecipeDef {
+    RecipeDef {
+        id: id.to_string(),
+        pattern: pattern.iter().map(|row| row.to_string()).collect(),
+        key: key
+            .iter()
+            .map(|(symbol, item)| (symbol.to_string(), item.to_string()))
+            .collect::<HashMap<_, _>>(),
+        ingredients: Vec::new(),
+        result: result.to_string(),
+        count: 1,
+        station: None,
+    }
+}
+
+fn invalid_reason(result: Result<RecipeBook, RecipeError>) -> String {
+    match result {
+        Err(RecipeError::Invalid { reason, .. }) => reason,
+        other => panic!("expected an invalid recipe, got {:?}", other.map(|book| book.len())),
+    }
+}
+
+#[test]
+fn shipped_recipes_load() {
+    let book = shipped_book();
+    assert!(!book.is_empty());
+
+    // Every tool tier can be crafted
+    for tier in ToolTier::ALL {
+        let pickaxe = ItemType::Tool {
+            kind: ToolType::Pickaxe,
+            tier,
+        };
+        assert!(book.recipes().iter().any(|recipe| recipe.result.item == pickaxe));
+    }
+}
+
+#[test]
+fn shaped_recipes_match_anywhere_and_mirrored() {
+    let book = shipped_book();
+    let w = Some(WOOD);
+
+    // A 2x2 workbench in the bottom right corner
+    let workbench = grid([[None, None, None], [None, w, w], [None, w, w]]);
+    let recipe = book.find(&workbench).expect("workbench matches");
+    assert_eq!(recipe.result.item.name(), "workbench");
+
+    // A pickaxe head with the handle moved over is a different shape
+    let r = Some(ROCK);
+    let pickaxe = grid([[r, r, r], [None, w, None], [None, w, None]]);
+    assert_eq!(book.find(&pickaxe).map(|recipe| recipe.id.as_str()), Some("stone_pickaxe"));
+    let bent = grid([[r, r, r], [w, None, None], [None, w, None]]);
+    assert!(book.find(&bent).is_none());
+
+    // Mirroring left to right still matches
+    let defs = vec![shaped("hook", &["RR", " W"], &[("R", "rock"), ("W", "wood")], "rock")];
+    let book = RecipeBook::from_defs(defs, &VoxelRegistry::builtin()).unwrap();
+    assert!(book.find(&grid([[r, r, None], [None, w, None], [None; 3]])).is_some());
+    assert!(book.find(&grid([[r, r, None], [w, None, None], [None; 3]])).is_some());
+    assert!(book.find(&grid([[r, r, None], [None, None, w], [None; 3]])).is_none());
+}
+
+#[test]
+fn shapeless_recipes_ignore_arrangement() {
+    let book = shipped_book();
+    let registry = VoxelRegistry::builtin();
+    let subsoil = ItemType::from_name("subsoil", &registry);
+    let leaves = ItemType::from_name("leaves", &registry);
+
+    let recipe = book
+        .find(&grid([[None, None, leaves], [None; 3], [subsoil, None, None]]))
+        .expect("topsoil matches");
+    assert_eq!(recipe.result.count, 2);
+
+    // Extra or missing ingredients do not match
+    assert!(book.find(&grid([[subsoil, leaves, leaves], [None; 3], [None; 3]])).is_none());
+    assert!(book.find(&grid([[subsoil, None, None], [None; 3], [None; 3]])).is_none());
+}
+
+#[test]
+fn invalid_recipes_are_rejected_at_load() {
+    let registry = VoxelRegistry::builtin();
+    let load = |defs: Vec<RecipeDef>| RecipeBook::from_defs(defs, &registry);
+
+    let unknown = shaped("bad", &["X"], &[("X", "unobtainium")], "rock");
+    assert!(invalid_reason(load(vec![unknown])).contains("unobtainium"));
+
+    let unused = shaped("bad", &["X"], &[("X", "rock"), ("Y", "wood")], "rock");
+    assert!(invalid_reason(load(vec![unused])).contains("'Y'"));
+
+    let missing = shaped("bad", &["XZ"], &[("X", "rock")], "rock");
+    assert!(invalid_reason(load(vec![missing])).contains("'Z'"));
+
+    let too_wide = shaped("bad", &["XXXX"], &[("X", "rock")], "rock");
+    assert!(invalid_reason(load(vec![too_wide])).contains("larger"));
+
+    let mut tool_stack = shaped("bad", &["X"], &[("X", "rock")], "wood_pickaxe");
+    tool_stack.count = 2;
+    assert!(invalid_reason(load(vec![tool_stack])).contains("count"));
+
+    let mut no_station = shaped("bad", &["X"], &[("X", "rock")], "rock");
+    no_station.station = Some("nowhere".to_string());
+    assert!(invalid_reason(load(vec![no_station])).contains("nowhere"));
+
+    let twice = shaped("same", &["X"], &[("X", "rock")], "rock");
+    assert!(matches!(
+        load(vec![twice.clone(), twice]),
+        Err(RecipeError::DuplicateId(id)) if id == "same"
+    ));
+}
+
+#[test]
+fn stations_must_be_in_range() {
+    let registry = VoxelRegistry::builtin();
+    let workbench = registry.by_name("workbench").expect("workbench is registered");
+    let book = shipped_book();
+    let recipe = book
+        .recipes()
+        .iter()
+        .find(|recipe| recipe.id == "wood_pickaxe")
+        .unwrap();
+    assert_eq!(recipe.station, Some(workbench));
+
+    let mut world = VoxelWorld::new(IVec3::splat(1));
+    world.insert_chunk(Chunk::new(IVec3::ZERO));
+    let player = IVec3::new(2, 2, 2);
+    assert!(!station_nearby(&world, player, workbench));
+
+    world.set_voxel(IVec3::new(6, 2, 2), workbench);
+    assert!(station_nearby(&world, player, workbench));
+    assert!(!station_nearby(&world, IVec3::new(11, 2, 2), workbench));
+}
+
+#[test]
+fn crafted_tools_feed_mining_speed() {
+    let book = shipped_book();
+    let rock = VoxelRegistry::builtin().info(VoxelType::Rock).clone();
+    let bare = HeldTool {
+        kind: ToolType::None,
+        tier: ToolTier::Wood,
+    };
+
+    let w = Some(WOOD);
+    let pickaxe = grid([[w, w, w], [None, w, None], [None, w, None]]);
+    let ItemType::Tool { kind, tier } = book.find(&pickaxe).unwrap().result.item else {
+        panic!("wood_pickaxe should craft a tool");
+    };
+    assert_eq!((kind, tier), (ToolType::Pickaxe, ToolTier::Wood));
+
+    let crafted = HeldTool { kind, tier };
+    assert!(crafted.break_seconds(&rock) < bare.break_seconds(&rock));
+}
+
+#[test]
+fn closing_keeps_what_does_not_fit_in_the_grid() {
+    let mut inventory = Inventory::default();
+    inventory.add_items(ROCK, INVENTORY_SLOTS as u32 * 64 - 1);
+
+    let mut grid = grid([[Some(ROCK), Some(WOOD), None], [None; 3], [None; 3]]);
+    assert_eq!(grid.return_to(&mut inventory), 1);
+    assert_eq!(grid.get(0, 0), None);
+    assert_eq!(grid.get(1, 0), Some(WOOD));
+    assert_eq!(inventory.get_count(ROCK), INVENTORY_SLOTS as u32 * 64);
+
+    assert!(inventory.remove_item(ROCK, 64));
+    assert_eq!(grid.return_to(&mut inventory), 0);
+    assert!(grid.is_empty());
+    assert_eq!(inventory.get_count(WOOD), 1);
+}
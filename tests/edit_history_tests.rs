@@ -0,0 +1,150 @@
+use bevy::math::IVec3;
+use voxel_builder::interaction::history::EditHistory;
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::VoxelWorld;
+
+fn world_with_chunks(positions: &[IVec3]) -> VoxelWorld {
+    let mut world = VoxelWorld::new(IVec3::new(4, 2, 4));
+    for pos in positions {
+        let mut chunk = Chunk::new(*pos);
+        chunk.clear_dirty();
+        world.insert_chunk(chunk);
+    }
+    world
+}
+
+fn clean_all(world: &mut VoxelWorld, positions: &[IVec3]) {
+    for pos in positions {
+        world.get_chunk_mut(*pos).unwrap().clear_dirty();
+    }
+}
+
+#[test]
+fn undo_and_redo_single_edits() {
+    let mut world = world_with_chunks(&[IVec3::ZERO]);
+    let mut history = EditHistory::default();
+    let pos = IVec3::new(3, 4, 5);
+
+    assert!(history.edit(&mut world, "Place block", pos, VoxelType::Wood));
+    assert!(history.edit(&mut world, "Break block", pos, VoxelType::Air));
+    assert_eq!(history.undo_len(), 2);
+
+    let step = history.undo(&mut world).expect("undo break");
+    assert_eq!(step.label, "Break block");
+    assert_eq!(world.get_voxel(pos), Some(VoxelType::Wood));
+
+    history.undo(&mut world).expect("undo place");
+    assert_eq!(world.get_voxel(pos), Some(VoxelType::Air));
+    assert!(history.undo(&mut world).is_none());
+
+    history.redo(&mut world).expect("redo place");
+    assert_eq!(world.get_voxel(pos), Some(VoxelType::Wood));
+    assert_eq!(history.redo_len(), 1);
+}
+
+#[test]
+fn transactions_undo_as_one_step() {
+    let mut world = world_with_chunks(&[IVec3::ZERO]);
+    let mut history = EditHistory::default();
+    let from = IVec3::new(1, 1, 1);
+    let to = IVec3::new(6, 1, 1);
+    world.set_voxel(from, VoxelType::Clay);
+
+    history.begin("Move block");
+    history.set_voxel(&mut world, from, VoxelType::Air);
+    history.set_voxel(&mut world, to, VoxelType::Clay);
+    history.commit();
+    assert_eq!(history.undo_len(), 1);
+
+    let step = history.undo(&mut world).expect("undo move");
+    assert_eq!(step.applied, 2);
+    assert_eq!(world.get_voxel(from), Some(VoxelType::Clay));
+    assert_eq!(world.get_voxel(to), Some(VoxelType::Air));
+}
+
+#[test]
+fn transactions_that_cancel_out_are_not_recorded() {
+    let mut world = world_with_chunks(&[IVec3::ZERO]);
+    let mut history = EditHistory::default();
+    let pos = IVec3::new(2, 2, 2);
+    world.set_voxel(pos, VoxelType::Rock);
+
+    // A dragged block dropped back where it was picked up
+    history.begin("Move block");
+    history.set_voxel(&mut world, pos, VoxelType::Air);
+    history.set_voxel(&mut world, pos, VoxelType::Rock);
+    history.commit();
+
+    assert!(!history.can_undo());
+}
+
+#[test]
+fn new_edits_clear_redo_and_depth_is_bounded() {
+    let mut world = world_with_chunks(&[IVec3::ZERO]);
+    let mut history = EditHistory::new(3);
+
+    for x in 0..5 {
+        history.edit(
+            &mut world,
+            "Place block",
+            IVec3::new(x, 0, 0),
+            VoxelType::Sand,
+        );
+    }
+    assert_eq!(history.undo_len(), 3);
+
+    history.undo(&mut world);
+    assert!(history.can_redo());
+    history.edit(
+        &mut world,
+        "Place block",
+        IVec3::new(9, 0, 0),
+        VoxelType::Sand,
+    );
+    assert!(!history.can_redo());
+
+    while history.undo(&mut world).is_some() {}
+    // The two oldest edits fell off the stack and stay in place
+    assert_eq!(world.get_voxel(IVec3::new(0, 0, 0)), Some(VoxelType::Sand));
+    assert_eq!(world.get_voxel(IVec3::new(1, 0, 0)), Some(VoxelType::Sand));
+    assert_eq!(world.get_voxel(IVec3::new(2, 0, 0)), Some(VoxelType::Air));
+}
+
+#[test]
+fn undo_marks_neighbor_chunks_dirty_at_edges() {
+    let chunks = [IVec3::ZERO, IVec3::new(1, 0, 0), IVec3::new(0, 0, 1)];
+    let mut world = world_with_chunks(&chunks);
+    let mut history = EditHistory::default();
+    let edge = IVec3::new(15, 3, 7);
+
+    history.edit(&mut world, "Place block", edge, VoxelType::Rock);
+    clean_all(&mut world, &chunks);
+
+    history.undo(&mut world).expect("undo");
+    assert!(world.get_chunk(IVec3::ZERO).unwrap().is_dirty());
+    assert!(world.get_chunk(IVec3::new(1, 0, 0)).unwrap().is_dirty());
+    assert!(!world.get_chunk(IVec3::new(0, 0, 1)).unwrap().is_dirty());
+}
+
+#[test]
+fn edits_in_unloaded_chunks_are_skipped() {
+    let mut world = world_with_chunks(&[IVec3::ZERO, IVec3::new(1, 0, 0)]);
+    let mut history = EditHistory::default();
+
+    history.begin("Place blocks");
+    history.set_voxel(&mut world, IVec3::new(1, 1, 1), VoxelType::Wood);
+    history.set_voxel(&mut world, IVec3::new(17, 1, 1), VoxelType::Wood);
+    history.commit();
+    assert!(!history.edit(
+        &mut world,
+        "Place block",
+        IVec3::new(40, 1, 1),
+        VoxelType::Wood
+    ));
+
+    world.remove_chunk(IVec3::new(1, 0, 0));
+    let step = history.undo(&mut world).expect("undo");
+    assert_eq!((step.applied, step.skipped), (1, 1));
+    assert_eq!(world.get_voxel(IVec3::new(1, 1, 1)), Some(VoxelType::Air));
+}
@@ -0,0 +1,289 @@
+use bevy::math::{IVec3, UVec3};
+use voxel_builder::rendering::ao_config::AmbientOcclusionConfig;
+use voxel_builder::voxel::chunk::{Chunk, LodLevel};
+use voxel_builder::voxel::fluid::{FluidSimulation, MAX_FLUID_LEVEL};
+use voxel_builder::voxel::meshing::generate_chunk_mesh_surface_nets;
+use voxel_builder::voxel::persistence::ChunkStore;
+use voxel_builder::voxel::skirt::{NeighborLods, SkirtConfig};
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::VoxelWorld;
+
+/// One loaded chunk with a rock floor at y = 0
+fn floored_world() -> VoxelWorld {
+    let mut world = VoxelWorld::new(IVec3::ONE);
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    for x in 0..16 {
+        for z in 0..16 {
+            chunk.set(UVec3::new(x, 0, z), VoxelType::Rock);
+        }
+    }
+    chunk.clear_dirty();
+    world.insert_chunk(chunk);
+    world
+}
+
+fn place(world: &mut VoxelWorld, simulation: &mut FluidSimulation, pos: IVec3, voxel: VoxelType) {
+    world.set_voxel(pos, voxel);
+    simulation.wake_around(world, pos);
+}
+
+/// Step until nothing is left to update; returns the number of steps
+fn settle(world: &mut VoxelWorld, simulation: &mut FluidSimulation) -> usize {
+    let mut steps = 0;
+    while !simulation.is_idle() {
+        simulation.step(world, usize::MAX);
+        steps += 1;
+        assert!(steps < 500, "liquid never settled");
+    }
+    steps
+}
+
+fn water_count(world: &VoxelWorld) -> usize {
+    let mut count = 0;
+    for x in 0..16 {
+        for y in 0..16 {
+            for z in 0..16 {
+                if world.get_voxel(IVec3::new(x, y, z)) == Some(VoxelType::Water) {
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
+
+#[test]
+fn water_spreads_one_level_lower_per_voxel() {
+    let mut world = floored_world();
+    let mut simulation = FluidSimulation::default();
+    let source = IVec3::new(8, 1, 8);
+    place(&mut world, &mut simulation, source, VoxelType::Water);
+    settle(&mut world, &mut simulation);
+
+    assert!(world.is_fluid_source(source));
+    assert_eq!(world.fluid_level(source), MAX_FLUID_LEVEL);
+    assert_eq!(world.fluid_level(IVec3::new(9, 1, 8)), MAX_FLUID_LEVEL - 1);
+    assert_eq!(world.fluid_level(IVec3::new(8, 1, 5)), MAX_FLUID_LEVEL - 3);
+    assert_eq!(
+        world.fluid_level(IVec3::new(11, 1, 11)),
+        MAX_FLUID_LEVEL - 6
+    );
+    assert_eq!(world.fluid_level(IVec3::new(15, 1, 8)), 1);
+    // Manhattan distance 8 is past the last level
+    assert_eq!(world.get_voxel(IVec3::new(12, 1, 12)), Some(VoxelType::Air));
+    assert_eq!(world.get_voxel(IVec3::new(8, 2, 8)), Some(VoxelType::Air));
+    assert!(!world.is_fluid_source(IVec3::new(9, 1, 8)));
+}
+
+#[test]
+fn water_drains_once_its_source_is_gone() {
+    let mut world = floored_world();
+    let mut simulation = FluidSimulation::default();
+    let source = IVec3::new(8, 1, 8);
+    place(&mut world, &mut simulation, source, VoxelType::Water);
+    settle(&mut world, &mut simulation);
+    assert!(water_count(&world) > 1);
+
+    place(&mut world, &mut simulation, source, VoxelType::Air);
+    settle(&mut world, &mut simulation);
+    assert_eq!(water_count(&world), 0);
+}
+
+#[test]
+fn water_falls_before_spreading() {
+    let mut world = floored_world();
+    let mut simulation = FluidSimulation::default();
+    place(
+        &mut world,
+        &mut simulation,
+        IVec3::new(8, 10, 8),
+        VoxelType::Water,
+    );
+    settle(&mut world, &mut simulation);
+
+    for y in 1..10 {
+        assert_eq!(
+            world.fluid_level(IVec3::new(8, y, 8)),
+            MAX_FLUID_LEVEL,
+            "falling column at y {}",
+            y
+        );
+        if y > 1 {
+            // The column only spreads where it lands
+            assert_eq!(world.get_voxel(IVec3::new(9, y, 8)), Some(VoxelType::Air));
+        }
+    }
+    assert_eq!(world.fluid_level(IVec3::new(9, 1, 8)), MAX_FLUID_LEVEL - 1);
+}
+
+#[test]
+fn water_between_two_sources_becomes_a_source() {
+    let mut world = floored_world();
+    let mut simulation = FluidSimulation::default();
+    place(
+        &mut world,
+        &mut simulation,
+        IVec3::new(4, 1, 4),
+        VoxelType::Water,
+    );
+    place(
+        &mut world,
+        &mut simulation,
+        IVec3::new(6, 1, 4),
+        VoxelType::Water,
+    );
+    settle(&mut world, &mut simulation);
+    assert!(world.is_fluid_source(IVec3::new(5, 1, 4)));
+
+    // The new source keeps feeding the pool when an original one is removed
+    place(
+        &mut world,
+        &mut simulation,
+        IVec3::new(4, 1, 4),
+        VoxelType::Air,
+    );
+    settle(&mut world, &mut simulation);
+    assert!(world.is_fluid_source(IVec3::new(5, 1, 4)));
+    assert_eq!(world.fluid_level(IVec3::new(4, 1, 4)), MAX_FLUID_LEVEL - 1);
+}
+
+#[test]
+fn digging_under_a_lake_fills_the_hole() {
+    let mut world = floored_world();
+    let mut simulation = FluidSimulation::default();
+    for x in 2..14 {
+        for z in 2..14 {
+            world.set_voxel(IVec3::new(x, 1, z), VoxelType::Rock);
+            world.set_voxel(IVec3::new(x, 2, z), VoxelType::Water);
+        }
+    }
+    // Placed lakes are still water until something changes
+    assert!(simulation.is_idle());
+
+    let hole = IVec3::new(7, 1, 7);
+    place(&mut world, &mut simulation, hole, VoxelType::Air);
+    settle(&mut world, &mut simulation);
+
+    assert_eq!(world.get_voxel(hole), Some(VoxelType::Water));
+    assert_eq!(world.fluid_level(hole), MAX_FLUID_LEVEL);
+    assert!(world.is_fluid_source(IVec3::new(7, 2, 7)));
+}
+
+#[test]
+fn settled_water_does_no_work() {
+    let mut world = floored_world();
+    let mut simulation = FluidSimulation::default();
+    assert_eq!(simulation.step(&mut world, usize::MAX), 0);
+
+    place(
+        &mut world,
+        &mut simulation,
+        IVec3::new(3, 1, 3),
+        VoxelType::Water,
+    );
+    settle(&mut world, &mut simulation);
+    assert_eq!(simulation.active_count(), 0);
+    assert_eq!(simulation.step(&mut world, usize::MAX), 0);
+
+    // Solid edits away from any liquid wake nothing
+    place(
+        &mut world,
+        &mut simulation,
+        IVec3::new(14, 5, 14),
+        VoxelType::Rock,
+    );
+    assert!(simulation.is_idle());
+}
+
+#[test]
+fn updates_per_step_are_capped() {
+    let mut world = floored_world();
+    let mut simulation = FluidSimulation::default();
+    place(
+        &mut world,
+        &mut simulation,
+        IVec3::new(8, 1, 8),
+        VoxelType::Water,
+    );
+    simulation.step(&mut world, usize::MAX);
+    simulation.step(&mut world, usize::MAX);
+
+    let pending = simulation.active_count();
+    assert!(pending > 2);
+    assert_eq!(simulation.step(&mut world, 2), 2);
+}
+
+#[test]
+fn replacing_flowing_water_makes_a_source() {
+    let mut world = floored_world();
+    let pos = IVec3::new(5, 1, 5);
+    world.set_voxel(pos, VoxelType::Water);
+    assert!(world.set_fluid_level(pos, Some(3)));
+    assert_eq!(world.fluid_level(pos), 3);
+
+    world.set_voxel(pos, VoxelType::Air);
+    world.set_voxel(pos, VoxelType::Water);
+    assert!(world.is_fluid_source(pos));
+    // Only liquids have levels
+    assert!(!world.set_fluid_level(IVec3::new(5, 0, 5), Some(3)));
+}
+
+#[test]
+fn fluid_levels_survive_a_save() {
+    let tmp_dir = tempfile::tempdir().expect("create temp dir");
+    let store = ChunkStore {
+        directory: tmp_dir.path().join("world"),
+    };
+
+    let mut chunk = Chunk::new(IVec3::new(2, 0, -1));
+    chunk.set(UVec3::new(1, 2, 3), VoxelType::Water);
+    chunk.set_fluid_level(UVec3::new(1, 2, 3), Some(5));
+    chunk.set(UVec3::new(4, 2, 3), VoxelType::Water);
+    store.save_chunks([&chunk].into_iter()).expect("save");
+
+    let loaded = store
+        .load_chunk(IVec3::new(2, 0, -1))
+        .expect("load")
+        .expect("stored");
+    assert_eq!(loaded.fluid_level(UVec3::new(1, 2, 3)), Some(5));
+    assert_eq!(loaded.fluid_level(UVec3::new(4, 2, 3)), None);
+    assert!(loaded.to_data().validate().is_ok());
+}
+
+#[test]
+fn each_flow_level_has_its_own_surface_height() {
+    let heights: Vec<f32> = (1..=MAX_FLUID_LEVEL)
+        .map(|level| {
+            let mut world = floored_world();
+            for x in 0..16 {
+                for z in 0..16 {
+                    let pos = IVec3::new(x, 1, z);
+                    world.set_voxel(pos, VoxelType::Water);
+                    world.set_fluid_level(pos, Some(level));
+                }
+            }
+            let mesh = generate_chunk_mesh_surface_nets(
+                world.get_chunk(IVec3::ZERO).unwrap(),
+                &world,
+                LodLevel::FULL,
+                NeighborLods {
+                    neg_x: None,
+                    pos_x: None,
+                    neg_z: None,
+                    pos_z: None,
+                },
+                &SkirtConfig::default(),
+                &AmbientOcclusionConfig::default().baked,
+            );
+            mesh.water
+                .positions
+                .iter()
+                .map(|position| position[1])
+                .fold(f32::MIN, f32::max)
+        })
+        .collect();
+
+    for pair in heights.windows(2) {
+        assert!(pair[0] < pair[1], "surface heights {:?}", heights);
+    }
+}
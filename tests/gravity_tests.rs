@@ -0,0 +1,172 @@
+use bevy::math::IVec3;
+use std::collections::HashSet;
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::gravity::{self, FallingBlocks, MAX_SUPPORT_DISTANCE};
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::{VoxelChanged, VoxelWorld};
+
+/// A 3x3x3 block of empty chunks with bedrock across the bottom
+fn bedrock_world() -> VoxelWorld {
+    let mut world = VoxelWorld::new(IVec3::splat(3));
+    for x in 0..3 {
+        for y in 0..3 {
+            for z in 0..3 {
+                world.insert_chunk(Chunk::new(IVec3::new(x, y, z)));
+            }
+        }
+    }
+    for x in 0..48 {
+        for z in 0..48 {
+            world.set_voxel(IVec3::new(x, 0, z), VoxelType::Bedrock);
+        }
+    }
+    world
+}
+
+/// Set a voxel and report the edit the way `VoxelChanged` would
+fn edit(world: &mut VoxelWorld, pos: IVec3, voxel: VoxelType) -> VoxelChanged {
+    let previous = world.get_voxel(pos).expect("loaded");
+    world.set_voxel(pos, voxel);
+    VoxelChanged {
+        position: pos,
+        previous,
+        voxel,
+    }
+}
+
+fn column(world: &mut VoxelWorld, base: IVec3, height: i32, voxel: VoxelType) {
+    for y in 0..height {
+        world.set_voxel(base + IVec3::new(0, y, 0), voxel);
+    }
+}
+
+/// Let a group fall in small steps until it lands
+fn drop_until_landed(world: &mut VoxelWorld, mut group: FallingBlocks) {
+    for _ in 0..1000 {
+        if group.fall(world, 0.02) {
+            return;
+        }
+    }
+    panic!("falling blocks never landed");
+}
+
+#[test]
+fn cutting_a_pillar_drops_what_it_held_up() {
+    let mut world = bedrock_world();
+    // A pillar carrying a small platform
+    column(&mut world, IVec3::new(10, 1, 10), 6, VoxelType::Rock);
+    for x in 9..=11 {
+        world.set_voxel(IVec3::new(x, 7, 10), VoxelType::Rock);
+    }
+
+    let change = edit(&mut world, IVec3::new(10, 3, 10), VoxelType::Air);
+    let groups = gravity::collapse(&mut world, &[change]);
+
+    // The lower stump still stands on bedrock; the top falls as one group
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].voxels().len(), 3 + 3);
+    assert_eq!(
+        world.get_voxel(IVec3::new(10, 2, 10)),
+        Some(VoxelType::Rock)
+    );
+    assert_eq!(world.get_voxel(IVec3::new(10, 7, 10)), Some(VoxelType::Air));
+
+    drop_until_landed(&mut world, groups.into_iter().next().unwrap());
+    // Lands on the stump, keeping its shape
+    assert_eq!(
+        world.get_voxel(IVec3::new(10, 3, 10)),
+        Some(VoxelType::Rock)
+    );
+    assert_eq!(
+        world.get_voxel(IVec3::new(10, 5, 10)),
+        Some(VoxelType::Rock)
+    );
+    assert_eq!(world.get_voxel(IVec3::new(9, 6, 10)), Some(VoxelType::Rock));
+    assert_eq!(world.get_voxel(IVec3::new(10, 7, 10)), Some(VoxelType::Air));
+}
+
+#[test]
+fn connected_structures_stay_up() {
+    let mut world = bedrock_world();
+    // An arch: two pillars joined at the top
+    column(&mut world, IVec3::new(5, 1, 5), 6, VoxelType::Rock);
+    column(&mut world, IVec3::new(9, 1, 5), 6, VoxelType::Rock);
+    for x in 5..=9 {
+        world.set_voxel(IVec3::new(x, 7, 5), VoxelType::Rock);
+    }
+
+    let change = edit(&mut world, IVec3::new(5, 2, 5), VoxelType::Air);
+    let groups = gravity::collapse(&mut world, &[change]);
+    assert!(groups.is_empty());
+    assert_eq!(world.get_voxel(IVec3::new(5, 6, 5)), Some(VoxelType::Rock));
+}
+
+#[test]
+fn support_search_stops_at_the_distance_limit() {
+    let mut world = bedrock_world();
+    // A beam longer than the search distance, held up only at its far end
+    let length = MAX_SUPPORT_DISTANCE as i32 + 4;
+    column(&mut world, IVec3::new(1, 1, 1), 4, VoxelType::Rock);
+    for z in 1..=length {
+        world.set_voxel(IVec3::new(1, 5, z), VoxelType::Rock);
+    }
+
+    let mut supported = HashSet::new();
+    assert!(
+        gravity::unsupported_island(&world, IVec3::new(1, 5, length), &mut supported).is_none()
+    );
+
+    // A short floating beam is found in full
+    for x in 20..25 {
+        world.set_voxel(IVec3::new(x, 10, 20), VoxelType::Wood);
+    }
+    let island = gravity::unsupported_island(&world, IVec3::new(22, 10, 20), &mut supported)
+        .expect("floating beam");
+    assert_eq!(island.len(), 5);
+}
+
+#[test]
+fn sand_falls_when_dug_out_from_below() {
+    let mut world = bedrock_world();
+    column(&mut world, IVec3::new(3, 1, 3), 2, VoxelType::Rock);
+    column(&mut world, IVec3::new(3, 3, 3), 3, VoxelType::Sand);
+    // Held sideways by rock, which would keep an attached voxel up
+    world.set_voxel(IVec3::new(4, 3, 3), VoxelType::Rock);
+    column(&mut world, IVec3::new(4, 1, 3), 2, VoxelType::Rock);
+
+    let change = edit(&mut world, IVec3::new(3, 2, 3), VoxelType::Air);
+    let groups = gravity::collapse(&mut world, &[change]);
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].voxels(), &[(IVec3::ZERO, VoxelType::Sand)]);
+
+    drop_until_landed(&mut world, groups.into_iter().next().unwrap());
+    assert_eq!(world.get_voxel(IVec3::new(3, 2, 3)), Some(VoxelType::Sand));
+}
+
+#[test]
+fn placed_sand_falls_but_placed_rock_hangs() {
+    let mut world = bedrock_world();
+    column(&mut world, IVec3::new(6, 1, 6), 5, VoxelType::Rock);
+
+    let rock = edit(&mut world, IVec3::new(7, 5, 6), VoxelType::Rock);
+    assert!(gravity::collapse(&mut world, &[rock]).is_empty());
+
+    let sand = edit(&mut world, IVec3::new(8, 5, 6), VoxelType::Sand);
+    let groups = gravity::collapse(&mut world, &[sand]);
+    assert_eq!(groups.len(), 1);
+    drop_until_landed(&mut world, groups.into_iter().next().unwrap());
+    assert_eq!(world.get_voxel(IVec3::new(8, 1, 6)), Some(VoxelType::Sand));
+    assert_eq!(world.get_voxel(IVec3::new(8, 5, 6)), Some(VoxelType::Air));
+}
+
+#[test]
+fn falling_blocks_stop_above_unloaded_chunks() {
+    let mut world = VoxelWorld::new(IVec3::ONE);
+    world.insert_chunk(Chunk::new(IVec3::ZERO));
+    let change = edit(&mut world, IVec3::new(2, 8, 2), VoxelType::Sand);
+
+    let groups = gravity::collapse(&mut world, &[change]);
+    drop_until_landed(&mut world, groups.into_iter().next().unwrap());
+    // Nothing below y = 0 is loaded, and y = 0 is the world floor
+    assert_eq!(world.get_voxel(IVec3::new(2, 0, 2)), Some(VoxelType::Sand));
+}
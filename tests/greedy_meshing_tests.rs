@@ -0,0 +1,98 @@
+use bevy::math::{IVec3, UVec3, Vec3};
+use voxel_builder::rendering::ao_config::AmbientOcclusionConfig;
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::meshing::{generate_chunk_mesh, generate_chunk_mesh_greedy, MeshData};
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::VoxelWorld;
+
+/// A single chunk world filled from `voxel_at`
+fn single_chunk(voxel_at: impl Fn(UVec3) -> VoxelType) -> VoxelWorld {
+    let mut world = VoxelWorld::new(IVec3::ONE);
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    for x in 0..16 {
+        for y in 0..16 {
+            for z in 0..16 {
+                let local = UVec3::new(x, y, z);
+                chunk.set(local, voxel_at(local));
+            }
+        }
+    }
+    world.insert_chunk(chunk);
+    world
+}
+
+/// Total triangle area facing each of the six axis directions
+fn area_by_normal(mesh: &MeshData) -> [f32; 6] {
+    let mut areas = [0.0; 6];
+    for triangle in mesh.indices.chunks(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(mesh.positions[triangle[i] as usize]));
+        let normal = Vec3::from(mesh.normals[triangle[0] as usize]);
+        let slot = match (normal.x as i32, normal.y as i32, normal.z as i32) {
+            (1, 0, 0) => 0,
+            (-1, 0, 0) => 1,
+            (0, 1, 0) => 2,
+            (0, -1, 0) => 3,
+            (0, 0, 1) => 4,
+            _ => 5,
+        };
+        areas[slot] += (b - a).cross(c - a).length() * 0.5;
+    }
+    areas
+}
+
+fn meshes(world: &VoxelWorld) -> (MeshData, MeshData) {
+    let ao_config = AmbientOcclusionConfig::default().baked;
+    let chunk = world.get_chunk(IVec3::ZERO).expect("chunk");
+    (
+        generate_chunk_mesh(chunk, world, &ao_config).solid,
+        generate_chunk_mesh_greedy(chunk, world, &ao_config).solid,
+    )
+}
+
+#[test]
+fn flat_ground_collapses_into_a_single_quad() {
+    let world = single_chunk(|local| {
+        if local.y < 4 {
+            VoxelType::Rock
+        } else {
+            VoxelType::Air
+        }
+    });
+    let (per_face, greedy) = meshes(&world);
+
+    assert_eq!(per_face.positions.len(), 16 * 16 * 4);
+    assert_eq!(greedy.positions.len(), 4);
+    assert_eq!(greedy.indices.len(), 6);
+    assert_eq!(area_by_normal(&per_face), area_by_normal(&greedy));
+}
+
+#[test]
+fn different_materials_are_not_merged() {
+    let world = single_chunk(|local| match (local.y, local.x < 8) {
+        (0, true) => VoxelType::Rock,
+        (0, false) => VoxelType::Sand,
+        _ => VoxelType::Air,
+    });
+    let (_, greedy) = meshes(&world);
+
+    assert_eq!(greedy.positions.len(), 2 * 4);
+    let materials: Vec<f32> = greedy.colors.iter().map(|color| color[3]).collect();
+    assert_ne!(materials[0], materials[4]);
+}
+
+#[test]
+fn greedy_mesh_covers_the_same_surface_as_the_per_face_mesh() {
+    // Steps and a pillar give faces with varying AO along them
+    let world = single_chunk(|local| {
+        let ground = 3 + local.x / 4;
+        if local.y < ground || (local.x == 8 && local.z == 8 && local.y < 12) {
+            VoxelType::Rock
+        } else {
+            VoxelType::Air
+        }
+    });
+    let (per_face, greedy) = meshes(&world);
+
+    assert!(greedy.positions.len() < per_face.positions.len());
+    assert_eq!(area_by_normal(&per_face), area_by_normal(&greedy));
+}
@@ -0,0 +1,73 @@
+use voxel_builder::entity::inventory::{HOTBAR_SLOTS, INVENTORY_SLOTS};
+use voxel_builder::entity::{Inventory, ItemStack, ItemType};
+use voxel_builder::voxel::types::VoxelType;
+
+const ROCK: ItemType = ItemType::Block(VoxelType::Rock);
+const SAND: ItemType = ItemType::Block(VoxelType::Sand);
+
+#[test]
+fn items_stack_up_to_their_limit() {
+    let mut inventory = Inventory::default();
+
+    assert_eq!(inventory.add_items(ROCK, 100), 0);
+    assert!(inventory.add_item(SAND));
+    assert_eq!(inventory.get_count(ROCK), 100);
+
+    let slots = inventory.slots();
+    assert_eq!(slots[0], Some(ItemStack { item: ROCK, count: 64 }));
+    assert_eq!(slots[1], Some(ItemStack { item: ROCK, count: 36 }));
+    assert_eq!(slots[2], Some(ItemStack { item: SAND, count: 1 }));
+
+    // Existing stacks are topped up before new slots are used
+    inventory.add_items(ROCK, 10);
+    assert_eq!(inventory.slots()[1].map(|stack| stack.count), Some(46));
+    assert_eq!(inventory.slots()[3], None);
+}
+
+#[test]
+fn full_inventories_report_leftovers() {
+    let mut inventory = Inventory::default();
+    let capacity = INVENTORY_SLOTS as u32 * 64;
+
+    assert_eq!(inventory.add_items(ROCK, capacity + 5), 5);
+    assert!(!inventory.add_item(SAND));
+    assert_eq!(inventory.get_count(SAND), 0);
+}
+
+#[test]
+fn removing_is_all_or_nothing() {
+    let mut inventory = Inventory::default();
+    inventory.add_items(ROCK, 70);
+
+    assert!(!inventory.remove_item(ROCK, 71));
+    assert_eq!(inventory.get_count(ROCK), 70);
+
+    // The later stack goes first so the hotbar keeps its blocks
+    assert!(inventory.remove_item(ROCK, 10));
+    assert_eq!(inventory.slots()[0].map(|stack| stack.count), Some(60));
+    assert_eq!(inventory.slots()[1], None);
+}
+
+#[test]
+fn hotbar_selection_wraps_and_placing_consumes() {
+    let mut inventory = Inventory::default();
+    inventory.add_items(ROCK, 2);
+    inventory.add_items(SAND, 1);
+
+    inventory.scroll_selection(-1);
+    assert_eq!(inventory.selected_slot(), HOTBAR_SLOTS - 1);
+    inventory.scroll_selection(2);
+    assert_eq!(inventory.selected_slot(), 1);
+
+    // Out-of-range slots are ignored
+    inventory.select_slot(HOTBAR_SLOTS);
+    assert_eq!(inventory.selected_slot(), 1);
+
+    assert_eq!(inventory.take_selected(), Some(SAND));
+    assert_eq!(inventory.selected_stack(), None);
+    assert_eq!(inventory.take_selected(), None);
+
+    inventory.select_slot(0);
+    assert_eq!(inventory.take_selected(), Some(ROCK));
+    assert_eq!(inventory.selected_stack(), Some(ItemStack { item: ROCK, count: 1 }));
+}
@@ -0,0 +1,185 @@
+use bevy::math::{IVec3, UVec3};
+use voxel_builder::interaction::DirtyChunks;
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::light::{self, LightChannel, MAX_LIGHT};
+use voxel_builder::voxel::registry::VoxelRegistry;
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::VoxelWorld;
+
+fn filled_chunk(position: IVec3, voxel: VoxelType) -> Chunk {
+    let mut chunk = Chunk::new(position);
+    for x in 0..16 {
+        for y in 0..16 {
+            for z in 0..16 {
+                chunk.set(UVec3::new(x, y, z), voxel);
+            }
+        }
+    }
+    chunk
+}
+
+fn glowstone() -> VoxelType {
+    VoxelRegistry::global()
+        .by_name("glowstone")
+        .expect("glowstone registered")
+}
+
+/// Light every loaded chunk, highest first like the game does
+fn light_all(world: &mut VoxelWorld) {
+    let mut positions: Vec<IVec3> = world.loaded_chunk_positions().collect();
+    positions.sort_by_key(|pos| -pos.y);
+    let mut dirty = DirtyChunks::default();
+    for pos in positions {
+        light::light_chunk(world, pos, &mut dirty);
+    }
+}
+
+/// Set a voxel and update the light the way `VoxelChanged` would
+fn edit(world: &mut VoxelWorld, pos: IVec3, voxel: VoxelType) {
+    world.set_voxel(pos, voxel);
+    light::relight_voxel(world, pos, &mut DirtyChunks::default());
+}
+
+fn sky(world: &VoxelWorld, pos: IVec3) -> u8 {
+    world.light(pos, LightChannel::Sky).expect("loaded")
+}
+
+fn block(world: &VoxelWorld, pos: IVec3) -> u8 {
+    world.light(pos, LightChannel::Block).expect("loaded")
+}
+
+/// An air chunk under a chunk of solid rock, so no sky reaches it
+fn covered_world() -> VoxelWorld {
+    let mut world = VoxelWorld::new(IVec3::new(1, 2, 1));
+    world.insert_chunk(Chunk::new(IVec3::ZERO));
+    world.insert_chunk(filled_chunk(IVec3::Y, VoxelType::Rock));
+    world
+}
+
+#[test]
+fn sky_light_falls_down_a_shaft_without_fading() {
+    let mut world = VoxelWorld::new(IVec3::ONE);
+    world.insert_chunk(filled_chunk(IVec3::ZERO, VoxelType::Rock));
+    for y in 5..16 {
+        world.set_voxel(IVec3::new(8, y, 8), VoxelType::Air);
+    }
+    // A side passage at the bottom of the shaft
+    world.set_voxel(IVec3::new(9, 5, 8), VoxelType::Air);
+    world.set_voxel(IVec3::new(10, 5, 8), VoxelType::Air);
+    light_all(&mut world);
+
+    assert_eq!(sky(&world, IVec3::new(8, 15, 8)), MAX_LIGHT);
+    assert_eq!(sky(&world, IVec3::new(8, 5, 8)), MAX_LIGHT);
+    assert_eq!(sky(&world, IVec3::new(9, 5, 8)), MAX_LIGHT - 1);
+    assert_eq!(sky(&world, IVec3::new(10, 5, 8)), MAX_LIGHT - 2);
+    assert_eq!(sky(&world, IVec3::new(8, 4, 8)), 0);
+    assert!(world.get_chunk(IVec3::ZERO).unwrap().is_lit());
+}
+
+#[test]
+fn enclosed_caves_stay_dark() {
+    let mut world = VoxelWorld::new(IVec3::new(1, 2, 1));
+    world.insert_chunk(filled_chunk(IVec3::ZERO, VoxelType::Rock));
+    world.insert_chunk(Chunk::new(IVec3::Y));
+    for x in 4..8 {
+        world.set_voxel(IVec3::new(x, 8, 8), VoxelType::Air);
+    }
+    light_all(&mut world);
+
+    assert_eq!(sky(&world, IVec3::new(8, 20, 8)), MAX_LIGHT);
+    for x in 4..8 {
+        assert_eq!(sky(&world, IVec3::new(x, 8, 8)), 0);
+        assert_eq!(block(&world, IVec3::new(x, 8, 8)), 0);
+    }
+}
+
+#[test]
+fn block_light_fades_one_level_per_voxel() {
+    let mut world = covered_world();
+    let lamp = IVec3::new(8, 8, 8);
+    world.set_voxel(lamp, glowstone());
+    light_all(&mut world);
+
+    assert_eq!(block(&world, lamp), MAX_LIGHT);
+    assert_eq!(block(&world, IVec3::new(9, 8, 8)), MAX_LIGHT - 1);
+    assert_eq!(block(&world, IVec3::new(11, 8, 8)), MAX_LIGHT - 3);
+    assert_eq!(block(&world, IVec3::new(10, 9, 7)), MAX_LIGHT - 4);
+    assert_eq!(sky(&world, IVec3::new(9, 8, 8)), 0);
+
+    // Water dims light faster than air: shine through a wall with a
+    // water-filled hole
+    let mut world = covered_world();
+    world.set_voxel(lamp, glowstone());
+    for y in 7..=9 {
+        for z in 7..=9 {
+            world.set_voxel(IVec3::new(9, y, z), VoxelType::Rock);
+        }
+    }
+    world.set_voxel(IVec3::new(9, 8, 8), VoxelType::Water);
+    world.set_voxel(IVec3::new(10, 8, 8), VoxelType::Water);
+    light_all(&mut world);
+    assert_eq!(block(&world, IVec3::new(9, 8, 8)), MAX_LIGHT - 2);
+    assert_eq!(block(&world, IVec3::new(10, 8, 8)), MAX_LIGHT - 4);
+}
+
+#[test]
+fn removing_a_light_source_darkens_only_its_own_light() {
+    let mut world = covered_world();
+    let left = IVec3::new(4, 8, 8);
+    let right = IVec3::new(12, 8, 8);
+    world.set_voxel(left, glowstone());
+    world.set_voxel(right, glowstone());
+    light_all(&mut world);
+    assert_eq!(block(&world, IVec3::new(11, 8, 8)), MAX_LIGHT - 1);
+
+    edit(&mut world, right, VoxelType::Air);
+    assert_eq!(block(&world, right), MAX_LIGHT - 8);
+    assert_eq!(block(&world, IVec3::new(11, 8, 8)), MAX_LIGHT - 7);
+    assert_eq!(block(&world, IVec3::new(8, 8, 8)), MAX_LIGHT - 4);
+
+    edit(&mut world, left, VoxelType::Air);
+    assert_eq!(block(&world, IVec3::new(8, 8, 8)), 0);
+    assert_eq!(block(&world, left), 0);
+
+    // Placing one lights the area up again
+    edit(&mut world, left, glowstone());
+    assert_eq!(block(&world, IVec3::new(6, 8, 8)), MAX_LIGHT - 2);
+}
+
+#[test]
+fn digging_through_a_roof_lets_the_sky_in() {
+    let mut world = VoxelWorld::new(IVec3::ONE);
+    world.insert_chunk(Chunk::new(IVec3::ZERO));
+    for x in 0..16 {
+        for z in 0..16 {
+            world.set_voxel(IVec3::new(x, 10, z), VoxelType::Rock);
+        }
+    }
+    light_all(&mut world);
+    let floor = IVec3::new(8, 2, 8);
+    assert_eq!(sky(&world, IVec3::new(8, 11, 8)), MAX_LIGHT);
+    assert_eq!(sky(&world, floor), 0);
+
+    let hole = IVec3::new(8, 10, 8);
+    edit(&mut world, hole, VoxelType::Air);
+    assert_eq!(sky(&world, floor), MAX_LIGHT);
+    assert_eq!(sky(&world, IVec3::new(9, 2, 8)), MAX_LIGHT - 1);
+    assert_eq!(sky(&world, IVec3::new(12, 2, 8)), MAX_LIGHT - 4);
+
+    edit(&mut world, hole, VoxelType::Rock);
+    assert_eq!(sky(&world, floor), 0);
+    assert_eq!(sky(&world, IVec3::new(12, 2, 8)), 0);
+}
+
+#[test]
+fn chunk_loaded_above_shades_the_chunk_below() {
+    let mut world = VoxelWorld::new(IVec3::new(1, 2, 1));
+    world.insert_chunk(Chunk::new(IVec3::ZERO));
+    light_all(&mut world);
+    assert_eq!(sky(&world, IVec3::new(8, 0, 8)), MAX_LIGHT);
+
+    world.insert_chunk(filled_chunk(IVec3::Y, VoxelType::Rock));
+    light::light_chunk(&mut world, IVec3::Y, &mut DirtyChunks::default());
+    assert_eq!(sky(&world, IVec3::new(8, 15, 8)), 0);
+    assert_eq!(sky(&world, IVec3::new(8, 0, 8)), 0);
+}
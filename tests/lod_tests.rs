@@ -0,0 +1,203 @@
+use bevy::math::{IVec2, IVec3, UVec3, Vec3};
+use voxel_builder::voxel::chunk::{Chunk, LodLevel};
+use voxel_builder::voxel::lod::{downsample, LodSelection, LodTile, MAX_LOD_LEVEL};
+use voxel_builder::voxel::plugin::LodSettings;
+use voxel_builder::voxel::skirt::{ChunkFace, NeighborLods};
+use voxel_builder::voxel::streaming::ChunkStreamingSettings;
+use voxel_builder::voxel::types::{Voxel, VoxelType};
+use voxel_builder::voxel::world::VoxelWorld;
+use voxel_builder::voxel::worldgen::{
+    FlatGenerator, GenerationStats, GeneratorConfig, HeightStage, Noise, TerrainPipeline,
+    WorldGenerator,
+};
+
+fn selection_at(camera: Vec3) -> LodSelection {
+    LodSelection::select(
+        camera,
+        &LodSettings::default(),
+        &ChunkStreamingSettings::default(),
+    )
+}
+
+#[test]
+fn tiles_cover_power_of_two_chunk_blocks() {
+    let tile = LodTile::containing(IVec3::new(-1, 2, 5), 2);
+    assert_eq!(tile.position, IVec3::new(-1, 0, 1));
+    assert_eq!(tile.min_chunk(), IVec3::new(-4, 0, 4));
+    assert_eq!(tile.origin(), IVec3::new(-64, 0, 64));
+    assert_eq!(tile.chunk_positions().count(), 64);
+    assert!(tile.chunk_positions().all(|pos| tile.contains_chunk(pos)));
+    assert!(!tile.contains_chunk(IVec3::new(0, 0, 4)));
+}
+
+#[test]
+fn every_column_is_drawn_at_exactly_one_level() {
+    let selection = selection_at(Vec3::new(8.0, 40.0, 8.0));
+
+    for x in -40..40 {
+        for z in -40..40 {
+            let column = IVec2::new(x, z);
+            let chunk = IVec3::new(x, 0, z);
+            let mut levels = usize::from(selection.full_detail.contains(&column));
+            levels += (1..=MAX_LOD_LEVEL)
+                .filter(|level| selection.tiles.contains(&LodTile::containing(chunk, *level)))
+                .count();
+            assert_eq!(levels, 1, "column {:?}", column);
+        }
+    }
+}
+
+#[test]
+fn levels_get_coarser_with_distance() {
+    let selection = selection_at(Vec3::new(8.0, 40.0, 8.0));
+
+    assert_eq!(selection.level_of_chunk(IVec3::ZERO), LodLevel::FULL);
+    let mut previous = 0;
+    for x in (0..180).step_by(4) {
+        let LodLevel::Level(level) = selection.level_of_chunk(IVec3::new(x, 0, 0)) else {
+            panic!("chunk {} culled inside the cull distance", x);
+        };
+        assert!(level >= previous, "level drops at chunk {}", x);
+        previous = level;
+    }
+    assert_eq!(previous, MAX_LOD_LEVEL);
+    assert_eq!(selection.level_of_chunk(IVec3::new(400, 0, 0)), LodLevel::Culled);
+}
+
+#[test]
+fn tiles_span_the_streamed_layers() {
+    let selection = selection_at(Vec3::new(8.0, 40.0, 8.0));
+    let streaming = ChunkStreamingSettings::default();
+
+    for tile in &selection.tiles {
+        let min_y = tile.min_chunk().y;
+        assert!(min_y + tile.size_chunks() > streaming.min_chunk_y);
+        assert!(min_y < streaming.max_chunk_y);
+    }
+}
+
+#[test]
+fn skirts_reach_deeper_towards_coarser_neighbors() {
+    let neighbors = NeighborLods {
+        neg_x: Some(LodLevel::Level(4)),
+        pos_x: Some(LodLevel::Level(1)),
+        neg_z: Some(LodLevel::Culled),
+        pos_z: None,
+    };
+    let me = LodLevel::Level(1);
+
+    assert_eq!(neighbors.skirt_scale(ChunkFace::NegX, me), Some(8.0));
+    assert_eq!(neighbors.skirt_scale(ChunkFace::PosX, me), None);
+    assert_eq!(neighbors.skirt_scale(ChunkFace::NegZ, me), Some(1.0));
+    assert_eq!(neighbors.skirt_scale(ChunkFace::PosZ, me), Some(1.0));
+    assert!(!neighbors.needs_skirt(ChunkFace::PosX, LodLevel::Level(2)));
+}
+
+#[test]
+fn downsampling_keeps_the_majority_of_each_cube() {
+    let tile = LodTile::containing(IVec3::ZERO, 1);
+    // Surface at y = 20, with a pool of water from y = 18 to 21 for x >= 16
+    let chunk = downsample(tile, |pos| {
+        if pos.x >= 16 && pos.y >= 18 && pos.y <= 21 {
+            VoxelType::Water
+        } else if pos.y <= 20 {
+            VoxelType::Rock
+        } else {
+            VoxelType::Air
+        }
+    });
+
+    assert_eq!(chunk.position(), tile.position);
+    assert_eq!(chunk.get(UVec3::new(0, 9, 0)), VoxelType::Rock);
+    // Covers y = 20 and 21: half rock is enough
+    assert_eq!(chunk.get(UVec3::new(0, 10, 0)), VoxelType::Rock);
+    assert_eq!(chunk.get(UVec3::new(0, 11, 0)), VoxelType::Air);
+    assert_eq!(chunk.get(UVec3::new(8, 9, 0)), VoxelType::Water);
+    assert_eq!(chunk.get(UVec3::new(8, 10, 0)), VoxelType::Water);
+    assert_eq!(chunk.get(UVec3::new(8, 11, 0)), VoxelType::Air);
+}
+
+#[test]
+fn default_lod_generation_downsamples_generated_chunks() {
+    let generator = FlatGenerator {
+        seed: 1,
+        height: 20,
+    };
+    let tile = LodTile::containing(IVec3::ZERO, 1);
+    let chunk = generator.generate_lod_chunk(tile, &mut GenerationStats::default());
+
+    assert_eq!(chunk.get(UVec3::new(3, 10, 3)), VoxelType::TopSoil);
+    assert_eq!(chunk.get(UVec3::new(3, 11, 3)), VoxelType::Air);
+}
+
+#[test]
+fn terrain_lod_tiles_follow_the_sampled_surface() {
+    let seed = 77;
+    let config = GeneratorConfig::default();
+    let pipeline = TerrainPipeline::new(seed, &config);
+    let heights = HeightStage::new(config.height.clone());
+    let noise = Noise::new(seed);
+    let tile = LodTile::containing(IVec3::ZERO, 2);
+    let scale = tile.size_chunks();
+
+    let chunk = pipeline.generate_lod_chunk(tile, &mut GenerationStats::default());
+    assert_eq!(chunk.position(), tile.position);
+
+    for x in 0..16 {
+        for z in 0..16 {
+            let sample = tile.origin() + IVec3::new(x, 0, z) * scale + IVec3::splat(scale / 2);
+            let height = heights.height_at(&noise, sample.x, sample.z);
+            for y in 0..16 {
+                let center = y as i32 * scale + scale / 2;
+                let solid = chunk.get(UVec3::new(x as u32, y, z as u32)).is_solid();
+                assert_eq!(solid, center <= height, "column ({}, {}) voxel {}", x, z, y);
+            }
+        }
+    }
+}
+
+#[test]
+fn loaded_chunks_under_a_tile_are_not_meshed() {
+    let selection = selection_at(Vec3::new(8.0, 40.0, 8.0));
+    let streaming = ChunkStreamingSettings::default();
+
+    let mut tiled = 0;
+    for chunk in streaming.positions_around(IVec3::ZERO) {
+        let level = selection.level_of_chunk(chunk);
+        if level == LodLevel::Culled {
+            continue;
+        }
+        let under_tile = (1..=MAX_LOD_LEVEL)
+            .any(|lvl| selection.tiles.contains(&LodTile::containing(chunk, lvl)));
+        // Exactly one of the chunk mesh and a tile draws each loaded chunk
+        assert_eq!(level.draws_chunk_mesh(), !under_tile, "chunk {:?}", chunk);
+        tiled += usize::from(under_tile);
+    }
+    // The default load radius reaches past the full-detail area
+    assert!(tiled > 0);
+    assert!(!LodLevel::Culled.draws_chunk_mesh());
+}
+
+#[test]
+fn tiles_over_fully_loaded_chunks_are_refreshed() {
+    let selection = selection_at(Vec3::new(8.0, 40.0, 8.0));
+    let tile = *selection
+        .tiles
+        .iter()
+        .find(|tile| tile.level == 1)
+        .expect("a level-1 tile");
+    let chunk = tile.min_chunk();
+
+    // Until every chunk is loaded the tile comes from the world generator
+    let mut world = VoxelWorld::new(IVec3::ONE);
+    assert!(selection.downsampled_tiles_over(&world, chunk).is_empty());
+    for pos in tile.chunk_positions() {
+        world.insert_chunk(Chunk::new(pos));
+    }
+    assert!(selection.downsampled_tiles_over(&world, chunk).contains(&tile));
+
+    // Chunks drawn at full detail are under no tile
+    assert!(selection
+        .downsampled_tiles_over(&world, IVec3::ZERO)
+        .is_empty());
+}
@@ -0,0 +1,91 @@
+use bevy::math::IVec3;
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::mesh_jobs::MeshJobQueue;
+use voxel_builder::voxel::meshing::{ChunkMeshResult, MeshData};
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::VoxelWorld;
+
+fn empty_mesh() -> ChunkMeshResult {
+    ChunkMeshResult {
+        solid: MeshData::new(),
+        water: MeshData::new(),
+    }
+}
+
+#[test]
+fn finished_jobs_come_back_through_the_queue() {
+    let mut queue = MeshJobQueue::default();
+    let ticket = queue.start(IVec3::new(1, 0, 2));
+    assert!(queue.is_in_flight(IVec3::new(1, 0, 2)));
+
+    std::thread::spawn(move || ticket.finish(empty_mesh()))
+        .join()
+        .expect("mesh thread");
+
+    let results = queue.drain_finished(8);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].chunk_pos, IVec3::new(1, 0, 2));
+    assert_eq!(queue.in_flight_count(), 0);
+}
+
+#[test]
+fn restarting_a_job_drops_the_stale_result() {
+    let mut queue = MeshJobQueue::default();
+    let chunk_pos = IVec3::new(0, 1, 0);
+
+    let stale = queue.start(chunk_pos);
+    let current = queue.start(chunk_pos);
+    assert!(stale.is_cancelled());
+    assert!(!current.is_cancelled());
+    let current_id = current.job_id;
+
+    // A cancelled job that finishes anyway is ignored
+    stale.finish(empty_mesh());
+    current.finish(empty_mesh());
+
+    let results = queue.drain_finished(8);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].job_id, current_id);
+}
+
+#[test]
+fn cancelled_jobs_produce_no_results() {
+    let mut queue = MeshJobQueue::default();
+    let ticket = queue.start(IVec3::ZERO);
+    queue.cancel(IVec3::ZERO);
+
+    assert!(ticket.is_cancelled());
+    ticket.finish(empty_mesh());
+    assert!(queue.drain_finished(8).is_empty());
+    assert!(!queue.is_in_flight(IVec3::ZERO));
+}
+
+#[test]
+fn drain_respects_the_per_frame_budget() {
+    let mut queue = MeshJobQueue::default();
+    for x in 0..5 {
+        queue.start(IVec3::new(x, 0, 0)).finish(empty_mesh());
+    }
+
+    assert_eq!(queue.drain_finished(2).len(), 2);
+    assert_eq!(queue.drain_finished(8).len(), 3);
+}
+
+#[test]
+fn snapshot_copies_chunk_and_neighbours_only() {
+    let mut world = VoxelWorld::new(IVec3::new(8, 4, 8));
+    for pos in [IVec3::new(2, 1, 2), IVec3::new(3, 1, 2), IVec3::new(5, 1, 2)] {
+        world.insert_chunk(Chunk::new(pos));
+    }
+    world.set_voxel(IVec3::new(48, 16, 32), VoxelType::Rock);
+
+    let snapshot = world.snapshot_around(IVec3::new(2, 1, 2));
+    assert_eq!(snapshot.loaded_chunk_count(), 2);
+    assert_eq!(snapshot.get_voxel(IVec3::new(48, 16, 32)), Some(VoxelType::Rock));
+    assert!(snapshot.get_chunk(IVec3::new(5, 1, 2)).is_none());
+
+    // Later edits don't leak into the snapshot
+    world.set_voxel(IVec3::new(48, 16, 32), VoxelType::Air);
+    assert_eq!(snapshot.get_voxel(IVec3::new(48, 16, 32)), Some(VoxelType::Rock));
+    assert!(!snapshot.get_chunk(IVec3::new(2, 1, 2)).unwrap().is_dirty());
+}
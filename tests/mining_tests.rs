@@ -0,0 +1,115 @@
+use bevy::math::IVec3;
+use voxel_builder::entity::{Inventory, ItemType};
+use voxel_builder::interaction::hotbar::held_tool_for;
+use voxel_builder::interaction::GameMode;
+use voxel_builder::interaction::mining::{
+    HeldTool, MiningState, MiningStep, CRACK_STAGES, MINING_TICK_SECONDS, SECONDS_PER_HARDNESS,
+};
+use voxel_builder::voxel::registry::VoxelRegistry;
+use voxel_builder::voxel::types::{ToolTier, ToolType, VoxelType};
+
+fn tool(kind: ToolType, tier: ToolTier) -> HeldTool {
+    HeldTool { kind, tier }
+}
+
+#[test]
+fn break_time_follows_hardness_and_tool() {
+    let registry = VoxelRegistry::builtin();
+    let rock = registry.info(VoxelType::Rock);
+    let bare = 4.0 * SECONDS_PER_HARDNESS;
+
+    assert_eq!(tool(ToolType::None, ToolTier::Iron).break_seconds(rock), Some(bare));
+    assert_eq!(tool(ToolType::Shovel, ToolTier::Iron).break_seconds(rock), Some(bare));
+    assert_eq!(tool(ToolType::Pickaxe, ToolTier::Wood).break_seconds(rock), Some(bare / 2.0));
+    assert_eq!(tool(ToolType::Pickaxe, ToolTier::Iron).break_seconds(rock), Some(bare / 6.0));
+
+    // Sand is softer than rock with the same tool
+    let sand = registry.info(VoxelType::Sand);
+    let pickaxe = HeldTool::default();
+    assert!(pickaxe.break_seconds(sand) < pickaxe.break_seconds(rock));
+}
+
+#[test]
+fn negative_hardness_is_unbreakable() {
+    let registry = VoxelRegistry::builtin();
+    let bedrock = registry.info(VoxelType::Bedrock);
+
+    assert!(!bedrock.is_breakable());
+    assert_eq!(tool(ToolType::Pickaxe, ToolTier::Iron).break_seconds(bedrock), None);
+}
+
+#[test]
+fn holding_on_a_block_ticks_until_it_breaks() {
+    let mut mining = MiningState::default();
+    let pos = IVec3::new(3, 10, -2);
+    let dt = MINING_TICK_SECONDS / 5.0;
+
+    // The first hit swings straight away
+    assert_eq!(mining.mine(pos, VoxelType::Rock, 1.0, dt), MiningStep::Tick);
+    assert_eq!(mining.crack_stage(), Some(0));
+
+    let mut ticks = 1;
+    let mut frames = 1;
+    loop {
+        match mining.mine(pos, VoxelType::Rock, 1.0, dt) {
+            MiningStep::Mining => {}
+            MiningStep::Tick => ticks += 1,
+            MiningStep::Broken => break,
+        }
+        frames += 1;
+        assert!(frames < 100, "block never broke");
+    }
+
+    assert_eq!(ticks, 4);
+    assert_eq!(mining.target(), None);
+}
+
+#[test]
+fn crack_stages_follow_progress() {
+    let mut mining = MiningState::default();
+    let pos = IVec3::ZERO;
+
+    mining.mine(pos, VoxelType::Rock, 2.0, 0.0);
+    mining.mine(pos, VoxelType::Rock, 2.0, 1.0);
+    assert!((mining.progress() - 0.5).abs() < 1e-6);
+    assert_eq!(mining.crack_stage(), Some(CRACK_STAGES / 2));
+
+    mining.mine(pos, VoxelType::Rock, 2.0, 0.99);
+    assert_eq!(mining.crack_stage(), Some(CRACK_STAGES - 1));
+}
+
+#[test]
+fn switching_targets_restarts_progress() {
+    let mut mining = MiningState::default();
+
+    mining.mine(IVec3::ZERO, VoxelType::Rock, 2.0, 0.0);
+    mining.mine(IVec3::ZERO, VoxelType::Rock, 2.0, 1.5);
+    assert!(mining.progress() > 0.5);
+
+    mining.mine(IVec3::X, VoxelType::Rock, 2.0, 0.5);
+    assert_eq!(mining.progress(), 0.0);
+    assert_eq!(mining.target(), Some(IVec3::X));
+
+    mining.stop();
+    assert_eq!(mining.crack_stage(), None);
+}
+
+#[test]
+fn creative_mines_with_the_default_tool() {
+    let mut inventory = Inventory::default();
+    assert_eq!(
+        held_tool_for(GameMode::Survival, &inventory),
+        tool(ToolType::None, ToolTier::default())
+    );
+    assert_eq!(held_tool_for(GameMode::Creative, &inventory), HeldTool::default());
+
+    inventory.add_item(ItemType::Tool {
+        kind: ToolType::Shovel,
+        tier: ToolTier::Stone,
+    });
+    assert_eq!(
+        held_tool_for(GameMode::Survival, &inventory),
+        tool(ToolType::Shovel, ToolTier::Stone)
+    );
+    assert_eq!(held_tool_for(GameMode::Creative, &inventory), HeldTool::default());
+}
@@ -0,0 +1,196 @@
+use bevy::math::IVec3;
+use voxel_builder::navigation::{NavAgent, NavLimits, NavMesh};
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::VoxelWorld;
+
+/// Two chunks side by side along x with a rock floor at y = 0 across
+/// `0..32` x `0..8`; creatures stand at y = 1
+fn flat_world() -> VoxelWorld {
+    let mut world = VoxelWorld::new(IVec3::new(2, 1, 1));
+    world.insert_chunk(Chunk::new(IVec3::ZERO));
+    world.insert_chunk(Chunk::new(IVec3::X));
+    for x in 0..32 {
+        for z in 0..8 {
+            world.set_voxel(IVec3::new(x, 0, z), VoxelType::Rock);
+        }
+    }
+    world
+}
+
+/// Raise everything from `x = 5` onward into a ledge `height` blocks tall
+fn with_ledge(height: i32) -> VoxelWorld {
+    let mut world = flat_world();
+    for x in 5..10 {
+        for z in 0..8 {
+            for y in 1..=height {
+                world.set_voxel(IVec3::new(x, y, z), VoxelType::Rock);
+            }
+        }
+    }
+    world
+}
+
+fn assert_connected(path: &[IVec3]) {
+    for step in path.windows(2) {
+        let delta = step[1] - step[0];
+        assert_eq!(
+            delta.x.abs() + delta.z.abs(),
+            1,
+            "{:?} -> {:?}",
+            step[0],
+            step[1]
+        );
+    }
+}
+
+#[test]
+fn paths_cross_chunk_boundaries() {
+    let world = flat_world();
+    let nav = NavMesh::build(&world);
+    assert_eq!(nav.chunk_count(), 2);
+
+    assert!(nav.is_walkable(IVec3::new(3, 1, 3)));
+    // The floor itself, and the air above where there is no floor, are not
+    assert!(!nav.is_walkable(IVec3::new(3, 0, 3)));
+    assert!(!nav.is_walkable(IVec3::new(3, 1, 12)));
+
+    let start = IVec3::new(2, 1, 2);
+    let goal = IVec3::new(28, 1, 5);
+    let path = nav
+        .find_path(start, goal, NavLimits::default())
+        .expect("flat floor is walkable");
+    assert_eq!(path.first(), Some(&start));
+    assert_eq!(path.last(), Some(&goal));
+    assert_eq!(path.len(), 26 + 3 + 1);
+    assert_connected(&path);
+}
+
+#[test]
+fn walls_are_walked_around() {
+    let mut world = flat_world();
+    for z in 0..6 {
+        for y in 1..=2 {
+            world.set_voxel(IVec3::new(8, y, z), VoxelType::Rock);
+        }
+    }
+    let nav = NavMesh::build(&world);
+
+    let path = nav
+        .find_path(
+            IVec3::new(4, 1, 1),
+            IVec3::new(12, 1, 1),
+            NavLimits::default(),
+        )
+        .expect("a gap is left at z = 6");
+    assert_connected(&path);
+    assert!(path.iter().all(|cell| cell.y == 1));
+    assert!(path.iter().any(|cell| cell.x == 8 && cell.z >= 6));
+}
+
+#[test]
+fn step_up_is_limited() {
+    let start = IVec3::new(2, 1, 2);
+    let limits = NavLimits::default();
+
+    let nav = NavMesh::build(&with_ledge(1));
+    let path = nav
+        .find_path(start, IVec3::new(7, 2, 2), limits)
+        .expect("one block is a step");
+    assert_connected(&path);
+
+    let nav = NavMesh::build(&with_ledge(2));
+    let goal = IVec3::new(7, 3, 2);
+    assert!(nav.is_walkable(goal));
+    assert!(nav.find_path(start, goal, limits).is_none());
+    let climber = NavLimits {
+        max_step_up: 2,
+        ..limits
+    };
+    assert!(nav.find_path(start, goal, climber).is_some());
+}
+
+#[test]
+fn drops_are_limited() {
+    let goal = IVec3::new(2, 1, 2);
+    let limits = NavLimits::default();
+
+    let nav = NavMesh::build(&with_ledge(3));
+    let path = nav
+        .find_path(IVec3::new(7, 4, 2), goal, limits)
+        .expect("three blocks is a safe drop");
+    assert_connected(&path);
+    // Can't climb back up
+    assert!(nav.find_path(goal, IVec3::new(7, 4, 2), limits).is_none());
+
+    let nav = NavMesh::build(&with_ledge(4));
+    assert!(nav.find_path(IVec3::new(7, 5, 2), goal, limits).is_none());
+}
+
+#[test]
+fn edits_rebuild_only_affected_chunks() {
+    let mut world = flat_world();
+    let mut nav = NavMesh::build(&world);
+    let start = IVec3::new(4, 1, 1);
+    let goal = IVec3::new(24, 1, 1);
+    assert!(nav.find_path(start, goal, NavLimits::default()).is_some());
+
+    // Nothing changed, nothing to rebuild
+    assert_eq!(nav.update(&world, usize::MAX), 0);
+
+    // Wall off the second chunk completely
+    for z in 0..8 {
+        for y in 1..=2 {
+            world.set_voxel(IVec3::new(20, y, z), VoxelType::Rock);
+        }
+    }
+    assert_eq!(nav.update(&world, usize::MAX), 1);
+    assert!(nav.find_path(start, goal, NavLimits::default()).is_none());
+
+    // Knocking the top off one wall block leaves a step over it
+    world.set_voxel(IVec3::new(20, 2, 0), VoxelType::Air);
+    assert_eq!(nav.update(&world, usize::MAX), 1);
+    assert!(nav.find_path(start, goal, NavLimits::default()).is_some());
+
+    // Unloaded chunks drop out of the mesh
+    world.remove_chunk(IVec3::X);
+    nav.update(&world, usize::MAX);
+    assert_eq!(nav.chunk_count(), 1);
+    assert!(!nav.is_walkable(goal));
+}
+
+#[test]
+fn rebuilds_respect_the_budget() {
+    let world = flat_world();
+    let mut nav = NavMesh::default();
+    assert_eq!(nav.update(&world, 1), 1);
+    assert_eq!(nav.chunk_count(), 1);
+    assert_eq!(nav.update(&world, 1), 1);
+    assert_eq!(nav.update(&world, 1), 0);
+}
+
+#[test]
+fn paths_are_dropped_when_a_crossed_chunk_is_rebuilt() {
+    let mut world = flat_world();
+    let mut nav = NavMesh::build(&world);
+    let path = nav
+        .find_path(
+            IVec3::new(2, 1, 2),
+            IVec3::new(10, 1, 5),
+            NavLimits::default(),
+        )
+        .expect("flat floor is walkable");
+    let mut agent = NavAgent::default();
+    agent.set_path(path, &nav);
+
+    // A rebuild of a chunk the path never enters keeps it
+    world.set_voxel(IVec3::new(24, 1, 1), VoxelType::Rock);
+    assert_eq!(nav.update(&world, usize::MAX), 1);
+    agent.clear_stale_path(&nav);
+    assert!(agent.has_path());
+
+    world.set_voxel(IVec3::new(6, 1, 7), VoxelType::Rock);
+    assert_eq!(nav.update(&world, usize::MAX), 1);
+    agent.clear_stale_path(&nav);
+    assert!(!agent.has_path());
+}
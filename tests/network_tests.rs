@@ -0,0 +1,460 @@
+use bevy::ecs::world::World;
+use bevy::math::{IVec3, Quat, Vec3};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+use voxel_builder::chat::commands::ChatCommand;
+use voxel_builder::network::client::NetClient;
+use voxel_builder::network::commands::{KickCommand, PlayersCommand};
+use voxel_builder::network::guest::{GuestEvent, GuestSession};
+use voxel_builder::network::host::{HostEvent, HostSession};
+use voxel_builder::network::protocol::{
+    self, ClientMessage, PlayerState, ServerMessage, Welcome, MAGIC, PROTOCOL_VERSION,
+};
+use voxel_builder::network::server::{
+    NetServer, ServerEvent, ServerSettings, WorldInfo, MAX_QUEUED_MESSAGES,
+};
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::registry::VoxelRegistry;
+use voxel_builder::voxel::streaming::ChunkStreamingSettings;
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::VoxelWorld;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Host {
+    session: HostSession,
+    world: VoxelWorld,
+    registry: VoxelRegistry,
+    streaming: ChunkStreamingSettings,
+}
+
+impl Host {
+    /// A 2x1x2 chunk world with a rock floor and a bedrock corner
+    fn start(password: &str) -> Self {
+        let mut world = VoxelWorld::new(IVec3::new(2, 1, 2));
+        for x in 0..2 {
+            for z in 0..2 {
+                world.insert_chunk(Chunk::new(IVec3::new(x, 0, z)));
+            }
+        }
+        for x in 0..32 {
+            for z in 0..32 {
+                world.set_voxel(IVec3::new(x, 0, z), VoxelType::Rock);
+            }
+        }
+        world.set_voxel(IVec3::new(0, 1, 0), VoxelType::Bedrock);
+
+        let settings = ServerSettings {
+            password: password.to_string(),
+            ..Default::default()
+        };
+        let mut session = HostSession::start(([127, 0, 0, 1], 0).into(), settings).unwrap();
+        session.set_world_info(WorldInfo {
+            seed: 42,
+            world_size_chunks: world.world_size_chunks(),
+            world_border: true,
+            spawn: Vec3::new(8.0, 4.0, 8.0),
+            host_name: None,
+        });
+
+        Self {
+            session,
+            world,
+            registry: VoxelRegistry::builtin(),
+            streaming: ChunkStreamingSettings {
+                load_radius: 2,
+                min_chunk_y: 0,
+                max_chunk_y: 1,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn addr(&self) -> SocketAddr {
+        self.session.local_addr()
+    }
+
+    fn update(&mut self) -> Vec<HostEvent> {
+        self.session
+            .update(&mut self.world, &self.registry, &self.streaming)
+    }
+
+    /// Connect on a worker thread while the host keeps accepting
+    fn join(&mut self, name: &str, password: &str) -> Result<Guest, String> {
+        let addr = self.addr();
+        let (name, password) = (name.to_string(), password.to_string());
+        let handle = thread::spawn(move || NetClient::connect(addr, &name, &password, TIMEOUT));
+        while !handle.is_finished() {
+            self.update();
+            thread::sleep(Duration::from_millis(5));
+        }
+        let (client, welcome) = handle.join().unwrap()?;
+        Ok(Guest::new(client, welcome))
+    }
+}
+
+struct Guest {
+    session: GuestSession,
+    world: VoxelWorld,
+    events: Vec<GuestEvent>,
+}
+
+impl Guest {
+    fn new(client: NetClient, welcome: Welcome) -> Self {
+        let world = VoxelWorld::new(welcome.world_size_chunks);
+        Self {
+            session: GuestSession::new(client, welcome),
+            world,
+            events: Vec::new(),
+        }
+    }
+
+    fn update(&mut self) {
+        let events = self.session.update(&mut self.world);
+        self.events.extend(events);
+    }
+}
+
+/// Update everyone until `done` holds
+fn pump(host: &mut Host, guests: &mut [&mut Guest], done: impl Fn(&Host, &[&mut Guest]) -> bool) {
+    let start = Instant::now();
+    while !done(host, guests) {
+        assert!(
+            start.elapsed() < TIMEOUT,
+            "timed out waiting for the session"
+        );
+        host.update();
+        for guest in guests.iter_mut() {
+            guest.update();
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn has_all_chunks(guest: &Guest) -> bool {
+    (0..2).all(|x| (0..2).all(|z| guest.world.chunk_exists(IVec3::new(x, 0, z))))
+}
+
+#[test]
+fn wrong_password_is_rejected() {
+    let mut host = Host::start("secret");
+    let err = host.join("mallory", "guess").err().unwrap();
+    assert!(err.contains("Incorrect password"), "{}", err);
+    assert_eq!(host.session.player_count(), 0);
+}
+
+#[test]
+fn mismatched_protocol_version_is_refused() {
+    let host = Host::start("");
+    let mut stream = TcpStream::connect(host.addr()).unwrap();
+    stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+
+    let mut prelude = MAGIC.to_vec();
+    prelude.extend_from_slice(&(PROTOCOL_VERSION + 1).to_le_bytes());
+    stream.write_all(&prelude).unwrap();
+
+    // The server still says which version it speaks, then hangs up
+    assert_eq!(
+        protocol::read_prelude(&mut stream).unwrap(),
+        PROTOCOL_VERSION
+    );
+    let mut rest = Vec::new();
+    assert!(stream
+        .read_to_end(&mut rest)
+        .map_or(true, |_| rest.is_empty()));
+}
+
+#[test]
+fn joining_guest_receives_world_info_and_chunks() {
+    let mut host = Host::start("secret");
+    let mut guest = host.join("alice", "secret").unwrap();
+
+    let welcome = guest.session.welcome();
+    assert_eq!(welcome.seed, 42);
+    assert_eq!(welcome.world_size_chunks, IVec3::new(2, 1, 2));
+    assert_eq!(host.session.player_count(), 1);
+
+    pump(&mut host, &mut [&mut guest], |_, guests| {
+        has_all_chunks(&guests[0])
+    });
+    assert_eq!(
+        guest.world.get_voxel(IVec3::new(5, 0, 20)),
+        Some(VoxelType::Rock)
+    );
+    assert_eq!(
+        guest.world.get_voxel(IVec3::new(0, 1, 0)),
+        Some(VoxelType::Bedrock)
+    );
+}
+
+#[test]
+fn client_that_stops_reading_is_dropped() {
+    let mut server =
+        NetServer::bind(([127, 0, 0, 1], 0).into(), ServerSettings::default()).unwrap();
+    let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+    protocol::write_prelude(&mut stream).unwrap();
+    protocol::read_prelude(&mut stream).unwrap();
+    protocol::write_frame(
+        &mut stream,
+        &ClientMessage::Hello {
+            name: "stalled".to_string(),
+            password: String::new(),
+        },
+    )
+    .unwrap();
+
+    // Never read again, so the socket buffers and then the queue fill up
+    let start = Instant::now();
+    let mut id = None;
+    let mut sent = 0;
+    let reason = 'dropped: loop {
+        assert!(
+            start.elapsed() < TIMEOUT,
+            "stalled client was never dropped"
+        );
+        for event in server.poll() {
+            match event {
+                ServerEvent::Joined { id: joined, .. } => id = Some(joined),
+                ServerEvent::Left { reason, .. } => break 'dropped reason,
+                _ => {}
+            }
+        }
+        if let Some(id) = id {
+            for _ in 0..64 {
+                server.send(
+                    id,
+                    ServerMessage::Chat {
+                        user: "host".to_string(),
+                        content: "x".repeat(16 * 1024),
+                    },
+                );
+                sent += 1;
+            }
+        }
+        thread::sleep(Duration::from_millis(5));
+    };
+    assert_eq!(reason, "fell too far behind");
+    assert!(sent > MAX_QUEUED_MESSAGES);
+    assert_eq!(server.player_count(), 0);
+}
+
+#[test]
+fn guest_edits_reach_the_host_and_other_guests() {
+    let mut host = Host::start("");
+    let mut alice = host.join("alice", "").unwrap();
+    let mut bob = host.join("bob", "").unwrap();
+    pump(&mut host, &mut [&mut alice, &mut bob], |_, guests| {
+        guests.iter().all(|guest| has_all_chunks(guest))
+    });
+
+    let target = IVec3::new(20, 0, 3);
+    alice.world.set_voxel(target, VoxelType::Sand);
+    pump(&mut host, &mut [&mut alice, &mut bob], |host, guests| {
+        host.world.get_voxel(target) == Some(VoxelType::Sand)
+            && guests[1].world.get_voxel(target) == Some(VoxelType::Sand)
+    });
+    assert_eq!(alice.world.get_voxel(target), Some(VoxelType::Sand));
+
+    // Host edits go out the same way
+    let placed = IVec3::new(3, 1, 3);
+    host.world.set_voxel(placed, VoxelType::Wood);
+    pump(&mut host, &mut [&mut alice, &mut bob], |_, guests| {
+        guests
+            .iter()
+            .all(|guest| guest.world.get_voxel(placed) == Some(VoxelType::Wood))
+    });
+}
+
+#[test]
+fn flowing_liquid_levels_reach_guests() {
+    let mut host = Host::start("");
+    let mut guest = host.join("alice", "").unwrap();
+    pump(&mut host, &mut [&mut guest], |_, guests| {
+        has_all_chunks(&guests[0])
+    });
+
+    let flow = IVec3::new(5, 1, 5);
+    host.world.set_voxel(flow, VoxelType::Water);
+    host.world.set_fluid_level(flow, Some(3));
+    pump(&mut host, &mut [&mut guest], |_, guests| {
+        guests[0].world.flow_level(flow) == Some(3)
+    });
+
+    // A level change alone is sent too
+    host.world.set_fluid_level(flow, Some(6));
+    pump(&mut host, &mut [&mut guest], |_, guests| {
+        guests[0].world.flow_level(flow) == Some(6)
+    });
+}
+
+#[test]
+fn unbreakable_voxel_edit_is_undone() {
+    let mut host = Host::start("");
+    let mut guest = host.join("alice", "").unwrap();
+    pump(&mut host, &mut [&mut guest], |_, guests| {
+        has_all_chunks(&guests[0])
+    });
+
+    let bedrock = IVec3::new(0, 1, 0);
+    guest.world.set_voxel(bedrock, VoxelType::Air);
+    assert_eq!(guest.world.get_voxel(bedrock), Some(VoxelType::Air));
+
+    pump(&mut host, &mut [&mut guest], |_, guests| {
+        guests[0].world.get_voxel(bedrock) == Some(VoxelType::Bedrock)
+    });
+    assert_eq!(host.world.get_voxel(bedrock), Some(VoxelType::Bedrock));
+}
+
+#[test]
+fn player_states_are_relayed() {
+    let mut host = Host::start("");
+    let mut alice = host.join("alice", "").unwrap();
+    let mut bob = host.join("bob", "").unwrap();
+    pump(&mut host, &mut [&mut alice, &mut bob], |_, guests| {
+        guests[0].session.players().count() == 1
+    });
+
+    let state = PlayerState {
+        position: Vec3::new(10.0, 3.0, -4.0),
+        rotation: Quat::from_rotation_y(1.0),
+    };
+    let start = Instant::now();
+    let bob_at = |bob: &Guest| {
+        bob.session
+            .players()
+            .find(|(_, player)| player.name == "alice")
+            .and_then(|(_, player)| player.state)
+    };
+    // Datagrams may be dropped, so keep sending like the game does
+    while bob_at(&bob) != Some(state) {
+        assert!(start.elapsed() < TIMEOUT, "state never arrived");
+        alice.session.send_state(state);
+        host.update();
+        host.session.send_states(None);
+        alice.update();
+        bob.update();
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(host
+        .session
+        .players()
+        .any(|(_, player)| player.state == Some(state)));
+}
+
+#[test]
+fn leaving_guest_is_reported() {
+    let mut host = Host::start("");
+    let mut alice = host.join("alice", "").unwrap();
+    let mut bob = host.join("bob", "").unwrap();
+    pump(&mut host, &mut [&mut alice, &mut bob], |_, guests| {
+        guests[0].session.players().count() == 1
+    });
+
+    bob.session.disconnect();
+    pump(&mut host, &mut [&mut alice], |host, guests| {
+        host.session.player_count() == 1 && guests[0].session.players().count() == 0
+    });
+    assert!(alice
+        .events
+        .iter()
+        .any(|event| matches!(event, GuestEvent::Left { name, .. } if name == "bob")));
+}
+
+#[test]
+fn chat_is_relayed_between_players() {
+    let mut host = Host::start("");
+    let mut alice = host.join("alice", "").unwrap();
+    let mut bob = host.join("bob", "").unwrap();
+
+    alice.session.send_chat("  hello\u{7} bob ");
+    let mut host_heard = Vec::new();
+    let start = Instant::now();
+    while !bob
+        .events
+        .iter()
+        .any(|event| matches!(event, GuestEvent::Chat { .. }))
+    {
+        assert!(start.elapsed() < TIMEOUT, "chat never arrived");
+        host_heard.extend(host.update());
+        alice.update();
+        bob.update();
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert!(bob.events.contains(&GuestEvent::Chat {
+        user: "alice".to_string(),
+        content: "hello bob".to_string(),
+    }));
+    assert!(host_heard
+        .iter()
+        .any(|event| matches!(event, HostEvent::Chat { name, content, .. } if name == "alice" && content == "hello bob")));
+
+    host.session.send_chat("host", "welcome");
+    pump(&mut host, &mut [&mut alice, &mut bob], |_, guests| {
+        guests.iter().all(|guest| {
+            guest.events.contains(&GuestEvent::Chat {
+                user: "host".to_string(),
+                content: "welcome".to_string(),
+            })
+        })
+    });
+    // The sender already shows its own message
+    assert!(!alice
+        .events
+        .iter()
+        .any(|event| matches!(event, GuestEvent::Chat { user, .. } if user == "alice")));
+}
+
+#[test]
+fn kick_command_disconnects_the_named_player() {
+    let mut host = Host::start("");
+    let mut alice = host.join("alice", "").unwrap();
+    let mut bob = host.join("bob", "").unwrap();
+    pump(&mut host, &mut [&mut alice, &mut bob], |_, guests| {
+        guests[0].session.players().count() == 1
+    });
+
+    let mut commands_world = World::new();
+    commands_world.insert_resource(host.session);
+    assert_eq!(
+        PlayersCommand.run(&[], &mut commands_world),
+        Ok("2 connected: alice, bob".to_string())
+    );
+    assert!(KickCommand.run(&["carol"], &mut commands_world).is_err());
+    KickCommand
+        .run(&["Bob", "too", "loud"], &mut commands_world)
+        .unwrap();
+    host.session = commands_world.remove_resource::<HostSession>().unwrap();
+    assert_eq!(host.session.player_count(), 1);
+
+    pump(&mut host, &mut [&mut alice, &mut bob], |_, guests| {
+        guests[0].session.players().count() == 0
+            && guests[1]
+                .events
+                .iter()
+                .any(|event| matches!(event, GuestEvent::Disconnected(_)))
+    });
+    assert!(bob
+        .events
+        .contains(&GuestEvent::Disconnected("Kicked: too loud".to_string())));
+}
+
+#[test]
+fn change_journal_records_only_real_changes() {
+    let mut world = VoxelWorld::new(IVec3::new(1, 1, 1));
+    world.insert_chunk(Chunk::new(IVec3::ZERO));
+
+    world.set_voxel(IVec3::new(1, 1, 1), VoxelType::Rock);
+    assert!(world.take_changes().is_empty(), "not recording yet");
+
+    world.record_changes(true);
+    world.set_voxel(IVec3::new(1, 1, 1), VoxelType::Rock);
+    world.set_voxel(IVec3::new(2, 1, 1), VoxelType::Sand);
+    world.set_voxel(IVec3::new(100, 1, 1), VoxelType::Sand);
+    assert_eq!(
+        world.take_changes(),
+        vec![(IVec3::new(2, 1, 1), VoxelType::Sand)]
+    );
+    assert!(world.take_changes().is_empty());
+}
@@ -0,0 +1,115 @@
+use bevy::math::{IVec3, Vec3};
+use voxel_builder::player::{
+    fall_damage, ground_spawn_position, PlayerVitals, DROWNING_DAMAGE, DROWNING_INTERVAL, MAX_BREATH_SECONDS,
+    SAFE_FALL_SPEED,
+};
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::persistence::{GameRules, WorldMetadata};
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::VoxelWorld;
+
+#[test]
+fn short_falls_are_free_and_long_ones_hurt() {
+    assert_eq!(fall_damage(0.0), 0.0);
+    assert_eq!(fall_damage(SAFE_FALL_SPEED), 0.0);
+    assert_eq!(fall_damage(SAFE_FALL_SPEED + 5.5), 5.0);
+    assert!(fall_damage(40.0) > fall_damage(25.0));
+}
+
+#[test]
+fn landing_reports_the_fastest_fall_speed() {
+    let mut vitals = PlayerVitals::default();
+
+    // Standing still never lands
+    assert_eq!(vitals.track_fall(false, 0.0), None);
+
+    assert_eq!(vitals.track_fall(true, 2.0), None);
+    assert_eq!(vitals.track_fall(true, -9.0), None);
+    assert_eq!(vitals.track_fall(true, -18.0), None);
+    // Touching down slows the body before Tnua reports the ground
+    assert_eq!(vitals.track_fall(false, -1.0), Some(18.0));
+    assert_eq!(vitals.track_fall(false, 0.0), None);
+
+    // Splashing into water forgets the fall
+    vitals.track_fall(true, -30.0);
+    vitals.reset_fall();
+    assert_eq!(vitals.track_fall(false, 0.0), None);
+}
+
+#[test]
+fn breath_runs_out_before_drowning_starts() {
+    let mut vitals = PlayerVitals::default();
+
+    assert_eq!(vitals.breathe(true, MAX_BREATH_SECONDS - 1.0), 0.0);
+    assert!((vitals.breath - 1.0).abs() < 1e-5);
+
+    // One second to run out, then one interval until the first hit
+    assert_eq!(vitals.breathe(true, 1.0), 0.0);
+    assert_eq!(vitals.breathe(true, DROWNING_INTERVAL), DROWNING_DAMAGE);
+    assert_eq!(vitals.breathe(true, DROWNING_INTERVAL * 2.0), DROWNING_DAMAGE * 2.0);
+
+    // Surfacing refills breath over time
+    assert_eq!(vitals.breathe(false, 0.5), 0.0);
+    assert!(vitals.breath > 0.0 && vitals.breath < MAX_BREATH_SECONDS);
+    vitals.breathe(false, MAX_BREATH_SECONDS);
+    assert_eq!(vitals.breath, MAX_BREATH_SECONDS);
+}
+
+#[test]
+fn game_rules_default_on_and_load_from_old_metadata() {
+    let mut rules = GameRules::default();
+    assert_eq!(rules.get("fall_damage"), Some(true));
+    assert!(rules.set("drowning", false));
+    assert!(!rules.drowning);
+    assert!(!rules.set("lava", false));
+    assert_eq!(rules.get("lava"), None);
+
+    let mut meta = WorldMetadata::new("Rules", 1, IVec3::splat(2));
+    meta.game_rules.fall_damage = false;
+    let json = serde_json::to_string(&meta).expect("serialize");
+    let parsed: WorldMetadata = serde_json::from_str(&json).expect("deserialize");
+    assert_eq!(parsed.game_rules, meta.game_rules);
+
+    // Worlds saved before game rules existed get every damage source
+    let old = json.replace(",\"game_rules\":{\"fall_damage\":false,\"drowning\":true}", "");
+    assert_ne!(old, json);
+    let parsed: WorldMetadata = serde_json::from_str(&old).expect("deserialize");
+    assert_eq!(parsed.game_rules, GameRules::default());
+
+    // Rules added later default on too
+    let partial = json.replace(",\"drowning\":true", "");
+    let parsed: WorldMetadata = serde_json::from_str(&partial).expect("deserialize");
+    assert!(!parsed.game_rules.fall_damage);
+    assert!(parsed.game_rules.drowning);
+}
+
+#[test]
+fn drop_after_spawn_or_teleport_is_free() {
+    let mut vitals = PlayerVitals::default();
+
+    // Spawned in the air: the first landing doesn't count
+    assert_eq!(vitals.track_fall(true, -35.0), None);
+    assert_eq!(vitals.track_fall(false, 0.0), None);
+
+    // After touching ground falls hurt again
+    vitals.track_fall(true, -25.0);
+    assert_eq!(vitals.track_fall(false, 0.0), Some(25.0));
+
+    vitals.teleported();
+    vitals.track_fall(true, -25.0);
+    assert_eq!(vitals.track_fall(false, 0.0), None);
+}
+
+#[test]
+fn respawn_stands_on_the_ground_below_the_spawn_point() {
+    let mut world = VoxelWorld::new(IVec3::new(2, 4, 2));
+    world.insert_chunk(Chunk::new(IVec3::ZERO));
+    world.set_voxel(IVec3::new(3, 10, 5), VoxelType::Rock);
+
+    let position = ground_spawn_position(&world, Vec3::new(3.5, 40.0, 5.5), 1.5)
+        .expect("ground below");
+    assert_eq!(position, Vec3::new(3.5, 12.5, 5.5));
+
+    // Nothing solid below: no ground to stand on
+    assert_eq!(ground_spawn_position(&world, Vec3::new(8.5, 40.0, 8.5), 1.5), None);
+}
@@ -0,0 +1,206 @@
+use bevy::math::{IVec3, UVec3};
+use std::fs;
+use std::io::{BufWriter, Write};
+use voxel_builder::constants::CHUNK_SIZE;
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::fluid::MAX_FLUID_LEVEL;
+use voxel_builder::voxel::persistence::{self, ChunkStore, WorldData};
+use voxel_builder::voxel::region::{region_pos, RegionFile, REGION_SIZE};
+use voxel_builder::voxel::registry::VoxelRegistry;
+use voxel_builder::voxel::types::VoxelType;
+
+fn temp_store() -> (tempfile::TempDir, ChunkStore) {
+    let tmp_dir = tempfile::tempdir().expect("create temp dir");
+    let store = ChunkStore {
+        directory: tmp_dir.path().join("world"),
+    };
+    (tmp_dir, store)
+}
+
+/// Chunk with pseudo-random builtin voxel ids and flow levels so it
+/// compresses past one sector; ids alone fit in one
+fn noisy_chunk(position: IVec3, seed: u32) -> Chunk {
+    let type_count = VoxelRegistry::builtin().len() as u32;
+    let mut chunk = Chunk::new(position);
+    let mut state = seed.wrapping_mul(2_654_435_761).max(1);
+    for x in 0..CHUNK_SIZE as u32 {
+        for y in 0..CHUNK_SIZE as u32 {
+            for z in 0..CHUNK_SIZE as u32 {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                let local = UVec3::new(x, y, z);
+                chunk.set(local, VoxelType::from_id((state % type_count) as u8));
+                let level = (state >> 8) % (MAX_FLUID_LEVEL as u32 + 1);
+                chunk.set_fluid_level(local, Some(level as u8));
+            }
+        }
+    }
+    chunk
+}
+
+fn assert_same_voxels(a: &Chunk, b: &Chunk) {
+    for x in 0..CHUNK_SIZE as u32 {
+        for y in 0..CHUNK_SIZE as u32 {
+            for z in 0..CHUNK_SIZE as u32 {
+                let local = UVec3::new(x, y, z);
+                assert_eq!(a.get(local), b.get(local), "voxel {:?}", local);
+                assert_eq!(a.fluid_level(local), b.fluid_level(local), "level {:?}", local);
+            }
+        }
+    }
+}
+
+#[test]
+fn region_pos_handles_negative_chunks() {
+    assert_eq!(region_pos(IVec3::new(0, 0, 0)), IVec3::ZERO);
+    assert_eq!(region_pos(IVec3::new(REGION_SIZE - 1, 3, 0)), IVec3::ZERO);
+    assert_eq!(region_pos(IVec3::new(REGION_SIZE, 0, -1)), IVec3::new(1, 0, -1));
+}
+
+#[test]
+fn chunks_round_trip_across_regions() {
+    let (_tmp, store) = temp_store();
+    let positions = [
+        IVec3::new(0, 0, 0),
+        IVec3::new(5, 2, 7),
+        IVec3::new(-1, 0, -1),
+        IVec3::new(REGION_SIZE + 3, 1, -REGION_SIZE * 2),
+    ];
+
+    let chunks: Vec<Chunk> = positions
+        .iter()
+        .enumerate()
+        .map(|(i, pos)| {
+            let mut chunk = Chunk::new(*pos);
+            chunk.set(UVec3::new(i as u32, 1, 2), VoxelType::Clay);
+            chunk
+        })
+        .collect();
+    assert_eq!(store.save_chunks(chunks.iter()).expect("save chunks"), 4);
+
+    for (i, pos) in positions.iter().enumerate() {
+        let loaded = store.load_chunk(*pos).expect("load").expect("stored");
+        assert_eq!(loaded.position(), *pos);
+        assert_eq!(loaded.get(UVec3::new(i as u32, 1, 2)), VoxelType::Clay);
+    }
+
+    assert!(store.load_chunk(IVec3::new(1, 0, 0)).expect("load").is_none());
+}
+
+#[test]
+fn rewriting_a_chunk_keeps_its_neighbours() {
+    let (tmp, _store) = temp_store();
+    let path = tmp.path().join("r.0.0.0.vxr");
+    let first = IVec3::new(0, 0, 0);
+    let second = IVec3::new(1, 0, 0);
+
+    let mut region = RegionFile::open_or_create(&path).expect("create region");
+    region.write_chunk(&Chunk::new(first).to_data()).expect("write first");
+    region.write_chunk(&noisy_chunk(second, 7).to_data()).expect("write second");
+
+    // The noisy chunk no longer fits the first chunk's sectors and must move
+    region.write_chunk(&noisy_chunk(first, 3).to_data()).expect("rewrite first");
+    drop(region);
+
+    let mut region = RegionFile::open(&path).expect("reopen region");
+    assert_eq!(region.chunk_count(), 2);
+
+    let loaded_first = Chunk::from_data(region.read_chunk(first).expect("read").expect("stored"));
+    let loaded_second = Chunk::from_data(region.read_chunk(second).expect("read").expect("stored"));
+    assert_same_voxels(&loaded_first, &noisy_chunk(first, 3));
+    assert_same_voxels(&loaded_second, &noisy_chunk(second, 7));
+}
+
+#[test]
+fn unknown_region_version_is_rejected() {
+    let (tmp, _store) = temp_store();
+    let path = tmp.path().join("r.0.0.0.vxr");
+    drop(RegionFile::open_or_create(&path).expect("create region"));
+
+    let mut bytes = fs::read(&path).expect("read region");
+    bytes[4..8].copy_from_slice(&99u32.to_le_bytes());
+    fs::write(&path, bytes).expect("write region");
+
+    let err = RegionFile::open(&path).err().expect("version mismatch");
+    assert!(err.contains("version 99"), "{}", err);
+}
+
+#[test]
+fn legacy_save_is_migrated_to_regions() {
+    let (tmp, store) = temp_store();
+    let legacy_path = tmp.path().join("world_data.bin");
+
+    let mut chunk = Chunk::new(IVec3::new(2, 1, 3));
+    chunk.set(UVec3::new(4, 5, 6), VoxelType::DungeonWall);
+    let data = WorldData {
+        world_size_chunks: IVec3::new(8, 4, 8),
+        chunks: vec![chunk.to_flat_data(), Chunk::new(IVec3::new(7, 0, 7)).to_flat_data()],
+    };
+
+    let file = fs::File::create(&legacy_path).expect("create legacy save");
+    let mut writer = BufWriter::new(file);
+    bincode::serialize_into(&mut writer, &data).expect("write legacy save");
+    writer.flush().expect("flush legacy save");
+
+    let migrated = persistence::migrate_legacy_world(&legacy_path, &store).expect("migrate");
+    assert_eq!(migrated, 2);
+    assert!(!legacy_path.exists());
+    assert!(tmp.path().join("world_data.bin.bak").exists());
+
+    let meta = store.read_meta().expect("meta written");
+    assert_eq!(meta.world_size_chunks, IVec3::new(8, 4, 8));
+
+    let loaded = store
+        .load_chunk(IVec3::new(2, 1, 3))
+        .expect("load")
+        .expect("migrated chunk");
+    assert_eq!(loaded.get(UVec3::new(4, 5, 6)), VoxelType::DungeonWall);
+}
+
+#[test]
+fn freed_sectors_are_reused() {
+    let (tmp, _store) = temp_store();
+    let path = tmp.path().join("r.0.0.0.vxr");
+    let first = IVec3::new(0, 0, 0);
+    let second = IVec3::new(1, 0, 0);
+    let third = IVec3::new(2, 0, 0);
+
+    let mut region = RegionFile::open_or_create(&path).expect("create region");
+    region.write_chunk(&Chunk::new(first).to_data()).expect("write first");
+    region.write_chunk(&noisy_chunk(second, 7).to_data()).expect("write second");
+    // Moves past the second chunk, freeing the sector it used
+    region.write_chunk(&noisy_chunk(first, 3).to_data()).expect("rewrite first");
+    let len = fs::metadata(&path).expect("region size").len();
+
+    region.write_chunk(&Chunk::new(third).to_data()).expect("write third");
+    assert_eq!(fs::metadata(&path).expect("region size").len(), len);
+    drop(region);
+
+    let mut region = RegionFile::open(&path).expect("reopen region");
+    assert_eq!(region.chunk_count(), 3);
+    let loaded_first = Chunk::from_data(region.read_chunk(first).expect("read").expect("stored"));
+    assert_same_voxels(&loaded_first, &noisy_chunk(first, 3));
+    assert!(region.read_chunk(third).expect("read").is_some());
+}
+
+#[test]
+fn stores_for_one_save_share_open_regions() {
+    let (_tmp, store) = temp_store();
+    let other = ChunkStore {
+        directory: store.directory.clone(),
+    };
+    let pos = IVec3::new(3, 0, 3);
+
+    store.save_chunk(&Chunk::new(pos)).expect("save");
+    assert!(store.load_chunk(pos).expect("load").is_some());
+
+    // A rewrite through another store moves the chunk; the first store
+    // must read it from its new place
+    other.save_chunk(&noisy_chunk(pos, 11)).expect("rewrite");
+    let loaded = store.load_chunk(pos).expect("load").expect("stored");
+    assert_same_voxels(&loaded, &noisy_chunk(pos, 11));
+
+    store.clear().expect("clear");
+    assert!(other.load_chunk(pos).expect("load after clear").is_none());
+}
@@ -0,0 +1,200 @@
+use bevy::math::{IVec3, UVec3, Vec3};
+use std::fs;
+use std::io::{BufWriter, Write};
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::persistence::{
+    self, ActiveWorld, SaveSlot, WorldData, WorldMetadata, DEFAULT_SLOT_ID, WORLD_FORMAT_VERSION,
+};
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::VoxelWorld;
+
+const WORLD_SIZE: IVec3 = IVec3::new(4, 2, 4);
+
+#[test]
+fn created_slots_are_listed_most_recent_first() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+
+    let (older, _) = persistence::create_save_slot(tmp.path(), "Old World", 1, WORLD_SIZE)
+        .expect("create slot");
+    let (newer, _) = persistence::create_save_slot(tmp.path(), "New World", 2, WORLD_SIZE)
+        .expect("create slot");
+
+    let mut meta = older.read_metadata().expect("read meta");
+    meta.last_played -= 100;
+    older.store().write_meta(&meta).expect("write meta");
+
+    let slots = persistence::list_save_slots(tmp.path());
+    let ids: Vec<&str> = slots.iter().map(|(slot, _)| slot.id.as_str()).collect();
+    assert_eq!(ids, vec![newer.id.as_str(), older.id.as_str()]);
+    assert_eq!(slots[0].1.seed, 2);
+    assert_eq!(slots[0].1.format_version, WORLD_FORMAT_VERSION);
+}
+
+#[test]
+fn slot_ids_are_unique_and_directory_safe() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+
+    let (first, _) = persistence::create_save_slot(tmp.path(), "My World!", 0, WORLD_SIZE)
+        .expect("create slot");
+    let (second, meta) = persistence::create_save_slot(tmp.path(), "My World!", 0, WORLD_SIZE)
+        .expect("create slot");
+
+    assert_eq!(first.id, "my_world");
+    assert_eq!(second.id, "my_world_2");
+    assert_eq!(meta.name, "My World!");
+}
+
+#[test]
+fn rename_duplicate_and_delete() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+    let (slot, _) = persistence::create_save_slot(tmp.path(), "Base", 42, WORLD_SIZE)
+        .expect("create slot");
+
+    let mut chunk = Chunk::new(IVec3::new(1, 0, 1));
+    chunk.set(UVec3::new(2, 3, 4), VoxelType::Clay);
+    slot.store().save_chunk(&chunk).expect("save chunk");
+
+    persistence::rename_save_slot(&slot, "Renamed").expect("rename");
+    assert_eq!(slot.read_metadata().expect("meta").name, "Renamed");
+    assert!(persistence::rename_save_slot(&slot, "   ").is_err());
+
+    let copy = persistence::duplicate_save_slot(&slot, "Copy").expect("duplicate");
+    assert_ne!(copy.id, slot.id);
+    let copy_meta = copy.read_metadata().expect("copy meta");
+    assert_eq!(copy_meta.name, "Copy");
+    assert_eq!(copy_meta.seed, 42);
+    let copied = copy
+        .store()
+        .load_chunk(IVec3::new(1, 0, 1))
+        .expect("load")
+        .expect("copied chunk");
+    assert_eq!(copied.get(UVec3::new(2, 3, 4)), VoxelType::Clay);
+
+    persistence::delete_saved_world(&slot).expect("delete");
+    assert!(!persistence::saved_world_exists(&slot));
+    assert!(persistence::saved_world_exists(&copy));
+    assert_eq!(persistence::list_save_slots(tmp.path()).len(), 1);
+}
+
+#[test]
+fn save_world_writes_only_modified_chunks() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+    let (slot, mut meta) = persistence::create_save_slot(tmp.path(), "Edits", 7, WORLD_SIZE)
+        .expect("create slot");
+    let store = slot.store();
+
+    let mut world = VoxelWorld::new(WORLD_SIZE);
+    let mut edited = Chunk::new(IVec3::new(0, 0, 0));
+    edited.set(UVec3::new(1, 1, 1), VoxelType::Wood);
+    world.insert_chunk(edited);
+
+    let mut untouched = Chunk::new(IVec3::new(1, 0, 0));
+    untouched.clear_modified();
+    world.insert_chunk(untouched);
+
+    persistence::save_world(&mut world, &store, &mut meta).expect("save");
+
+    assert!(store.load_chunk(IVec3::new(0, 0, 0)).expect("load").is_some());
+    assert!(store.load_chunk(IVec3::new(1, 0, 0)).expect("load").is_none());
+    assert!(!world.get_chunk(IVec3::ZERO).unwrap().is_modified());
+
+    let (loaded, loaded_meta) = persistence::load_world(&slot).expect("load world");
+    assert_eq!(loaded.world_size_chunks(), WORLD_SIZE);
+    assert_eq!(loaded_meta.seed, 7);
+}
+
+#[test]
+fn metadata_round_trips_as_json() {
+    let mut meta = WorldMetadata::new("Json", 123, WORLD_SIZE);
+    meta.play_time_secs = 3600;
+    meta.thumbnail = Some("thumbnail.png".to_string());
+
+    let json = serde_json::to_string(&meta).expect("serialize");
+    let parsed: WorldMetadata = serde_json::from_str(&json).expect("deserialize");
+    assert_eq!(parsed.name, "Json");
+    assert_eq!(parsed.play_time_secs, 3600);
+    assert_eq!(parsed.thumbnail.as_deref(), Some("thumbnail.png"));
+
+    // Metadata written before thumbnails existed still loads
+    let without_thumbnail = json.replace(",\"thumbnail\":\"thumbnail.png\"", "");
+    let parsed: WorldMetadata = serde_json::from_str(&without_thumbnail).expect("deserialize");
+    assert!(parsed.thumbnail.is_none());
+}
+
+#[test]
+fn spawn_point_is_saved_per_world() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+    let (first, mut meta) = persistence::create_save_slot(tmp.path(), "First", 1, WORLD_SIZE)
+        .expect("create slot");
+    let (second, _) = persistence::create_save_slot(tmp.path(), "Second", 2, WORLD_SIZE)
+        .expect("create slot");
+
+    meta.spawn_point = Some(Vec3::new(4.0, 20.0, -3.5));
+    first.store().write_meta(&meta).expect("write meta");
+
+    assert_eq!(
+        first.read_metadata().expect("meta").spawn_point,
+        Some(Vec3::new(4.0, 20.0, -3.5))
+    );
+    assert_eq!(second.read_metadata().expect("meta").spawn_point, None);
+
+    // Metadata written before spawn points were saved still loads
+    let json =
+        serde_json::to_string(&WorldMetadata::new("Old", 0, WORLD_SIZE)).expect("serialize");
+    let without_spawn = json.replace(",\"spawn_point\":null", "");
+    assert_ne!(json, without_spawn);
+    let parsed: WorldMetadata = serde_json::from_str(&without_spawn).expect("deserialize");
+    assert_eq!(parsed.spawn_point, None);
+}
+
+#[test]
+fn legacy_save_migrates_into_default_slot() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+    let saves = tmp.path().join("saves");
+    let legacy_path = tmp.path().join("world_data.bin");
+
+    let mut chunk = Chunk::new(IVec3::new(0, 1, 0));
+    chunk.set(UVec3::new(0, 0, 0), VoxelType::Rock);
+    let data = WorldData {
+        world_size_chunks: WORLD_SIZE,
+        chunks: vec![chunk.to_flat_data()],
+    };
+    let mut writer = BufWriter::new(fs::File::create(&legacy_path).expect("create legacy save"));
+    bincode::serialize_into(&mut writer, &data).expect("write legacy save");
+    writer.flush().expect("flush legacy save");
+    drop(writer);
+
+    let slot = persistence::migrate_legacy_save(&saves, &legacy_path)
+        .expect("migrate")
+        .expect("migrated slot");
+    assert_eq!(slot, SaveSlot::in_dir(&saves, DEFAULT_SLOT_ID));
+    assert!(persistence::saved_world_exists(&slot));
+
+    // Nothing left to migrate the second time
+    assert!(persistence::migrate_legacy_save(&saves, &legacy_path)
+        .expect("migrate")
+        .is_none());
+}
+
+#[test]
+fn unreadable_slot_is_left_alone() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+    let (slot, _) = persistence::create_save_slot(tmp.path(), "Corrupt", 3, WORLD_SIZE)
+        .expect("create slot");
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    chunk.set(UVec3::new(1, 1, 1), VoxelType::Rock);
+    slot.store().save_chunk(&chunk).expect("save chunk");
+
+    let meta_path = slot.directory().join("world.json");
+    fs::write(&meta_path, "{ not json").expect("corrupt meta");
+
+    let active = ActiveWorld::open_or_new(slot.clone(), WORLD_SIZE);
+    assert_ne!(active.slot, slot);
+    assert!(!persistence::saved_world_exists(&active.slot));
+    assert_eq!(fs::read_to_string(&meta_path).expect("meta"), "{ not json");
+    assert!(slot.store().load_chunk(IVec3::ZERO).expect("load").is_some());
+
+    // A slot that was never saved is used as is
+    let empty = SaveSlot::in_dir(tmp.path(), "empty");
+    assert_eq!(persistence::unused_slot_like(&empty), empty);
+}
@@ -0,0 +1,143 @@
+use bevy::math::IVec3;
+use std::fs;
+use voxel_builder::interaction::history::EditHistory;
+use voxel_builder::interaction::schematic::{
+    list_schematics, unused_schematic_path, Schematic, SchematicFormat, SchematicMapping,
+    SCHEMATIC_MAPPING_PATH,
+};
+use voxel_builder::interaction::world_edit::VoxelBuffer;
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::registry::VoxelRegistry;
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::VoxelWorld;
+
+/// An asymmetric 3x4x2 schematic so axis mix-ups change the result
+fn sample_schematic() -> Schematic {
+    let mut buffer = VoxelBuffer::new(IVec3::new(3, 4, 2));
+    buffer.set(IVec3::new(0, 0, 0), VoxelType::Rock);
+    buffer.set(IVec3::new(1, 0, 0), VoxelType::Rock);
+    buffer.set(IVec3::new(2, 1, 0), VoxelType::Sand);
+    buffer.set(IVec3::new(0, 3, 1), VoxelType::Wood);
+    buffer.set(IVec3::new(2, 2, 1), VoxelType::Leaves);
+    Schematic::new("sample", buffer)
+}
+
+fn round_trip(extension: &str) -> Schematic {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(format!("sample.{}", extension));
+    let registry = VoxelRegistry::builtin();
+    let mapping = SchematicMapping::default();
+
+    sample_schematic().save(&path, &mapping, &registry).unwrap();
+    Schematic::load(&path, &mapping, &registry).unwrap()
+}
+
+#[test]
+fn native_round_trip_keeps_name_and_voxels() {
+    let loaded = round_trip("vxs");
+    assert_eq!(loaded, sample_schematic());
+}
+
+#[test]
+fn vox_round_trip_keeps_orientation() {
+    let loaded = round_trip("vox");
+    assert_eq!(loaded.buffer, sample_schematic().buffer);
+}
+
+#[test]
+fn sponge_round_trip_keeps_orientation() {
+    let loaded = round_trip("schem");
+    assert_eq!(loaded.buffer, sample_schematic().buffer);
+}
+
+#[test]
+fn block_states_are_ignored_and_unknown_blocks_fall_back() {
+    let registry = VoxelRegistry::builtin();
+    let mapping = SchematicMapping::default();
+
+    assert_eq!(
+        mapping.voxel_for_block("minecraft:oak_log[axis=y]", &registry),
+        VoxelType::Wood
+    );
+    assert_eq!(mapping.voxel_for_block("sand", &registry), VoxelType::Sand);
+    assert_eq!(
+        mapping.voxel_for_block("minecraft:redstone_lamp[lit=true]", &registry),
+        VoxelType::Rock
+    );
+    assert_eq!(
+        mapping.block_for_voxel(VoxelType::Leaves, &registry),
+        "minecraft:oak_leaves[persistent=true]"
+    );
+}
+
+#[test]
+fn vox_colors_match_the_nearest_entry() {
+    let registry = VoxelRegistry::builtin();
+    let mapping = SchematicMapping::default();
+
+    assert_eq!(
+        mapping.voxel_for_vox(1, Some([215, 200, 140]), &registry),
+        VoxelType::Sand
+    );
+    assert_eq!(mapping.voxel_for_vox(1, None, &registry), VoxelType::Rock);
+}
+
+#[test]
+fn corrupt_files_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let registry = VoxelRegistry::builtin();
+    let mapping = SchematicMapping::default();
+
+    for name in ["broken.vxs", "broken.vox", "broken.schem"] {
+        let path = dir.path().join(name);
+        fs::write(&path, b"not a schematic").unwrap();
+        assert!(
+            Schematic::load(&path, &mapping, &registry).is_err(),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn placing_a_schematic_is_one_undo_step() {
+    let mut world = VoxelWorld::new(IVec3::new(4, 2, 4));
+    let mut chunk = Chunk::new(IVec3::ZERO);
+    chunk.clear_dirty();
+    world.insert_chunk(chunk);
+    let mut history = EditHistory::default();
+
+    let summary = sample_schematic()
+        .place(&mut world, &mut history, IVec3::new(4, 4, 4))
+        .unwrap();
+    assert_eq!(summary.changed, 5);
+    assert_eq!(world.get_voxel(IVec3::new(4, 7, 5)), Some(VoxelType::Wood));
+
+    history.undo(&mut world).unwrap();
+    assert_eq!(world.get_voxel(IVec3::new(4, 7, 5)), Some(VoxelType::Air));
+    assert!(!history.can_undo());
+}
+
+#[test]
+fn listing_finds_schematics_and_picks_unused_names() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("tower.vxs"), b"").unwrap();
+    fs::write(dir.path().join("house.schem"), b"").unwrap();
+    fs::write(dir.path().join("notes.txt"), b"").unwrap();
+    fs::write(dir.path().join("selection_1.vxs"), b"").unwrap();
+
+    let names: Vec<_> = list_schematics(dir.path())
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(names, ["house.schem", "selection_1.vxs", "tower.vxs"]);
+
+    let next = unused_schematic_path(dir.path(), "selection", SchematicFormat::Native);
+    assert_eq!(next, dir.path().join("selection_2.vxs"));
+}
+
+#[test]
+fn shipped_mapping_matches_builtin_table() {
+    let mapping = SchematicMapping::load(SCHEMATIC_MAPPING_PATH).unwrap();
+    assert_eq!(mapping, SchematicMapping::default());
+}
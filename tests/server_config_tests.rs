@@ -0,0 +1,44 @@
+use std::fs;
+use voxel_builder::network::config::{ServerConfig, ServerConfigError, SERVER_CONFIG_PATH};
+
+#[test]
+fn shipped_server_config_matches_the_defaults() {
+    let config = ServerConfig::load(SERVER_CONFIG_PATH).expect("server.yaml should load");
+    assert_eq!(config, ServerConfig::default());
+    assert_eq!(config.settings().max_players, 8);
+}
+
+#[test]
+fn missing_fields_use_defaults() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("server.yaml");
+    fs::write(&path, "port: 9000\npassword: hunter2\n").unwrap();
+
+    let config = ServerConfig::load(&path).unwrap();
+    assert_eq!(config.port, 9000);
+    assert_eq!(config.settings().password, "hunter2");
+    assert_eq!(config.world, ServerConfig::default().world);
+}
+
+#[test]
+fn invalid_server_config_is_rejected() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("server.yaml");
+
+    for yaml in [
+        "max_players: 0\n",
+        "autosave_interval_secs: -5\n",
+        "world: ../elsewhere\n",
+        "world: \"\"\n",
+    ] {
+        fs::write(&path, yaml).unwrap();
+        assert!(
+            matches!(
+                ServerConfig::load(&path),
+                Err(ServerConfigError::Invalid(_))
+            ),
+            "{} should be rejected",
+            yaml.trim()
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use voxel_builder::voxel::registry::{RegistryError, VoxelRegistry, VOXEL_TYPES_CONFIG_PATH};
+use voxel_builder::voxel::types::{ToolType, VoxelType};
+
+#[test]
+fn shipped_voxel_types_match_builtin_registry() {
+    let loaded = VoxelRegistry::load(VOXEL_TYPES_CONFIG_PATH).expect("voxel_types.yaml should load");
+    let builtin = VoxelRegistry::builtin();
+
+    assert_eq!(loaded.len(), builtin.len());
+    for ((loaded_type, loaded_info), (builtin_type, builtin_info)) in loaded.iter().zip(builtin.iter()) {
+        assert_eq!(loaded_type, builtin_type);
+        assert_eq!(loaded_info.name, builtin_info.name);
+        assert_eq!(loaded_info.solid, builtin_info.solid);
+        assert_eq!(loaded_info.transparent, builtin_info.transparent);
+        assert_eq!(loaded_info.liquid, builtin_info.liquid);
+        assert_eq!(loaded_info.hardness, builtin_info.hardness);
+        assert_eq!(loaded_info.tool_required, builtin_info.tool_required);
+        assert_eq!(loaded_info.atlas_index, builtin_info.atlas_index);
+        assert_eq!(loaded_info.material, builtin_info.material);
+        assert_eq!(loaded_info.support, builtin_info.support);
+        assert_eq!(loaded_info.light_emission, builtin_info.light_emission);
+    }
+}
+
+#[test]
+fn builtin_ids_resolve_by_name() {
+    let registry = VoxelRegistry::builtin();
+
+    assert_eq!(registry.by_name("rock"), Some(VoxelType::Rock));
+    assert_eq!(registry.by_name("dungeon_floor"), Some(VoxelType::DungeonFloor));
+    assert_eq!(registry.info(VoxelType::Rock).tool_required, ToolType::Pickaxe);
+    assert!(!registry.info(VoxelType::Bedrock).is_breakable());
+    assert!(registry.info(VoxelType::Leaves).transparent);
+}
+
+#[test]
+fn custom_types_are_appended_after_builtins() {
+    let tmp_file = tempfile::NamedTempFile::new().expect("create temp file");
+    let mut yaml = std::fs::read_to_string(VOXEL_TYPES_CONFIG_PATH).expect("read shipped config");
+    yaml.push_str(
+        r#"
+  - id: marble
+    solid: true
+    hardness: 5.0
+    tool_required: pickaxe
+    atlas_index: 12
+    material: rock
+"#,
+    );
+    std::fs::write(tmp_file.path(), yaml).expect("write config");
+
+    let registry = VoxelRegistry::load(tmp_file.path()).expect("extended config should load");
+    let marble = registry.by_name("marble").expect("marble registered");
+
+    assert_eq!(marble, VoxelType::from_id(VoxelRegistry::builtin().len() as u8));
+    assert_eq!(registry.info(marble).hardness, 5.0);
+    assert!(registry.placeable().any(|(voxel, _)| voxel == marble));
+}
+
+#[test]
+fn reordered_builtins_are_rejected() {
+    let tmp_file = tempfile::NamedTempFile::new().expect("create temp file");
+    std::fs::write(
+        tmp_file.path(),
+        r#"
+voxel_types:
+  - id: rock
+    solid: true
+"#,
+    )
+    .expect("write config");
+
+    match VoxelRegistry::load(tmp_file.path()) {
+        Err(RegistryError::BuiltinMismatch { index: 0, expected: "air", .. }) => {}
+        other => panic!("expected builtin mismatch, got {:?}", other.map(|r| r.len())),
+    }
+}
+
+#[test]
+fn voxel_type_serializes_like_the_old_enum() {
+    let bytes = bincode::serialize(&VoxelType::Clay).expect("serialize voxel");
+    assert_eq!(bytes, 6u32.to_le_bytes().to_vec());
+
+    let decoded: VoxelType = bincode::deserialize(&bytes).expect("deserialize voxel");
+    assert_eq!(decoded, VoxelType::Clay);
+}
+
+#[test]
+fn unknown_voxel_ids_are_rejected() {
+    assert_eq!(VoxelType::try_from(6u32), Ok(VoxelType::Clay));
+    // Used to truncate to Air and SubSoil
+    assert!(VoxelType::try_from(256u32).is_err());
+    assert!(VoxelType::try_from(258u32).is_err());
+
+    let unregistered = VoxelRegistry::global().len() as u32;
+    assert!(VoxelType::try_from(unregistered).is_err());
+    let bytes = bincode::serialize(&unregistered).expect("serialize id");
+    assert!(bincode::deserialize::<VoxelType>(&bytes).is_err());
+}
@@ -0,0 +1,157 @@
+use bevy::math::IVec3;
+use voxel_builder::interaction::history::EditHistory;
+use voxel_builder::interaction::world_edit::{self, MirrorAxis, Region, VoxelBuffer};
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::world::VoxelWorld;
+
+/// A 3x1x3 block of loaded, clean chunks starting at the origin
+fn loaded_world() -> VoxelWorld {
+    let mut world = VoxelWorld::new(IVec3::new(4, 2, 4));
+    for x in 0..3 {
+        for z in 0..3 {
+            let mut chunk = Chunk::new(IVec3::new(x, 0, z));
+            chunk.clear_dirty();
+            world.insert_chunk(chunk);
+        }
+    }
+    world
+}
+
+fn count(world: &VoxelWorld, region: Region, voxel: VoxelType) -> usize {
+    region
+        .positions()
+        .filter(|pos| world.get_voxel(*pos) == Some(voxel))
+        .count()
+}
+
+#[test]
+fn fill_is_one_undo_step_and_dirties_each_chunk_once() {
+    let mut world = loaded_world();
+    let mut history = EditHistory::default();
+    let region = Region::new(IVec3::new(8, 0, 8), IVec3::new(20, 9, 12));
+
+    let summary =
+        world_edit::fill(&mut world, &mut history, region, VoxelType::Rock).expect("fill");
+    assert_eq!(summary.changed as i64, region.volume());
+    // Chunks (0,0,0) and (1,0,0), plus (0,-1,0) and (1,-1,0) below the floor
+    assert_eq!(summary.chunks, 4);
+    assert_eq!(history.undo_len(), 1);
+    assert!(world.get_chunk(IVec3::new(1, 0, 0)).unwrap().is_dirty());
+    assert!(!world.get_chunk(IVec3::new(0, 0, 1)).unwrap().is_dirty());
+
+    history.undo(&mut world).expect("undo fill");
+    assert_eq!(
+        count(&world, region, VoxelType::Air) as i64,
+        region.volume()
+    );
+}
+
+#[test]
+fn replace_only_touches_matching_voxels() {
+    let mut world = loaded_world();
+    let mut history = EditHistory::default();
+    let region = Region::new(IVec3::ZERO, IVec3::new(3, 3, 3));
+    world.set_voxel(IVec3::new(1, 1, 1), VoxelType::Sand);
+    world.set_voxel(IVec3::new(2, 2, 2), VoxelType::Sand);
+    world.set_voxel(IVec3::new(3, 3, 3), VoxelType::Clay);
+
+    let summary = world_edit::replace(
+        &mut world,
+        &mut history,
+        region,
+        VoxelType::Sand,
+        VoxelType::Wood,
+    )
+    .expect("replace");
+    assert_eq!(summary.changed, 2);
+    assert_eq!(count(&world, region, VoxelType::Wood), 2);
+    assert_eq!(world.get_voxel(IVec3::new(3, 3, 3)), Some(VoxelType::Clay));
+}
+
+#[test]
+fn hollow_and_walls_shape_the_box() {
+    let mut world = loaded_world();
+    let mut history = EditHistory::default();
+    let region = Region::new(IVec3::new(2, 2, 2), IVec3::new(6, 6, 6));
+
+    world_edit::fill(&mut world, &mut history, region, VoxelType::Rock).expect("fill");
+    world_edit::hollow(&mut world, &mut history, region).expect("hollow");
+    // 5³ minus the 3³ interior
+    assert_eq!(count(&world, region, VoxelType::Rock), 125 - 27);
+
+    world_edit::clear(&mut world, &mut history, region).expect("clear");
+    world_edit::walls(&mut world, &mut history, region, VoxelType::Wood).expect("walls");
+    // Four sides of a 5x5 footprint, five high, without floor or ceiling
+    assert_eq!(count(&world, region, VoxelType::Wood), 16 * 5);
+    assert_eq!(world.get_voxel(IVec3::new(4, 2, 4)), Some(VoxelType::Air));
+}
+
+#[test]
+fn unbreakable_voxels_are_left_alone() {
+    let mut world = loaded_world();
+    let mut history = EditHistory::default();
+    let region = Region::new(IVec3::ZERO, IVec3::new(1, 0, 0));
+    world.set_voxel(IVec3::ZERO, VoxelType::Bedrock);
+
+    let summary = world_edit::clear(&mut world, &mut history, region).expect("clear");
+    let summary_fill =
+        world_edit::fill(&mut world, &mut history, region, VoxelType::Sand).expect("fill");
+    assert_eq!(summary.changed, 0);
+    assert_eq!((summary_fill.changed, summary_fill.skipped), (1, 1));
+    assert_eq!(world.get_voxel(IVec3::ZERO), Some(VoxelType::Bedrock));
+}
+
+#[test]
+fn oversized_selections_are_rejected() {
+    let mut world = loaded_world();
+    let mut history = EditHistory::default();
+    let region = Region::new(IVec3::ZERO, IVec3::new(200, 200, 200));
+
+    assert!(world_edit::fill(&mut world, &mut history, region, VoxelType::Rock).is_err());
+    assert!(world_edit::copy(&world, region).is_err());
+    assert!(!history.can_undo());
+}
+
+#[test]
+fn copy_and_paste_rotated_and_mirrored() {
+    let mut world = loaded_world();
+    let mut history = EditHistory::default();
+    // An L shape: two blocks along x, one along z
+    world.set_voxel(IVec3::new(0, 0, 0), VoxelType::Wood);
+    world.set_voxel(IVec3::new(1, 0, 0), VoxelType::Wood);
+    world.set_voxel(IVec3::new(0, 0, 1), VoxelType::Clay);
+
+    let buffer =
+        world_edit::copy(&world, Region::new(IVec3::ZERO, IVec3::new(1, 0, 1))).expect("copy");
+    assert_eq!(buffer.size(), IVec3::new(2, 1, 2));
+
+    let rotated = buffer.rotated_y(1);
+    assert_eq!(rotated.get(IVec3::new(1, 0, 0)), Some(VoxelType::Wood));
+    assert_eq!(rotated.get(IVec3::new(1, 0, 1)), Some(VoxelType::Wood));
+    assert_eq!(rotated.get(IVec3::new(0, 0, 0)), Some(VoxelType::Clay));
+    assert_eq!(buffer.rotated_y(4), buffer);
+    assert_eq!(buffer.rotated_y(-1), buffer.rotated_y(3));
+
+    let mirrored = buffer.mirrored(MirrorAxis::X);
+    assert_eq!(mirrored.get(IVec3::new(1, 0, 1)), Some(VoxelType::Clay));
+    assert_eq!(mirrored.mirrored(MirrorAxis::X), buffer);
+
+    let origin = IVec3::new(20, 4, 20);
+    world_edit::paste(&mut world, &mut history, &rotated, origin).expect("paste");
+    assert_eq!(
+        world.get_voxel(origin + IVec3::new(1, 0, 1)),
+        Some(VoxelType::Wood)
+    );
+    assert_eq!(world.get_voxel(origin), Some(VoxelType::Clay));
+
+    history.undo(&mut world).expect("undo paste");
+    assert_eq!(world.get_voxel(origin), Some(VoxelType::Air));
+}
+
+#[test]
+fn voxel_buffers_check_their_size() {
+    assert!(VoxelBuffer::from_voxels(IVec3::new(2, 2, 2), vec![VoxelType::Air; 8]).is_ok());
+    assert!(VoxelBuffer::from_voxels(IVec3::new(2, 2, 2), vec![VoxelType::Air; 7]).is_err());
+    assert!(VoxelBuffer::from_voxels(IVec3::new(0, 2, 2), Vec::new()).is_err());
+}
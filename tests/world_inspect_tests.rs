@@ -0,0 +1,132 @@
+use bevy::math::IVec3;
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+use std::process::Command;
+use voxel_builder::constants::CHUNK_SIZE_I32;
+use voxel_builder::voxel::inspect;
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::worldgen::{FlatGenerator, GeneratorConfig, TerrainPipeline};
+
+const WORLD_SIZE: IVec3 = IVec3::new(2, 2, 2);
+
+#[test]
+fn generated_world_stores_every_chunk_and_validates() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+    let generator = TerrainPipeline::new(11, &GeneratorConfig::default());
+
+    let (slot, _) = inspect::generate_world(tmp.path(), "Fixture", &generator, WORLD_SIZE)
+        .expect("generate world");
+    assert_eq!(slot.read_metadata().expect("meta").seed, 11);
+
+    let stats = inspect::collect_stats(&slot.store(), WORLD_SIZE, Some(&generator))
+        .expect("collect stats");
+    assert_eq!(stats.stored_chunks, 8);
+    assert_eq!(stats.generated_chunks, 0);
+    assert_eq!(stats.total_voxels(), 8 * 16 * 16 * 16);
+    assert!(stats.count(VoxelType::Bedrock) > 0);
+
+    let report = inspect::validate_save(&slot);
+    assert!(report.is_ok(), "{:?}", report.errors);
+    assert_eq!(report.chunks_checked, 8);
+}
+
+#[test]
+fn missing_chunks_are_generated_unless_stored_only() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+    let generator = FlatGenerator { seed: 3, height: 4 };
+    let (slot, _) = voxel_builder::voxel::persistence::create_save_slot(
+        tmp.path(),
+        "Empty",
+        3,
+        WORLD_SIZE,
+    )
+    .expect("create slot");
+
+    let stored_only = inspect::collect_stats(&slot.store(), WORLD_SIZE, None).expect("stats");
+    assert_eq!(stored_only.chunk_count(), 0);
+
+    let stats = inspect::collect_stats(&slot.store(), WORLD_SIZE, Some(&generator)).expect("stats");
+    assert_eq!(stats.generated_chunks, 8);
+    // Four bottom chunks with five soil layers each, the top ones uniform air
+    assert_eq!(stats.count(VoxelType::TopSoil), 4 * 5 * 16 * 16);
+    assert_eq!(stats.uniform_chunks, 4);
+    assert_eq!(stats.histogram()[0].0, VoxelType::Air);
+}
+
+#[test]
+fn heightmap_covers_world_bounds() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+    let generator = FlatGenerator { seed: 0, height: 20 };
+    let (slot, _) = inspect::generate_world(tmp.path(), "Flat", &generator, WORLD_SIZE)
+        .expect("generate world");
+
+    let heightmap = inspect::build_heightmap(&slot.store(), WORLD_SIZE, None).expect("heightmap");
+    assert_eq!(heightmap.width, 2 * CHUNK_SIZE_I32 as usize);
+    assert_eq!(heightmap.depth, 2 * CHUNK_SIZE_I32 as usize);
+    assert_eq!(heightmap.get(0, 0), Some(20));
+    assert_eq!(heightmap.get(31, 31), Some(20));
+    assert_eq!(heightmap.max_height(), Some(20));
+
+    let pixels = heightmap.to_grayscale(2 * CHUNK_SIZE_I32 - 1);
+    assert_eq!(pixels.len(), 32 * 32);
+    assert!(pixels.iter().all(|p| *p == pixels[0] && *p > 0));
+}
+
+#[test]
+fn validation_reports_corrupt_chunks() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+    let generator = TerrainPipeline::new(5, &GeneratorConfig::default());
+    let (slot, _) = inspect::generate_world(tmp.path(), "Broken", &generator, WORLD_SIZE)
+        .expect("generate world");
+
+    let (_, region_path) = slot.store().region_files().expect("regions").remove(0);
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(&region_path)
+        .expect("open region");
+    // Garbage over the payload of the first chunk (right after the header)
+    let header_bytes = 8 + 16 * 16 * 8 * 8;
+    let first_payload = (header_bytes as u64).div_ceil(4096) * 4096;
+    file.seek(SeekFrom::Start(first_payload + 5)).expect("seek");
+    file.write_all(&[0xAB; 64]).expect("corrupt chunk");
+    drop(file);
+
+    let report = inspect::validate_save(&slot);
+    assert!(!report.is_ok());
+    assert_eq!(report.chunks_checked, 8);
+}
+
+#[test]
+fn validation_requires_metadata() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+    let slot = voxel_builder::voxel::persistence::SaveSlot::in_dir(tmp.path(), "missing");
+
+    let report = inspect::validate_save(&slot);
+    assert!(!report.is_ok());
+}
+
+#[test]
+fn worldgen_stored_only_flag_before_slot_dir() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+    let (slot, _) = voxel_builder::voxel::persistence::create_save_slot(
+        tmp.path(),
+        "Empty",
+        3,
+        WORLD_SIZE,
+    )
+    .expect("create slot");
+
+    // The slot directory must not be taken as the flag's value
+    let output = Command::new(env!("CARGO_BIN_EXE_worldgen"))
+        .args(["stats", "--stored-only"])
+        .arg(slot.directory())
+        .output()
+        .expect("run worldgen");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("0 stored, 0 generated"), "{}", stdout);
+}
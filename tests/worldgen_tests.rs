@@ -0,0 +1,167 @@
+use bevy::math::{IVec3, UVec3};
+use voxel_builder::constants::CHUNK_SIZE;
+use voxel_builder::voxel::chunk::Chunk;
+use voxel_builder::voxel::types::VoxelType;
+use voxel_builder::voxel::worldgen::{
+    ChunkContext, DungeonStage, GenerationStage, GenerationStats, GeneratorConfig,
+    GeneratorConfigError, Noise, TerrainPipeline, WorldGenerator, WORLDGEN_CONFIG_PATH,
+};
+
+fn voxels(chunk: &Chunk) -> Vec<VoxelType> {
+    let mut voxels = Vec::new();
+    for x in 0..CHUNK_SIZE as u32 {
+        for y in 0..CHUNK_SIZE as u32 {
+            for z in 0..CHUNK_SIZE as u32 {
+                voxels.push(chunk.get(UVec3::new(x, y, z)));
+            }
+        }
+    }
+    voxels
+}
+
+fn sample_positions() -> Vec<IVec3> {
+    vec![
+        IVec3::new(0, 0, 0),
+        IVec3::new(0, 1, 0),
+        IVec3::new(3, 1, -2),
+        IVec3::new(-7, 0, 5),
+    ]
+}
+
+#[test]
+fn same_seed_generates_identical_chunks() {
+    let config = GeneratorConfig::default();
+    let a = TerrainPipeline::new(1234, &config);
+    let b = TerrainPipeline::new(1234, &config);
+
+    for pos in sample_positions() {
+        let chunk_a = a.generate_chunk(pos, &mut GenerationStats::default());
+        let chunk_b = b.generate_chunk(pos, &mut GenerationStats::default());
+        assert_eq!(voxels(&chunk_a), voxels(&chunk_b), "chunk {:?}", pos);
+        assert!(!chunk_a.is_modified());
+    }
+}
+
+#[test]
+fn different_seeds_generate_different_terrain() {
+    let config = GeneratorConfig::default();
+    let a = TerrainPipeline::new(1, &config);
+    let b = a.with_seed(2);
+    assert_eq!(b.seed(), 2);
+
+    let differs = sample_positions().into_iter().any(|pos| {
+        let chunk_a = a.generate_chunk(pos, &mut GenerationStats::default());
+        let chunk_b = b.generate_chunk(pos, &mut GenerationStats::default());
+        voxels(&chunk_a) != voxels(&chunk_b)
+    });
+    assert!(differs);
+}
+
+#[test]
+fn default_stages_run_in_order() {
+    let pipeline = TerrainPipeline::new(0, &GeneratorConfig::default());
+    let names: Vec<&str> = pipeline.stage_names().collect();
+    assert_eq!(names, vec!["height", "biome", "caves", "dungeons", "trees"]);
+}
+
+struct GlassCeiling;
+
+impl GenerationStage for GlassCeiling {
+    fn name(&self) -> &str {
+        "glass_ceiling"
+    }
+
+    fn apply(&self, ctx: &mut ChunkContext) {
+        for x in 0..CHUNK_SIZE as u32 {
+            for z in 0..CHUNK_SIZE as u32 {
+                ctx.set(UVec3::new(x, CHUNK_SIZE as u32 - 1, z), VoxelType::Clay);
+            }
+        }
+    }
+}
+
+#[test]
+fn custom_stage_runs_and_respects_structures() {
+    let mut pipeline = TerrainPipeline::new(9, &GeneratorConfig::default());
+    assert!(pipeline.insert_stage_after("dungeons", GlassCeiling));
+    assert!(pipeline.remove_stage("trees"));
+
+    // The entrance shaft of the dungeon at the origin passes through
+    // chunk (0, 1, 0) and is reserved, so the ceiling goes around it
+    let chunk_pos = IVec3::new(0, 1, 0);
+    let chunk = pipeline.generate_chunk(chunk_pos, &mut GenerationStats::default());
+    let dungeon = DungeonStage::new(GeneratorConfig::default().dungeons);
+
+    let mut shaft_voxels = 0;
+    for x in 0..CHUNK_SIZE as u32 {
+        for z in 0..CHUNK_SIZE as u32 {
+            let local = UVec3::new(x, CHUNK_SIZE as u32 - 1, z);
+            let world = chunk_pos * CHUNK_SIZE as i32 + local.as_ivec3();
+            let structure = dungeon.voxel_at(world);
+            shaft_voxels += structure.is_some() as usize;
+            let expected = structure.unwrap_or(VoxelType::Clay);
+            assert_eq!(chunk.get(local), expected, "voxel {:?}", local);
+        }
+    }
+    assert_eq!(shaft_voxels, 9);
+}
+
+#[test]
+fn generation_stats_count_voxels() {
+    let pipeline = TerrainPipeline::new(5, &GeneratorConfig::default());
+    let mut stats = GenerationStats::default();
+    let chunk = pipeline.generate_chunk(IVec3::ZERO, &mut stats);
+
+    let walls = voxels(&chunk)
+        .into_iter()
+        .filter(|v| *v == VoxelType::DungeonWall)
+        .count();
+    assert_eq!(stats.dungeon_wall as usize, walls);
+    assert!(stats.dungeon_floor > 0);
+}
+
+#[test]
+fn shipped_config_loads_and_matches_defaults() {
+    let config = GeneratorConfig::load(WORLDGEN_CONFIG_PATH).expect("worldgen.yaml loads");
+    let defaults = GeneratorConfig::default();
+
+    let from_file = TerrainPipeline::new(77, &config);
+    let built_in = TerrainPipeline::new(77, &defaults);
+    let chunk_a = from_file.generate_chunk(IVec3::new(2, 1, 2), &mut GenerationStats::default());
+    let chunk_b = built_in.generate_chunk(IVec3::new(2, 1, 2), &mut GenerationStats::default());
+    assert_eq!(voxels(&chunk_a), voxels(&chunk_b));
+}
+
+#[test]
+fn invalid_config_is_rejected() {
+    let tmp = tempfile::tempdir().expect("create temp dir");
+    let path = tmp.path().join("worldgen.yaml");
+    std::fs::write(&path, "dungeons:\n  spacing: 10\n  size: 20\n").expect("write config");
+
+    match GeneratorConfig::load(&path) {
+        Err(GeneratorConfigError::Invalid(message)) => assert!(message.contains("spacing")),
+        other => panic!("expected invalid config, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn noise_uses_the_whole_seed() {
+    // These used to fold to the same 32-bit seed
+    let a = Noise::new(1);
+    let b = Noise::new(1 << 32);
+    let differs = (0..16).any(|i| a.hash(i, -i) != b.hash(i, -i));
+    assert!(differs);
+
+    // Seed 0 keeps the unseeded hash
+    let legacy = |x: i32, z: i32| {
+        let n = x
+            .wrapping_mul(374761393)
+            .wrapping_add(z.wrapping_mul(668265263));
+        let n = (n ^ (n >> 13)).wrapping_mul(1274126177);
+        ((n ^ (n >> 16)) as u32 as f32) / u32::MAX as f32
+    };
+    let unseeded = Noise::new(0);
+    for (x, z) in [(0, 0), (5, -3), (-100, 42)] {
+        assert_eq!(unseeded.hash(x, z), legacy(x, z));
+    }
+}